@@ -0,0 +1,330 @@
+use crate::expressions::{Expression, LiteralValue, MatchArmBody};
+use crate::patterns::Pattern;
+use crate::statements::{FunctionDeclarationStatement, Statement};
+use crate::type_annotation::type_parameters_to_string;
+
+// Produce a readable outline of a syntax tree, with each node on its own line and its children
+// indented below it.
+pub fn describe_statements(statements: &[Statement]) -> String {
+    let mut output: String = String::new();
+
+    for statement in statements {
+        describe_statement(statement, 0, &mut output);
+    }
+
+    return output;
+}
+
+fn line(output: &mut String, depth: usize, text: String) -> () {
+    *output += &("  ".repeat(depth) + &text + "\n");
+}
+
+// Public declarations are marked, since only they can be used by other modules.
+fn visibility(public: bool) -> &'static str {
+    return if public { "Public " } else { "" };
+}
+
+fn describe_literal(value: &LiteralValue) -> String {
+    return match value {
+        LiteralValue::Integer(value) => value.to_string(),
+        LiteralValue::Float(value) => format!("{:?}", value),
+        LiteralValue::String(value) => format!("{:?}", value),
+        LiteralValue::Char(value) => format!("{:?}", value),
+        LiteralValue::Boolean(value) => value.to_string(),
+        LiteralValue::Null => "null".to_owned()
+    };
+}
+
+fn describe_function(kind: &str, function: &FunctionDeclarationStatement, depth: usize,
+                     output: &mut String) -> () {
+    let parameters: Vec<String> = function.parameters.iter()
+        .map(|parameter| parameter.parameter_type.to_string() + " " + &parameter.name.value())
+        .collect();
+
+    line(output, depth, visibility(function.public).to_owned() + kind + " " +
+        &function.name.token.value() + &type_parameters_to_string(&function.type_parameters) +
+        "(" + &parameters.join(", ") + ") -> " + &function.return_type.to_string());
+    describe_block(&function.body, depth + 1, output);
+}
+
+fn describe_block(statements: &[Statement], depth: usize, output: &mut String) -> () {
+    for statement in statements {
+        describe_statement(statement, depth, output);
+    }
+}
+
+fn describe_statement(statement: &Statement, depth: usize, output: &mut String) -> () {
+    match statement {
+        Statement::Expression(expression_statement) => {
+            describe_expression(&expression_statement.expression, depth, output);
+        },
+        Statement::VariableDeclaration(declaration) => {
+            line(output, depth, visibility(declaration.public).to_owned() + "Variable " +
+                &declaration.variable_type.to_string() + " " +
+                &declaration.identifier.token.value());
+
+            if let Some(initialization) = &declaration.initialization {
+                describe_expression(initialization, depth + 1, output);
+            }
+        },
+        Statement::Block(block) => {
+            line(output, depth, "Block".to_owned());
+            describe_block(&block.statements, depth + 1, output);
+        },
+        Statement::If(if_statement) => {
+            line(output, depth, "If".to_owned());
+            describe_expression(&if_statement.condition, depth + 1, output);
+            line(output, depth, "Then".to_owned());
+            describe_block(&if_statement.then_branch, depth + 1, output);
+
+            if !if_statement.else_branch.is_empty() {
+                line(output, depth, "Else".to_owned());
+                describe_block(&if_statement.else_branch, depth + 1, output);
+            }
+        },
+        Statement::While(while_statement) => {
+            line(output, depth, "While".to_owned());
+            describe_expression(&while_statement.condition, depth + 1, output);
+            line(output, depth, "Do".to_owned());
+            describe_block(&while_statement.body, depth + 1, output);
+        },
+        Statement::Loop(loop_statement) => {
+            line(output, depth, "Loop".to_owned());
+            describe_block(&loop_statement.body, depth + 1, output);
+        },
+        Statement::For(for_statement) => {
+            line(output, depth, "For ".to_owned() + &for_statement.variable_type.to_string() +
+                " " + &for_statement.variable.value());
+            describe_expression(&for_statement.initializer, depth + 1, output);
+            describe_expression(&for_statement.condition, depth + 1, output);
+            describe_expression(&for_statement.increment, depth + 1, output);
+            line(output, depth, "Do".to_owned());
+            describe_block(&for_statement.body, depth + 1, output);
+        },
+        Statement::ForIn(for_in_statement) => {
+            let variables: Vec<String> = for_in_statement.variables.iter()
+                .map(|variable| variable.value())
+                .collect();
+
+            line(output, depth, "ForIn ".to_owned() + &variables.join(", "));
+            describe_expression(&for_in_statement.iterable, depth + 1, output);
+            line(output, depth, "Do".to_owned());
+            describe_block(&for_in_statement.body, depth + 1, output);
+        },
+        Statement::Break(_) => line(output, depth, "Break".to_owned()),
+        Statement::Continue(_) => line(output, depth, "Continue".to_owned()),
+        Statement::FunctionDeclaration(declaration) => {
+            describe_function("Function", declaration, depth, output);
+        },
+        Statement::Return(return_statement) => {
+            line(output, depth, "Return".to_owned());
+
+            if let Some(value) = &return_statement.value {
+                describe_expression(value, depth + 1, output);
+            }
+        },
+        Statement::ClassDeclaration(declaration) => {
+            let mut header: String = visibility(declaration.public).to_owned() + "Class " +
+                &declaration.name.token.value() +
+                &type_parameters_to_string(&declaration.type_parameters);
+
+            if let Some(superclass) = declaration.superclass_type() {
+                header += &(" extends ".to_owned() + &superclass.to_string());
+            }
+
+            if !declaration.interfaces.is_empty() {
+                let interfaces: Vec<String> = declaration.interfaces.iter()
+                    .map(|interface| interface.to_string())
+                    .collect();
+
+                header += &(" implements ".to_owned() + &interfaces.join(", "));
+            }
+
+            line(output, depth, header);
+
+            for property in &declaration.properties {
+                describe_statement(&Statement::VariableDeclaration(property.to_owned()),
+                                   depth + 1, output);
+            }
+
+            if let Some(constructor) = &declaration.constructor {
+                describe_function("Constructor", constructor, depth + 1, output);
+            }
+
+            for method in &declaration.methods {
+                describe_function("Method", method, depth + 1, output);
+            }
+        },
+        Statement::InterfaceDeclaration(declaration) => {
+            line(output, depth, visibility(declaration.public).to_owned() + "Interface " +
+                &declaration.name.token.value() +
+                &type_parameters_to_string(&declaration.type_parameters));
+
+            for method in &declaration.methods {
+                describe_function("Method", method, depth + 1, output);
+            }
+        },
+        Statement::EnumDeclaration(declaration) => {
+            line(output, depth, visibility(declaration.public).to_owned() + "Enum " +
+                &declaration.name.token.value());
+
+            for variant in &declaration.variants {
+                let fields: Vec<String> = variant.fields.iter()
+                    .map(|field| field.parameter_type.to_string() + " " + &field.name.value())
+                    .collect();
+
+                line(output, depth + 1, "Variant ".to_owned() + &variant.name.value() + "(" +
+                    &fields.join(", ") + ")");
+            }
+        },
+        Statement::Import(import) => {
+            line(output, depth, "Import ".to_owned() + &import.path + " as " +
+                &import.name.value());
+        },
+        Statement::Test(test) => {
+            line(output, depth, format!("Test {:?}", test.name.value()));
+            describe_block(&test.body, depth + 1, output);
+        },
+        Statement::Throw(throw_statement) => {
+            line(output, depth, "Throw".to_owned());
+            describe_expression(&throw_statement.value, depth + 1, output);
+        },
+        Statement::Try(try_statement) => {
+            line(output, depth, "Try".to_owned());
+            describe_block(&try_statement.body, depth + 1, output);
+
+            for catch in &try_statement.catches {
+                let mut header: String = "Catch ".to_owned() + &catch.variable.value();
+
+                if let Some(error_type) = &catch.error_type {
+                    header += &(": ".to_owned() + &error_type.to_string());
+                }
+
+                line(output, depth, header);
+                describe_block(&catch.body, depth + 1, output);
+            }
+
+            if let Some(finally) = &try_statement.finally {
+                line(output, depth, "Finally".to_owned());
+                describe_block(finally, depth + 1, output);
+            }
+        },
+        Statement::Trivia(_) => unreachable!()
+    }
+}
+
+fn describe_pattern(pattern: &Pattern) -> String {
+    return match pattern {
+        Pattern::Literal(literal) => describe_literal(&literal.value),
+        Pattern::Binding(name) => name.value(),
+        Pattern::Wildcard(_) => "_".to_owned(),
+        Pattern::Variant(variant) => {
+            let mut description: String = variant.enum_name.value() + "." +
+                &variant.variant.value();
+
+            if !variant.fields.is_empty() {
+                let fields: Vec<String> = variant.fields.iter().map(describe_pattern).collect();
+
+                description += &("(".to_owned() + &fields.join(", ") + ")");
+            }
+
+            description
+        }
+    };
+}
+
+fn describe_expression(expression: &Expression, depth: usize, output: &mut String) -> () {
+    match expression {
+        Expression::Literal(literal) => {
+            line(output, depth, "Literal ".to_owned() + &describe_literal(&literal.value));
+        },
+        Expression::Identifier(identifier) => {
+            line(output, depth, "Identifier ".to_owned() + &identifier.token.value());
+        },
+        Expression::Binary(binary) => {
+            line(output, depth, "Binary ".to_owned() + &binary.operator.to_string());
+            describe_expression(&binary.left, depth + 1, output);
+            describe_expression(&binary.right, depth + 1, output);
+        },
+        Expression::Unary(unary) => {
+            line(output, depth, "Unary ".to_owned() + &unary.operator.to_string());
+            describe_expression(&unary.operand, depth + 1, output);
+        },
+        Expression::Assignment(assignment) => {
+            let operator: String = assignment.operator
+                .map(|operator| operator.to_string())
+                .unwrap_or_default() + "=";
+
+            line(output, depth, "Assignment ".to_owned() + &operator);
+            describe_expression(&assignment.target, depth + 1, output);
+            describe_expression(&assignment.value, depth + 1, output);
+        },
+        Expression::FunctionCall(call) => {
+            line(output, depth, "Call".to_owned());
+            describe_expression(&call.callee, depth + 1, output);
+
+            for argument in &call.arguments {
+                describe_expression(argument, depth + 1, output);
+            }
+        },
+        Expression::Property(property) => {
+            let access: &str = if property.safe { "?." } else { "." };
+
+            line(output, depth, "Property ".to_owned() + access + &property.name.value());
+            describe_expression(&property.object, depth + 1, output);
+        },
+        Expression::This(_) => line(output, depth, "This".to_owned()),
+        Expression::Super(super_expression) => {
+            line(output, depth, "Super ".to_owned() + &super_expression.method.value());
+        },
+        Expression::Is(is) => {
+            line(output, depth, "Is ".to_owned() + &is.class_name.value());
+            describe_expression(&is.value, depth + 1, output);
+        },
+        Expression::Map(map) => {
+            line(output, depth, "Map".to_owned());
+
+            for (key, value) in &map.entries {
+                line(output, depth + 1, "Entry".to_owned());
+                describe_expression(key, depth + 2, output);
+                describe_expression(value, depth + 2, output);
+            }
+        },
+        Expression::List(list) => {
+            line(output, depth, "List".to_owned());
+
+            for element in &list.elements {
+                describe_expression(element, depth + 1, output);
+            }
+        },
+        Expression::Range(range) => {
+            line(output, depth, "Range".to_owned());
+            describe_expression(&range.start, depth + 1, output);
+            describe_expression(&range.end, depth + 1, output);
+        },
+        Expression::Match(match_expression) => {
+            line(output, depth, "Match".to_owned());
+            describe_expression(&match_expression.value, depth + 1, output);
+
+            for arm in &match_expression.arms {
+                line(output, depth + 1, "Arm ".to_owned() + &describe_pattern(&arm.pattern));
+
+                if let Some(guard) = &arm.guard {
+                    line(output, depth + 2, "If".to_owned());
+                    describe_expression(guard, depth + 3, output);
+                }
+
+                match &arm.body {
+                    MatchArmBody::Expression(body) => {
+                        describe_expression(body, depth + 2, output);
+                    },
+                    MatchArmBody::Block(body) => describe_block(body, depth + 2, output)
+                }
+            }
+        },
+        Expression::Propagate(propagate) => {
+            line(output, depth, "Propagate".to_owned());
+            describe_expression(&propagate.value, depth + 1, output);
+        }
+    }
+}
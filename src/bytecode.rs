@@ -0,0 +1,336 @@
+use crate::expressions::{BinaryOperator, UnaryOperator};
+use crate::position::Position;
+
+// The values which can be stored in the constant pool of a compiled program. Names of variables,
+// properties, methods, classes and enum variants are stored there as strings.
+#[derive(PartialEq, Clone)]
+pub enum Constant {
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Char(char),
+    Boolean(bool)
+}
+
+// A single instruction of the virtual machine. Jump targets are indices into the code of the
+// function containing the instruction, and names are indices into the constant pool.
+#[derive(PartialEq, Clone, Copy)]
+pub enum Instruction {
+    Constant(usize),
+    Null,
+    Void,
+    Pop,
+    Swap, // Swap the two values on top of the stack.
+    GetLocal(usize),
+    SetLocal(usize), // Leaves the value on the stack.
+    GetGlobal(usize),
+    SetGlobal(usize), // Leaves the value on the stack.
+    GetField(usize),
+    SetField(usize), // Pops the object from above the value, leaving the value on the stack.
+    Binary(BinaryOperator),
+    Unary(UnaryOperator),
+    Jump(usize),
+    JumpIfFalse(usize), // Pops the condition.
+    JumpIfFalseOrPop(usize), // Keeps the condition when jumping, for `&&`.
+    JumpIfTrueOrPop(usize), // Keeps the condition when jumping, for `||`.
+    JumpIfNull(usize), // Keeps the value on the stack either way, for `?.`.
+    JumpIfNotNull(usize), // Keeps the value on the stack either way, for `??`.
+    Call(usize, usize), // The function and the number of arguments, including any receiver.
+    CallMethod(usize, usize), // The name and the number of arguments, excluding the receiver.
+    New(usize, usize), // The class and the number of arguments.
+    CallNative(usize, usize), // The native function and the number of arguments.
+    Return,
+    List(usize), // The number of elements.
+    Map(usize), // The number of entries, each of which is a key followed by a value.
+    Range,
+    Is(usize), // The name of the class.
+    MakeEnum(usize, usize, usize), // The names of the enum and variant, and the number of fields.
+    IsVariant(usize), // Replaces the value with whether it is the named variant.
+    VariantField(usize), // Replaces an enum value with one of its fields.
+    IterStart, // Pops the value being iterated over.
+    IterNext(usize, usize), // Where to jump once done, and how many values to push otherwise.
+    IterEnd,
+    // Jump to the target if a runtime error happens before the matching `PopHandler`, going back
+    // to the frame and stack height of the handler.
+    PushHandler(usize),
+    PopHandler,
+    Throw, // Stop with a runtime error, whose message is popped.
+    // Keeps the value on the stack either way, for `?`. Jumps when it is a `Result.Ok` or an
+    // `Option.Some`.
+    JumpIfPresent(usize)
+}
+
+// The operators, in the order used to number them in serialized bytecode.
+const BINARY_OPERATORS: [BinaryOperator; 15] = [
+    BinaryOperator::Add,
+    BinaryOperator::Subtract,
+    BinaryOperator::Multiply,
+    BinaryOperator::Divide,
+    BinaryOperator::Remainder,
+    BinaryOperator::Power,
+    BinaryOperator::Equal,
+    BinaryOperator::NotEqual,
+    BinaryOperator::LessThan,
+    BinaryOperator::LessThanOrEqual,
+    BinaryOperator::GreaterThan,
+    BinaryOperator::GreaterThanOrEqual,
+    BinaryOperator::And,
+    BinaryOperator::Or,
+    BinaryOperator::Coalesce
+];
+const UNARY_OPERATORS: [UnaryOperator; 3] = [
+    UnaryOperator::Negate,
+    UnaryOperator::Not,
+    UnaryOperator::ToFloat
+];
+
+impl Instruction {
+    // The name of the instruction, used when disassembling.
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Instruction::Constant(_) => "Constant",
+            Instruction::Null => "Null",
+            Instruction::Void => "Void",
+            Instruction::Pop => "Pop",
+            Instruction::Swap => "Swap",
+            Instruction::GetLocal(_) => "GetLocal",
+            Instruction::SetLocal(_) => "SetLocal",
+            Instruction::GetGlobal(_) => "GetGlobal",
+            Instruction::SetGlobal(_) => "SetGlobal",
+            Instruction::GetField(_) => "GetField",
+            Instruction::SetField(_) => "SetField",
+            Instruction::Binary(_) => "Binary",
+            Instruction::Unary(_) => "Unary",
+            Instruction::Jump(_) => "Jump",
+            Instruction::JumpIfFalse(_) => "JumpIfFalse",
+            Instruction::JumpIfFalseOrPop(_) => "JumpIfFalseOrPop",
+            Instruction::JumpIfTrueOrPop(_) => "JumpIfTrueOrPop",
+            Instruction::JumpIfNull(_) => "JumpIfNull",
+            Instruction::JumpIfNotNull(_) => "JumpIfNotNull",
+            Instruction::Call(_, _) => "Call",
+            Instruction::CallMethod(_, _) => "CallMethod",
+            Instruction::New(_, _) => "New",
+            Instruction::CallNative(_, _) => "CallNative",
+            Instruction::Return => "Return",
+            Instruction::List(_) => "List",
+            Instruction::Map(_) => "Map",
+            Instruction::Range => "Range",
+            Instruction::Is(_) => "Is",
+            Instruction::MakeEnum(_, _, _) => "MakeEnum",
+            Instruction::IsVariant(_) => "IsVariant",
+            Instruction::VariantField(_) => "VariantField",
+            Instruction::IterStart => "IterStart",
+            Instruction::IterNext(_, _) => "IterNext",
+            Instruction::IterEnd => "IterEnd",
+            Instruction::PushHandler(_) => "PushHandler",
+            Instruction::PopHandler => "PopHandler",
+            Instruction::Throw => "Throw",
+            Instruction::JumpIfPresent(_) => "JumpIfPresent"
+        };
+    }
+
+    // Split the instruction into a number identifying it and its operands, for serializing it.
+    // Operators are stored as their index in the tables above.
+    pub fn encode(&self) -> (u8, Vec<usize>) {
+        let binary = |operator: &BinaryOperator| -> usize {
+            return BINARY_OPERATORS.iter().position(|other| other == operator).unwrap();
+        };
+        let unary = |operator: &UnaryOperator| -> usize {
+            return UNARY_OPERATORS.iter().position(|other| other == operator).unwrap();
+        };
+
+        return match self {
+            Instruction::Constant(index) => (0, vec![*index]),
+            Instruction::Null => (1, vec![]),
+            Instruction::Void => (2, vec![]),
+            Instruction::Pop => (3, vec![]),
+            Instruction::Swap => (4, vec![]),
+            Instruction::GetLocal(slot) => (5, vec![*slot]),
+            Instruction::SetLocal(slot) => (6, vec![*slot]),
+            Instruction::GetGlobal(slot) => (7, vec![*slot]),
+            Instruction::SetGlobal(slot) => (8, vec![*slot]),
+            Instruction::GetField(name) => (9, vec![*name]),
+            Instruction::SetField(name) => (10, vec![*name]),
+            Instruction::Binary(operator) => (11, vec![binary(operator)]),
+            Instruction::Unary(operator) => (12, vec![unary(operator)]),
+            Instruction::Jump(target) => (13, vec![*target]),
+            Instruction::JumpIfFalse(target) => (14, vec![*target]),
+            Instruction::JumpIfFalseOrPop(target) => (15, vec![*target]),
+            Instruction::JumpIfTrueOrPop(target) => (16, vec![*target]),
+            Instruction::JumpIfNull(target) => (17, vec![*target]),
+            Instruction::JumpIfNotNull(target) => (18, vec![*target]),
+            Instruction::Call(function, count) => (19, vec![*function, *count]),
+            Instruction::CallMethod(name, count) => (20, vec![*name, *count]),
+            Instruction::New(class, count) => (21, vec![*class, *count]),
+            Instruction::CallNative(native, count) => (22, vec![*native, *count]),
+            Instruction::Return => (23, vec![]),
+            Instruction::List(count) => (24, vec![*count]),
+            Instruction::Map(count) => (25, vec![*count]),
+            Instruction::Range => (26, vec![]),
+            Instruction::Is(name) => (27, vec![*name]),
+            Instruction::MakeEnum(enum_name, variant, count) => {
+                (28, vec![*enum_name, *variant, *count])
+            },
+            Instruction::IsVariant(variant) => (29, vec![*variant]),
+            Instruction::VariantField(index) => (30, vec![*index]),
+            Instruction::IterStart => (31, vec![]),
+            Instruction::IterNext(target, count) => (32, vec![*target, *count]),
+            Instruction::IterEnd => (33, vec![]),
+            Instruction::PushHandler(target) => (34, vec![*target]),
+            Instruction::PopHandler => (35, vec![]),
+            Instruction::Throw => (36, vec![]),
+            Instruction::JumpIfPresent(target) => (37, vec![*target])
+        };
+    }
+
+    // The number of operands following each kind of instruction, or `None` if there is no such
+    // instruction.
+    pub fn operand_count(opcode: u8) -> Option<usize> {
+        return match opcode {
+            1..=4 | 23 | 26 | 31 | 33 | 35 | 36 => Some(0),
+            0 | 5..=18 | 24 | 25 | 27 | 29 | 30 | 34 | 37 => Some(1),
+            19..=22 | 32 => Some(2),
+            28 => Some(3),
+            _ => None
+        };
+    }
+
+    // Rebuild an instruction from what `encode` produced.
+    pub fn decode(opcode: u8, operands: &[usize]) -> Option<Instruction> {
+        let operand = |index: usize| -> usize {
+            return operands[index];
+        };
+
+        return Some(match opcode {
+            0 => Instruction::Constant(operand(0)),
+            1 => Instruction::Null,
+            2 => Instruction::Void,
+            3 => Instruction::Pop,
+            4 => Instruction::Swap,
+            5 => Instruction::GetLocal(operand(0)),
+            6 => Instruction::SetLocal(operand(0)),
+            7 => Instruction::GetGlobal(operand(0)),
+            8 => Instruction::SetGlobal(operand(0)),
+            9 => Instruction::GetField(operand(0)),
+            10 => Instruction::SetField(operand(0)),
+            11 => Instruction::Binary(*BINARY_OPERATORS.get(operand(0))?),
+            12 => Instruction::Unary(*UNARY_OPERATORS.get(operand(0))?),
+            13 => Instruction::Jump(operand(0)),
+            14 => Instruction::JumpIfFalse(operand(0)),
+            15 => Instruction::JumpIfFalseOrPop(operand(0)),
+            16 => Instruction::JumpIfTrueOrPop(operand(0)),
+            17 => Instruction::JumpIfNull(operand(0)),
+            18 => Instruction::JumpIfNotNull(operand(0)),
+            19 => Instruction::Call(operand(0), operand(1)),
+            20 => Instruction::CallMethod(operand(0), operand(1)),
+            21 => Instruction::New(operand(0), operand(1)),
+            22 => Instruction::CallNative(operand(0), operand(1)),
+            23 => Instruction::Return,
+            24 => Instruction::List(operand(0)),
+            25 => Instruction::Map(operand(0)),
+            26 => Instruction::Range,
+            27 => Instruction::Is(operand(0)),
+            28 => Instruction::MakeEnum(operand(0), operand(1), operand(2)),
+            29 => Instruction::IsVariant(operand(0)),
+            30 => Instruction::VariantField(operand(0)),
+            31 => Instruction::IterStart,
+            32 => Instruction::IterNext(operand(0), operand(1)),
+            33 => Instruction::IterEnd,
+            34 => Instruction::PushHandler(operand(0)),
+            35 => Instruction::PopHandler,
+            36 => Instruction::Throw,
+            37 => Instruction::JumpIfPresent(operand(0)),
+            _ => return None
+        });
+    }
+
+    // How many values the instruction takes from the top of the stack, or looks at there.
+    pub fn stack_inputs(&self) -> usize {
+        return match self {
+            Instruction::Constant(_) | Instruction::Null | Instruction::Void |
+            Instruction::GetLocal(_) | Instruction::GetGlobal(_) | Instruction::Jump(_) |
+            Instruction::IterNext(_, _) | Instruction::IterEnd | Instruction::PushHandler(_) |
+            Instruction::PopHandler => 0,
+            Instruction::Pop | Instruction::SetLocal(_) | Instruction::SetGlobal(_) |
+            Instruction::GetField(_) | Instruction::Unary(_) | Instruction::JumpIfFalse(_) |
+            Instruction::JumpIfFalseOrPop(_) | Instruction::JumpIfTrueOrPop(_) |
+            Instruction::JumpIfNull(_) | Instruction::JumpIfNotNull(_) |
+            Instruction::JumpIfPresent(_) | Instruction::Return | Instruction::Is(_) |
+            Instruction::IsVariant(_) | Instruction::VariantField(_) | Instruction::IterStart |
+            Instruction::Throw => 1,
+            Instruction::Swap | Instruction::SetField(_) | Instruction::Binary(_) |
+            Instruction::Range => 2,
+            Instruction::Call(_, count) | Instruction::New(_, count) |
+            Instruction::CallNative(_, count) | Instruction::List(count) |
+            Instruction::MakeEnum(_, _, count) => *count,
+            Instruction::CallMethod(_, count) => *count + 1,
+            Instruction::Map(count) => 2 * *count
+        };
+    }
+
+    // How the instruction changes the height of the stack when execution continues with the next
+    // instruction.
+    pub fn stack_effect(&self) -> isize {
+        return match self {
+            Instruction::Constant(_) | Instruction::Null | Instruction::Void |
+            Instruction::GetLocal(_) | Instruction::GetGlobal(_) => 1,
+            Instruction::Pop | Instruction::SetField(_) | Instruction::Binary(_) |
+            Instruction::JumpIfFalse(_) | Instruction::JumpIfFalseOrPop(_) |
+            Instruction::JumpIfTrueOrPop(_) | Instruction::Return | Instruction::Range |
+            Instruction::IterStart | Instruction::Throw => -1,
+            Instruction::Swap | Instruction::SetLocal(_) | Instruction::SetGlobal(_) |
+            Instruction::GetField(_) | Instruction::Unary(_) | Instruction::Jump(_) |
+            Instruction::JumpIfNull(_) | Instruction::JumpIfNotNull(_) |
+            Instruction::JumpIfPresent(_) | Instruction::Is(_) | Instruction::IsVariant(_) |
+            Instruction::VariantField(_) | Instruction::IterEnd | Instruction::PushHandler(_) |
+            Instruction::PopHandler => 0,
+            Instruction::Call(_, count) | Instruction::New(_, count) |
+            Instruction::CallNative(_, count) => 1 - *count as isize,
+            Instruction::CallMethod(_, count) => -(*count as isize),
+            Instruction::List(count) => 1 - *count as isize,
+            Instruction::Map(count) => 1 - 2 * *count as isize,
+            Instruction::MakeEnum(_, _, count) => 1 - *count as isize,
+            Instruction::IterNext(_, count) => *count as isize
+        };
+    }
+}
+
+pub struct FunctionProto {
+    pub name: String,
+    // The number of arguments it is called with, counting the instance for methods, which are its
+    // first locals.
+    pub arity: usize,
+    pub local_count: usize,
+    pub code: Vec<Instruction>,
+    pub positions: Vec<Position> // The source position of each instruction.
+}
+
+impl FunctionProto {
+    // The number of locals which the code uses, up to the last one any instruction refers to.
+    pub fn used_slots(&self) -> usize {
+        return self.code.iter()
+            .filter_map(|instruction| match instruction {
+                Instruction::GetLocal(slot) | Instruction::SetLocal(slot) => Some(slot + 1),
+                _ => None
+            })
+            .max()
+            .unwrap_or(0);
+    }
+}
+
+pub struct ClassProto {
+    pub name: String,
+    pub superclass: Option<usize>,
+    pub initializer: usize, // Gives the properties declared by the class their initial values.
+    pub constructor: Option<usize>,
+    pub methods: Vec<(String, usize)>
+}
+
+// A whole program lowered to bytecode. Classes and functions refer to each other by their index.
+pub struct CompiledProgram {
+    pub constants: Vec<Constant>,
+    pub functions: Vec<FunctionProto>,
+    pub classes: Vec<ClassProto>,
+    pub globals: Vec<String>, // The names of the globals, by slot.
+    pub main: usize // The function holding the top level of the program.
+}
@@ -0,0 +1,234 @@
+// Parsing of the command line, which picks what to do and what to do it with.
+
+pub const USAGE: &str = "\
+Usage: ribbit-rs [options] [command] [input] [arguments...]
+
+Commands:
+  run <input> [arguments...]      Run a program, passing it any arguments (the default).
+  check <input>                   Check a program for errors without running it.
+  fmt <input> [paths...]          Format a program, printing the result.
+  tokens <input>                  Print the tokens of a program.
+  ast <input>                     Print the syntax tree of a program.
+  test [paths...]                 Run the tests of every program in the paths, reporting which
+                                  fail. Programs without tests are skipped.
+  compile <input> [<output.rbc>]  Compile a program to bytecode.
+  disassemble <input>             Print the compiled bytecode of a program.
+  repl                            Start the interactive prompt (the default without input).
+  lsp                             Start a language server on standard input and output.
+
+Options:
+  -e <code>                       Use the code given instead of reading a file.
+  --backend=vm|tree               Choose what runs programs; `vm` is the default.
+  --test=<name>                   Make `run` run the named test after the top level of the
+                                  program.
+  --filter=<text>                 Make `test` only run the tests whose names contain the text.
+  --check                         Make `fmt` list the programs in its paths which are not
+                                  formatted, failing if there are any.
+  --write                         Make `fmt` format the programs in its paths in place, listing
+                                  those it changes.
+  -h, --help                      Print this message.
+  -V, --version                   Print the version.
+
+An input of `-` reads the program from standard input. Arguments after the input, or after
+`--`, are passed to the program.
+
+Imported modules are looked for next to the importing file, then in each directory listed in the
+`RIBBIT_PATH` environment variable.";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Command {
+    Run,
+    Check,
+    Format,
+    Tokens,
+    Ast,
+    Test,
+    Compile, // Write the compiled bytecode to a `.rbc` file.
+    Disassemble, // Print the compiled bytecode.
+    Repl,
+    Lsp, // Run the language server.
+    Help,
+    Version
+}
+
+impl Command {
+    fn from_name(name: &str) -> Option<Self> {
+        return match name {
+            "run" => Some(Command::Run),
+            "check" => Some(Command::Check),
+            "fmt" => Some(Command::Format),
+            "tokens" => Some(Command::Tokens),
+            "ast" => Some(Command::Ast),
+            "test" => Some(Command::Test),
+            "compile" => Some(Command::Compile),
+            "disassemble" => Some(Command::Disassemble),
+            "repl" => Some(Command::Repl),
+            "lsp" => Some(Command::Lsp),
+            _ => None
+        };
+    }
+}
+
+// What runs a program once it has been checked.
+#[derive(Clone, Copy)]
+pub enum Backend {
+    Tree, // The tree-walking interpreter.
+    VM // The bytecode compiler and virtual machine.
+}
+
+// What `fmt` does with the programs it formats.
+#[derive(Clone, Copy, PartialEq)]
+pub enum FormatMode {
+    Print,
+    Check,
+    Write
+}
+
+// Where the source of a program comes from.
+#[derive(Clone)]
+pub enum Input {
+    File(String),
+    Stdin, // `-`.
+    Code(String) // `-e <code>`.
+}
+
+impl ToString for Input {
+    fn to_string(&self) -> String {
+        return match self {
+            Input::File(path) => path.to_owned(),
+            Input::Stdin => "<stdin>".to_owned(),
+            Input::Code(_) => "<code>".to_owned()
+        };
+    }
+}
+
+pub struct Options {
+    pub command: Command,
+    pub backend: Backend,
+    pub format_mode: FormatMode,
+    pub test: Option<String>, // The test to run along with the program.
+    pub filter: Option<String>, // What the names of the tests to run have to contain.
+    pub input: Option<Input>,
+    // Whatever follows the input, which depends on the command: the arguments passed to a
+    // program, the output of `compile` or the other paths given to `test`.
+    pub rest: Vec<String>
+}
+
+fn to_input(arg: &str) -> Input {
+    if arg == "-" {
+        return Input::Stdin;
+    }
+
+    return Input::File(arg.to_owned());
+}
+
+// Parse the arguments the process was started with, not including its own path.
+pub fn parse(args: &[String]) -> Result<Options, String> {
+    let mut command: Option<Command> = None;
+    let mut backend: Backend = Backend::VM;
+    let mut format_mode: FormatMode = FormatMode::Print;
+    let mut test: Option<String> = None;
+    let mut filter: Option<String> = None;
+    let mut input: Option<Input> = None;
+    let mut index: usize = 0;
+
+    // Options come before the input; everything after it belongs to the command.
+    while index < args.len() && input.is_none() {
+        let arg: &str = args[index].as_str();
+
+        index += 1;
+
+        match arg {
+            "-h" | "--help" => command = Some(Command::Help),
+            "-V" | "--version" => command = Some(Command::Version),
+            "--check" => format_mode = FormatMode::Check,
+            "--write" => format_mode = FormatMode::Write,
+            "-e" => match args.get(index) {
+                Some(code) => {
+                    input = Some(Input::Code(code.to_owned()));
+                    index += 1;
+                },
+                None => return Err("`-e` needs some code to run.".to_owned())
+            },
+            "--" => {
+                if let Some(path) = args.get(index) {
+                    input = Some(to_input(path));
+                    index += 1;
+                }
+
+                break;
+            },
+            _ if arg.starts_with("--backend=") => {
+                backend = match &arg["--backend=".len()..] {
+                    "vm" => Backend::VM,
+                    "tree" => Backend::Tree,
+                    other => {
+                        return Err("Unknown backend `".to_owned() + other +
+                            "`; expected `vm` or `tree`.");
+                    }
+                };
+            },
+            _ if arg.starts_with("--test=") => test = Some(arg["--test=".len()..].to_owned()),
+            _ if arg.starts_with("--filter=") => {
+                filter = Some(arg["--filter=".len()..].to_owned());
+            },
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err("Unknown option `".to_owned() + arg + "`.");
+            },
+            _ => match Command::from_name(arg) {
+                Some(name) if command.is_none() => command = Some(name),
+                _ => input = Some(to_input(arg))
+            }
+        }
+    }
+
+    let mut rest: Vec<String> = args[index..].to_vec();
+
+    // A `--` straight after the input only separates it from the arguments.
+    if rest.first().is_some_and(|arg| arg == "--") {
+        rest.remove(0);
+    }
+
+    // Without any input, the REPL is started.
+    let command: Command = command.unwrap_or(if input.is_none() && rest.is_empty() {
+        Command::Repl
+    } else {
+        Command::Run
+    });
+
+    let valid: bool = match command {
+        Command::Run => input.is_some(),
+        Command::Compile => input.is_some() && rest.len() <= 1,
+        // Only checking and rewriting can go through several paths.
+        Command::Format => input.is_some() && (rest.is_empty() || format_mode != FormatMode::Print),
+        Command::Test | Command::Help | Command::Version => true,
+        Command::Repl | Command::Lsp => input.is_none() && rest.is_empty(),
+        _ => input.is_some() && rest.is_empty()
+    };
+
+    if !valid {
+        return Err("Wrong arguments for this command.".to_owned());
+    }
+
+    if format_mode != FormatMode::Print && command != Command::Format {
+        return Err("`--check` and `--write` can only be used with `fmt`.".to_owned());
+    }
+
+    if test.is_some() && command != Command::Run {
+        return Err("`--test` can only be used with `run`.".to_owned());
+    }
+
+    if filter.is_some() && command != Command::Test {
+        return Err("`--filter` can only be used with `test`.".to_owned());
+    }
+
+    return Ok(Options {
+        command,
+        backend,
+        format_mode,
+        test,
+        filter,
+        input,
+        rest
+    });
+}
@@ -0,0 +1,469 @@
+// The commands of the command line interface, which read programs and run them on a backend.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::env;
+use std::process;
+use std::process::Output;
+use std::thread;
+use crate::ast::describe_statements;
+use crate::bytecode::CompiledProgram;
+use crate::{cli, keywords, rbc, SOURCE_EXTENSION};
+use crate::cli::{Backend, Command, FormatMode, Input, Options, USAGE};
+use crate::compiler::Compiler;
+use crate::disassembler::disassemble;
+use crate::formatter::Formatter;
+use crate::interpreter::Interpreter;
+use crate::lexer::Lexer;
+use crate::lsp::LanguageServer;
+use crate::optimizer::Optimizer;
+use crate::parser::Parser;
+use crate::program::{CompileResult, Program, SourceFile};
+use crate::repl::Repl;
+use crate::resolver::{Resolution, Resolver};
+use crate::statements::Statement;
+use crate::testing::{lower_tests, test_names};
+use crate::token::Token;
+use crate::type_checker::TypeChecker;
+use crate::vm::VM;
+
+// The stack size of the thread programs are run on. The tree walker may use half of it, which
+// leaves it limited by its number of calls rather than by the stack.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+pub const STACK_LIMIT: usize = STACK_SIZE / 2;
+
+// The extension of files holding compiled bytecode.
+const COMPILED_EXTENSION: &str = "rbc";
+
+fn is_compiled(input: &Input) -> bool {
+    return match input {
+        Input::File(path) => {
+            Path::new(path).extension().is_some_and(|extension| extension == COMPILED_EXTENSION)
+        },
+        _ => false
+    };
+}
+
+// Stop with an error which is not about a position in a program.
+fn fail(message: String) -> ! {
+    eprintln!("{}", message);
+
+    process::exit(1);
+}
+
+// What lexing, parsing or checking a program gave, stopping with the error found if there was one.
+fn or_die<T>(program: &Program, result: CompileResult<T>) -> T {
+    return match result {
+        Ok(value) => value,
+        Err((message, position)) => program.die_with_error(message, position)
+    };
+}
+
+// Read the source of a program, stopping with an error if it cannot be read.
+fn read_source(input: &Input) -> String {
+    if is_compiled(input) {
+        fail("Compiled programs can only be run or disassembled.".to_owned());
+    }
+
+    let source: io::Result<String> = match input {
+        Input::File(path) => fs::read_to_string(path),
+        Input::Stdin => io::read_to_string(io::stdin()),
+        Input::Code(code) => Ok(code.to_owned())
+    };
+
+    return match source {
+        Ok(source) => source,
+        Err(error) => fail("Could not read `".to_owned() + &input.to_string() + "`: " +
+            &error.to_string())
+    };
+}
+
+// Make a program from the source of its input, which imports are found relative to.
+fn load_program(input: &Input) -> Program {
+    let mut program: Program = Program::new(keywords(), read_source(input));
+
+    if let Input::File(path) = input {
+        program.set_path(path.to_owned());
+    }
+
+    program.set_stack_limit(STACK_LIMIT);
+
+    return program;
+}
+
+// Lex and parse the source of a program.
+pub fn parse(program: &mut Program) -> CompileResult<Vec<Statement>> {
+    let mut lexer: Lexer = program.create_lexer();
+    let tokens: Vec<Token> = lexer.lex()?;
+
+    program.set_tokens(tokens);
+
+    let mut parser: Parser = program.create_parser();
+    return parser.parse();
+}
+
+// Lex and parse the source of a program along with the modules it imports, then type check and
+// optimize it. The test given, if any, is run after the top level of the program.
+fn analyze(program: &mut Program, test: Option<&str>) -> CompileResult<Vec<Statement>> {
+    let statements: Vec<Statement> = parse(program)?;
+
+    if let Some(test) = test {
+        if !test_names(&statements).iter().any(|name| name == test) {
+            fail("There is no test named `".to_owned() + test + "`.");
+        }
+    }
+
+    let statements: Vec<Statement> = lower_tests(statements, test)?;
+    let statements: Vec<Statement> = program.create_module_loader().load(statements)?;
+
+    let mut type_checker: TypeChecker = program.create_type_checker();
+    let statements: Vec<Statement> = type_checker.check(statements)?;
+
+    let resolution: Resolution = Resolver::new().resolve(&statements);
+
+    for (message, position) in resolution.warnings {
+        program.warn(message, position);
+    }
+
+    let mut optimizer: Optimizer = program.create_optimizer();
+    return optimizer.optimize(statements);
+}
+
+// Load a program as bytecode, either by compiling its source or by reading a `.rbc` file, along
+// with its source files.
+fn load_compiled(input: &Input, test: Option<&str>) -> (CompiledProgram, Vec<SourceFile>) {
+    if let (true, Input::File(path)) = (is_compiled(input), input) {
+        if test.is_some() {
+            fail("Tests can only be run from source files.".to_owned());
+        }
+
+        let bytes: Vec<u8> = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(error) => fail("Could not read `".to_owned() + path + "`: " + &error.to_string())
+        };
+
+        return match rbc::read(&bytes) {
+            Ok(loaded) => loaded,
+            Err(message) => fail("Could not load `".to_owned() + path + "`: " + &message)
+        };
+    }
+
+    let mut program: Program = load_program(input);
+    let analyzed: CompileResult<Vec<Statement>> = analyze(&mut program, test);
+    let statements: Vec<Statement> = or_die(&program, analyzed);
+
+    return (Compiler::new().compile(&statements), program.files().to_vec());
+}
+
+fn run_program(input: &Input, backend: Backend, arguments: Vec<String>, test: Option<&str>) -> () {
+    match backend {
+        Backend::Tree => {
+            if is_compiled(input) {
+                fail("Compiled programs can only be run by the `vm` backend.".to_owned());
+            }
+
+            let mut program: Program = load_program(input);
+            let analyzed: CompileResult<Vec<Statement>> = analyze(&mut program, test);
+            let statements: Vec<Statement> = or_die(&program, analyzed);
+
+            program.set_arguments(arguments);
+
+            let mut interpreter: Interpreter = program.create_interpreter();
+            interpreter.interpret(&statements);
+        },
+        Backend::VM => {
+            let (compiled, files) = load_compiled(input, test);
+            let mut program: Program = Program::from_files(keywords(), files);
+
+            program.set_arguments(arguments);
+
+            let mut vm: VM = program.create_vm(compiled);
+            vm.run();
+        }
+    }
+}
+
+fn check_program(input: &Input) -> () {
+    let mut program: Program = load_program(input);
+    let analyzed: CompileResult<Vec<Statement>> = analyze(&mut program, None);
+
+    or_die(&program, analyzed);
+}
+
+fn print_tokens(input: &Input) -> () {
+    let program: Program = Program::new(keywords(), read_source(input));
+    let tokens: CompileResult<Vec<Token>> = program.create_lexer().lex();
+
+    for token in or_die(&program, tokens) {
+        println!("{}", token.to_string());
+    }
+}
+
+fn print_ast(input: &Input) -> () {
+    let mut program: Program = Program::new(keywords(), read_source(input));
+    let statements: CompileResult<Vec<Statement>> = parse(&mut program);
+
+    print!("{}", describe_statements(&or_die(&program, statements)));
+}
+
+// Format the source of a program, keeping its comments and blank lines.
+fn format_source(source: String) -> String {
+    let mut program: Program = Program::new(keywords(), source);
+    let mut lexer: Lexer = program.create_lexer();
+
+    lexer.set_keep_trivia(true);
+
+    let tokens: CompileResult<Vec<Token>> = lexer.lex();
+    let tokens: Vec<Token> = or_die(&program, tokens);
+
+    program.set_tokens(tokens);
+
+    let statements: CompileResult<Vec<Statement>> = program.create_parser().parse();
+    let statements: Vec<Statement> = or_die(&program, statements);
+    let mut formatter: Formatter = program.create_formatter();
+
+    return formatter.format(&statements);
+}
+
+// Print a formatted program, or check or rewrite every program in the given paths, looking
+// through directories recursively.
+fn format_programs(input: &Input, paths: Vec<String>, mode: FormatMode) -> () {
+    let path: &String = match (input, mode) {
+        (_, FormatMode::Print) => {
+            print!("{}", format_source(read_source(input)));
+
+            return;
+        },
+        (Input::File(path), _) => path,
+        (_, FormatMode::Check) => {
+            let source: String = read_source(input);
+
+            if format_source(source.to_owned()) != source {
+                println!("{}", input.to_string());
+                process::exit(1);
+            }
+
+            return;
+        },
+        (_, FormatMode::Write) => fail("Only files can be formatted in place.".to_owned())
+    };
+    let mut programs: Vec<PathBuf> = Vec::new();
+    let mut unformatted: usize = 0;
+
+    for path in [path.to_owned()].iter().chain(&paths) {
+        find_programs(Path::new(path), &mut programs);
+    }
+
+    for program in programs {
+        let name: String = program.display().to_string();
+        let source: String = read_source(&Input::File(name.to_owned()));
+        let formatted: String = format_source(source.to_owned());
+
+        if formatted == source {
+            continue;
+        }
+
+        unformatted += 1;
+        println!("{}", name);
+
+        if mode == FormatMode::Write {
+            if let Err(error) = fs::write(&program, formatted) {
+                fail("Could not write `".to_owned() + &name + "`: " + &error.to_string());
+            }
+        }
+    }
+
+    // Checking fails when anything would change, so that it can guard a build.
+    if mode == FormatMode::Check && unformatted > 0 {
+        process::exit(1);
+    }
+}
+
+fn compile_program(input: &Input, output: Option<String>) -> () {
+    let (compiled, files) = load_compiled(input, None);
+    let output: PathBuf = match (output, input) {
+        (Some(output), _) => PathBuf::from(output),
+        (None, Input::File(path)) => Path::new(path).with_extension(COMPILED_EXTENSION),
+        (None, _) => fail("Compiling code which is not in a file needs an output path.".to_owned())
+    };
+
+    if let Err(error) = fs::write(&output, rbc::write(&compiled, &files)) {
+        fail("Could not write `".to_owned() + &output.display().to_string() + "`: " +
+            &error.to_string());
+    }
+}
+
+fn disassemble_program(input: &Input) -> () {
+    let (compiled, _) = load_compiled(input, None);
+
+    print!("{}", disassemble(&compiled));
+}
+
+// Find the programs in a path, looking through directories recursively.
+fn find_programs(path: &Path, programs: &mut Vec<PathBuf>) -> () {
+    if !path.is_dir() {
+        programs.push(path.to_owned());
+
+        return;
+    }
+
+    let entries: fs::ReadDir = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(error) => fail("Could not read `".to_owned() + &path.display().to_string() + "`: " +
+            &error.to_string())
+    };
+    let mut paths: Vec<PathBuf> = entries.filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+
+    paths.sort();
+
+    for path in paths {
+        let is_source: bool = path.extension()
+            .is_some_and(|extension| extension == SOURCE_EXTENSION);
+
+        if path.is_dir() || is_source {
+            find_programs(&path, programs);
+        }
+    }
+}
+
+// The tests declared by a program, found by parsing it, or `None` when it cannot be read or parsed.
+fn find_tests(path: &Path) -> Option<Vec<String>> {
+    let source: String = fs::read_to_string(path).ok()?;
+    let mut program: Program = Program::new(keywords(), source);
+
+    return parse(&mut program).ok().map(|statements| test_names(&statements));
+}
+
+// Run each test of the programs in the given paths in a process of its own, reporting those which
+// stop with an error. Programs without tests are skipped rather than run, since they may be scripts
+// which do anything at all, while programs which cannot be parsed are run whole to report why,
+// even when the tests are filtered.
+fn run_tests(paths: Vec<String>, backend: Backend, filter: Option<&str>) -> () {
+    let paths: Vec<String> = if paths.is_empty() { vec![".".to_owned()] } else { paths };
+    let mut programs: Vec<PathBuf> = Vec::new();
+    let executable: PathBuf = match env::current_exe() {
+        Ok(executable) => executable,
+        Err(error) => fail("Could not start the tests: ".to_owned() + &error.to_string())
+    };
+    let backend: &str = match backend {
+        Backend::Tree => "--backend=tree",
+        Backend::VM => "--backend=vm"
+    };
+
+    for path in paths {
+        find_programs(Path::new(&path), &mut programs);
+    }
+
+    let mut passed: usize = 0;
+    let mut failed: usize = 0;
+
+    for program in &programs {
+        // Each run is named, and may be of a single test.
+        let runs: Vec<(String, Option<String>)> = match find_tests(program) {
+            Some(tests) if tests.is_empty() => {
+                if filter.is_none() {
+                    println!("SKIP {} (no tests)", program.display());
+                }
+
+                continue;
+            },
+            Some(tests) => tests.into_iter()
+                .filter(|test| filter.is_none_or(|filter| test.contains(filter)))
+                .map(|test| (program.display().to_string() + ": " + &test, Some(test)))
+                .collect(),
+            None => vec![(program.display().to_string(), None)]
+        };
+
+        for (name, test) in runs {
+            let mut command: process::Command = process::Command::new(&executable);
+
+            command.arg(backend);
+
+            if let Some(test) = test {
+                command.arg("--test=".to_owned() + &test);
+            }
+
+            let output: Output = match command.args(["run", "--"]).arg(program).output() {
+                Ok(output) => output,
+                Err(error) => fail("Could not run `".to_owned() + &program.display().to_string() +
+                    "`: " + &error.to_string())
+            };
+
+            if output.status.success() {
+                passed += 1;
+                println!("PASS {}", name);
+            } else {
+                failed += 1;
+                println!("FAIL {}", name);
+
+                for line in String::from_utf8_lossy(&output.stderr).lines() {
+                    println!("    {}", line);
+                }
+            }
+        }
+    }
+
+    println!("\n{} passed, {} failed.", passed, failed);
+
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+
+fn execute(options: Options) -> () {
+    let input: Option<Input> = options.input;
+    let mut rest: Vec<String> = options.rest;
+
+    match (options.command, input) {
+        (Command::Help, _) => println!("{}", USAGE),
+        (Command::Version, _) => println!("ribbit-rs {}", env!("CARGO_PKG_VERSION")),
+        (Command::Repl, _) => Repl::new().start(),
+        (Command::Lsp, _) => LanguageServer::new().start(),
+        (Command::Test, input) => {
+            match input {
+                Some(Input::File(path)) => rest.insert(0, path),
+                Some(_) => fail("Tests can only be run from files.".to_owned()),
+                None => {}
+            }
+
+            run_tests(rest, options.backend, options.filter.as_deref());
+        },
+        (command, Some(input)) => match command {
+            Command::Run => run_program(&input, options.backend, rest, options.test.as_deref()),
+            Command::Check => check_program(&input),
+            Command::Format => format_programs(&input, rest, options.format_mode),
+            Command::Tokens => print_tokens(&input),
+            Command::Ast => print_ast(&input),
+            Command::Compile => compile_program(&input, rest.pop()),
+            Command::Disassemble => disassemble_program(&input),
+            _ => unreachable!()
+        },
+        (_, None) => unreachable!()
+    }
+}
+
+// Run the command line interface with the given arguments, leaving out the name of the program.
+pub fn start(args: Vec<String>) -> () {
+    let options: Options = match cli::parse(&args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+
+            process::exit(1);
+        }
+    };
+
+    // The interpreter recurses for every nested call, so everything runs on a thread with a larger
+    // stack than the main one.
+    let runner: thread::JoinHandle<()> = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || execute(options))
+        .unwrap();
+
+    if runner.join().is_err() {
+        process::exit(101);
+    }
+}
@@ -0,0 +1,1168 @@
+use std::collections::{HashMap, HashSet};
+use crate::bytecode::{ClassProto, CompiledProgram, Constant, FunctionProto, Instruction};
+use crate::expressions::{AssignmentExpression, BinaryOperator, Expression, FunctionCallExpression,
+                         LiteralValue, MatchArmBody, MatchExpression, PropertyExpression};
+use crate::natives::{find_module_native, find_native, is_built_in_enum, EXPECT_ERROR};
+use crate::patterns::Pattern;
+use crate::position::Position;
+use crate::statements::{ClassDeclarationStatement, ForInStatement, ForStatement,
+                        FunctionDeclarationStatement, Statement, TryStatement};
+
+// Where the value of a variable is kept.
+#[derive(Clone, Copy)]
+enum Variable {
+    Local(usize),
+    Global(usize)
+}
+
+// The jumps out of a loop which are waiting for their targets to be known.
+struct LoopState {
+    depth: isize, // The height of the stack when the loop started.
+    handlers: usize, // The number of error handlers when the loop started.
+    finally_blocks: usize, // The number of finally blocks around the loop.
+    breaks: Vec<usize>,
+    continues: Vec<usize>
+}
+
+// A finally block which has to run before leaving the `try` it belongs to by a `return`, `break` or
+// `continue`, which happens by compiling it again before the jump.
+#[derive(Clone)]
+struct FinallyState {
+    body: Vec<Statement>,
+    handlers: usize // The number of error handlers outside of the `try`.
+}
+
+// The state of the function currently being compiled.
+struct FunctionState {
+    code: Vec<Instruction>,
+    positions: Vec<Position>,
+    scopes: Vec<Vec<(String, usize)>>,
+    slot_count: usize, // The number of slots used by the variables currently in scope.
+    local_count: usize, // The most slots that have been in use at once.
+    arity: usize, // The number of slots taken by the arguments.
+    depth: isize, // The height of the stack, relative to the start of the call.
+    handlers: usize, // The number of error handlers pushed by the function which are in effect.
+    loops: Vec<LoopState>,
+    finally_blocks: Vec<FinallyState>, // Those around the code being compiled, innermost last.
+    top_level: bool // Variables in the outermost scope of the top level are globals.
+}
+
+impl FunctionState {
+    fn new(top_level: bool) -> Self {
+        return Self {
+            code: Vec::new(),
+            positions: Vec::new(),
+            scopes: vec![Vec::new()],
+            slot_count: 0,
+            local_count: 0,
+            arity: 0,
+            depth: 0,
+            handlers: 0,
+            loops: Vec::new(),
+            finally_blocks: Vec::new(),
+            top_level
+        };
+    }
+}
+
+// What the compiler knows about a class before compiling it.
+struct ClassInfo {
+    index: usize,
+    superclass: Option<String>,
+    methods: HashMap<String, usize>
+}
+
+// Lowers a type checked program to bytecode for the virtual machine.
+pub struct Compiler {
+    constants: Vec<Constant>,
+    functions: Vec<FunctionProto>,
+    classes: Vec<ClassProto>,
+    globals: Vec<String>,
+    global_slots: HashMap<String, usize>,
+    function_indices: HashMap<String, usize>,
+    class_info: HashMap<String, ClassInfo>,
+    enums: HashSet<String>,
+    function: FunctionState,
+    current_class: Option<String> // The class whose methods are being compiled, used by `super`.
+}
+
+impl Compiler {
+    pub fn new() -> Self {
+        return Self {
+            constants: Vec::new(),
+            functions: Vec::new(),
+            classes: Vec::new(),
+            globals: Vec::new(),
+            global_slots: HashMap::new(),
+            function_indices: HashMap::new(),
+            class_info: HashMap::new(),
+            enums: HashSet::new(),
+            function: FunctionState::new(true),
+            current_class: None
+        };
+    }
+
+    pub fn compile(mut self, statements: &[Statement]) -> CompiledProgram {
+        // Functions, classes and enums can be used before they are declared, so they are given
+        // their indices up front.
+        for statement in statements {
+            match statement {
+                Statement::FunctionDeclaration(declaration) => {
+                    let name: String = declaration.name.token.value();
+                    let index: usize = self.reserve_function(name.to_owned());
+
+                    self.function_indices.insert(name, index);
+                },
+                Statement::ClassDeclaration(declaration) => self.reserve_class(declaration),
+                Statement::EnumDeclaration(declaration) => {
+                    self.enums.insert(declaration.name.token.value());
+                },
+                _ => {}
+            }
+        }
+
+        for class in self.classes.iter_mut() {
+            class.superclass = self.class_info[&class.name].superclass.as_ref()
+                .map(|superclass| self.class_info[superclass].index);
+        }
+
+        let main: usize = self.reserve_function("<script>".to_owned());
+        let position: Position = Position::new_single(1, 1);
+
+        for statement in statements {
+            self.compile_statement(statement);
+        }
+
+        self.emit(Instruction::Void, position);
+        self.emit(Instruction::Return, position);
+
+        let state: FunctionState = std::mem::replace(&mut self.function, FunctionState::new(false));
+
+        self.finish_function(main, state);
+
+        return CompiledProgram {
+            constants: self.constants,
+            functions: self.functions,
+            classes: self.classes,
+            globals: self.globals,
+            main
+        };
+    }
+
+    fn reserve_function(&mut self, name: String) -> usize {
+        self.functions.push(FunctionProto {
+            name,
+            arity: 0,
+            local_count: 0,
+            code: Vec::new(),
+            positions: Vec::new()
+        });
+
+        return self.functions.len() - 1;
+    }
+
+    fn reserve_class(&mut self, declaration: &ClassDeclarationStatement) -> () {
+        let name: String = declaration.name.token.value();
+        let initializer: usize = self.reserve_function(name.to_owned() + ".<init>");
+        let constructor: Option<usize> = declaration.constructor.as_ref()
+            .map(|_| self.reserve_function(name.to_owned()));
+        let mut methods: HashMap<String, usize> = HashMap::new();
+
+        for method in &declaration.methods {
+            let method_name: String = method.name.token.value();
+            let index: usize = self.reserve_function(name.to_owned() + "." + &method_name);
+
+            methods.insert(method_name, index);
+        }
+
+        self.classes.push(ClassProto {
+            name: name.to_owned(),
+            superclass: None,
+            initializer,
+            constructor,
+            methods: declaration.methods.iter()
+                .map(|method| {
+                    let method_name: String = method.name.token.value();
+
+                    (method_name.to_owned(), methods[&method_name])
+                })
+                .collect()
+        });
+
+        self.class_info.insert(name, ClassInfo {
+            index: self.classes.len() - 1,
+            superclass: declaration.superclass.as_ref()
+                .map(|superclass| superclass.token.value()),
+            methods
+        });
+    }
+
+    fn finish_function(&mut self, index: usize, state: FunctionState) -> () {
+        let proto: &mut FunctionProto = &mut self.functions[index];
+
+        proto.arity = state.arity;
+        proto.code = state.code;
+        proto.positions = state.positions;
+        // Variables declared without a value and never used need no slot after the others.
+        proto.local_count = state.local_count.min(proto.used_slots().max(state.arity));
+    }
+
+    fn emit(&mut self, instruction: Instruction, position: Position) -> usize {
+        self.function.code.push(instruction);
+        self.function.positions.push(position);
+        self.function.depth += instruction.stack_effect();
+
+        return self.function.code.len() - 1;
+    }
+
+    // Point a previously emitted jump at the next instruction to be emitted.
+    fn patch_jump(&mut self, jump: usize) -> () {
+        let target: usize = self.function.code.len();
+
+        self.function.code[jump] = match self.function.code[jump] {
+            Instruction::Jump(_) => Instruction::Jump(target),
+            Instruction::JumpIfFalse(_) => Instruction::JumpIfFalse(target),
+            Instruction::JumpIfFalseOrPop(_) => Instruction::JumpIfFalseOrPop(target),
+            Instruction::JumpIfTrueOrPop(_) => Instruction::JumpIfTrueOrPop(target),
+            Instruction::JumpIfNull(_) => Instruction::JumpIfNull(target),
+            Instruction::JumpIfNotNull(_) => Instruction::JumpIfNotNull(target),
+            Instruction::IterNext(_, count) => Instruction::IterNext(target, count),
+            Instruction::PushHandler(_) => Instruction::PushHandler(target),
+            Instruction::JumpIfPresent(_) => Instruction::JumpIfPresent(target),
+            _ => unreachable!()
+        };
+    }
+
+    fn constant(&mut self, constant: Constant) -> usize {
+        if let Some(index) = self.constants.iter().position(|existing| *existing == constant) {
+            return index;
+        }
+
+        self.constants.push(constant);
+
+        return self.constants.len() - 1;
+    }
+
+    fn name(&mut self, name: String) -> usize {
+        return self.constant(Constant::String(name));
+    }
+
+    fn push_scope(&mut self) -> () {
+        self.function.scopes.push(Vec::new());
+    }
+
+    fn pop_scope(&mut self) -> () {
+        let scope: Vec<(String, usize)> = self.function.scopes.pop().unwrap();
+
+        self.function.slot_count -= scope.len();
+    }
+
+    fn declare_variable(&mut self, name: String) -> Variable {
+        if self.function.top_level && self.function.scopes.len() == 1 {
+            self.globals.push(name.to_owned());
+            self.global_slots.insert(name, self.globals.len() - 1);
+
+            return Variable::Global(self.globals.len() - 1);
+        }
+
+        let slot: usize = self.function.slot_count;
+
+        self.function.scopes.last_mut().unwrap().push((name, slot));
+        self.function.slot_count += 1;
+        self.function.local_count = self.function.local_count.max(self.function.slot_count);
+
+        return Variable::Local(slot);
+    }
+
+    fn find_variable(&self, name: &str) -> Option<Variable> {
+        for scope in self.function.scopes.iter().rev() {
+            if let Some((_, slot)) = scope.iter().rev().find(|(other, _)| other == name) {
+                return Some(Variable::Local(*slot));
+            }
+        }
+
+        return self.global_slots.get(name).map(|slot| Variable::Global(*slot));
+    }
+
+    fn set_variable(&mut self, variable: Variable, position: Position) -> () {
+        match variable {
+            Variable::Local(slot) => self.emit(Instruction::SetLocal(slot), position),
+            Variable::Global(slot) => self.emit(Instruction::SetGlobal(slot), position)
+        };
+    }
+
+    // Compile a function, method or constructor. Methods receive the instance as their first
+    // argument.
+    fn compile_function(&mut self, index: usize, declaration: &FunctionDeclarationStatement,
+                        method: bool) -> () {
+        let enclosing: FunctionState = std::mem::replace(&mut self.function,
+                                                         FunctionState::new(false));
+        let position: Position = declaration.name.token.token_position;
+
+        if method {
+            self.declare_variable(String::new());
+        }
+
+        for parameter in &declaration.parameters {
+            self.declare_variable(parameter.name.value());
+        }
+
+        self.function.arity = self.function.slot_count;
+
+        self.compile_block(&declaration.body);
+        self.emit(Instruction::Void, position);
+        self.emit(Instruction::Return, position);
+
+        let state: FunctionState = std::mem::replace(&mut self.function, enclosing);
+
+        self.finish_function(index, state);
+    }
+
+    fn compile_class(&mut self, declaration: &ClassDeclarationStatement) -> () {
+        let name: String = declaration.name.token.value();
+        let class: &ClassProto = &self.classes[self.class_info[&name].index];
+        let initializer: usize = class.initializer;
+        let constructor: Option<usize> = class.constructor;
+        let methods: Vec<(String, usize)> = class.methods.to_owned();
+
+        // Property initializers can see the properties declared before them, but not `this`,
+        // which is only used to store the values.
+        let enclosing: FunctionState = std::mem::replace(&mut self.function,
+                                                         FunctionState::new(false));
+
+        self.declare_variable(String::new());
+        self.function.arity = 1;
+
+        for property in &declaration.properties {
+            let property_name: String = property.identifier.token.value();
+            let position: Position = property.identifier.token.token_position;
+
+            if let Some(initialization) = &property.initialization {
+                self.compile_expression(initialization);
+
+                let variable: Variable = self.declare_variable(property_name.to_owned());
+                let name: usize = self.name(property_name);
+
+                self.set_variable(variable, position);
+                self.emit(Instruction::GetLocal(0), position);
+                self.emit(Instruction::SetField(name), position);
+                self.emit(Instruction::Pop, position);
+            } else {
+                self.declare_variable(property_name);
+            }
+        }
+
+        let position: Position = declaration.name.token.token_position;
+
+        self.emit(Instruction::Void, position);
+        self.emit(Instruction::Return, position);
+
+        let state: FunctionState = std::mem::replace(&mut self.function, enclosing);
+
+        self.finish_function(initializer, state);
+        self.current_class = Some(name);
+
+        if let (Some(index), Some(declaration)) = (constructor, &declaration.constructor) {
+            self.compile_function(index, declaration, true);
+        }
+
+        for (method, (_, index)) in declaration.methods.iter().zip(methods) {
+            self.compile_function(index, method, true);
+        }
+
+        self.current_class = None;
+    }
+
+    fn compile_block(&mut self, statements: &[Statement]) -> () {
+        self.push_scope();
+
+        for statement in statements {
+            self.compile_statement(statement);
+        }
+
+        self.pop_scope();
+    }
+
+    // Compile the body of a loop, whose `continue`s jump to the given target if it is known.
+    fn compile_loop_body(&mut self, body: &[Statement]) -> () {
+        self.function.loops.push(LoopState {
+            depth: self.function.depth,
+            handlers: self.function.handlers,
+            finally_blocks: self.function.finally_blocks.len(),
+            breaks: Vec::new(),
+            continues: Vec::new()
+        });
+
+        self.compile_block(body);
+    }
+
+    // Finish a loop, pointing its `break`s at the next instruction to be emitted.
+    fn patch_breaks(&mut self, loop_state: LoopState) -> () {
+        for jump in loop_state.breaks {
+            self.patch_jump(jump);
+        }
+    }
+
+    fn patch_continues(&mut self, loop_state: &mut LoopState, target: usize) -> () {
+        for jump in loop_state.continues.drain(..) {
+            self.function.code[jump] = Instruction::Jump(target);
+        }
+    }
+
+    // Leave the innermost loop, discarding whatever the loop left on the stack, along with the
+    // error handlers pushed inside of it.
+    fn compile_loop_exit(&mut self, is_break: bool, position: Position) -> () {
+        let loop_state: &LoopState = self.function.loops.last().unwrap();
+        let (loop_depth, loop_handlers): (isize, usize) = (loop_state.depth, loop_state.handlers);
+        let depth: isize = self.function.depth;
+        let handlers: usize = self.function.handlers;
+
+        self.compile_finally_blocks(loop_state.finally_blocks, position);
+
+        for _ in loop_depth..depth {
+            self.emit(Instruction::Pop, position);
+        }
+
+        for _ in loop_handlers..self.function.handlers {
+            self.emit(Instruction::PopHandler, position);
+        }
+
+        self.function.handlers = handlers;
+
+        let jump: usize = self.emit(Instruction::Jump(0), position);
+        let loop_state: &mut LoopState = self.function.loops.last_mut().unwrap();
+
+        if is_break {
+            loop_state.breaks.push(jump);
+        } else {
+            loop_state.continues.push(jump);
+        }
+
+        // The code after the jump is unreachable, and is compiled as if the jump was not there.
+        self.function.depth = depth;
+    }
+
+    fn compile_statement(&mut self, statement: &Statement) -> () {
+        match statement {
+            Statement::Expression(expression_statement) => {
+                let position: Position = expression_statement.expression.position();
+
+                self.compile_expression(&expression_statement.expression);
+                self.emit(Instruction::Pop, position);
+            },
+            Statement::VariableDeclaration(declaration) => {
+                let position: Position = declaration.identifier.token.token_position;
+
+                match &declaration.initialization {
+                    Some(initialization) => {
+                        self.compile_expression(initialization);
+
+                        let variable: Variable = self.declare_variable(
+                            declaration.identifier.token.value()
+                        );
+
+                        self.set_variable(variable, position);
+                        self.emit(Instruction::Pop, position);
+                    },
+                    None => {
+                        self.declare_variable(declaration.identifier.token.value());
+                    }
+                }
+            },
+            Statement::Block(block) => self.compile_block(&block.statements),
+            Statement::If(if_statement) => {
+                let position: Position = if_statement.condition.position();
+
+                self.compile_expression(&if_statement.condition);
+
+                let else_jump: usize = self.emit(Instruction::JumpIfFalse(0), position);
+
+                self.compile_block(&if_statement.then_branch);
+
+                let end_jump: usize = self.emit(Instruction::Jump(0), position);
+
+                self.patch_jump(else_jump);
+                self.compile_block(&if_statement.else_branch);
+                self.patch_jump(end_jump);
+            },
+            Statement::While(while_statement) => {
+                let position: Position = while_statement.condition.position();
+                let start: usize = self.function.code.len();
+
+                self.compile_expression(&while_statement.condition);
+
+                let exit_jump: usize = self.emit(Instruction::JumpIfFalse(0), position);
+
+                self.compile_loop_body(&while_statement.body);
+
+                let mut loop_state: LoopState = self.function.loops.pop().unwrap();
+
+                self.patch_continues(&mut loop_state, start);
+                self.emit(Instruction::Jump(start), position);
+                self.patch_jump(exit_jump);
+                self.patch_breaks(loop_state);
+            },
+            Statement::Loop(loop_statement) => {
+                let start: usize = self.function.code.len();
+
+                self.compile_loop_body(&loop_statement.body);
+
+                // Loops have no position of their own, so the jump back shares the position of
+                // the end of the body.
+                let position: Position = self.function.positions.last().copied()
+                    .unwrap_or(Position::new_single(1, 1));
+                let mut loop_state: LoopState = self.function.loops.pop().unwrap();
+
+                self.patch_continues(&mut loop_state, start);
+                self.emit(Instruction::Jump(start), position);
+                self.patch_breaks(loop_state);
+            },
+            Statement::For(for_statement) => {
+                self.push_scope();
+                self.compile_for(for_statement);
+                self.pop_scope();
+            },
+            Statement::ForIn(for_in_statement) => self.compile_for_in(for_in_statement),
+            Statement::Break(break_statement) => {
+                self.compile_loop_exit(true, break_statement.position);
+            },
+            Statement::Continue(continue_statement) => {
+                self.compile_loop_exit(false, continue_statement.position);
+            },
+            Statement::Return(return_statement) => {
+                let position: Position = return_statement.position;
+                let depth: isize = self.function.depth;
+
+                let handlers: usize = self.function.handlers;
+
+                match &return_statement.value {
+                    Some(value) => self.compile_expression(value),
+                    None => {
+                        self.emit(Instruction::Void, position);
+                    }
+                }
+
+                self.compile_finally_blocks(0, position);
+                self.emit(Instruction::Return, position);
+                self.function.depth = depth;
+                self.function.handlers = handlers;
+            },
+            Statement::FunctionDeclaration(declaration) => {
+                let index: usize = self.function_indices[&declaration.name.token.value()];
+
+                self.compile_function(index, declaration, false);
+            },
+            Statement::ClassDeclaration(declaration) => self.compile_class(declaration),
+            // Interfaces only matter to the type checker.
+            Statement::InterfaceDeclaration(_) | Statement::EnumDeclaration(_) => {},
+            Statement::Throw(throw_statement) => {
+                let depth: isize = self.function.depth;
+
+                self.compile_expression(&throw_statement.value);
+                self.emit(Instruction::Throw, throw_statement.position);
+                self.function.depth = depth;
+            },
+            Statement::Try(try_statement) => self.compile_try(try_statement),
+            Statement::Import(_) | Statement::Test(_) | Statement::Trivia(_) => unreachable!()
+        }
+    }
+
+    // Compile a `try`, whose handlers are given the error object on top of the stack. Errors
+    // which none of the catches are for, and those escaping the catches, go through a second
+    // handler when there is a finally block, which runs it before throwing them again.
+    fn compile_try(&mut self, try_statement: &TryStatement) -> () {
+        let position: Position = try_statement.position;
+        let finally: &Vec<Statement> = match &try_statement.finally {
+            Some(finally) => finally,
+            None => {
+                self.compile_catches(try_statement);
+
+                return;
+            }
+        };
+
+        self.function.finally_blocks.push(FinallyState {
+            body: finally.to_owned(),
+            handlers: self.function.handlers
+        });
+
+        let handler: usize = self.emit(Instruction::PushHandler(0), position);
+
+        self.function.handlers += 1;
+
+        if try_statement.catches.is_empty() {
+            self.compile_block(&try_statement.body);
+        } else {
+            self.compile_catches(try_statement);
+        }
+
+        self.emit(Instruction::PopHandler, position);
+        self.function.handlers -= 1;
+        self.function.finally_blocks.pop();
+        self.compile_block(finally);
+
+        let end: usize = self.emit(Instruction::Jump(0), position);
+
+        self.patch_jump(handler);
+        self.function.depth += 1;
+        self.compile_block(finally);
+        self.emit(Instruction::Throw, position);
+        self.patch_jump(end);
+    }
+
+    // Compile the body of a `try` along with its catches, which test the error against their
+    // classes in order, throwing it again if none of them is for it.
+    fn compile_catches(&mut self, try_statement: &TryStatement) -> () {
+        let position: Position = try_statement.position;
+        let handler: usize = self.emit(Instruction::PushHandler(0), position);
+
+        self.function.handlers += 1;
+        self.compile_block(&try_statement.body);
+        self.emit(Instruction::PopHandler, position);
+        self.function.handlers -= 1;
+
+        let mut ends: Vec<usize> = vec![self.emit(Instruction::Jump(0), position)];
+
+        // The error is kept in a variable that cannot be named by the program, in a scope of its
+        // own, so that it is never a global.
+        self.patch_jump(handler);
+        self.function.depth += 1;
+        self.push_scope();
+
+        let error: usize = match self.declare_variable(String::new()) {
+            Variable::Local(slot) => slot,
+            Variable::Global(_) => unreachable!()
+        };
+
+        self.emit(Instruction::SetLocal(error), position);
+        self.emit(Instruction::Pop, position);
+
+        for catch in &try_statement.catches {
+            let position: Position = catch.variable.token_position;
+            let mut next: Option<usize> = None;
+
+            if let Some(error_type) = &catch.error_type {
+                let name: usize = self.name(error_type.name.value());
+
+                self.emit(Instruction::GetLocal(error), position);
+                self.emit(Instruction::Is(name), position);
+                next = Some(self.emit(Instruction::JumpIfFalse(0), position));
+            }
+
+            self.push_scope();
+            self.emit(Instruction::GetLocal(error), position);
+
+            let variable: Variable = self.declare_variable(catch.variable.value());
+
+            self.set_variable(variable, position);
+            self.emit(Instruction::Pop, position);
+            self.compile_block(&catch.body);
+            self.pop_scope();
+            ends.push(self.emit(Instruction::Jump(0), position));
+
+            if let Some(next) = next {
+                self.patch_jump(next);
+            }
+        }
+
+        self.emit(Instruction::GetLocal(error), position);
+        self.emit(Instruction::Throw, position);
+        self.pop_scope();
+
+        for end in ends {
+            self.patch_jump(end);
+        }
+    }
+
+    // Run the finally blocks from the innermost one out to the given one before leaving them,
+    // taking down the error handlers inside of each first, so that an error in a finally block
+    // does not run it again. The caller restores the number of handlers once it has jumped.
+    fn compile_finally_blocks(&mut self, outermost: usize, position: Position) -> () {
+        let finally_blocks: Vec<FinallyState> = self.function.finally_blocks.to_owned();
+
+        for (index, finally) in finally_blocks.iter().enumerate().skip(outermost).rev() {
+            for _ in finally.handlers..self.function.handlers {
+                self.emit(Instruction::PopHandler, position);
+            }
+
+            self.function.handlers = finally.handlers;
+            self.function.finally_blocks.truncate(index);
+            self.compile_block(&finally.body);
+        }
+
+        self.function.finally_blocks = finally_blocks;
+    }
+
+    fn compile_for(&mut self, for_statement: &ForStatement) -> () {
+        let position: Position = for_statement.variable.token_position;
+
+        self.compile_expression(&for_statement.initializer);
+
+        let variable: Variable = self.declare_variable(for_statement.variable.value());
+
+        self.set_variable(variable, position);
+        self.emit(Instruction::Pop, position);
+
+        let start: usize = self.function.code.len();
+
+        self.compile_expression(&for_statement.condition);
+
+        let exit_jump: usize = self.emit(Instruction::JumpIfFalse(0), position);
+
+        self.compile_loop_body(&for_statement.body);
+
+        let mut loop_state: LoopState = self.function.loops.pop().unwrap();
+        let increment: usize = self.function.code.len();
+
+        self.patch_continues(&mut loop_state, increment);
+        self.compile_expression(&for_statement.increment);
+        self.emit(Instruction::Pop, position);
+        self.emit(Instruction::Jump(start), position);
+        self.patch_jump(exit_jump);
+        self.patch_breaks(loop_state);
+    }
+
+    fn compile_for_in(&mut self, for_in_statement: &ForInStatement) -> () {
+        let position: Position = for_in_statement.iterable.position();
+        let count: usize = for_in_statement.variables.len();
+
+        self.compile_expression(&for_in_statement.iterable);
+        self.emit(Instruction::IterStart, position);
+        self.push_scope();
+
+        let variables: Vec<Variable> = for_in_statement.variables.iter()
+            .map(|variable| self.declare_variable(variable.value()))
+            .collect();
+        let start: usize = self.emit(Instruction::IterNext(0, count), position);
+
+        // The values are pushed in order, so the last variable is assigned first.
+        for variable in variables.iter().rev() {
+            self.set_variable(*variable, position);
+            self.emit(Instruction::Pop, position);
+        }
+
+        self.compile_loop_body(&for_in_statement.body);
+
+        let mut loop_state: LoopState = self.function.loops.pop().unwrap();
+
+        self.patch_continues(&mut loop_state, start);
+        self.emit(Instruction::Jump(start), position);
+        self.pop_scope();
+
+        // Breaking out of the loop still has to finish the iteration.
+        self.patch_jump(start);
+        self.patch_breaks(loop_state);
+        self.emit(Instruction::IterEnd, position);
+    }
+
+    // If the property names a variant of an enum, such as `Shape.Circle`, return the name of the
+    // enum.
+    fn find_enum(&self, property: &PropertyExpression) -> Option<String> {
+        return match property.object.as_ref() {
+            Expression::Identifier(identifier) => {
+                let name: String = identifier.token.value();
+
+                let is_enum: bool = self.enums.contains(&name) || is_built_in_enum(&name);
+
+                if is_enum && self.find_variable(&name).is_none() {
+                    Some(name)
+                } else {
+                    None
+                }
+            },
+            _ => None
+        };
+    }
+
+    fn compile_arguments(&mut self, arguments: &[Expression]) -> () {
+        for argument in arguments {
+            self.compile_expression(argument);
+        }
+    }
+
+    fn compile_expression(&mut self, expression: &Expression) -> () {
+        let position: Position = expression.position();
+
+        match expression {
+            Expression::Literal(literal) => {
+                let constant: Constant = match &literal.value {
+                    LiteralValue::Integer(value) => Constant::Integer(*value),
+                    LiteralValue::Float(value) => Constant::Float(*value),
+                    LiteralValue::String(value) => Constant::String(value.to_owned()),
+                    LiteralValue::Char(value) => Constant::Char(*value),
+                    LiteralValue::Boolean(value) => Constant::Boolean(*value),
+                    LiteralValue::Null => {
+                        self.emit(Instruction::Null, position);
+
+                        return;
+                    }
+                };
+                let index: usize = self.constant(constant);
+
+                self.emit(Instruction::Constant(index), position);
+            },
+            Expression::Identifier(identifier) => {
+                // The type checker makes sure the variable exists.
+                match self.find_variable(&identifier.token.value()).unwrap() {
+                    Variable::Local(slot) => self.emit(Instruction::GetLocal(slot), position),
+                    Variable::Global(slot) => self.emit(Instruction::GetGlobal(slot), position)
+                };
+            },
+            Expression::Binary(binary) => {
+                self.compile_expression(&binary.left);
+
+                // `&&`, `||` and `??` only evaluate their right side when they need to.
+                let jump: Option<usize> = match binary.operator {
+                    BinaryOperator::And => {
+                        Some(self.emit(Instruction::JumpIfFalseOrPop(0), position))
+                    },
+                    BinaryOperator::Or => {
+                        Some(self.emit(Instruction::JumpIfTrueOrPop(0), position))
+                    },
+                    BinaryOperator::Coalesce => {
+                        let jump: usize = self.emit(Instruction::JumpIfNotNull(0), position);
+
+                        self.emit(Instruction::Pop, position);
+
+                        Some(jump)
+                    },
+                    _ => None
+                };
+
+                self.compile_expression(&binary.right);
+
+                match jump {
+                    Some(jump) => self.patch_jump(jump),
+                    None => {
+                        self.emit(Instruction::Binary(binary.operator), position);
+                    }
+                }
+            },
+            Expression::Unary(unary) => {
+                self.compile_expression(&unary.operand);
+                self.emit(Instruction::Unary(unary.operator), position);
+            },
+            Expression::Assignment(assignment) => self.compile_assignment(assignment),
+            Expression::FunctionCall(call) => self.compile_call(call),
+            Expression::Property(property) => {
+                if let Some(enum_name) = self.find_enum(property) {
+                    let enum_name: usize = self.name(enum_name);
+                    let variant: usize = self.name(property.name.value());
+
+                    self.emit(Instruction::MakeEnum(enum_name, variant, 0), position);
+
+                    return;
+                }
+
+                self.compile_expression(&property.object);
+
+                let jump: Option<usize> = if property.safe {
+                    Some(self.emit(Instruction::JumpIfNull(0), position))
+                } else {
+                    None
+                };
+                let name: usize = self.name(property.name.value());
+
+                self.emit(Instruction::GetField(name), position);
+
+                if let Some(jump) = jump {
+                    self.patch_jump(jump);
+                }
+            },
+            Expression::This(_) => {
+                self.emit(Instruction::GetLocal(0), position);
+            },
+            Expression::Super(_) => unreachable!(),
+            Expression::Is(is) => {
+                let class_name: usize = self.name(is.class_name.value());
+
+                self.compile_expression(&is.value);
+                self.emit(Instruction::Is(class_name), position);
+            },
+            Expression::Map(map) => {
+                for (key, value) in &map.entries {
+                    self.compile_expression(key);
+                    self.compile_expression(value);
+                }
+
+                self.emit(Instruction::Map(map.entries.len()), position);
+            },
+            Expression::List(list) => {
+                self.compile_arguments(&list.elements);
+                self.emit(Instruction::List(list.elements.len()), position);
+            },
+            Expression::Range(range) => {
+                self.compile_expression(&range.start);
+                self.compile_expression(&range.end);
+                self.emit(Instruction::Range, position);
+            },
+            Expression::Match(match_expression) => self.compile_match(match_expression),
+            Expression::Propagate(propagate) => {
+                self.compile_expression(&propagate.value);
+
+                let depth: isize = self.function.depth;
+                let handlers: usize = self.function.handlers;
+                let jump: usize = self.emit(Instruction::JumpIfPresent(0), position);
+
+                // A `Result.Err` or `Option.None` is returned as it is, as by `return`.
+                self.compile_finally_blocks(0, position);
+                self.emit(Instruction::Return, position);
+                self.function.depth = depth;
+                self.function.handlers = handlers;
+                self.patch_jump(jump);
+                self.emit(Instruction::VariantField(0), position);
+            }
+        }
+    }
+
+    fn compile_assignment(&mut self, assignment: &AssignmentExpression) -> () {
+        let position: Position = assignment.position;
+
+        self.compile_expression(&assignment.value);
+
+        if let Some(operator) = assignment.operator {
+            self.compile_expression(&assignment.target);
+            self.emit(Instruction::Swap, position);
+            self.emit(Instruction::Binary(operator), position);
+        }
+
+        match assignment.target.as_ref() {
+            Expression::Identifier(identifier) => {
+                let variable: Variable = self.find_variable(&identifier.token.value()).unwrap();
+
+                self.set_variable(variable, position);
+            },
+            Expression::Property(property) => {
+                let name: usize = self.name(property.name.value());
+
+                self.compile_expression(&property.object);
+                self.emit(Instruction::SetField(name), position);
+            },
+            _ => unreachable!()
+        }
+    }
+
+    // Evaluate an expression which has to stop with an error, and produce `void` once it does.
+    fn compile_expect_error(&mut self, expression: &Expression, position: Position) -> () {
+        let handler: usize = self.emit(Instruction::PushHandler(0), position);
+
+        self.function.handlers += 1;
+        self.compile_expression(expression);
+        self.emit(Instruction::Pop, position);
+        self.emit(Instruction::PopHandler, position);
+        self.function.handlers -= 1;
+
+        let message: usize = self.constant(Constant::String(
+            "Expected an error, but there was none.".to_owned()
+        ));
+
+        self.emit(Instruction::Constant(message), position);
+        self.emit(Instruction::Throw, position);
+
+        // The handler goes back to the height of the stack before the expression, and pushes the
+        // error, which is not needed.
+        self.patch_jump(handler);
+        self.function.depth += 1;
+        self.emit(Instruction::Pop, position);
+        self.emit(Instruction::Void, position);
+    }
+
+    fn compile_call(&mut self, call: &FunctionCallExpression) -> () {
+        let position: Position = call.position;
+        let count: usize = call.arguments.len();
+
+        match call.callee.as_ref() {
+            Expression::Identifier(identifier) => {
+                let name: String = identifier.token.value();
+
+                if name == EXPECT_ERROR && !self.function_indices.contains_key(&name)
+                    && !self.class_info.contains_key(&name) {
+                    self.compile_expect_error(&call.arguments[0], position);
+
+                    return;
+                }
+
+                self.compile_arguments(&call.arguments);
+
+                if let Some(function) = self.function_indices.get(&name) {
+                    self.emit(Instruction::Call(*function, count), position);
+                } else if let Some(class) = self.class_info.get(&name) {
+                    self.emit(Instruction::New(class.index, count), position);
+                } else {
+                    // Otherwise, this is a native function.
+                    let (native, _) = find_native(&name).unwrap();
+
+                    self.emit(Instruction::CallNative(native, count), position);
+                }
+            },
+            Expression::Property(property) => {
+                if let Some((native, _)) = find_module_native(
+                    property, |name| self.find_variable(name).is_some()
+                ) {
+                    self.compile_arguments(&call.arguments);
+                    self.emit(Instruction::CallNative(native, count), position);
+
+                    return;
+                }
+
+                if let Some(enum_name) = self.find_enum(property) {
+                    let enum_name: usize = self.name(enum_name);
+                    let variant: usize = self.name(property.name.value());
+
+                    self.compile_arguments(&call.arguments);
+                    self.emit(Instruction::MakeEnum(enum_name, variant, count), position);
+
+                    return;
+                }
+
+                self.compile_expression(&property.object);
+
+                // The arguments are not evaluated when `?.` skips the call.
+                let jump: Option<usize> = if property.safe {
+                    Some(self.emit(Instruction::JumpIfNull(0), position))
+                } else {
+                    None
+                };
+                let name: usize = self.name(property.name.value());
+
+                self.compile_arguments(&call.arguments);
+                self.emit(Instruction::CallMethod(name, count), position);
+
+                if let Some(jump) = jump {
+                    self.patch_jump(jump);
+                }
+            },
+            Expression::Super(super_expression) => {
+                // The method is known statically, from the superclass of the current class.
+                let method_name: String = super_expression.method.value();
+                let current_class: &String = self.current_class.as_ref().unwrap();
+                let mut class: Option<String> = self.class_info[current_class].superclass
+                    .to_owned();
+                let mut method: Option<usize> = None;
+
+                while let Some(name) = class {
+                    let info: &ClassInfo = &self.class_info[&name];
+
+                    if let Some(index) = info.methods.get(&method_name) {
+                        method = Some(*index);
+
+                        break;
+                    }
+
+                    class = info.superclass.to_owned();
+                }
+
+                self.emit(Instruction::GetLocal(0), position);
+                self.compile_arguments(&call.arguments);
+                self.emit(Instruction::Call(method.unwrap(), count + 1), position);
+            },
+            _ => unreachable!()
+        }
+    }
+
+    fn compile_match(&mut self, match_expression: &MatchExpression) -> () {
+        let position: Position = match_expression.position;
+
+        // The value being matched is kept in a variable that cannot be named by the program. It
+        // is declared in a scope of its own, so it is never a global.
+        self.compile_expression(&match_expression.value);
+        self.push_scope();
+
+        let slot: usize = match self.declare_variable(String::new()) {
+            Variable::Local(slot) => slot,
+            Variable::Global(_) => unreachable!()
+        };
+
+        self.emit(Instruction::SetLocal(slot), position);
+        self.emit(Instruction::Pop, position);
+
+        let depth: isize = self.function.depth;
+        let mut end_jumps: Vec<usize> = Vec::new();
+
+        for arm in &match_expression.arms {
+            let mut failure_jumps: Vec<usize> = Vec::new();
+
+            self.function.depth = depth;
+            self.push_scope();
+            self.compile_pattern(&arm.pattern, slot, &mut Vec::new(), &mut failure_jumps);
+
+            if let Some(guard) = &arm.guard {
+                self.compile_expression(guard);
+                failure_jumps.push(self.emit(Instruction::JumpIfFalse(0), guard.position()));
+            }
+
+            match &arm.body {
+                MatchArmBody::Expression(expression) => self.compile_expression(expression),
+                MatchArmBody::Block(statements) => {
+                    self.compile_block(statements);
+                    self.emit(Instruction::Void, position);
+                }
+            }
+
+            end_jumps.push(self.emit(Instruction::Jump(0), position));
+            self.pop_scope();
+
+            for jump in failure_jumps {
+                self.patch_jump(jump);
+            }
+        }
+
+        // The type checker makes sure that every match is exhaustive, so one of the arms has
+        // produced the value. The path taken when none matches still leaves one, so that the
+        // stack is as high wherever the match ends.
+        self.function.depth = depth;
+        self.emit(Instruction::Null, position);
+
+        for jump in end_jumps {
+            self.patch_jump(jump);
+        }
+
+        self.pop_scope();
+    }
+
+    // Compile the tests of a pattern against the value found by following the path of variant
+    // fields from the matched value, jumping away if any of them fails.
+    fn compile_pattern(&mut self, pattern: &Pattern, slot: usize, path: &mut Vec<usize>,
+                       failure_jumps: &mut Vec<usize>) -> () {
+        let position: Position = pattern.position();
+
+        match pattern {
+            Pattern::Literal(literal) => {
+                self.compile_pattern_value(slot, path, position);
+                self.compile_expression(&Expression::Literal(literal.to_owned()));
+                self.emit(Instruction::Binary(BinaryOperator::Equal), position);
+                failure_jumps.push(self.emit(Instruction::JumpIfFalse(0), position));
+            },
+            Pattern::Binding(name) => {
+                self.compile_pattern_value(slot, path, position);
+
+                let variable: Variable = self.declare_variable(name.value());
+
+                self.set_variable(variable, position);
+                self.emit(Instruction::Pop, position);
+            },
+            Pattern::Wildcard(_) => {},
+            Pattern::Variant(variant_pattern) => {
+                let variant: usize = self.name(variant_pattern.variant.value());
+
+                self.compile_pattern_value(slot, path, position);
+                self.emit(Instruction::IsVariant(variant), position);
+                failure_jumps.push(self.emit(Instruction::JumpIfFalse(0), position));
+
+                for (index, field) in variant_pattern.fields.iter().enumerate() {
+                    path.push(index);
+                    self.compile_pattern(field, slot, path, failure_jumps);
+                    path.pop();
+                }
+            }
+        }
+    }
+
+    fn compile_pattern_value(&mut self, slot: usize, path: &[usize], position: Position) -> () {
+        self.emit(Instruction::GetLocal(slot), position);
+
+        for index in path {
+            self.emit(Instruction::VariantField(*index), position);
+        }
+    }
+}
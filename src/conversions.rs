@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+use std::mem;
+use crate::expressions::{Expression, LiteralExpression, LiteralValue, MatchArmBody,
+                         UnaryExpression, UnaryOperator};
+use crate::position::Position;
+use crate::program::{CompileResult, StackGuard};
+use crate::statements::{FunctionDeclarationStatement, Statement};
+
+// Turns the ints which the type checker accepted as floats into floats, by wrapping the
+// expressions giving them in a conversion. The type checker runs it on the program it checked, so
+// the program behaves the same whether it is optimized or not.
+pub struct Converter {
+    conversions: HashSet<Position>, // The positions of the expressions to convert.
+    stack: StackGuard
+}
+
+impl Converter {
+    pub fn new(conversions: HashSet<Position>, stack: StackGuard) -> Self {
+        return Self {
+            conversions,
+            stack
+        };
+    }
+
+    pub fn convert(&mut self, mut statements: Vec<Statement>) -> CompileResult<Vec<Statement>> {
+        self.convert_statements(&mut statements)?;
+
+        return Ok(statements);
+    }
+
+    fn convert_statements(&mut self, statements: &mut [Statement]) -> CompileResult<()> {
+        for statement in statements {
+            self.convert_statement(statement)?;
+        }
+
+        return Ok(());
+    }
+
+    fn convert_function(&mut self, function: &mut FunctionDeclarationStatement)
+        -> CompileResult<()> {
+        return self.convert_statements(&mut function.body);
+    }
+
+    fn convert_statement(&mut self, statement: &mut Statement) -> CompileResult<()> {
+        self.stack.check(statement.position())?;
+
+        match statement {
+            Statement::Expression(expression_statement) => {
+                self.convert_expression(&mut expression_statement.expression)?;
+            },
+            Statement::VariableDeclaration(declaration) => {
+                if let Some(initialization) = &mut declaration.initialization {
+                    self.convert_expression(initialization)?;
+                }
+            },
+            Statement::Block(block) => self.convert_statements(&mut block.statements)?,
+            Statement::If(if_statement) => {
+                self.convert_expression(&mut if_statement.condition)?;
+                self.convert_statements(&mut if_statement.then_branch)?;
+                self.convert_statements(&mut if_statement.else_branch)?;
+            },
+            Statement::While(while_statement) => {
+                self.convert_expression(&mut while_statement.condition)?;
+                self.convert_statements(&mut while_statement.body)?;
+            },
+            Statement::Loop(loop_statement) => self.convert_statements(&mut loop_statement.body)?,
+            Statement::For(for_statement) => {
+                self.convert_expression(&mut for_statement.initializer)?;
+                self.convert_expression(&mut for_statement.condition)?;
+                self.convert_expression(&mut for_statement.increment)?;
+                self.convert_statements(&mut for_statement.body)?;
+            },
+            Statement::ForIn(for_in_statement) => {
+                self.convert_expression(&mut for_in_statement.iterable)?;
+                self.convert_statements(&mut for_in_statement.body)?;
+            },
+            Statement::Return(return_statement) => {
+                if let Some(value) = &mut return_statement.value {
+                    self.convert_expression(value)?;
+                }
+            },
+            Statement::FunctionDeclaration(declaration) => self.convert_function(declaration)?,
+            Statement::ClassDeclaration(declaration) => {
+                for property in &mut declaration.properties {
+                    if let Some(initialization) = &mut property.initialization {
+                        self.convert_expression(initialization)?;
+                    }
+                }
+
+                if let Some(constructor) = &mut declaration.constructor {
+                    self.convert_function(constructor)?;
+                }
+
+                for method in &mut declaration.methods {
+                    self.convert_function(method)?;
+                }
+            },
+            Statement::Throw(throw_statement) => {
+                self.convert_expression(&mut throw_statement.value)?;
+            },
+            Statement::Try(try_statement) => {
+                self.convert_statements(&mut try_statement.body)?;
+
+                for catch in &mut try_statement.catches {
+                    self.convert_statements(&mut catch.body)?;
+                }
+
+                if let Some(finally) = &mut try_statement.finally {
+                    self.convert_statements(finally)?;
+                }
+            },
+            Statement::Break(_) | Statement::Continue(_) | Statement::InterfaceDeclaration(_)
+            | Statement::EnumDeclaration(_) => {},
+            Statement::Import(_) | Statement::Test(_) | Statement::Trivia(_) => unreachable!()
+        }
+
+        return Ok(());
+    }
+
+    fn convert_expression(&mut self, expression: &mut Expression) -> CompileResult<()> {
+        let position: Position = expression.position();
+
+        self.stack.check(position)?;
+
+        // Expressions inside of a converted one can share its position, so it is only converted
+        // once, at the outermost expression.
+        if self.conversions.remove(&position) {
+            self.convert_expression(expression)?;
+
+            let placeholder: Expression = Expression::Literal(LiteralExpression {
+                value: LiteralValue::Null,
+                position
+            });
+
+            *expression = Expression::Unary(UnaryExpression {
+                operator: UnaryOperator::ToFloat,
+                operand: Box::new(mem::replace(expression, placeholder)),
+                position
+            });
+
+            return Ok(());
+        }
+
+        match expression {
+            Expression::Literal(_) | Expression::Identifier(_) | Expression::This(_) |
+            Expression::Super(_) => {},
+            Expression::Binary(binary) => {
+                self.convert_expression(&mut binary.left)?;
+                self.convert_expression(&mut binary.right)?;
+            },
+            Expression::Unary(unary) => self.convert_expression(&mut unary.operand)?,
+            Expression::Assignment(assignment) => {
+                self.convert_expression(&mut assignment.target)?;
+                self.convert_expression(&mut assignment.value)?;
+            },
+            Expression::FunctionCall(call) => {
+                self.convert_expression(&mut call.callee)?;
+
+                for argument in &mut call.arguments {
+                    self.convert_expression(argument)?;
+                }
+            },
+            Expression::Property(property) => self.convert_expression(&mut property.object)?,
+            Expression::Is(is) => self.convert_expression(&mut is.value)?,
+            Expression::Map(map) => {
+                for (key, value) in &mut map.entries {
+                    self.convert_expression(key)?;
+                    self.convert_expression(value)?;
+                }
+            },
+            Expression::List(list) => {
+                for element in &mut list.elements {
+                    self.convert_expression(element)?;
+                }
+            },
+            Expression::Range(range) => {
+                self.convert_expression(&mut range.start)?;
+                self.convert_expression(&mut range.end)?;
+            },
+            Expression::Match(match_expression) => {
+                self.convert_expression(&mut match_expression.value)?;
+
+                for arm in &mut match_expression.arms {
+                    if let Some(guard) = &mut arm.guard {
+                        self.convert_expression(guard)?;
+                    }
+
+                    match &mut arm.body {
+                        MatchArmBody::Expression(body) => self.convert_expression(body)?,
+                        MatchArmBody::Block(body) => self.convert_statements(body)?
+                    }
+                }
+            },
+            Expression::Propagate(propagate) => self.convert_expression(&mut propagate.value)?
+        }
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,92 @@
+use crate::bytecode::{CompiledProgram, Constant, FunctionProto, Instruction};
+use crate::natives::natives;
+
+// Produce a readable listing of a compiled program, with each instruction next to the line of
+// source code it came from.
+pub fn disassemble(program: &CompiledProgram) -> String {
+    let mut output: String = String::new();
+
+    for (index, function) in program.functions.iter().enumerate() {
+        if index > 0 {
+            output += "\n";
+        }
+
+        output += &disassemble_function(program, function);
+    }
+
+    return output;
+}
+
+fn disassemble_function(program: &CompiledProgram, function: &FunctionProto) -> String {
+    let mut output: String = "== ".to_owned() + &function.name + " (" +
+        &function.local_count.to_string() + " locals) ==\n";
+    let mut previous_line: Option<i32> = None;
+
+    for (offset, (instruction, position)) in function.code.iter()
+        .zip(&function.positions)
+        .enumerate() {
+        // Consecutive instructions from the same line only show the line once.
+        let line: String = if previous_line == Some(position.line) {
+            "|".to_owned()
+        } else {
+            position.line.to_string()
+        };
+
+        previous_line = Some(position.line);
+        output += &format!("{:04} {:>4}  {}\n", offset, line,
+                           describe_instruction(program, instruction));
+    }
+
+    return output;
+}
+
+fn describe_constant(constant: &Constant) -> String {
+    return match constant {
+        Constant::Integer(value) => value.to_string(),
+        Constant::Float(value) => format!("{:?}", value),
+        Constant::String(value) => format!("{:?}", value),
+        Constant::Char(value) => format!("{:?}", value),
+        Constant::Boolean(value) => value.to_string()
+    };
+}
+
+fn describe_instruction(program: &CompiledProgram, instruction: &Instruction) -> String {
+    let constant = |index: usize| -> String {
+        return describe_constant(&program.constants[index]);
+    };
+    let name = |index: usize| -> String {
+        return match &program.constants[index] {
+            Constant::String(name) => name.to_owned(),
+            other => describe_constant(other)
+        };
+    };
+
+    // The raw operands, followed by what they refer to.
+    let detail: String = match *instruction {
+        Instruction::Constant(index) => constant(index),
+        Instruction::GetGlobal(slot) | Instruction::SetGlobal(slot) => {
+            program.globals[slot].to_owned()
+        },
+        Instruction::GetField(index) | Instruction::SetField(index) |
+        Instruction::CallMethod(index, _) | Instruction::Is(index) |
+        Instruction::IsVariant(index) => name(index),
+        Instruction::Binary(operator) => operator.to_string(),
+        Instruction::Unary(operator) => operator.to_string(),
+        Instruction::Call(function, _) => program.functions[function].name.to_owned(),
+        Instruction::New(class, _) => program.classes[class].name.to_owned(),
+        Instruction::CallNative(native, _) => natives()[native].name.to_owned(),
+        Instruction::MakeEnum(enum_name, variant, _) => name(enum_name) + "." + &name(variant),
+        _ => String::new()
+    };
+    let operands: Vec<String> = match instruction {
+        Instruction::Binary(_) | Instruction::Unary(_) => Vec::new(),
+        _ => instruction.encode().1.iter().map(|operand| operand.to_string()).collect()
+    };
+    let mut description: String = format!("{:<16}", instruction.name()) + &operands.join(" ");
+
+    if !detail.is_empty() {
+        description = format!("{:<28}", description) + "(" + &detail + ")";
+    }
+
+    return description.trim_end().to_owned();
+}
@@ -0,0 +1,394 @@
+use std::error;
+use std::fmt;
+use std::rc::Rc;
+use crate::expressions::Expression;
+use crate::host::{HostCallable, HostClass, HostMethod, ScriptValue};
+use crate::interpreter::Interpreter;
+use crate::keywords;
+use crate::natives::{HostFunction, HostType, HostValue};
+use crate::position::Position;
+use crate::program::{CompileResult, Program, RuntimeError, SourceFile, Stop, DEFAULT_STACK_LIMIT};
+use crate::statements::Statement;
+use crate::token::Token;
+use crate::types::{FunctionType, Type};
+use crate::value::Value;
+
+// Whether an error was found before a script started, or stopped it while it was running. Scripts
+// calling `exit` stop with its exit code, which is left to the host to act on.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ErrorKind {
+    Compile,
+    Runtime,
+    Exit(i32)
+}
+
+// A call which was running when a runtime error happened: the function called, and where it was
+// called from.
+#[derive(Clone, Debug)]
+pub struct Call {
+    pub function: String,
+    pub file: String,
+    pub line: i32,
+    pub column: i32
+}
+
+// An error in a script, along with where it is.
+#[derive(Clone, Debug)]
+pub struct Error {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub file: String, // The path of the module the error is in, which is empty for the script.
+    pub line: i32,
+    pub column: i32,
+    pub trace: Vec<Call>, // The calls a runtime error happened in, innermost first.
+    rendered: String // The error as the command line shows it, followed by its line.
+}
+
+impl Error {
+    fn compile(program: &Program, message: String, position: Position) -> Self {
+        return Self {
+            kind: ErrorKind::Compile,
+            message: message.to_owned(),
+            file: program.files()[position.file].name.to_owned(),
+            line: position.line,
+            column: position.start,
+            trace: Vec::new(),
+            rendered: program.render("Error", message, position)
+        };
+    }
+
+    fn runtime(program: &Program, error: RuntimeError) -> Self {
+        return Self {
+            kind: ErrorKind::Runtime,
+            message: error.message.to_owned(),
+            file: program.files()[error.position.file].name.to_owned(),
+            line: error.position.line,
+            column: error.position.start,
+            trace: error.trace.iter()
+                .map(|frame| Call {
+                    function: frame.function.to_owned(),
+                    file: program.files()[frame.position.file].name.to_owned(),
+                    line: frame.position.line,
+                    column: frame.position.start
+                })
+                .collect(),
+            rendered: program.render_runtime_error(&error)
+        };
+    }
+
+    // A script run by an engine which no longer gives it something it was checked against.
+    fn unregistered(registration: &str) -> Self {
+        let message: String = "`".to_owned() + registration + "` is no longer registered as it \
+            was when the script was compiled.";
+
+        return Self {
+            kind: ErrorKind::Compile,
+            message: message.to_owned(),
+            file: String::new(),
+            line: 0,
+            column: 0,
+            trace: Vec::new(),
+            rendered: "Error: ".to_owned() + &message
+        };
+    }
+
+    fn exit(code: i32) -> Self {
+        let message: String = "The script exited with code ".to_owned() + &code.to_string() + ".";
+
+        return Self {
+            kind: ErrorKind::Exit(code),
+            message: message.to_owned(),
+            file: String::new(),
+            line: 0,
+            column: 0,
+            trace: Vec::new(),
+            rendered: message
+        };
+    }
+}
+
+// Host programs usually show errors as the command line does, and pass them on with `?`.
+impl fmt::Display for Error {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return formatter.write_str(&self.rendered);
+    }
+}
+
+impl error::Error for Error {}
+
+// A script which has been checked, and can be run any number of times.
+pub struct Script {
+    files: Vec<SourceFile>,
+    // What was registered with the engine when the script was checked, which it is run with too.
+    registrations: Vec<String>,
+    statements: Vec<Statement>,
+    result: Option<Expression> // The expression the script ends with, which gives its value.
+}
+
+// Compiles and runs scripts for a Rust program embedding the language, giving them the functions
+// and values registered with it. Scripts run on the thread the engine is used from. The
+// interpreter recurses for each nested call, so scripts are stopped with an error once they use
+// more of that thread's stack than they are allowed to.
+pub struct Engine {
+    functions: Vec<HostFunction>,
+    values: Vec<HostValue>,
+    types: Vec<HostType>,
+    arguments: Vec<String>,
+    stack_limit: usize
+}
+
+impl Default for Engine {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl Engine {
+    pub fn new() -> Self {
+        return Self {
+            functions: Vec::new(),
+            values: Vec::new(),
+            types: Vec::new(),
+            arguments: Vec::new(),
+            stack_limit: DEFAULT_STACK_LIMIT
+        };
+    }
+
+    // Make a Rust closure callable from scripts. The type checker makes sure that it is given
+    // arguments of the types of its parameters, and it returns its result, or the message of a
+    // runtime error.
+    pub fn register_function<Arguments>(&mut self, name: &str,
+                                         function: impl HostCallable<Arguments>) -> () {
+        self.functions.retain(|existing| existing.name != name);
+        self.functions.push(host_function(name, function));
+    }
+
+    // Like `register_function`, but for a closure working with script values directly. Returning
+    // a value which is not of the declared type is a runtime error in the script calling it.
+    pub fn register_dynamic_function(&mut self, name: &str, parameters: Vec<Type>,
+                                     return_type: Type,
+                                     function: impl Fn(Vec<Value>) -> Result<Value, String> +
+                                         'static) -> () {
+        let expected: Type = return_type.to_owned();
+        let mistyped: String = "`".to_owned() + name + "` returned a value which is not of type `" +
+            &return_type.to_string() + "`.";
+
+        self.functions.retain(|existing| existing.name != name);
+        self.functions.push(HostFunction {
+            name: name.to_owned(),
+            function_type: FunctionType {
+                type_parameters: Vec::new(),
+                parameters,
+                return_type
+            },
+            function: Rc::new(move |arguments: Vec<Value>| {
+                let value: Value = function(arguments)?;
+
+                if !value.has_type(&expected) {
+                    return Err(mistyped.to_owned());
+                }
+
+                return Ok(value);
+            })
+        });
+    }
+
+    // Make a Rust type usable by scripts, which get its objects from registered functions.
+    pub fn register_class<T: HostClass>(&mut self) -> () {
+        if !self.types.iter().any(|host_type| host_type.name == T::NAME) {
+            self.types.push(HostType {
+                name: T::NAME.to_owned(),
+                methods: Vec::new()
+            });
+        }
+    }
+
+    // Give a registered class a method, whose first parameter is the object it is called on.
+    pub fn register_method<T: HostClass, Arguments>(&mut self, name: &str,
+                                                    function: impl HostMethod<T, Arguments>)
+        -> () {
+        let method: HostFunction = HostFunction {
+            name: name.to_owned(),
+            function_type: method_type(&function),
+            function: function.into_body()
+        };
+
+        self.register_class::<T>();
+
+        let host_type: &mut HostType = self.types.iter_mut()
+            .find(|host_type| host_type.name == T::NAME)
+            .unwrap();
+
+        host_type.methods.retain(|existing| existing.name != name);
+        host_type.methods.push(method);
+    }
+
+    // Give scripts a global variable, converted from a Rust value.
+    pub fn register_value(&mut self, name: &str, value: impl ScriptValue) -> () {
+        self.insert_value(name, type_of(&value), value.into_value());
+    }
+
+    // Give scripts a global variable of the given type. Lists, maps and objects are shared rather
+    // than copied, so the changes scripts make to them are seen by the host and by later runs. A
+    // value which is not of the given type is refused.
+    pub fn register_dynamic_value(&mut self, name: &str, value_type: Type, value: Value)
+        -> Result<(), String> {
+        if !value.has_type(&value_type) {
+            return Err("The value of `".to_owned() + name + "` is not of type `" +
+                       &value_type.to_string() + "`.");
+        }
+
+        self.insert_value(name, value_type, value);
+
+        return Ok(());
+    }
+
+    fn insert_value(&mut self, name: &str, value_type: Type, value: Value) -> () {
+        self.values.retain(|existing| existing.name != name);
+        self.values.push(HostValue {
+            name: name.to_owned(),
+            value_type,
+            value
+        });
+    }
+
+    // The arguments scripts get from `args()`.
+    pub fn set_arguments(&mut self, arguments: Vec<String>) -> () {
+        self.arguments = arguments;
+    }
+
+    // How much of the stack, in bytes, scripts may use, which is 1 MiB unless set. Hosts running
+    // scripts on a thread with a larger stack can let them recurse more deeply.
+    pub fn set_stack_limit(&mut self, stack_limit: usize) -> () {
+        self.stack_limit = stack_limit;
+    }
+
+    // The functions, values, classes and methods registered, each described by its signature.
+    fn registrations(&self) -> Vec<String> {
+        let functions = self.functions.iter()
+            .map(|function| function.function_type.signature(&function.name));
+        let values = self.values.iter()
+            .map(|value| value.value_type.to_string() + " " + &value.name);
+        let types = self.types.iter()
+            .flat_map(|host_type| {
+                host_type.methods.iter()
+                    .map(|method| {
+                        method.function_type.signature(&(host_type.name.to_owned() + "." +
+                            &method.name))
+                    })
+                    .chain(["class ".to_owned() + &host_type.name])
+            });
+
+        return functions.chain(values).chain(types).collect();
+    }
+
+    fn create_program(&self, files: Vec<SourceFile>) -> Program {
+        let mut program: Program = Program::from_files(keywords(), files);
+
+        program.set_print_errors(false);
+        program.set_host(self.functions.to_owned(), self.values.to_owned(),
+                         self.types.to_owned());
+        program.set_arguments(self.arguments.to_owned());
+        program.set_stack_limit(self.stack_limit);
+
+        return program;
+    }
+
+    // Lex, parse and check a script along with the modules it imports, which are found relative
+    // to the working directory. Its tests are left out.
+    pub fn compile(&self, source: &str) -> Result<Script, Error> {
+        let mut program: Program = self.create_program(vec![SourceFile {
+            name: String::new(),
+            source: source.to_owned()
+        }]);
+        let compiled: CompileResult<(Vec<Statement>, Option<Expression>)> =
+            compile_program(&mut program);
+
+        return match compiled {
+            Ok((statements, result)) => Ok(Script {
+                files: program.files().to_vec(),
+                registrations: self.registrations(),
+                statements,
+                result
+            }),
+            Err((message, position)) => {
+                Err(Error::compile(&program, message, position))
+            }
+        };
+    }
+
+    // Run a script, producing the value of the expression it ends with, or `void` if it does not
+    // end with one. What the script was checked against has to still be registered, though
+    // functions and values can be registered again with the same types.
+    pub fn execute(&self, script: &Script) -> Result<Value, Error> {
+        let registrations: Vec<String> = self.registrations();
+
+        if let Some(missing) = script.registrations.iter()
+            .find(|registration| !registrations.contains(registration)) {
+            return Err(Error::unregistered(missing));
+        }
+
+        let mut program: Program = self.create_program(script.files.to_owned());
+        let mut interpreter: Interpreter = program.create_interpreter();
+        let outcome: Result<Value, Stop> = interpreter.run(&script.statements)
+            .and_then(|()| match &script.result {
+                Some(expression) => interpreter.evaluate_expression(expression),
+                None => Ok(Value::Void)
+            });
+
+        return outcome.map_err(|stop| match stop {
+            Stop::Error(error) => Error::runtime(&program, error),
+            Stop::Exit(code) => Error::exit(code)
+        });
+    }
+
+    // Compile a script and run it.
+    pub fn run(&self, source: &str) -> Result<Value, Error> {
+        return self.execute(&self.compile(source)?);
+    }
+}
+
+// Lex, parse, load, check and optimize a program, splitting off the expression it ends with.
+fn compile_program(program: &mut Program) -> CompileResult<(Vec<Statement>, Option<Expression>)> {
+    let tokens: Vec<Token> = program.create_lexer().lex()?;
+
+    program.set_tokens(tokens);
+
+    let statements: Vec<Statement> = program.create_parser().parse()?;
+    let statements: Vec<Statement> = program.create_module_loader().load(statements)?;
+
+    let statements: Vec<Statement> = program.create_type_checker().check(statements)?;
+    let mut statements: Vec<Statement> = program.create_optimizer().optimize(statements)?;
+    let result: Option<Expression> = match statements.pop() {
+        Some(Statement::Expression(statement)) => Some(statement.expression),
+        last => {
+            statements.extend(last);
+
+            None
+        }
+    };
+
+    return Ok((statements, result));
+}
+
+fn host_function<Arguments>(name: &str, function: impl HostCallable<Arguments>) -> HostFunction {
+    return HostFunction {
+        name: name.to_owned(),
+        function_type: function_type(&function),
+        function: function.into_body()
+    };
+}
+
+// The signature of a closure, which is known from its type alone.
+fn function_type<Arguments, Function: HostCallable<Arguments>>(_: &Function) -> FunctionType {
+    return Function::function_type();
+}
+
+fn method_type<T: HostClass, Arguments, Function: HostMethod<T, Arguments>>(_: &Function)
+        -> FunctionType {
+    return Function::function_type();
+}
+
+fn type_of<T: ScriptValue>(_: &T) -> Type {
+    return T::script_type();
+}
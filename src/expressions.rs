@@ -1,69 +1,234 @@
-use crate::statements::Statement;
+use crate::patterns::Pattern;
+use crate::position::Position;
+use crate::statements::{Statement, Trivia};
 use crate::token::Token;
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone)]
 pub enum LiteralValue {
     Integer(i64),
-    Float(i64),
+    Float(f64),
     String(String),
     Char(char),
-    Boolean(bool)
+    Boolean(bool),
+    Null
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone, Copy)]
 pub enum BinaryOperator {
     Add,
     Subtract,
     Multiply,
     Divide,
     Remainder,
-    Power
+    Power,
+    Equal,
+    NotEqual,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+    And,
+    Or,
+    Coalesce // `??`, which replaces a null value on the left with the value on the right.
 }
 
-#[derive(PartialEq)]
+impl ToString for BinaryOperator {
+    fn to_string(&self) -> String {
+        return match self {
+            BinaryOperator::Add => String::from("+"),
+            BinaryOperator::Subtract => String::from("-"),
+            BinaryOperator::Multiply => String::from("*"),
+            BinaryOperator::Divide => String::from("/"),
+            BinaryOperator::Remainder => String::from("%"),
+            BinaryOperator::Power => String::from("^"),
+            BinaryOperator::Equal => String::from("=="),
+            BinaryOperator::NotEqual => String::from("!="),
+            BinaryOperator::LessThan => String::from("<"),
+            BinaryOperator::LessThanOrEqual => String::from("<="),
+            BinaryOperator::GreaterThan => String::from(">"),
+            BinaryOperator::GreaterThanOrEqual => String::from(">="),
+            BinaryOperator::And => String::from("&&"),
+            BinaryOperator::Or => String::from("||"),
+            BinaryOperator::Coalesce => String::from("??")
+        };
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
 pub enum UnaryOperator {
     Negate,
-    Not
+    Not,
+    // Turns an int into a float. Programs cannot write it; the type checker adds it wherever it
+    // accepted an int as a float.
+    ToFloat
+}
+
+impl ToString for UnaryOperator {
+    fn to_string(&self) -> String {
+        return match self {
+            UnaryOperator::Negate => String::from("-"),
+            UnaryOperator::Not => String::from("!"),
+            UnaryOperator::ToFloat => String::from("float")
+        };
+    }
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone)]
 pub enum Expression {
     Literal(LiteralExpression),
     Identifier(IdentifierExpression),
-    Binary(BinaryExpression)
+    Binary(BinaryExpression),
+    Unary(UnaryExpression),
+    Assignment(AssignmentExpression),
+    FunctionCall(FunctionCallExpression),
+    Property(PropertyExpression),
+    This(ThisExpression),
+    Super(SuperExpression),
+    Is(IsExpression),
+    Map(MapExpression),
+    List(ListExpression),
+    Range(RangeExpression),
+    Match(MatchExpression),
+    Propagate(PropagateExpression)
 }
 
+impl Expression {
+    // The position used when reporting an error about this expression.
+    pub fn position(&self) -> Position {
+        return match self {
+            Expression::Literal(literal) => literal.position,
+            Expression::Identifier(identifier) => identifier.token.token_position,
+            Expression::Binary(binary) => binary.position,
+            Expression::Unary(unary) => unary.position,
+            Expression::Assignment(assignment) => assignment.position,
+            Expression::FunctionCall(call) => call.position,
+            Expression::Property(property) => property.name.token_position,
+            Expression::This(this) => this.position,
+            Expression::Super(super_expression) => super_expression.method.token_position,
+            Expression::Is(is) => is.class_name.token_position,
+            Expression::Map(map) => map.position,
+            Expression::List(list) => list.position,
+            Expression::Range(range) => range.position,
+            Expression::Match(match_expression) => match_expression.position,
+            Expression::Propagate(propagate) => propagate.position
+        };
+    }
+}
+
+#[derive(PartialEq, Clone)]
 pub struct LiteralExpression {
-    pub value: LiteralValue
+    pub value: LiteralValue,
+    pub position: Position
 }
 
+#[derive(PartialEq, Clone)]
 pub struct IdentifierExpression {
     pub token: Token
 }
 
+#[derive(PartialEq, Clone)]
 pub struct BinaryExpression {
     pub operator: BinaryOperator,
     pub left: Box<Expression>,
-    pub right: Box<Expression>
+    pub right: Box<Expression>,
+    pub position: Position // The position of the operator.
 }
 
+#[derive(PartialEq, Clone)]
 pub struct UnaryExpression {
     pub operator: UnaryOperator,
-    pub operand: Box<Expression>
+    pub operand: Box<Expression>,
+    pub position: Position
 }
 
+// Plain (`=`) and positional (`+=`, `++`, etc.) assignments. The target is either an identifier
+// or a property.
+#[derive(PartialEq, Clone)]
 pub struct AssignmentExpression {
-    pub identifier: IdentifierExpression,
-    pub value: Box<Expression>
+    pub target: Box<Expression>,
+    pub operator: Option<BinaryOperator>,
+    pub value: Box<Expression>,
+    pub position: Position
 }
 
+#[derive(PartialEq, Clone)]
 pub struct FunctionCallExpression {
-    pub identifier: IdentifierExpression,
-    pub arguments: Vec<Expression>
+    pub callee: Box<Expression>,
+    pub arguments: Vec<Expression>,
+    pub position: Position
+}
+
+#[derive(PartialEq, Clone)]
+pub struct PropertyExpression {
+    pub object: Box<Expression>,
+    pub name: Token,
+    pub safe: bool // `object?.name`, which produces null instead when the object is null.
+}
+
+#[derive(PartialEq, Clone)]
+pub struct ThisExpression {
+    pub position: Position
+}
+
+// `super.method`, which is only valid as the callee of a function call.
+#[derive(PartialEq, Clone)]
+pub struct SuperExpression {
+    pub method: Token
+}
+
+// `value is ClassName`.
+#[derive(PartialEq, Clone)]
+pub struct IsExpression {
+    pub value: Box<Expression>,
+    pub class_name: Token
 }
 
-pub struct ConditionalExpression {
-    pub condition: Box<Expression>,
-    pub then_branch: Box<Statement>,
-    pub else_branch: Box<Statement>
-}
\ No newline at end of file
+#[derive(PartialEq, Clone)]
+pub struct MapExpression {
+    pub entries: Vec<(Expression, Expression)>,
+    pub position: Position
+}
+
+#[derive(PartialEq, Clone)]
+pub struct ListExpression {
+    pub elements: Vec<Expression>,
+    pub position: Position
+}
+
+// `start..end`, which includes `start` but not `end`.
+#[derive(PartialEq, Clone)]
+pub struct RangeExpression {
+    pub start: Box<Expression>,
+    pub end: Box<Expression>,
+    pub position: Position // The position of the `..`.
+}
+
+// `value?`, which gives the value inside a `Result.Ok` or `Option.Some`, and otherwise returns the
+// `Result.Err` or `Option.None` from the function it is in.
+#[derive(PartialEq, Clone)]
+pub struct PropagateExpression {
+    pub value: Box<Expression>,
+    pub position: Position // The position of the `?`.
+}
+
+#[derive(PartialEq, Clone)]
+pub struct MatchExpression {
+    pub value: Box<Expression>,
+    pub arms: Vec<MatchArm>,
+    pub position: Position, // The position of the `match` keyword.
+    pub trivia: Vec<Trivia> // The comments and blank lines between the arms.
+}
+
+#[derive(PartialEq, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expression>, // `pattern if condition => ...`.
+    pub body: MatchArmBody
+}
+
+// The arm of a match either produces a value, or runs a block of statements and produces nothing.
+#[derive(PartialEq, Clone)]
+pub enum MatchArmBody {
+    Expression(Expression),
+    Block(Vec<Statement>)
+}
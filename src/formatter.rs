@@ -0,0 +1,957 @@
+use crate::expressions::{BinaryOperator, Expression, LiteralExpression, LiteralValue, MatchArm,
+                         MatchArmBody, MatchExpression, PropertyExpression};
+use crate::patterns::Pattern;
+use crate::position::Position;
+use crate::program::Program;
+use crate::SOURCE_EXTENSION;
+use crate::statements::{ClassDeclarationStatement, EnumDeclarationStatement,
+                        EnumVariantDeclaration, FunctionDeclarationStatement, IfStatement,
+                        ImportStatement, InterfaceDeclarationStatement, ParameterDeclaration,
+                        Statement, Trivia, TryStatement, VariableDeclarationStatement};
+use crate::type_annotation::type_parameters_to_string;
+
+// The width lines are kept within where they can be broken, and the indentation of each level.
+const MAX_WIDTH: usize = 100;
+const INDENT: &str = "    ";
+
+// The precedence of expressions which bind tighter than any operator, such as calls and literals.
+const POSTFIX: u8 = 13;
+
+// A member of a class, which are printed in the order they were written in.
+enum Member<'b> {
+    Property(&'b VariableDeclarationStatement),
+    Constructor(&'b FunctionDeclarationStatement),
+    Method(&'b FunctionDeclarationStatement),
+    Trivia(&'b Trivia)
+}
+
+// Order things by where they are in the source.
+fn source_order(position: Position) -> (i32, i32) {
+    return (position.line, position.start);
+}
+
+fn indent(depth: usize) -> String {
+    return INDENT.repeat(depth);
+}
+
+fn width(text: &str) -> usize {
+    return text.chars().count();
+}
+
+fn escape(value: &str, quote: char) -> String {
+    let mut escaped: String = String::new();
+
+    for character in value.chars() {
+        match character {
+            '\n' => escaped += "\\n",
+            '\r' => escaped += "\\r",
+            '\t' => escaped += "\\t",
+            '\\' => escaped += "\\\\",
+            '\0' => escaped += "\\0",
+            _ if character == quote => {
+                escaped.push('\\');
+                escaped.push(character);
+            },
+            _ => escaped.push(character)
+        }
+    }
+
+    return escaped;
+}
+
+// Floats always keep their decimal point, so that they are read back as floats.
+fn float_text(value: f64) -> String {
+    let text: String = value.to_string();
+
+    return if text.contains('.') { text } else { text + ".0" };
+}
+
+// The precedence of an expression, where higher binds tighter. The parser decides these through
+// the order it parses in; the formatter needs them to put back the parentheses it dropped.
+fn precedence(expression: &Expression) -> u8 {
+    return match expression {
+        Expression::Assignment(_) => 1,
+        Expression::Binary(binary) => binary_precedence(binary.operator),
+        Expression::Is(_) => 6,
+        Expression::Range(_) => 8,
+        Expression::Unary(_) => 11,
+        _ => POSTFIX
+    };
+}
+
+// The precedence the object of a property needs to go without parentheses. A `?` right before
+// the `.` would be read as `?.`.
+fn object_precedence(property: &PropertyExpression) -> u8 {
+    return match property.object.as_ref() {
+        Expression::Propagate(_) => POSTFIX + 1,
+        _ => POSTFIX
+    };
+}
+
+fn binary_precedence(operator: BinaryOperator) -> u8 {
+    return match operator {
+        BinaryOperator::Coalesce => 2,
+        BinaryOperator::Or => 3,
+        BinaryOperator::And => 4,
+        BinaryOperator::Equal | BinaryOperator::NotEqual => 5,
+        BinaryOperator::LessThan | BinaryOperator::LessThanOrEqual |
+        BinaryOperator::GreaterThan | BinaryOperator::GreaterThanOrEqual => 7,
+        BinaryOperator::Add | BinaryOperator::Subtract => 9,
+        BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Remainder => 10,
+        BinaryOperator::Power => 12
+    };
+}
+
+// The precedences the left and right operands of an operator need to go without parentheses.
+fn operand_precedences(operator: BinaryOperator) -> (u8, u8) {
+    return match operator {
+        // These are right-associative, and powers take a unary operator on their right.
+        BinaryOperator::Coalesce => (3, 2),
+        BinaryOperator::Power => (POSTFIX, 11),
+        _ => {
+            let precedence: u8 = binary_precedence(operator);
+
+            (precedence, precedence + 1)
+        }
+    };
+}
+
+// Whether an expression starts with a `{` or `match`, which would be read as a block or a match
+// statement at the start of a statement.
+fn starts_like_statement(expression: &Expression) -> bool {
+    return match expression {
+        Expression::Map(_) | Expression::Match(_) => true,
+        Expression::Binary(binary) => starts_like_statement(&binary.left),
+        Expression::Assignment(assignment) => starts_like_statement(&assignment.target),
+        Expression::FunctionCall(call) => starts_like_statement(&call.callee),
+        Expression::Property(property) => starts_like_statement(&property.object),
+        Expression::Is(is) => starts_like_statement(&is.value),
+        Expression::Range(range) => starts_like_statement(&range.start),
+        Expression::Propagate(propagate) => starts_like_statement(&propagate.value),
+        _ => false
+    };
+}
+
+// Prints a syntax tree back as source code in a single style, keeping its comments and the blank
+// lines between its statements. Expressions are kept on one line where they fit, and are otherwise
+// broken across several at their arguments, elements and operators.
+pub struct Formatter<'a> {
+    program: &'a Program,
+    lines: Vec<String>,
+    blank_line: bool, // Whether a blank line goes before the next line.
+    block_start: bool // Whether the last line opened a block, which never starts with a blank line.
+}
+
+impl<'a> Formatter<'a> {
+    pub fn new(program: &'a Program) -> Self {
+        return Self {
+            program,
+            lines: Vec::new(),
+            blank_line: false,
+            block_start: false
+        };
+    }
+
+    pub fn format(&mut self, statements: &[Statement]) -> String {
+        self.statements(statements, 0);
+
+        if self.lines.is_empty() {
+            return String::new();
+        }
+
+        return self.lines.join("\n") + "\n";
+    }
+
+    // The text of the source at a position, which is on a single line.
+    fn source_text(&self, position: Position) -> String {
+        let source: &str = &self.program.files()[position.file].source;
+        let line: &str = source.lines().nth((position.line - 1) as usize).unwrap_or_default();
+
+        return line.chars()
+            .skip((position.start - 1).max(0) as usize)
+            .take((position.end - position.start).max(0) as usize)
+            .collect();
+    }
+
+    // Add a line at the given depth. The lines after the first of a text which spans several
+    // already carry their own indentation.
+    fn line(&mut self, depth: usize, text: &str) -> () {
+        if self.blank_line && !self.lines.is_empty() {
+            self.lines.push(String::new());
+        }
+
+        self.blank_line = false;
+        self.block_start = false;
+
+        for (index, line) in text.split('\n').enumerate() {
+            if index == 0 {
+                self.lines.push(indent(depth) + line);
+            } else {
+                self.lines.push(line.to_owned());
+            }
+        }
+    }
+
+    fn open(&mut self, depth: usize, text: &str) -> () {
+        self.line(depth, text);
+        self.block_start = true;
+    }
+
+    fn close(&mut self, depth: usize, text: &str) -> () {
+        self.blank_line = false;
+        self.line(depth, text);
+    }
+
+    // Whether a text fits within the width, starting at the given column.
+    fn fits(&self, text: &str, column: usize) -> bool {
+        return text.split('\n').enumerate().all(|(index, line)| {
+            if index == 0 {
+                column + width(line) <= MAX_WIDTH
+            } else {
+                width(line) <= MAX_WIDTH
+            }
+        });
+    }
+
+    // Print a text over several lines, such as a match expression, as part of an expression which
+    // starts at the given depth.
+    fn nested(&self, depth: usize, print: impl FnOnce(&mut Formatter<'a>)) -> String {
+        let mut formatter: Formatter<'a> = Formatter::new(self.program);
+
+        print(&mut formatter);
+
+        let text: String = formatter.lines.join("\n");
+
+        return text[indent(depth).len()..].to_owned();
+    }
+
+    fn trivia(&mut self, trivia: &Trivia, depth: usize) -> () {
+        match trivia {
+            Trivia::Comment(comment) => match self.lines.last_mut() {
+                Some(last) if comment.trailing => *last += &(" ".to_owned() + &comment.text),
+                _ => self.line(depth, &comment.text)
+            },
+            Trivia::BlankLine(_) => {
+                if !self.block_start {
+                    self.blank_line = true;
+                }
+            }
+        }
+    }
+
+    fn statements(&mut self, statements: &[Statement], depth: usize) -> () {
+        for statement in statements {
+            self.statement(statement, depth);
+        }
+    }
+
+    // Print a block after a header such as `while condition `, keeping empty ones on one line.
+    fn block(&mut self, depth: usize, header: &str, body: &[Statement]) -> () {
+        if body.is_empty() {
+            self.line(depth, &(header.to_owned() + "{}"));
+
+            return;
+        }
+
+        self.open(depth, &(header.to_owned() + "{"));
+        self.statements(body, depth + 1);
+        self.close(depth, "}");
+    }
+
+    fn statement(&mut self, statement: &Statement, depth: usize) -> () {
+        let column: usize = depth * INDENT.len();
+
+        match statement {
+            Statement::Expression(expression_statement) => {
+                let expression: &Expression = &expression_statement.expression;
+
+                // A match on its own is a statement, which needs no semicolon.
+                let text: String = if let Expression::Match(_) = expression {
+                    self.expression(expression, depth, column)
+                } else if starts_like_statement(expression) {
+                    "(".to_owned() + &self.expression(expression, depth, column + 1) + ");"
+                } else {
+                    self.expression(expression, depth, column) + ";"
+                };
+
+                self.line(depth, &text);
+            },
+            Statement::VariableDeclaration(declaration) => {
+                let text: String = self.variable_declaration(declaration, depth);
+
+                self.line(depth, &text);
+            },
+            Statement::Block(block) => self.block(depth, "", &block.statements),
+            Statement::If(if_statement) => self.if_statement(if_statement, depth),
+            Statement::While(while_statement) => {
+                let header: String = "while ".to_owned() +
+                    &self.expression(&while_statement.condition, depth, column + 6) + " ";
+
+                self.block(depth, &header, &while_statement.body);
+            },
+            Statement::Loop(loop_statement) => self.block(depth, "loop ", &loop_statement.body),
+            Statement::For(for_statement) => {
+                let header: String = "for (".to_owned() +
+                    &for_statement.variable_type.to_string() + " " +
+                    &for_statement.variable.value() + " = " +
+                    &self.flat(&for_statement.initializer, depth) + "; " +
+                    &self.flat(&for_statement.condition, depth) + "; " +
+                    &self.flat(&for_statement.increment, depth) + ") ";
+
+                self.block(depth, &header, &for_statement.body);
+            },
+            Statement::ForIn(for_in_statement) => {
+                let variables: Vec<String> = for_in_statement.variables.iter()
+                    .map(|variable| variable.value())
+                    .collect();
+                let variables: String = if variables.len() == 1 {
+                    variables.join("")
+                } else {
+                    "(".to_owned() + &variables.join(", ") + ")"
+                };
+                let prefix: String = "for ".to_owned() + &variables + " in ";
+                let header: String = prefix.to_owned() +
+                    &self.expression(&for_in_statement.iterable, depth, column + width(&prefix)) +
+                    " ";
+
+                self.block(depth, &header, &for_in_statement.body);
+            },
+            Statement::Break(_) => self.line(depth, "break;"),
+            Statement::Continue(_) => self.line(depth, "continue;"),
+            Statement::Return(return_statement) => {
+                let text: String = match &return_statement.value {
+                    Some(value) => "return ".to_owned() +
+                        &self.expression(value, depth, column + 7) + ";",
+                    None => "return;".to_owned()
+                };
+
+                self.line(depth, &text);
+            },
+            Statement::FunctionDeclaration(declaration) => {
+                self.function(declaration, depth, false);
+            },
+            Statement::ClassDeclaration(declaration) => self.class(declaration, depth),
+            Statement::InterfaceDeclaration(declaration) => self.interface(declaration, depth),
+            Statement::EnumDeclaration(declaration) => self.enum_declaration(declaration, depth),
+            Statement::Import(import) => {
+                let text: String = "import ".to_owned() + &self.import_path(import) + ";";
+
+                self.line(depth, &text);
+            },
+            Statement::Test(test) => {
+                let header: String = "test \"".to_owned() + &escape(&test.name.value(), '"') +
+                    "\" ";
+
+                self.block(depth, &header, &test.body);
+            },
+            Statement::Throw(throw_statement) => {
+                let text: String = "throw ".to_owned() +
+                    &self.expression(&throw_statement.value, depth, column + 6) + ";";
+
+                self.line(depth, &text);
+            },
+            Statement::Try(try_statement) => self.try_statement(try_statement, depth),
+            Statement::Trivia(trivia) => self.trivia(trivia, depth)
+        }
+    }
+
+    // Print `try`, its catches and its finally block, each continuing the line which closes the
+    // one before.
+    fn try_statement(&mut self, try_statement: &TryStatement, depth: usize) -> () {
+        let mut sections: Vec<(String, &[Statement])> = vec![
+            ("try ".to_owned(), &try_statement.body)
+        ];
+
+        for catch in &try_statement.catches {
+            let mut header: String = "catch (".to_owned() + &catch.variable.value();
+
+            if let Some(error_type) = &catch.error_type {
+                header += &(": ".to_owned() + &error_type.to_string());
+            }
+
+            sections.push((header + ") ", &catch.body));
+        }
+
+        if let Some(finally) = &try_statement.finally {
+            sections.push(("finally ".to_owned(), finally));
+        }
+
+        for (index, (header, body)) in sections.into_iter().enumerate() {
+            if index == 0 {
+                self.line(depth, &(header + "{"));
+            } else {
+                self.close(depth, &("} ".to_owned() + &header + "{"));
+            }
+
+            self.block_start = true;
+            self.statements(body, depth + 1);
+        }
+
+        self.close(depth, "}");
+    }
+
+    fn variable_declaration(&self, declaration: &VariableDeclarationStatement, depth: usize)
+        -> String {
+        let mut text: String = if declaration.public { "pub ".to_owned() } else { String::new() };
+
+        text += &(declaration.variable_type.to_string() + " " +
+            &declaration.identifier.token.value());
+
+        if let Some(initialization) = &declaration.initialization {
+            let column: usize = depth * INDENT.len() + width(&text) + 3;
+
+            text += &(" = ".to_owned() + &self.expression(initialization, depth, column));
+        }
+
+        return text + ";";
+    }
+
+    fn if_statement(&mut self, if_statement: &IfStatement, depth: usize) -> () {
+        let mut current: &IfStatement = if_statement;
+        let mut prefix: &str = "if ";
+
+        loop {
+            let column: usize = depth * INDENT.len() + width(prefix);
+            let header: String = prefix.to_owned() +
+                &self.expression(&current.condition, depth, column) + " ";
+
+            let empty: bool = current.then_branch.is_empty() && current.else_branch.is_empty();
+            let header: String = header + if empty { "{}" } else { "{" };
+
+            // Later branches continue the line which closes the one before.
+            if prefix == "if " {
+                self.line(depth, &header);
+            } else {
+                self.close(depth, &header);
+            }
+
+            if empty {
+                return;
+            }
+
+            self.block_start = true;
+
+            self.statements(&current.then_branch, depth + 1);
+
+            match current.else_branch.as_slice() {
+                [] => {
+                    self.close(depth, "}");
+
+                    return;
+                },
+                // `else if`, which is parsed as an else branch holding just the next `if`.
+                [Statement::If(next)] => {
+                    current = next;
+                    prefix = "} else if ";
+                },
+                else_branch => {
+                    self.close(depth, "} else {");
+                    self.block_start = true;
+                    self.statements(else_branch, depth + 1);
+                    self.close(depth, "}");
+
+                    return;
+                }
+            }
+        }
+    }
+
+    fn parameters(&self, parameters: &[ParameterDeclaration]) -> Vec<String> {
+        return parameters.iter()
+            .map(|parameter| parameter.parameter_type.to_string() + " " + &parameter.name.value())
+            .collect();
+    }
+
+    fn function(&mut self, declaration: &FunctionDeclarationStatement, depth: usize,
+                constructor: bool) -> () {
+        let header: String = self.function_header(declaration, depth, constructor);
+
+        self.block(depth, &header, &declaration.body);
+    }
+
+    // The signature of a function up to its body, breaking the parameters over several lines if
+    // they do not fit on one.
+    fn function_header(&self, declaration: &FunctionDeclarationStatement, depth: usize,
+                       constructor: bool) -> String {
+        let mut prefix: String = if declaration.public { "pub ".to_owned() } else { String::new() };
+
+        // Constructors are written without their return type.
+        if !constructor {
+            prefix += &(declaration.return_type.to_string() + " ");
+        }
+
+        prefix += &(declaration.name.token.value() +
+            &type_parameters_to_string(&declaration.type_parameters));
+
+        let parameters: Vec<String> = self.parameters(&declaration.parameters);
+        let flat: String = prefix.to_owned() + "(" + &parameters.join(", ") + ") ";
+        return if self.fits(&(flat.to_owned() + "{"), depth * INDENT.len()) {
+            flat
+        } else {
+            prefix + "(\n" + &self.broken(&parameters, depth) + ") "
+        };
+    }
+
+    fn class(&mut self, declaration: &ClassDeclarationStatement, depth: usize) -> () {
+        let mut header: String = if declaration.public { "pub ".to_owned() } else { String::new() };
+
+        header += &("class ".to_owned() + &declaration.name.token.value() +
+            &type_parameters_to_string(&declaration.type_parameters) + " ");
+
+        if let Some(superclass) = declaration.superclass_type() {
+            header += &("extends ".to_owned() + &superclass.to_string() + " ");
+        }
+
+        if !declaration.interfaces.is_empty() {
+            let interfaces: Vec<String> = declaration.interfaces.iter()
+                .map(|interface| interface.to_string())
+                .collect();
+
+            header += &("implements ".to_owned() + &interfaces.join(", ") + " ");
+        }
+
+        let mut members: Vec<(Position, Member)> = Vec::new();
+
+        for property in &declaration.properties {
+            members.push((property.variable_type.position(), Member::Property(property)));
+        }
+
+        if let Some(constructor) = &declaration.constructor {
+            members.push((constructor.name.token.token_position, Member::Constructor(constructor)));
+        }
+
+        for method in &declaration.methods {
+            members.push((method.return_type.position(), Member::Method(method)));
+        }
+
+        for trivia in &declaration.trivia {
+            members.push((trivia.position(), Member::Trivia(trivia)));
+        }
+
+        if members.is_empty() {
+            self.line(depth, &(header + "{}"));
+
+            return;
+        }
+
+        members.sort_by_key(|(position, _)| source_order(*position));
+        self.open(depth, &(header + "{"));
+
+        for (_, member) in members {
+            match member {
+                Member::Property(property) => {
+                    let text: String = self.variable_declaration(property, depth + 1);
+
+                    self.line(depth + 1, &text);
+                },
+                Member::Constructor(constructor) => self.function(constructor, depth + 1, true),
+                Member::Method(method) => self.function(method, depth + 1, false),
+                Member::Trivia(trivia) => self.trivia(trivia, depth + 1)
+            }
+        }
+
+        self.close(depth, "}");
+    }
+
+    fn interface(&mut self, declaration: &InterfaceDeclarationStatement, depth: usize) -> () {
+        let mut header: String = if declaration.public { "pub ".to_owned() } else { String::new() };
+
+        header += &("interface ".to_owned() + &declaration.name.token.value() +
+            &type_parameters_to_string(&declaration.type_parameters) + " ");
+
+        let mut items: Vec<(Position, Option<&FunctionDeclarationStatement>, Option<&Trivia>)> =
+            Vec::new();
+
+        for method in &declaration.methods {
+            items.push((method.return_type.position(), Some(method), None));
+        }
+
+        for trivia in &declaration.trivia {
+            items.push((trivia.position(), None, Some(trivia)));
+        }
+
+        if items.is_empty() {
+            self.line(depth, &(header + "{}"));
+
+            return;
+        }
+
+        items.sort_by_key(|(position, _, _)| source_order(*position));
+        self.open(depth, &(header + "{"));
+
+        for (_, method, trivia) in items {
+            if let Some(trivia) = trivia {
+                self.trivia(trivia, depth + 1);
+
+                continue;
+            }
+
+            // Interface methods have no body, only a signature.
+            let header: String = self.function_header(method.unwrap(), depth + 1, false);
+
+            self.line(depth + 1, &(header.trim_end().to_owned() + ";"));
+        }
+
+        self.close(depth, "}");
+    }
+
+    fn enum_declaration(&mut self, declaration: &EnumDeclarationStatement, depth: usize) -> () {
+        let mut header: String = if declaration.public { "pub ".to_owned() } else { String::new() };
+
+        header += &("enum ".to_owned() + &declaration.name.token.value() + " ");
+
+        let mut items: Vec<(Position, Option<&EnumVariantDeclaration>, Option<&Trivia>)> =
+            Vec::new();
+
+        for variant in &declaration.variants {
+            items.push((variant.name.token_position, Some(variant), None));
+        }
+
+        for trivia in &declaration.trivia {
+            items.push((trivia.position(), None, Some(trivia)));
+        }
+
+        if items.is_empty() {
+            self.line(depth, &(header + "{}"));
+
+            return;
+        }
+
+        items.sort_by_key(|(position, _, _)| source_order(*position));
+        self.open(depth, &(header + "{"));
+
+        let mut remaining: usize = declaration.variants.len();
+
+        for (_, variant, trivia) in items {
+            if let Some(trivia) = trivia {
+                self.trivia(trivia, depth + 1);
+
+                continue;
+            }
+
+            let variant: &EnumVariantDeclaration = variant.unwrap();
+            let mut text: String = variant.name.value();
+
+            if !variant.fields.is_empty() {
+                text += &("(".to_owned() + &self.parameters(&variant.fields).join(", ") + ")");
+            }
+
+            remaining -= 1;
+
+            if remaining > 0 {
+                text.push(',');
+            }
+
+            self.line(depth + 1, &text);
+        }
+
+        self.close(depth, "}");
+    }
+
+    // Imports keep the form they were written in, as a path or as a dotted name.
+    fn import_path(&self, import: &ImportStatement) -> String {
+        if self.source_text(import.name.token_position).starts_with('"') {
+            return "\"".to_owned() + &escape(&import.path, '"') + "\"";
+        }
+
+        let extension: String = ".".to_owned() + SOURCE_EXTENSION;
+
+        return import.path.strip_suffix(&extension).unwrap_or(&import.path).replace('/', ".");
+    }
+
+    fn literal(&self, literal: &LiteralExpression) -> String {
+        // Numbers are written as they were in the source, which may separate their digits with
+        // underscores, as long as that is what they were parsed from.
+        let source: String = self.source_text(literal.position).replace('_', "");
+
+        return match &literal.value {
+            LiteralValue::Integer(value) if source.parse::<i64>() == Ok(*value) => {
+                self.source_text(literal.position)
+            },
+            LiteralValue::Float(value) if source.parse::<f64>() == Ok(*value) => {
+                self.source_text(literal.position)
+            },
+            LiteralValue::Integer(value) => value.to_string(),
+            LiteralValue::Float(value) => float_text(*value),
+            LiteralValue::String(value) => "\"".to_owned() + &escape(value, '"') + "\"",
+            LiteralValue::Char(value) => "'".to_owned() + &escape(&value.to_string(), '\'') + "'",
+            LiteralValue::Boolean(value) => value.to_string(),
+            LiteralValue::Null => "null".to_owned()
+        };
+    }
+
+    fn pattern(&self, pattern: &Pattern) -> String {
+        return match pattern {
+            Pattern::Literal(literal) => self.literal(literal),
+            Pattern::Binding(name) => name.value(),
+            Pattern::Wildcard(_) => "_".to_owned(),
+            Pattern::Variant(variant) => {
+                let mut text: String = variant.enum_name.value() + "." + &variant.variant.value();
+
+                if !variant.fields.is_empty() {
+                    let fields: Vec<String> = variant.fields.iter()
+                        .map(|field| self.pattern(field))
+                        .collect();
+
+                    text += &("(".to_owned() + &fields.join(", ") + ")");
+                }
+
+                text
+            }
+        };
+    }
+
+    // Put items each on a line of their own, one level deeper than the given depth, ending with
+    // the indentation of whatever closes them.
+    fn broken(&self, items: &[String], depth: usize) -> String {
+        let items: Vec<String> = items.iter()
+            .map(|item| indent(depth + 1) + item)
+            .collect();
+
+        return items.join(",\n") + "\n" + &indent(depth);
+    }
+
+    // An expression, with parentheses if it binds looser than the given precedence.
+    fn operand(&self, expression: &Expression, required: u8, depth: usize, column: usize)
+        -> String {
+        if precedence(expression) < required {
+            return "(".to_owned() + &self.expression(expression, depth, column + 1) + ")";
+        }
+
+        return self.expression(expression, depth, column);
+    }
+
+    fn flat_operand(&self, expression: &Expression, required: u8, depth: usize) -> String {
+        if precedence(expression) < required {
+            return "(".to_owned() + &self.flat(expression, depth) + ")";
+        }
+
+        return self.flat(expression, depth);
+    }
+
+    // An expression starting at the given column of a line at the given depth, which is broken
+    // across several lines if it does not fit on one.
+    fn expression(&self, expression: &Expression, depth: usize, column: usize) -> String {
+        let flat: String = self.flat(expression, depth);
+
+        if self.fits(&flat, column) {
+            return flat;
+        }
+
+        let inner: usize = (depth + 1) * INDENT.len();
+
+        return match expression {
+            Expression::FunctionCall(call) if !call.arguments.is_empty() => {
+                let arguments: Vec<String> = call.arguments.iter()
+                    .map(|argument| self.expression(argument, depth + 1, inner))
+                    .collect();
+
+                self.operand(&call.callee, POSTFIX, depth, column) + "(\n" +
+                    &self.broken(&arguments, depth) + ")"
+            },
+            Expression::List(list) if !list.elements.is_empty() => {
+                let elements: Vec<String> = list.elements.iter()
+                    .map(|element| self.expression(element, depth + 1, inner))
+                    .collect();
+
+                "[\n".to_owned() + &self.broken(&elements, depth) + "]"
+            },
+            Expression::Map(map) if !map.entries.is_empty() => {
+                let entries: Vec<String> = map.entries.iter()
+                    .map(|(key, value)| {
+                        let key: String = self.expression(key, depth + 1, inner);
+                        let column: usize = inner + width(&key) + 2;
+
+                        key + ": " + &self.expression(value, depth + 1, column)
+                    })
+                    .collect();
+
+                "{\n".to_owned() + &self.broken(&entries, depth) + "}"
+            },
+            // Operators are broken after, with the right operand on the next line.
+            Expression::Binary(binary) => {
+                let (left, right): (u8, u8) = operand_precedences(binary.operator);
+
+                self.operand(&binary.left, left, depth, column) + " " +
+                    &binary.operator.to_string() + "\n" + &indent(depth + 1) +
+                    &self.operand(&binary.right, right, depth + 1, inner)
+            },
+            Expression::Assignment(assignment) if !self.is_increment(expression) => {
+                let target: String = self.flat_operand(&assignment.target, POSTFIX, depth);
+                let operator: String = match assignment.operator {
+                    Some(operator) => operator.to_string() + "=",
+                    None => "=".to_owned()
+                };
+                let column: usize = column + width(&target) + width(&operator) + 2;
+
+                target + " " + &operator + " " + &self.operand(&assignment.value, 1, depth, column)
+            },
+            Expression::Unary(unary) => {
+                unary.operator.to_string() + &self.operand(&unary.operand, 11, depth, column + 1)
+            },
+            Expression::Property(property) => {
+                let access: &str = if property.safe { "?." } else { "." };
+
+                self.operand(&property.object, object_precedence(property), depth, column) +
+                    access + &property.name.value()
+            },
+            _ => flat
+        };
+    }
+
+    // Whether an assignment is `x++` or `x--`, which the parser turns into `x += 1` and `x -= 1`
+    // with the `1` at the position of the operator.
+    fn is_increment(&self, expression: &Expression) -> bool {
+        if let Expression::Assignment(assignment) = expression {
+            if let Expression::Literal(literal) = assignment.value.as_ref() {
+                return literal.value == LiteralValue::Integer(1)
+                    && literal.position == assignment.position
+                    && matches!(assignment.operator,
+                                Some(BinaryOperator::Add) | Some(BinaryOperator::Subtract));
+            }
+        }
+
+        return false;
+    }
+
+    // An expression on a single line, apart from the arms of any match in it.
+    fn flat(&self, expression: &Expression, depth: usize) -> String {
+        return match expression {
+            Expression::Literal(literal) => self.literal(literal),
+            Expression::Identifier(identifier) => identifier.token.value(),
+            Expression::Binary(binary) => {
+                let (left, right): (u8, u8) = operand_precedences(binary.operator);
+
+                self.flat_operand(&binary.left, left, depth) + " " +
+                    &binary.operator.to_string() + " " +
+                    &self.flat_operand(&binary.right, right, depth)
+            },
+            Expression::Unary(unary) => {
+                unary.operator.to_string() + &self.flat_operand(&unary.operand, 11, depth)
+            },
+            Expression::Assignment(assignment) => {
+                let target: String = self.flat_operand(&assignment.target, POSTFIX, depth);
+
+                if self.is_increment(expression) {
+                    let operator: &str = if assignment.operator == Some(BinaryOperator::Add) {
+                        "++"
+                    } else {
+                        "--"
+                    };
+
+                    return target + operator;
+                }
+
+                let operator: String = match assignment.operator {
+                    Some(operator) => operator.to_string() + "=",
+                    None => "=".to_owned()
+                };
+
+                target + " " + &operator + " " + &self.flat_operand(&assignment.value, 1, depth)
+            },
+            Expression::FunctionCall(call) => {
+                let arguments: Vec<String> = call.arguments.iter()
+                    .map(|argument| self.flat(argument, depth))
+                    .collect();
+
+                self.flat_operand(&call.callee, POSTFIX, depth) + "(" + &arguments.join(", ") +
+                    ")"
+            },
+            Expression::Property(property) => {
+                let access: &str = if property.safe { "?." } else { "." };
+
+                self.flat_operand(&property.object, object_precedence(property), depth) +
+                    access + &property.name.value()
+            },
+            Expression::This(_) => "this".to_owned(),
+            Expression::Super(super_expression) => {
+                "super.".to_owned() + &super_expression.method.value()
+            },
+            Expression::Is(is) => {
+                self.flat_operand(&is.value, 6, depth) + " is " + &is.class_name.value()
+            },
+            Expression::Map(map) => {
+                let entries: Vec<String> = map.entries.iter()
+                    .map(|(key, value)| self.flat(key, depth) + ": " + &self.flat(value, depth))
+                    .collect();
+
+                "{".to_owned() + &entries.join(", ") + "}"
+            },
+            Expression::List(list) => {
+                let elements: Vec<String> = list.elements.iter()
+                    .map(|element| self.flat(element, depth))
+                    .collect();
+
+                "[".to_owned() + &elements.join(", ") + "]"
+            },
+            Expression::Range(range) => {
+                self.flat_operand(&range.start, 9, depth) + ".." +
+                    &self.flat_operand(&range.end, 9, depth)
+            },
+            Expression::Match(match_expression) => self.match_expression(match_expression, depth),
+            Expression::Propagate(propagate) => {
+                self.flat_operand(&propagate.value, POSTFIX, depth) + "?"
+            }
+        };
+    }
+
+    fn match_expression(&self, match_expression: &MatchExpression, depth: usize) -> String {
+        let header: String = "match ".to_owned() +
+            &self.expression(&match_expression.value, depth, depth * INDENT.len() + 6) + " ";
+
+        if match_expression.arms.is_empty() && match_expression.trivia.is_empty() {
+            return header + "{}";
+        }
+
+        let mut items: Vec<(Position, Option<usize>, Option<&Trivia>)> = Vec::new();
+
+        for (index, arm) in match_expression.arms.iter().enumerate() {
+            items.push((arm.pattern.position(), Some(index), None));
+        }
+
+        for trivia in &match_expression.trivia {
+            items.push((trivia.position(), None, Some(trivia)));
+        }
+
+        items.sort_by_key(|(position, _, _)| source_order(*position));
+
+        return self.nested(depth, |formatter| {
+            formatter.open(depth, &(header + "{"));
+
+            for (_, index, trivia) in items {
+                if let Some(trivia) = trivia {
+                    formatter.trivia(trivia, depth + 1);
+
+                    continue;
+                }
+
+                let index: usize = index.unwrap();
+                let arm: &MatchArm = &match_expression.arms[index];
+                let mut prefix: String = formatter.pattern(&arm.pattern);
+
+                if let Some(guard) = &arm.guard {
+                    prefix += &(" if ".to_owned() + &formatter.flat(guard, depth + 1));
+                }
+
+                prefix += " => ";
+
+                match &arm.body {
+                    MatchArmBody::Expression(body) => {
+                        let column: usize = (depth + 1) * INDENT.len() + width(&prefix);
+                        let mut text: String = prefix + &formatter.expression(body, depth + 1,
+                                                                              column);
+
+                        if index + 1 < match_expression.arms.len() {
+                            text.push(',');
+                        }
+
+                        formatter.line(depth + 1, &text);
+                    },
+                    MatchArmBody::Block(body) => formatter.block(depth + 1, &prefix, body)
+                }
+            }
+
+            formatter.close(depth, "}");
+        });
+    }
+}
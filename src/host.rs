@@ -0,0 +1,305 @@
+use std::any::Any;
+use std::cell::{Ref, RefCell, RefMut};
+use std::rc::Rc;
+use crate::types::{FunctionType, Type};
+use crate::value::{HostObject, Value};
+
+// A Rust type which can be passed to scripts and back, as values of the given script type.
+pub trait ScriptValue: Sized {
+    fn script_type() -> Type;
+
+    fn into_value(self) -> Value;
+
+    // The Rust value of a script value, or `None` if it is not of the script type.
+    fn from_value(value: Value) -> Option<Self>;
+}
+
+impl ScriptValue for () {
+    fn script_type() -> Type {
+        return Type::Void;
+    }
+
+    fn into_value(self) -> Value {
+        return Value::Void;
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        return match value {
+            Value::Void => Some(()),
+            _ => None
+        };
+    }
+}
+
+impl ScriptValue for i64 {
+    fn script_type() -> Type {
+        return Type::Integer;
+    }
+
+    fn into_value(self) -> Value {
+        return Value::Integer(self);
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        return match value {
+            Value::Integer(value) => Some(value),
+            _ => None
+        };
+    }
+}
+
+impl ScriptValue for f64 {
+    fn script_type() -> Type {
+        return Type::Float;
+    }
+
+    fn into_value(self) -> Value {
+        return Value::Float(self);
+    }
+
+    // Integers can be given where floats are expected.
+    fn from_value(value: Value) -> Option<Self> {
+        return match value {
+            Value::Float(value) => Some(value),
+            Value::Integer(value) => Some(value as f64),
+            _ => None
+        };
+    }
+}
+
+impl ScriptValue for bool {
+    fn script_type() -> Type {
+        return Type::Boolean;
+    }
+
+    fn into_value(self) -> Value {
+        return Value::Boolean(self);
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        return match value {
+            Value::Boolean(value) => Some(value),
+            _ => None
+        };
+    }
+}
+
+impl ScriptValue for char {
+    fn script_type() -> Type {
+        return Type::Char;
+    }
+
+    fn into_value(self) -> Value {
+        return Value::Char(self);
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        return match value {
+            Value::Char(value) => Some(value),
+            _ => None
+        };
+    }
+}
+
+impl ScriptValue for String {
+    fn script_type() -> Type {
+        return Type::String;
+    }
+
+    fn into_value(self) -> Value {
+        return Value::String(self);
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        return match value {
+            Value::String(value) => Some(value),
+            _ => None
+        };
+    }
+}
+
+// Lists are copied, so changes made to them on either side are not seen by the other.
+impl<T: ScriptValue> ScriptValue for Vec<T> {
+    fn script_type() -> Type {
+        return Type::List(Box::new(T::script_type()));
+    }
+
+    fn into_value(self) -> Value {
+        let elements: Vec<Value> = self.into_iter().map(|element| element.into_value()).collect();
+
+        return Value::List(Rc::new(RefCell::new(elements)));
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        return match value {
+            Value::List(list) => list.borrow().iter()
+                .map(|element| T::from_value(element.to_owned()))
+                .collect(),
+            _ => None
+        };
+    }
+}
+
+impl<T: ScriptValue> ScriptValue for Option<T> {
+    fn script_type() -> Type {
+        return T::script_type().to_nullable();
+    }
+
+    fn into_value(self) -> Value {
+        return match self {
+            Some(value) => value.into_value(),
+            None => Value::Null
+        };
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        return match value {
+            Value::Null => Some(None),
+            value => T::from_value(value).map(Some)
+        };
+    }
+}
+
+// A Rust type whose values scripts can hold and call the registered methods of, but not look
+// inside of.
+pub trait HostClass: 'static {
+    const NAME: &'static str; // The name scripts know the type by.
+}
+
+// An object of a host class, shared between the host and the scripts holding it.
+pub struct Object<T: HostClass> {
+    object: Rc<RefCell<T>>
+}
+
+impl<T: HostClass> Object<T> {
+    pub fn new(value: T) -> Self {
+        return Self {
+            object: Rc::new(RefCell::new(value))
+        };
+    }
+
+    pub fn borrow(&self) -> Ref<'_, T> {
+        return self.object.borrow();
+    }
+
+    pub fn borrow_mut(&self) -> RefMut<'_, T> {
+        return self.object.borrow_mut();
+    }
+}
+
+impl<T: HostClass> Clone for Object<T> {
+    fn clone(&self) -> Self {
+        return Self {
+            object: self.object.to_owned()
+        };
+    }
+}
+
+impl<T: HostClass> ScriptValue for Object<T> {
+    fn script_type() -> Type {
+        return Type::Class(T::NAME.to_owned(), Vec::new());
+    }
+
+    fn into_value(self) -> Value {
+        return Value::Host(HostObject {
+            type_name: T::NAME.to_owned(),
+            object: self.object
+        });
+    }
+
+    fn from_value(value: Value) -> Option<Self> {
+        let object: Rc<dyn Any> = match value {
+            Value::Host(host_object) => host_object.object,
+            _ => return None
+        };
+
+        return object.downcast::<RefCell<T>>().ok().map(|object| Self { object });
+    }
+}
+
+// Runs a host function with the values of its arguments, or returns the message of a runtime
+// error.
+pub type HostFunctionBody = Rc<dyn Fn(Vec<Value>) -> Result<Value, String>>;
+
+// A Rust closure which scripts can call, with the types of its parameters and result given by its
+// signature. `Arguments` is the tuple of its parameter types, which tells the implementations for
+// each number of parameters apart.
+pub trait HostCallable<Arguments> {
+    fn function_type() -> FunctionType;
+
+    fn into_body(self) -> HostFunctionBody;
+}
+
+// Like `HostCallable`, but for methods of a host class, which are given the object they are called
+// on before their arguments.
+pub trait HostMethod<Class: HostClass, Arguments> {
+    fn function_type() -> FunctionType; // The signature without the object.
+
+    fn into_body(self) -> HostFunctionBody;
+}
+
+macro_rules! host_callable {
+    ($($parameter:ident),*) => {
+        impl<Function, Output, $($parameter),*> HostCallable<($($parameter,)*)> for Function
+            where Function: Fn($($parameter),*) -> Result<Output, String> + 'static,
+                  Output: ScriptValue,
+                  $($parameter: ScriptValue),* {
+            fn function_type() -> FunctionType {
+                return FunctionType {
+                    type_parameters: Vec::new(),
+                    parameters: vec![$($parameter::script_type()),*],
+                    return_type: Output::script_type()
+                };
+            }
+
+            #[allow(unused_mut, unused_variables)]
+            fn into_body(self) -> HostFunctionBody {
+                return Rc::new(move |arguments: Vec<Value>| {
+                    let mut arguments = arguments.into_iter();
+                    // The type checker has made sure the arguments have the declared types.
+                    let output: Output = self($(
+                        $parameter::from_value(arguments.next().unwrap()).unwrap()
+                    ),*)?;
+
+                    return Ok(output.into_value());
+                });
+            }
+        }
+
+        impl<Function, Output, Class, $($parameter),*> HostMethod<Class, ($($parameter,)*)>
+            for Function
+            where Function: Fn(Object<Class>, $($parameter),*) -> Result<Output, String> + 'static,
+                  Output: ScriptValue,
+                  Class: HostClass,
+                  $($parameter: ScriptValue),* {
+            fn function_type() -> FunctionType {
+                return FunctionType {
+                    type_parameters: Vec::new(),
+                    parameters: vec![$($parameter::script_type()),*],
+                    return_type: Output::script_type()
+                };
+            }
+
+            fn into_body(self) -> HostFunctionBody {
+                return Rc::new(move |arguments: Vec<Value>| {
+                    let mut arguments = arguments.into_iter();
+                    let object: Object<Class> = Object::from_value(arguments.next().unwrap())
+                        .unwrap();
+                    let output: Output = self(object, $(
+                        $parameter::from_value(arguments.next().unwrap()).unwrap()
+                    ),*)?;
+
+                    return Ok(output.into_value());
+                });
+            }
+        }
+    };
+}
+
+host_callable!();
+host_callable!(A);
+host_callable!(A, B);
+host_callable!(A, B, C);
+host_callable!(A, B, C, D);
+host_callable!(A, B, C, D, E);
+host_callable!(A, B, C, D, E, F);
@@ -0,0 +1,1060 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::process;
+use std::rc::Rc;
+use crate::expressions::{AssignmentExpression, BinaryOperator, Expression, FunctionCallExpression,
+                         MatchArmBody, MatchExpression, PropertyExpression};
+use crate::natives::{find_module_native, find_native, is_built_in_enum, HostFunction,
+                     NativeError, ERROR_CLASS, EXPECT_ERROR};
+use crate::operations::{binary_operation, list_method, map_method, unary_operation};
+use crate::patterns::match_pattern;
+use crate::position::Position;
+use crate::program::{stack_address, Program, RuntimeError, StackFrame, Stop};
+use crate::statements::{ClassDeclarationStatement, ForInStatement, ForStatement,
+                        FunctionDeclarationStatement, Statement, TryStatement};
+use crate::token::Token;
+use crate::token_type::TokenType;
+use crate::type_annotation::TypeAnnotation;
+use crate::value::{ClassValue, EnumValue, FunctionCode, InstanceValue, MapKey, MapValue, Value};
+
+// The deepest that function calls can be nested before the program is stopped.
+const MAX_CALL_DEPTH: usize = 1000;
+
+// Anything which stops the normal flow of execution.
+pub enum Interrupt {
+    Return(Value),
+    Break,
+    Continue,
+    Error(String, Position),
+    Exit(i32) // A call to `exit`, which nothing catches.
+}
+
+type Execution<T> = Result<T, Interrupt>;
+
+// The variables of a single function call.
+struct Frame {
+    scopes: Vec<HashMap<String, Option<Value>>>, // Variables declared without a value are unset.
+    this: Option<Value>,
+    class: Option<Rc<ClassValue>>, // The class declaring the running method, used by `super`.
+    stack_start: usize // Where the stack was when the call started.
+}
+
+impl Frame {
+    fn new(this: Option<Value>, class: Option<Rc<ClassValue>>) -> Self {
+        return Self {
+            scopes: vec![HashMap::new()],
+            this,
+            class,
+            stack_start: stack_address()
+        };
+    }
+}
+
+// The tree-walking interpreter, which runs the statements of a program directly.
+pub struct Interpreter<'a> {
+    program: &'a mut Program,
+    functions: HashMap<String, Rc<FunctionDeclarationStatement>>,
+    classes: HashMap<String, Rc<ClassValue>>,
+    enums: HashSet<String>,
+    frames: Vec<Frame>, // The first frame is the top level of the program, holding the globals.
+    // The calls an error has left so far while going back to the top level, innermost first.
+    trace: Vec<StackFrame>,
+    thrown: Option<Value>, // The object an error was thrown with, while it goes back up.
+    stack_start: usize // Where the stack was when the program started running.
+}
+
+impl<'a> Interpreter<'a> {
+    pub fn new(program: &'a mut Program) -> Self {
+        let mut frame: Frame = Frame::new(None, None);
+
+        // The values given by a program embedding the language are globals from the start.
+        for value in program.host_values() {
+            frame.scopes[0].insert(value.name.to_owned(), Some(value.value.to_owned()));
+        }
+
+        return Self {
+            program,
+            functions: HashMap::new(),
+            classes: HashMap::new(),
+            enums: HashSet::new(),
+            frames: vec![frame],
+            trace: Vec::new(),
+            thrown: None,
+            stack_start: stack_address()
+        };
+    }
+
+    pub fn interpret(&mut self, statements: &[Statement]) -> () {
+        match self.run(statements) {
+            Ok(()) => {},
+            Err(Stop::Error(error)) => self.program.die_with_runtime_error(error),
+            Err(Stop::Exit(code)) => process::exit(code)
+        }
+    }
+
+    // Run statements at the top level, keeping what they declare for any statements run after
+    // them. After an error, the interpreter is left ready to run more statements.
+    pub fn run(&mut self, statements: &[Statement]) -> Result<(), Stop> {
+        self.stack_start = stack_address();
+
+        // Functions, classes and enums can be used before they are declared.
+        for statement in statements {
+            match statement {
+                Statement::FunctionDeclaration(declaration) => {
+                    self.functions.insert(declaration.name.token.value(),
+                                          Rc::new(declaration.to_owned()));
+                },
+                Statement::ClassDeclaration(declaration) => {
+                    self.declare_class(declaration, statements);
+                },
+                Statement::EnumDeclaration(declaration) => {
+                    self.enums.insert(declaration.name.token.value());
+                },
+                _ => {}
+            }
+        }
+
+        for statement in statements {
+            if let Err(interrupt) = self.execute(statement) {
+                return Err(self.recover(interrupt));
+            }
+        }
+
+        return Ok(());
+    }
+
+    // Evaluate an expression at the top level.
+    pub fn evaluate_expression(&mut self, expression: &Expression) -> Result<Value, Stop> {
+        self.stack_start = stack_address();
+
+        return self.evaluate(expression).map_err(|interrupt| self.recover(interrupt));
+    }
+
+    // Go back to the top level of the program after an error or a call to `exit`.
+    fn recover(&mut self, interrupt: Interrupt) -> Stop {
+        self.frames.truncate(1);
+        self.frame().scopes.truncate(1);
+        self.thrown = None;
+
+        return match interrupt {
+            Interrupt::Error(message, position) => Stop::Error(RuntimeError {
+                message,
+                position,
+                trace: std::mem::take(&mut self.trace)
+            }),
+            Interrupt::Exit(code) => Stop::Exit(code),
+            // The type checker makes sure nothing else can escape to the top level.
+            _ => unreachable!()
+        };
+    }
+
+    fn declare_class(&mut self, declaration: &ClassDeclarationStatement,
+                     statements: &[Statement]) -> Rc<ClassValue> {
+        let name: String = declaration.name.token.value();
+
+        if let Some(class) = self.classes.get(&name) {
+            return class.to_owned();
+        }
+
+        // The superclass has to exist before its subclasses can.
+        let superclass: Option<Rc<ClassValue>> = declaration.superclass.as_ref()
+            .map(|superclass| {
+                // It may have been declared by statements which were run earlier.
+                if let Some(class) = self.classes.get(&superclass.token.value()) {
+                    return class.to_owned();
+                }
+
+                let superclass_declaration: &ClassDeclarationStatement = statements.iter()
+                    .find_map(|statement| match statement {
+                        Statement::ClassDeclaration(other)
+                            if other.name.token.value() == superclass.token.value() => Some(other),
+                        _ => None
+                    })
+                    .unwrap();
+
+                self.declare_class(superclass_declaration, statements)
+            });
+
+        let initializer: FunctionDeclarationStatement = FunctionDeclarationStatement {
+            return_type: TypeAnnotation {
+                name: Token::new(TokenType::Identifier, Some("void".to_owned()),
+                                 declaration.name.token.token_position.to_owned()),
+                arguments: Vec::new(),
+                nullable: false
+            },
+            name: declaration.name.to_owned(),
+            type_parameters: Vec::new(),
+            parameters: Vec::new(),
+            body: declaration.properties.iter()
+                .map(|property| Statement::VariableDeclaration(property.to_owned()))
+                .collect(),
+            public: false
+        };
+
+        let class: Rc<ClassValue> = Rc::new(ClassValue {
+            name: name.to_owned(),
+            superclass,
+            initializer: FunctionCode::Declaration(Rc::new(initializer)),
+            constructor: declaration.constructor.to_owned()
+                .map(|constructor| FunctionCode::Declaration(Rc::new(constructor))),
+            methods: declaration.methods.iter()
+                .map(|method| (
+                    method.name.token.value(),
+                    FunctionCode::Declaration(Rc::new(method.to_owned()))
+                ))
+                .collect()
+        });
+
+        self.classes.insert(name, class.to_owned());
+
+        return class;
+    }
+
+    fn declaration(code: FunctionCode) -> Rc<FunctionDeclarationStatement> {
+        return match code {
+            FunctionCode::Declaration(declaration) => declaration,
+            FunctionCode::Compiled(_) | FunctionCode::Native => unreachable!()
+        };
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        return self.frames.last_mut().unwrap();
+    }
+
+    fn declare_variable(&mut self, name: String, value: Option<Value>) -> () {
+        self.frame().scopes.last_mut().unwrap().insert(name, value);
+    }
+
+    // Find the slot holding a variable, looking through the scopes of the current call and then
+    // the globals.
+    fn find_variable(&mut self, name: &str) -> &mut Option<Value> {
+        let current: usize = self.frames.len() - 1;
+        let scope: Option<usize> = self.frames[current].scopes.iter()
+            .rposition(|scope| scope.contains_key(name));
+
+        // The type checker makes sure the variable exists.
+        return match scope {
+            Some(scope) => self.frames[current].scopes[scope].get_mut(name).unwrap(),
+            None => self.frames[0].scopes[0].get_mut(name).unwrap()
+        };
+    }
+
+    fn is_variable(&self, name: &str) -> bool {
+        return self.frames.last().unwrap().scopes.iter().any(|scope| scope.contains_key(name))
+            || self.frames[0].scopes[0].contains_key(name);
+    }
+
+    // If the property names a variant of an enum, such as `Shape.Circle`, return the name of the
+    // enum.
+    fn find_enum(&self, property: &PropertyExpression) -> Option<String> {
+        return match property.object.as_ref() {
+            Expression::Identifier(identifier) => {
+                let name: String = identifier.token.value();
+
+                let is_enum: bool = self.enums.contains(&name) || is_built_in_enum(&name);
+
+                if is_enum && !self.is_variable(&name) {
+                    Some(name)
+                } else {
+                    None
+                }
+            },
+            _ => None
+        };
+    }
+
+    fn execute_block(&mut self, statements: &[Statement]) -> Execution<()> {
+        self.frame().scopes.push(HashMap::new());
+
+        let mut result: Execution<()> = Ok(());
+
+        for statement in statements {
+            result = self.execute(statement);
+
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.frame().scopes.pop();
+
+        return result;
+    }
+
+    // Run the body of a loop, returning whether the loop should stop.
+    fn execute_loop_body(&mut self, body: &[Statement]) -> Execution<bool> {
+        return match self.execute_block(body) {
+            Ok(()) | Err(Interrupt::Continue) => Ok(false),
+            Err(Interrupt::Break) => Ok(true),
+            Err(interrupt) => Err(interrupt)
+        };
+    }
+
+    fn execute(&mut self, statement: &Statement) -> Execution<()> {
+        self.check_nesting(statement.position())?;
+
+        match statement {
+            Statement::Expression(expression_statement) => {
+                self.evaluate(&expression_statement.expression)?;
+            },
+            Statement::VariableDeclaration(declaration) => {
+                let value: Option<Value> = match &declaration.initialization {
+                    Some(initialization) => Some(self.evaluate(initialization)?),
+                    None => None
+                };
+
+                self.declare_variable(declaration.identifier.token.value(), value);
+            },
+            Statement::Block(block) => self.execute_block(&block.statements)?,
+            Statement::If(if_statement) => {
+                if self.evaluate_condition(&if_statement.condition)? {
+                    self.execute_block(&if_statement.then_branch)?;
+                } else {
+                    self.execute_block(&if_statement.else_branch)?;
+                }
+            },
+            Statement::While(while_statement) => {
+                while self.evaluate_condition(&while_statement.condition)? {
+                    if self.execute_loop_body(&while_statement.body)? {
+                        break;
+                    }
+                }
+            },
+            Statement::Loop(loop_statement) => {
+                while !self.execute_loop_body(&loop_statement.body)? {}
+            },
+            Statement::For(for_statement) => {
+                self.frame().scopes.push(HashMap::new());
+
+                let result: Execution<()> = self.execute_for(for_statement);
+
+                self.frame().scopes.pop();
+
+                result?;
+            },
+            Statement::ForIn(for_in_statement) => self.execute_for_in(for_in_statement)?,
+            Statement::Break(_) => return Err(Interrupt::Break),
+            Statement::Continue(_) => return Err(Interrupt::Continue),
+            Statement::Return(return_statement) => {
+                let value: Value = match &return_statement.value {
+                    Some(value) => self.evaluate(value)?,
+                    None => Value::Void
+                };
+
+                return Err(Interrupt::Return(value));
+            },
+            Statement::Throw(throw_statement) => {
+                let value: Value = self.evaluate(&throw_statement.value)?;
+
+                return Err(self.throw(value, throw_statement.position.to_owned()));
+            },
+            Statement::Try(try_statement) => self.execute_try(try_statement)?,
+            // These were declared before the program started running, and interfaces only matter
+            // to the type checker.
+            Statement::FunctionDeclaration(_) | Statement::ClassDeclaration(_) |
+            Statement::InterfaceDeclaration(_) | Statement::EnumDeclaration(_) => {},
+            Statement::Import(_) | Statement::Test(_) | Statement::Trivia(_) => unreachable!()
+        }
+
+        return Ok(());
+    }
+
+    fn execute_try(&mut self, try_statement: &TryStatement) -> Execution<()> {
+        let frames: usize = self.frames.len();
+        let scopes: usize = self.frame().scopes.len();
+        let mut result: Execution<()> = self.execute_block(&try_statement.body);
+
+        // Without catches, errors are left to go up once the finally block has run.
+        if !try_statement.catches.is_empty() {
+            if let Err(Interrupt::Error(message, position)) = result {
+                self.frames.truncate(frames);
+                self.frame().scopes.truncate(scopes);
+
+                let error: Value = self.catch(message, position);
+
+                result = self.execute_catches(try_statement, error);
+            }
+        }
+
+        if let Some(finally) = &try_statement.finally {
+            // An error on its way up waits for the finally block, which can replace it with one of
+            // its own.
+            let thrown: Option<Value> = self.thrown.take();
+            let trace: Vec<StackFrame> = std::mem::take(&mut self.trace);
+
+            self.execute_block(finally)?;
+            self.thrown = thrown;
+            self.trace = trace;
+        }
+
+        return result;
+    }
+
+    // Run the first catch the error is an instance of the class of, or throw the error again if
+    // there is none.
+    fn execute_catches(&mut self, try_statement: &TryStatement, error: Value) -> Execution<()> {
+        for catch in &try_statement.catches {
+            let caught: bool = match (&catch.error_type, &error) {
+                (Some(error_type), Value::Instance(instance)) => {
+                    instance.borrow().class.is_subclass_of(&error_type.name.value())
+                },
+                _ => true
+            };
+
+            if caught {
+                self.frame().scopes.push(HashMap::new());
+                self.declare_variable(catch.variable.value(), Some(error));
+
+                let result: Execution<()> = self.execute_block(&catch.body);
+
+                self.frame().scopes.pop();
+
+                return result;
+            }
+        }
+
+        return Err(self.throw(error, try_statement.position.to_owned()));
+    }
+
+    // The error object for an error which is being caught, which is the one it was thrown with,
+    // or an `Error` for one found while running. It remembers the error, in case it is thrown
+    // again.
+    fn catch(&mut self, message: String, position: Position) -> Value {
+        let error: Value = self.thrown.take().unwrap_or_else(|| {
+            InstanceValue::error(self.classes[ERROR_CLASS].to_owned(), message.to_owned())
+        });
+
+        if let Value::Instance(instance) = &error {
+            instance.borrow_mut().origin = Some(RuntimeError {
+                message,
+                position,
+                trace: std::mem::take(&mut self.trace)
+            });
+        }
+
+        return error;
+    }
+
+    // Start an error with an error object. One which has been caught before carries on from where
+    // it started, along with the calls it had left by then.
+    fn throw(&mut self, error: Value, position: Position) -> Interrupt {
+        let interrupt: Interrupt = match &error {
+            Value::Instance(instance) => match &instance.borrow().origin {
+                Some(origin) => {
+                    self.trace = origin.trace.to_owned();
+
+                    Interrupt::Error(origin.message.to_owned(), origin.position.to_owned())
+                },
+                None => Interrupt::Error(instance.borrow().error_message(), position)
+            },
+            _ => unreachable!()
+        };
+
+        self.thrown = Some(error);
+
+        return interrupt;
+    }
+
+    fn execute_for(&mut self, for_statement: &ForStatement) -> Execution<()> {
+        let initial: Value = self.evaluate(&for_statement.initializer)?;
+
+        self.declare_variable(for_statement.variable.value(), Some(initial));
+
+        while self.evaluate_condition(&for_statement.condition)? {
+            if self.execute_loop_body(&for_statement.body)? {
+                break;
+            }
+
+            self.evaluate(&for_statement.increment)?;
+        }
+
+        return Ok(());
+    }
+
+    fn execute_for_in(&mut self, for_in_statement: &ForInStatement) -> Execution<()> {
+        let iterable: Value = self.evaluate(&for_in_statement.iterable)?;
+        let position: Position = for_in_statement.iterable.position();
+        let variables: &[Token] = &for_in_statement.variables;
+        let body: &[Statement] = &for_in_statement.body;
+
+        // Lists and maps are copied before iterating, so changing them inside of the loop does
+        // not affect which items are visited.
+        match iterable {
+            Value::Range(start, end) => {
+                for index in start..end {
+                    if self.execute_iteration(variables, vec![Value::Integer(index)], body)? {
+                        break;
+                    }
+                }
+            },
+            Value::List(list) => {
+                let elements: Vec<Value> = list.borrow().to_owned();
+
+                for element in elements {
+                    if self.execute_iteration(variables, vec![element], body)? {
+                        break;
+                    }
+                }
+            },
+            Value::Map(map) => {
+                let entries: Vec<(MapKey, Value)> = map.borrow().entries().to_owned();
+
+                for (key, value) in entries {
+                    let values: Vec<Value> = if variables.len() == 2 {
+                        vec![key.to_value(), value]
+                    } else {
+                        vec![key.to_value()]
+                    };
+
+                    if self.execute_iteration(variables, values, body)? {
+                        break;
+                    }
+                }
+            },
+            Value::Instance(_) | Value::Host(_) => {
+                let iterator: Value = if self.has_method(&iterable, "iterator") {
+                    self.call_method(iterable.to_owned(), "iterator", Vec::new(), position)?
+                } else {
+                    iterable.to_owned()
+                };
+
+                while let Value::Boolean(true) = self.call_method(iterator.to_owned(),
+                                                                  "has_next", Vec::new(),
+                                                                  position)? {
+                    let item: Value = self.call_method(iterator.to_owned(), "next", Vec::new(),
+                                                       position)?;
+
+                    if self.execute_iteration(variables, vec![item], body)? {
+                        break;
+                    }
+                }
+            },
+            _ => unreachable!()
+        }
+
+        return Ok(());
+    }
+
+    fn has_method(&self, object: &Value, name: &str) -> bool {
+        return match object {
+            Value::Instance(instance) => instance.borrow().class.find_method(name).is_some(),
+            Value::Host(host_object) => {
+                self.program.find_host_method(&host_object.type_name, name).is_some()
+            },
+            _ => false
+        };
+    }
+
+    // Run a single iteration of a `for ... in` loop, returning whether the loop should stop.
+    fn execute_iteration(&mut self, variables: &[Token], values: Vec<Value>, body: &[Statement])
+        -> Execution<bool> {
+        self.frame().scopes.push(HashMap::new());
+
+        for (variable, value) in variables.iter().zip(values) {
+            self.declare_variable(variable.value(), Some(value));
+        }
+
+        let result: Execution<bool> = self.execute_loop_body(body);
+
+        self.frame().scopes.pop();
+
+        return result;
+    }
+
+    fn evaluate_condition(&mut self, condition: &Expression) -> Execution<bool> {
+        return match self.evaluate(condition)? {
+            Value::Boolean(value) => Ok(value),
+            _ => unreachable!()
+        };
+    }
+
+    fn evaluate(&mut self, expression: &Expression) -> Execution<Value> {
+        self.check_nesting(expression.position())?;
+
+        return match expression {
+            Expression::Literal(literal) => Ok(Value::from_literal(&literal.value)),
+            Expression::Identifier(identifier) => {
+                let name: String = identifier.token.value();
+
+                match self.find_variable(&name) {
+                    Some(value) => Ok(value.to_owned()),
+                    None => Err(Interrupt::Error(
+                        "Variable `".to_owned() + &name + "` is used before it is given a value.",
+                        identifier.token.token_position.to_owned()
+                    ))
+                }
+            },
+            Expression::Binary(binary) => {
+                let left: Value = self.evaluate(&binary.left)?;
+
+                // `&&`, `||` and `??` only evaluate their right side when they need to.
+                match (binary.operator, &left) {
+                    (BinaryOperator::And, Value::Boolean(false)) => return Ok(left),
+                    (BinaryOperator::Or, Value::Boolean(true)) => return Ok(left),
+                    (BinaryOperator::And | BinaryOperator::Or, _) => {
+                        return self.evaluate(&binary.right);
+                    },
+                    (BinaryOperator::Coalesce, Value::Null) => return self.evaluate(&binary.right),
+                    (BinaryOperator::Coalesce, _) => return Ok(left),
+                    _ => {}
+                }
+
+                let right: Value = self.evaluate(&binary.right)?;
+
+                binary_operation(binary.operator, &left, &right)
+                    .map_err(|message| Interrupt::Error(message, binary.position.to_owned()))
+            },
+            Expression::Unary(unary) => {
+                let operand: Value = self.evaluate(&unary.operand)?;
+
+                unary_operation(unary.operator, &operand)
+                    .map_err(|message| Interrupt::Error(message, unary.position.to_owned()))
+            },
+            Expression::Assignment(assignment) => self.evaluate_assignment(assignment),
+            Expression::FunctionCall(call) => self.evaluate_call(call),
+            Expression::Property(property) => {
+                if let Some(enum_name) = self.find_enum(property) {
+                    return Ok(Value::Enum(Rc::new(EnumValue {
+                        enum_name,
+                        variant: property.name.value(),
+                        fields: Vec::new()
+                    })));
+                }
+
+                let object: Value = self.evaluate(&property.object)?;
+                let name: String = property.name.value();
+
+                let instance: Rc<RefCell<InstanceValue>> = match object {
+                    Value::Instance(instance) => instance,
+                    Value::Null if property.safe => return Ok(Value::Null),
+                    Value::Null => return Err(Interrupt::Error(
+                        "Cannot get property `".to_owned() + &name + "` of null.",
+                        property.name.token_position.to_owned()
+                    )),
+                    _ => unreachable!()
+                };
+
+                let value: Option<Value> = instance.borrow().fields[&name].to_owned();
+
+                match value {
+                    Some(value) => Ok(value),
+                    None => Err(Interrupt::Error(
+                        "Property `".to_owned() + &name + "` is used before it is given a value.",
+                        property.name.token_position.to_owned()
+                    ))
+                }
+            },
+            Expression::This(_) => Ok(self.frame().this.to_owned().unwrap()),
+            Expression::Super(_) => unreachable!(),
+            Expression::Is(is) => match self.evaluate(&is.value)? {
+                Value::Instance(instance) => Ok(Value::Boolean(
+                    instance.borrow().class.is_subclass_of(&is.class_name.value())
+                )),
+                Value::Host(host_object) => {
+                    Ok(Value::Boolean(host_object.type_name == is.class_name.value()))
+                },
+                _ => unreachable!()
+            },
+            Expression::Map(map) => {
+                let mut map_value: MapValue = MapValue::new();
+
+                for (key, value) in &map.entries {
+                    let key: MapKey = MapKey::from_value(&self.evaluate(key)?).unwrap();
+                    let value: Value = self.evaluate(value)?;
+
+                    map_value.set(key, value);
+                }
+
+                Ok(Value::Map(Rc::new(RefCell::new(map_value))))
+            },
+            Expression::List(list) => {
+                let elements: Vec<Value> = self.evaluate_arguments(&list.elements)?;
+
+                Ok(Value::List(Rc::new(RefCell::new(elements))))
+            },
+            Expression::Range(range) => {
+                let start: Value = self.evaluate(&range.start)?;
+                let end: Value = self.evaluate(&range.end)?;
+
+                match (start, end) {
+                    (Value::Integer(start), Value::Integer(end)) => Ok(Value::Range(start, end)),
+                    _ => unreachable!()
+                }
+            },
+            Expression::Match(match_expression) => self.evaluate_match(match_expression),
+            Expression::Propagate(propagate) => match self.evaluate(&propagate.value)? {
+                Value::Enum(enum_value) if enum_value.is_present() => {
+                    Ok(enum_value.fields[0].to_owned())
+                },
+                value => Err(Interrupt::Return(value))
+            }
+        };
+    }
+
+    fn evaluate_match(&mut self, match_expression: &MatchExpression) -> Execution<Value> {
+        let value: Value = self.evaluate(&match_expression.value)?;
+
+        for arm in &match_expression.arms {
+            let mut bindings: Vec<(String, Value)> = Vec::new();
+
+            if !match_pattern(&arm.pattern, &value, &mut bindings) {
+                continue;
+            }
+
+            self.frame().scopes.push(HashMap::new());
+
+            for (name, bound) in bindings {
+                self.declare_variable(name, Some(bound));
+            }
+
+            let result: Execution<Option<Value>> = self.evaluate_arm(arm.guard.as_ref(),
+                                                                     &arm.body);
+
+            self.frame().scopes.pop();
+
+            if let Some(result) = result? {
+                return Ok(result);
+            }
+        }
+
+        // The type checker makes sure that every match is exhaustive.
+        unreachable!();
+    }
+
+    // Run the body of a match arm whose pattern matched, or return `None` if its guard fails.
+    fn evaluate_arm(&mut self, guard: Option<&Expression>, body: &MatchArmBody)
+        -> Execution<Option<Value>> {
+        if let Some(guard) = guard {
+            if !self.evaluate_condition(guard)? {
+                return Ok(None);
+            }
+        }
+
+        return match body {
+            MatchArmBody::Expression(expression) => Ok(Some(self.evaluate(expression)?)),
+            MatchArmBody::Block(statements) => {
+                self.execute_block(statements)?;
+
+                Ok(Some(Value::Void))
+            }
+        };
+    }
+
+    fn evaluate_assignment(&mut self, assignment: &AssignmentExpression) -> Execution<Value> {
+        let mut value: Value = self.evaluate(&assignment.value)?;
+
+        if let Some(operator) = assignment.operator {
+            let current: Value = self.evaluate(&assignment.target)?;
+
+            value = binary_operation(operator, &current, &value)
+                .map_err(|message| Interrupt::Error(message, assignment.position.to_owned()))?;
+        }
+
+        match assignment.target.as_ref() {
+            Expression::Identifier(identifier) => {
+                *self.find_variable(&identifier.token.value()) = Some(value.to_owned());
+            },
+            Expression::Property(property) => {
+                match self.evaluate(&property.object)? {
+                    Value::Instance(instance) => {
+                        instance.borrow_mut().fields.insert(property.name.value(),
+                                                            Some(value.to_owned()));
+                    },
+                    _ => unreachable!()
+                }
+            },
+            _ => unreachable!()
+        }
+
+        return Ok(value);
+    }
+
+    fn evaluate_arguments(&mut self, arguments: &[Expression]) -> Execution<Vec<Value>> {
+        let mut values: Vec<Value> = Vec::new();
+
+        for argument in arguments {
+            values.push(self.evaluate(argument)?);
+        }
+
+        return Ok(values);
+    }
+
+    // Evaluate an expression which has to stop with an error, and produce `void` once it does.
+    fn expect_error(&mut self, expression: &Expression, position: Position)
+        -> Execution<Value> {
+        let frames: usize = self.frames.len();
+        let scopes: usize = self.frame().scopes.len();
+        let trace: usize = self.trace.len();
+
+        return match self.evaluate(expression) {
+            Ok(_) => Err(Interrupt::Error(
+                "Expected an error, but there was none.".to_owned(),
+                position
+            )),
+            Err(Interrupt::Error(_, _)) => {
+                self.frames.truncate(frames);
+                self.frame().scopes.truncate(scopes);
+                self.trace.truncate(trace);
+                self.thrown = None;
+
+                Ok(Value::Void)
+            },
+            Err(interrupt) => Err(interrupt)
+        };
+    }
+
+    fn evaluate_call(&mut self, call: &FunctionCallExpression) -> Execution<Value> {
+        let position: Position = call.position.to_owned();
+
+        match call.callee.as_ref() {
+            Expression::Identifier(identifier) => {
+                let name: String = identifier.token.value();
+
+                if name == EXPECT_ERROR && !self.functions.contains_key(&name)
+                    && !self.classes.contains_key(&name) {
+                    return self.expect_error(&call.arguments[0], position);
+                }
+
+                let arguments: Vec<Value> = self.evaluate_arguments(&call.arguments)?;
+
+                if let Some(function) = self.functions.get(&name) {
+                    return self.call_function(function.to_owned(), arguments, None, None,
+                                              position);
+                }
+
+                if let Some(class) = self.classes.get(&name) {
+                    return self.instantiate(class.to_owned(), arguments, position);
+                }
+
+                if let Some(function) = self.program.find_host_function(&name) {
+                    return (function.function)(arguments)
+                        .map_err(|message| Interrupt::Error(message, position));
+                }
+
+                // Otherwise, this is a native function.
+                let (_, native) = find_native(&name).unwrap();
+
+                return (native.function)(self.program, arguments)
+                    .map_err(|error| native_interrupt(error, position));
+            },
+            Expression::Property(property) => {
+                if let Some((_, native)) = find_module_native(
+                    property, |name| self.is_variable(name)
+                ) {
+                    let arguments: Vec<Value> = self.evaluate_arguments(&call.arguments)?;
+
+                    return (native.function)(self.program, arguments)
+                        .map_err(|error| native_interrupt(error, position));
+                }
+
+                if let Some(enum_name) = self.find_enum(property) {
+                    return Ok(Value::Enum(Rc::new(EnumValue {
+                        enum_name,
+                        variant: property.name.value(),
+                        fields: self.evaluate_arguments(&call.arguments)?
+                    })));
+                }
+
+                let object: Value = self.evaluate(&property.object)?;
+
+                // The arguments are not evaluated when `?.` skips the call.
+                if property.safe && object == Value::Null {
+                    return Ok(Value::Null);
+                }
+
+                let arguments: Vec<Value> = self.evaluate_arguments(&call.arguments)?;
+                let name: String = property.name.value();
+
+                return self.call_method(object, &name, arguments, position);
+            },
+            Expression::Super(super_expression) => {
+                let arguments: Vec<Value> = self.evaluate_arguments(&call.arguments)?;
+                let this: Option<Value> = self.frame().this.to_owned();
+                let superclass: Rc<ClassValue> = self.frame().class.as_ref().unwrap()
+                    .superclass.to_owned().unwrap();
+                let (owner, method) = superclass.find_method(&super_expression.method.value())
+                    .unwrap();
+
+                return self.call_function(Self::declaration(method), arguments, this, Some(owner),
+                                          position);
+            },
+            _ => unreachable!()
+        }
+    }
+
+    fn call_method(&mut self, object: Value, name: &str, arguments: Vec<Value>,
+                   position: Position) -> Execution<Value> {
+        return match &object {
+            Value::Map(map) => map_method(map, name, &arguments)
+                .map_err(|message| Interrupt::Error(message, position)),
+            Value::List(list) => list_method(list, name, &arguments)
+                .map_err(|message| Interrupt::Error(message, position)),
+            Value::Instance(instance) => {
+                let class: Rc<ClassValue> = instance.borrow().class.to_owned();
+                let (owner, method) = class.find_method(name).unwrap();
+
+                self.call_function(Self::declaration(method), arguments, Some(object.to_owned()),
+                                   Some(owner), position)
+            },
+            Value::Host(host_object) => {
+                let method: &HostFunction = self.program
+                    .find_host_method(&host_object.type_name, name)
+                    .unwrap();
+                let mut method_arguments: Vec<Value> = vec![object.to_owned()];
+
+                method_arguments.extend(arguments);
+
+                (method.function)(method_arguments)
+                    .map_err(|message| Interrupt::Error(message, position))
+            },
+            Value::Null => Err(Interrupt::Error(
+                "Cannot call method `".to_owned() + name + "` of null.",
+                position
+            )),
+            _ => unreachable!()
+        };
+    }
+
+    fn call_function(&mut self, function: Rc<FunctionDeclarationStatement>, arguments: Vec<Value>,
+                     this: Option<Value>, class: Option<Rc<ClassValue>>, position: Position)
+        -> Execution<Value> {
+        self.check_depth(position.to_owned())?;
+
+        // Methods are named along with the class declaring them, and constructors after it, as
+        // they keep the name of their class as written in its module.
+        let name: String = match &class {
+            Some(class) => match &class.constructor {
+                Some(FunctionCode::Declaration(constructor))
+                    if Rc::ptr_eq(constructor, &function) => class.name.to_owned(),
+                _ => class.name.to_owned() + "." + &function.name.token.value()
+            },
+            None => function.name.token.value()
+        };
+        let mut frame: Frame = Frame::new(this, class);
+
+        for (parameter, argument) in function.parameters.iter().zip(arguments) {
+            frame.scopes[0].insert(parameter.name.value(), Some(argument));
+        }
+
+        self.frames.push(frame);
+
+        let result: Execution<()> = self.execute_block(&function.body);
+
+        self.frames.pop();
+
+        return match result {
+            Ok(()) => Ok(Value::Void),
+            Err(Interrupt::Return(value)) => Ok(value),
+            Err(interrupt) => Err(self.trace_call(interrupt, name, position))
+        };
+    }
+
+    fn instantiate(&mut self, class: Rc<ClassValue>, arguments: Vec<Value>, position: Position)
+        -> Execution<Value> {
+        let mut fields: HashMap<String, Option<Value>> = HashMap::new();
+        let mut chain: Vec<Rc<ClassValue>> = Vec::new();
+        let mut current: Option<Rc<ClassValue>> = Some(class.to_owned());
+
+        while let Some(class) = current {
+            current = class.superclass.to_owned();
+            chain.push(class);
+        }
+
+        // Superclass properties are initialized first. Initializers can only see the globals, so
+        // they run in a frame of their own, whose variables become the fields of the instance.
+        for class in chain.iter().rev() {
+            let initializer: Rc<FunctionDeclarationStatement> = Self::declaration(
+                class.initializer.to_owned()
+            );
+            let mut result: Execution<()> = Ok(());
+
+            self.check_depth(position.to_owned())?;
+            self.frames.push(Frame::new(None, None));
+
+            for statement in &initializer.body {
+                result = self.execute(statement);
+
+                if result.is_err() {
+                    break;
+                }
+            }
+
+            let frame: Frame = self.frames.pop().unwrap();
+
+            // Errors in initializers are shown as happening in the class.
+            result.map_err(|interrupt| {
+                self.trace_call(interrupt, class.name.to_owned(), position.to_owned())
+            })?;
+            fields.extend(frame.scopes.into_iter().next().unwrap());
+        }
+
+        let instance: Value = Value::Instance(Rc::new(RefCell::new(
+            InstanceValue::new(class.to_owned(), fields)
+        )));
+
+        if let Some((owner, constructor)) = class.find_constructor() {
+            self.call_function(Self::declaration(constructor), arguments,
+                               Some(instance.to_owned()), Some(owner), position)?;
+        }
+
+        return Ok(instance);
+    }
+
+    // Stop a call which would nest too deeply. Each call takes more of the stack than the frame
+    // kept for it, by an amount depending on what it does and how the interpreter was built, so
+    // how much of the stack is in use is checked as well as the number of frames.
+    fn check_depth(&self, position: Position) -> Execution<()> {
+        let used: usize = self.stack_start.abs_diff(stack_address());
+
+        if self.frames.len() >= MAX_CALL_DEPTH || used >= self.program.stack_limit() {
+            return Err(Interrupt::Error(
+                "Stack overflow; functions are nested too deeply.".to_owned(),
+                position
+            ));
+        }
+
+        return Ok(());
+    }
+
+    // Stop a statement or expression nested too deeply to run within the stack. Deep recursion
+    // reaches the limit here as well, so the nesting is only blamed when the program is not in a
+    // call, or the innermost call has used much of the stack by itself.
+    fn check_nesting(&self, position: Position) -> Execution<()> {
+        let limit: usize = self.program.stack_limit();
+
+        if self.stack_start.abs_diff(stack_address()) < limit {
+            return Ok(());
+        }
+
+        let frame: &Frame = self.frames.last().unwrap();
+        let nested: bool = self.frames.len() == 1 ||
+            frame.stack_start.abs_diff(stack_address()) >= limit / 2;
+        let message: &str = if nested {
+            "Stack overflow; statements and expressions are nested too deeply."
+        } else {
+            "Stack overflow; functions are nested too deeply."
+        };
+
+        return Err(Interrupt::Error(message.to_owned(), position));
+    }
+
+    // Add a call an error is leaving to its trace.
+    fn trace_call(&mut self, interrupt: Interrupt, function: String, position: Position)
+        -> Interrupt {
+        if let Interrupt::Error(_, _) = interrupt {
+            self.trace.push(StackFrame {
+                function,
+                position
+            });
+        }
+
+        return interrupt;
+    }
+}
+
+fn native_interrupt(error: NativeError, position: Position) -> Interrupt {
+    return match error {
+        NativeError::Error(message) => Interrupt::Error(message, position),
+        NativeError::Exit(code) => Interrupt::Exit(code)
+    };
+}
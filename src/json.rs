@@ -0,0 +1,338 @@
+// A small JSON codec, which is all the language server needs to speak its protocol.
+
+#[derive(PartialEq, Clone)]
+pub enum Json {
+    Null,
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>) // The members, in the order they were written in.
+}
+
+impl Json {
+    // Make an object from its members.
+    pub fn object(members: Vec<(&str, Json)>) -> Json {
+        return Json::Object(members.into_iter()
+            .map(|(name, value)| (name.to_owned(), value))
+            .collect());
+    }
+
+    pub fn string(value: &str) -> Json {
+        return Json::String(value.to_owned());
+    }
+
+    // The value of a member of an object, or `None` if this is not an object or has no such
+    // member.
+    pub fn get(&self, name: &str) -> Option<&Json> {
+        return match self {
+            Json::Object(members) => members.iter()
+                .find(|(member, _)| member == name)
+                .map(|(_, value)| value),
+            _ => None
+        };
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        return match self {
+            Json::String(value) => Some(value),
+            _ => None
+        };
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        return match self {
+            Json::Number(value) if value.fract() == 0.0 => Some(*value as i64),
+            _ => None
+        };
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Json>> {
+        return match self {
+            Json::Array(elements) => Some(elements),
+            _ => None
+        };
+    }
+}
+
+fn write_string(value: &str, output: &mut String) -> () {
+    output.push('"');
+
+    for character in value.chars() {
+        match character {
+            '"' => *output += "\\\"",
+            '\\' => *output += "\\\\",
+            '\n' => *output += "\\n",
+            '\r' => *output += "\\r",
+            '\t' => *output += "\\t",
+            _ if (character as u32) < 0x20 => {
+                *output += &format!("\\u{:04x}", character as u32);
+            },
+            _ => output.push(character)
+        }
+    }
+
+    output.push('"');
+}
+
+fn write(value: &Json, output: &mut String) -> () {
+    match value {
+        Json::Null => *output += "null",
+        Json::Boolean(value) => *output += &value.to_string(),
+        // Whole numbers are written without a fraction, since they are usually integers.
+        Json::Number(value) if value.fract() == 0.0 && value.abs() < 1e15 => {
+            *output += &(*value as i64).to_string();
+        },
+        Json::Number(value) if value.is_finite() => *output += &value.to_string(),
+        Json::Number(_) => *output += "null",
+        Json::String(value) => write_string(value, output),
+        Json::Array(elements) => {
+            output.push('[');
+
+            for (index, element) in elements.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+
+                write(element, output);
+            }
+
+            output.push(']');
+        },
+        Json::Object(members) => {
+            output.push('{');
+
+            for (index, (name, value)) in members.iter().enumerate() {
+                if index > 0 {
+                    output.push(',');
+                }
+
+                write_string(name, output);
+                output.push(':');
+                write(value, output);
+            }
+
+            output.push('}');
+        }
+    }
+}
+
+impl ToString for Json {
+    fn to_string(&self) -> String {
+        let mut output: String = String::new();
+
+        write(self, &mut output);
+
+        return output;
+    }
+}
+
+struct Reader<'a> {
+    characters: &'a [char],
+    position: usize
+}
+
+type Parsed<T> = Result<T, String>;
+
+impl<'a> Reader<'a> {
+    fn peek(&self) -> Option<char> {
+        return self.characters.get(self.position).copied();
+    }
+
+    fn skip_whitespace(&mut self) -> () {
+        while self.peek().is_some_and(|character| character.is_whitespace()) {
+            self.position += 1;
+        }
+    }
+
+    fn error<T>(&self, message: &str) -> Parsed<T> {
+        return Err(message.to_owned() + " at character " + &self.position.to_string() + ".");
+    }
+
+    // Move past the given text, which is expected next.
+    fn expect(&mut self, text: &str) -> Parsed<()> {
+        for expected in text.chars() {
+            if self.peek() != Some(expected) {
+                return self.error(&("Expected `".to_owned() + text + "`"));
+            }
+
+            self.position += 1;
+        }
+
+        return Ok(());
+    }
+
+    fn value(&mut self) -> Parsed<Json> {
+        self.skip_whitespace();
+
+        return match self.peek() {
+            Some('n') => self.expect("null").map(|_| Json::Null),
+            Some('t') => self.expect("true").map(|_| Json::Boolean(true)),
+            Some('f') => self.expect("false").map(|_| Json::Boolean(false)),
+            Some('"') => self.string().map(Json::String),
+            Some('[') => self.array(),
+            Some('{') => self.object(),
+            Some(character) if character == '-' || character.is_ascii_digit() => self.number(),
+            _ => self.error("Expected a value")
+        };
+    }
+
+    fn number(&mut self) -> Parsed<Json> {
+        let start: usize = self.position;
+
+        while self.peek().is_some_and(|character| {
+            character.is_ascii_digit() || "+-.eE".contains(character)
+        }) {
+            self.position += 1;
+        }
+
+        let text: String = self.characters[start..self.position].iter().collect();
+
+        return match text.parse::<f64>() {
+            Ok(value) => Ok(Json::Number(value)),
+            Err(_) => self.error("Invalid number")
+        };
+    }
+
+    // Four hexadecimal digits, after a `\u`.
+    fn code_unit(&mut self) -> Parsed<u32> {
+        let digits: String = self.characters.iter().skip(self.position).take(4).collect();
+
+        self.position += 4;
+
+        return match u32::from_str_radix(&digits, 16) {
+            Ok(value) if digits.len() == 4 => Ok(value),
+            _ => self.error("Invalid unicode escape")
+        };
+    }
+
+    fn string(&mut self) -> Parsed<String> {
+        let mut value: String = String::new();
+
+        self.expect("\"")?;
+
+        loop {
+            let character: char = match self.peek() {
+                Some(character) => character,
+                None => return self.error("Unclosed string")
+            };
+
+            self.position += 1;
+
+            match character {
+                '"' => return Ok(value),
+                '\\' => {
+                    let escaped: Option<char> = self.peek();
+
+                    self.position += 1;
+
+                    match escaped {
+                        Some('"') => value.push('"'),
+                        Some('\\') => value.push('\\'),
+                        Some('/') => value.push('/'),
+                        Some('b') => value.push('\u{8}'),
+                        Some('f') => value.push('\u{c}'),
+                        Some('n') => value.push('\n'),
+                        Some('r') => value.push('\r'),
+                        Some('t') => value.push('\t'),
+                        Some('u') => {
+                            let mut code: u32 = self.code_unit()?;
+
+                            // Characters outside the basic plane are written as surrogate pairs.
+                            if (0xD800..0xDC00).contains(&code) {
+                                self.expect("\\u")?;
+
+                                let low: u32 = self.code_unit()?;
+
+                                code = 0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00);
+                            }
+
+                            value.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+                        },
+                        _ => return self.error("Invalid escape")
+                    }
+                },
+                _ => value.push(character)
+            }
+        }
+    }
+
+    fn array(&mut self) -> Parsed<Json> {
+        let mut elements: Vec<Json> = Vec::new();
+
+        self.expect("[")?;
+        self.skip_whitespace();
+
+        if self.peek() == Some(']') {
+            self.position += 1;
+
+            return Ok(Json::Array(elements));
+        }
+
+        loop {
+            elements.push(self.value()?);
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some(']') => {
+                    self.position += 1;
+
+                    return Ok(Json::Array(elements));
+                },
+                _ => return self.error("Expected `,` or `]`")
+            }
+        }
+    }
+
+    fn object(&mut self) -> Parsed<Json> {
+        let mut members: Vec<(String, Json)> = Vec::new();
+
+        self.expect("{")?;
+        self.skip_whitespace();
+
+        if self.peek() == Some('}') {
+            self.position += 1;
+
+            return Ok(Json::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+
+            let name: String = self.string()?;
+
+            self.skip_whitespace();
+            self.expect(":")?;
+            members.push((name, self.value()?));
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some('}') => {
+                    self.position += 1;
+
+                    return Ok(Json::Object(members));
+                },
+                _ => return self.error("Expected `,` or `}`")
+            }
+        }
+    }
+}
+
+pub fn parse(text: &str) -> Parsed<Json> {
+    let characters: Vec<char> = text.chars().collect();
+    let mut reader: Reader = Reader {
+        characters: &characters,
+        position: 0
+    };
+    let value: Json = reader.value()?;
+
+    reader.skip_whitespace();
+
+    if reader.position != characters.len() {
+        return reader.error("Unexpected data after the value");
+    }
+
+    return Ok(value);
+}
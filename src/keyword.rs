@@ -14,6 +14,10 @@ impl Keyword {
         };
     }
 
+    pub fn pattern(&self) -> &str {
+        return &self.pattern;
+    }
+
     pub fn matches(&self, pattern: String) -> bool {
         return pattern.eq(&self.pattern);
     }
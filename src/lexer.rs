@@ -1,41 +1,50 @@
 use crate::keyword::Keyword;
 use crate::position::Position;
-use crate::program::Program;
+use crate::program::CompileResult;
 use crate::token::Token;
 use crate::token_type::TokenType;
 
 // The lexer, or tokenizer. This takes the input text and transforms it into tokens which are then
 // parsed by the parser.
-pub struct Lexer<'a> {
-    program: &'a mut Program,
-    source: String, // The actual source to tokenize, or lex.
-    source_length: i32, // The length of the source.
+pub struct Lexer {
+    characters: Vec<char>, // The actual source to tokenize, or lex, split into characters.
+    source_length: i32, // The length of the source, in characters.
     position: i32, // Position in the source.
     running: bool, // Whether or not the lexer is running.
     keywords: Vec<Keyword>, // The keywords that the lexer will check.
     line: i32,
-    column: i32
+    column: i32,
+    file: usize, // The index of the file being lexed in the program.
+    // Whether comments and blank lines are kept as tokens, which only the formatter needs.
+    keep_trivia: bool
 }
 
-impl<'a> Lexer<'a> {
-    pub fn new(program: &'a mut Program, source: String, keywords: Vec<Keyword>) -> Self {
+impl Lexer {
+    pub fn new(source: String, keywords: Vec<Keyword>, file: usize) -> Self {
+        let characters: Vec<char> = source.chars().collect();
+
         return Self {
-            program,
-            source: source.to_owned(),
-            source_length: (source.len().to_owned()) as i32,
+            source_length: characters.len() as i32,
+            characters,
             position: -1,
             running: false,
             keywords,
-            line: 0,
-            column: -1
+            line: 1,
+            column: 0,
+            file,
+            keep_trivia: false
         };
     }
 
+    pub fn set_keep_trivia(&mut self, keep_trivia: bool) -> () {
+        self.keep_trivia = keep_trivia;
+    }
+
     fn parse_escape_sequence(input: char) -> (char, bool) {
         return match input {
             'n' => ('\n', true),
             'r' => ('\r', true),
-            't' => ('\r', true),
+            't' => ('\t', true),
             '\'' => ('\'', true),
             '"' => ('\"', true),
             '\\' => ('\\', true),
@@ -46,7 +55,7 @@ impl<'a> Lexer<'a> {
 
     fn peek(&self) -> char {
         if self.position < self.source_length {
-            return self.source.chars().nth(self.position as usize).unwrap();
+            return self.characters[self.position as usize];
         }
 
         return '\0';
@@ -54,43 +63,54 @@ impl<'a> Lexer<'a> {
 
     fn peek_ahead(&self, amount: i32) -> char {
         if self.position + amount < self.source_length {
-            return self.source.chars().nth((self.position + amount) as usize).unwrap();
+            return self.characters[(self.position + amount) as usize];
         }
 
         return '\0';
     }
 
     fn advance(&mut self) -> () {
+        // Moving past a newline puts the next character at the start of the following line.
+        if self.position >= 0 && self.peek() == '\n' {
+            self.line += 1;
+            self.column = 0;
+        }
+
         self.position += 1;
+        self.column += 1;
 
         if self.position < self.source_length {
             self.running = true;
-
-            if self.peek() == '\n' {
-                self.line += 1;
-                self.column = 0;
-            } else {
-                self.column += 1;
-            }
         } else {
             // The lexer has reached the end of the input string.
             self.running = false;
         }
     }
 
-    fn consume(&mut self, amount: i32) -> () {
-        self.position += amount;
+    // Whether the line after the current newline holds nothing but whitespace.
+    fn is_blank_line_next(&self) -> bool {
+        let mut amount: i32 = 1;
+
+        while self.peek_ahead(amount) == ' ' || self.peek_ahead(amount) == '\t'
+            || self.peek_ahead(amount) == '\r' {
+            amount += 1;
+        }
+
+        return self.peek_ahead(amount) == '\n';
+    }
 
+    fn consume(&mut self, amount: i32) -> () {
         for _ in 0..amount {
             self.advance()
         }
     }
 
-    fn die_with_error(&mut self, message: String, position: Position) {
-        self.program.die_with_error(message, position);
+    // An error at a position in the file being lexed.
+    fn error(&self, message: String, position: Position) -> (String, Position) {
+        return (message, position.in_file(self.file));
     }
 
-    pub fn lex(&mut self) -> Vec<Token> {
+    pub fn lex(&mut self) -> CompileResult<Vec<Token>> {
         let mut tokens: Vec<Token> = Vec::new();
 
         // The match strings.
@@ -130,19 +150,20 @@ impl<'a> Lexer<'a> {
                     tokens.push(token);
 
                     continue;
-                } else {
-                    // Check if the found identifier matches any of the keywords.
-                    for keyword in self.keywords.clone().into_iter() {
-                        if keyword.matches(identifier_string.to_owned()) {
-                            let token: Token = Token::new_empty(
-                                keyword.token_type,
-                                position.to_owned()
-                            );
-                            tokens.push(token);
-
-                            continue;
-                        }
-                    }
+                }
+
+                // Check if the found identifier matches any of the keywords.
+                let keyword: Option<&Keyword> = self.keywords.iter()
+                    .find(|keyword| keyword.matches(identifier_string.to_owned()));
+
+                if let Some(keyword) = keyword {
+                    let token: Token = Token::new_empty(
+                        keyword.token_type.to_owned(),
+                        position.to_owned()
+                    );
+                    tokens.push(token);
+
+                    continue;
                 }
 
                 // Create the identifier token and add it to the list of tokens.
@@ -162,18 +183,23 @@ impl<'a> Lexer<'a> {
                 let start_column: i32 = self.column.to_owned();
 
                 while number_full.to_owned().contains(self.peek()) {
+                    // A `..` after a number is a range, such as `0..10`.
+                    if self.peek() == '.' && self.peek_ahead(1) == '.' {
+                        break;
+                    }
+
                     if self.peek() == '.' {
                         dot_count += 1;
 
                         if dot_count > 1 {
-                            self.die_with_error(
+                            return Err(self.error(
                                 "Floating point numbers can only have a single decimal \
                                 point.".to_owned(),
                                 Position::new_single(
                                     self.line.to_owned(),
                                     self.column.to_owned()
                                 )
-                            );
+                            ));
                         } else {
                             number_string.push(self.peek());
                         }
@@ -224,26 +250,24 @@ impl<'a> Lexer<'a> {
                     } else {
                         if self.peek() == '\\' {
                             let escape_sequence_start: i32 = self.column.to_owned();
-                            let mut escape_sequence: char = '\0';
-                            let mut success: bool = false;
 
                             self.advance(); // Move past the backslash.
 
-                            (escape_sequence, success) =
+                            let (escape_sequence, success): (char, bool) =
                                 Self::parse_escape_sequence(self.peek());
 
                             if success {
                                 string_value.push(escape_sequence);
                             } else {
-                                self.die_with_error(
+                                return Err(self.error(
                                     "Invalid escape sequence: `\\".to_owned() + &self
                                         .peek().to_string() + "`.",
                                     Position::new(
                                         self.line.to_owned(),
                                         escape_sequence_start,
-                                        self.column.to_owned()
+                                        self.column.to_owned() + 1
                                     )
-                                );
+                                ));
                             }
                         } else {
                             // Append the current character to the value string.
@@ -265,18 +289,20 @@ impl<'a> Lexer<'a> {
                         )
                     );
                     tokens.push(token);
+
+                    continue;
                 } else {
-                    self.die_with_error(
+                    return Err(self.error(
                         "Unclosed string literal.".to_owned(),
                         Position::new_single(
                             line,
                             start_column
                         )
-                    );
+                    ));
                 }
             } else if self.peek() == '\'' {
                 // Char literal.
-                let mut char_value: char = '\0';
+                let char_value: char;
                 let line: i32 = self.line.to_owned();
                 let start_column: i32 = self.column.to_owned();
 
@@ -284,26 +310,24 @@ impl<'a> Lexer<'a> {
 
                 if self.peek() == '\\' {
                     let escape_sequence_start: i32 = self.column.to_owned();
-                    let mut escape_sequence: char = '\0';
-                    let mut success: bool = false;
 
                     self.advance(); // Move past the backslash.
 
-                    (escape_sequence, success) =
+                    let (escape_sequence, success): (char, bool) =
                         Self::parse_escape_sequence(self.peek());
 
                     if success {
                         char_value = escape_sequence;
                     } else {
-                        self.die_with_error(
+                        return Err(self.error(
                             "Invalid escape sequence: `\\".to_owned() + &self
                                 .peek().to_string() + "`.",
                             Position::new(
                                 self.line.to_owned(),
                                 escape_sequence_start,
-                                self.column.to_owned()
+                                self.column.to_owned() + 1
                             )
-                        );
+                        ));
                     }
                 } else {
                     char_value = self.peek();
@@ -327,15 +351,21 @@ impl<'a> Lexer<'a> {
 
                     continue;
                 } else {
-                    self.die_with_error(
+                    return Err(self.error(
                         "Unexpected character `".to_owned() + &self.peek().to_string() +
                             "`. Expected (closing) single quote.",
                         Position::new_single(
                             line,
                             self.column.to_owned()
                         )
-                    );
+                    ));
                 }
+            } else if self.keep_trivia && self.peek() == '\n' && self.is_blank_line_next() {
+                let token: Token = Token::new_empty(
+                    TokenType::BlankLine,
+                    Position::new_single(self.line.to_owned() + 1, 1)
+                );
+                tokens.push(token);
             } else if !skip.contains(self.peek()) {
                 let single_position: Position = Position::new_single(
                     self.line.to_owned(),
@@ -345,7 +375,7 @@ impl<'a> Lexer<'a> {
                 let double_position: Position = Position::new(
                     self.line.to_owned(),
                     self.column.to_owned(),
-                    self.column.to_owned() + 1,
+                    self.column.to_owned() + 2,
                 );
 
                 if self.peek() == '+' {
@@ -412,7 +442,30 @@ impl<'a> Lexer<'a> {
                         tokens.push(token);
                     }
                 } else if self.peek() == '/' {
-                    if self.peek_ahead(1) == '=' {
+                    if self.peek_ahead(1) == '/' {
+                        // Line comment; skip everything up to the end of the line.
+                        let mut comment: String = String::new();
+                        let line: i32 = self.line.to_owned();
+                        let start_column: i32 = self.column.to_owned();
+
+                        while self.running && self.peek() != '\n' {
+                            comment.push(self.peek());
+                            self.advance();
+                        }
+
+                        if self.keep_trivia {
+                            let comment: String = comment.trim_end().to_owned();
+                            let end_column: i32 = start_column + comment.chars().count() as i32;
+
+                            tokens.push(Token::new(
+                                TokenType::Comment,
+                                Some(comment),
+                                Position::new(line, start_column, end_column)
+                            ));
+                        }
+
+                        continue;
+                    } else if self.peek_ahead(1) == '=' {
                         // Positional divide.
                         let token: Token = Token::new_empty(TokenType::PositionalDivide,
                                                             double_position.to_owned());
@@ -457,10 +510,45 @@ impl<'a> Lexer<'a> {
                                                             single_position.to_owned());
                         tokens.push(token);
                     }
-                } else if self.peek() == '.' {
-                    let token: Token = Token::new_empty(TokenType::Period,
+                } else if self.peek() == '(' {
+                    let token: Token = Token::new_empty(TokenType::LeftParenthesis,
                                                         single_position.to_owned());
                     tokens.push(token);
+                } else if self.peek() == ')' {
+                    let token: Token = Token::new_empty(TokenType::RightParenthesis,
+                                                        single_position.to_owned());
+                    tokens.push(token);
+                } else if self.peek() == '[' {
+                    let token: Token = Token::new_empty(TokenType::LeftBracket,
+                                                        single_position.to_owned());
+                    tokens.push(token);
+                } else if self.peek() == ']' {
+                    let token: Token = Token::new_empty(TokenType::RightBracket,
+                                                        single_position.to_owned());
+                    tokens.push(token);
+                } else if self.peek() == '{' {
+                    let token: Token = Token::new_empty(TokenType::LeftBrace,
+                                                        single_position.to_owned());
+                    tokens.push(token);
+                } else if self.peek() == '}' {
+                    let token: Token = Token::new_empty(TokenType::RightBrace,
+                                                        single_position.to_owned());
+                    tokens.push(token);
+                } else if self.peek() == '.' {
+                    if self.peek_ahead(1) == '.' {
+                        // Range.
+                        let token: Token = Token::new_empty(TokenType::DoublePeriod,
+                                                            double_position.to_owned());
+                        tokens.push(token);
+
+                        self.consume(2);
+                        continue;
+                    } else {
+                        // Period.
+                        let token: Token = Token::new_empty(TokenType::Period,
+                                                            single_position.to_owned());
+                        tokens.push(token);
+                    }
                 } else if self.peek() == ',' {
                     let token: Token = Token::new_empty(TokenType::Comma,
                                                         single_position.to_owned());
@@ -480,6 +568,14 @@ impl<'a> Lexer<'a> {
                                                             double_position.to_owned());
                         tokens.push(token);
 
+                        self.consume(2);
+                        continue;
+                    } else if self.peek_ahead(1) == '>' {
+                        // Arrow.
+                        let token: Token = Token::new_empty(TokenType::Arrow,
+                                                            double_position.to_owned());
+                        tokens.push(token);
+
                         self.consume(2);
                         continue;
                     } else {
@@ -541,16 +637,39 @@ impl<'a> Lexer<'a> {
                         );
                         tokens.push(token);
                     }
+                } else if self.peek() == '?' {
+                    if self.peek_ahead(1) == '.' {
+                        // Safe member access.
+                        let token: Token = Token::new_empty(TokenType::QuestionPeriod,
+                                                            double_position.to_owned());
+                        tokens.push(token);
+
+                        self.consume(2);
+                        continue;
+                    } else if self.peek_ahead(1) == '?' {
+                        // Null coalescing.
+                        let token: Token = Token::new_empty(TokenType::DoubleQuestionMark,
+                                                            double_position.to_owned());
+                        tokens.push(token);
+
+                        self.consume(2);
+                        continue;
+                    } else {
+                        // Question mark, used by nullable types.
+                        let token: Token = Token::new_empty(TokenType::QuestionMark,
+                                                            single_position.to_owned());
+                        tokens.push(token);
+                    }
                 } else if self.peek() == '&' && self.peek_ahead(1) == '&' {
                     let token: Token = Token::new_empty(TokenType::And,
-                                                        single_position.to_owned());
+                                                        double_position.to_owned());
                     tokens.push(token);
 
                     self.consume(2);
                     continue;
                 } else if self.peek() == '|' && self.peek_ahead(1) == '|' {
                     let token: Token = Token::new_empty(TokenType::Or,
-                                                        single_position.to_owned());
+                                                        double_position.to_owned());
                     tokens.push(token);
 
                     self.consume(2);
@@ -576,6 +695,10 @@ impl<'a> Lexer<'a> {
             )
         ));
 
-        return tokens;
+        for token in &mut tokens {
+            token.token_position = token.token_position.in_file(self.file);
+        }
+
+        return Ok(tokens);
     }
 }
@@ -0,0 +1,80 @@
+mod program;
+mod token_type;
+mod position;
+mod token;
+mod keyword;
+mod lexer;
+mod expressions;
+mod parser;
+mod statements;
+mod patterns;
+mod type_annotation;
+mod types;
+mod type_checker;
+mod conversions;
+mod value;
+mod operations;
+mod natives;
+mod modules;
+mod resolver;
+mod optimizer;
+mod interpreter;
+mod bytecode;
+mod compiler;
+mod vm;
+mod rbc;
+mod disassembler;
+mod ast;
+mod formatter;
+mod json;
+mod lsp;
+mod repl;
+mod testing;
+mod cli;
+mod commands;
+mod engine;
+mod host;
+
+use crate::keyword::Keyword;
+use crate::token_type::TokenType;
+
+// The interface for embedding the language in Rust programs.
+pub use crate::engine::{Call, Engine, Error, ErrorKind, Script};
+pub use crate::host::{HostCallable, HostClass, HostMethod, Object, ScriptValue};
+pub use crate::types::Type;
+pub use crate::value::{MapKey, MapValue, Value};
+// The command line interface, which the `ribbit-rs` binary runs.
+pub use crate::commands::start;
+
+// The extension of source files.
+pub(crate) const SOURCE_EXTENSION: &str = "rbt";
+
+pub(crate) fn keywords() -> Vec<Keyword> {
+    return vec![
+        Keyword::new("class".to_owned(), TokenType::Class),
+        Keyword::new("super".to_owned(), TokenType::Super),
+        Keyword::new("this".to_owned(), TokenType::This),
+        Keyword::new("for".to_owned(), TokenType::For),
+        Keyword::new("in".to_owned(), TokenType::In),
+        Keyword::new("while".to_owned(), TokenType::While),
+        Keyword::new("loop".to_owned(), TokenType::Loop),
+        Keyword::new("break".to_owned(), TokenType::Break),
+        Keyword::new("continue".to_owned(), TokenType::Continue),
+        Keyword::new("if".to_owned(), TokenType::If),
+        Keyword::new("else".to_owned(), TokenType::Else),
+        Keyword::new("match".to_owned(), TokenType::Match),
+        Keyword::new("enum".to_owned(), TokenType::Enum),
+        Keyword::new("try".to_owned(), TokenType::Try),
+        Keyword::new("catch".to_owned(), TokenType::Catch),
+        Keyword::new("finally".to_owned(), TokenType::Finally),
+        Keyword::new("throw".to_owned(), TokenType::Throw),
+        Keyword::new("return".to_owned(), TokenType::Return),
+        Keyword::new("is".to_owned(), TokenType::Is),
+        Keyword::new("null".to_owned(), TokenType::Null),
+        Keyword::new("extends".to_owned(), TokenType::Extends),
+        Keyword::new("interface".to_owned(), TokenType::Interface),
+        Keyword::new("implements".to_owned(), TokenType::Implements),
+        Keyword::new("import".to_owned(), TokenType::Import),
+        Keyword::new("pub".to_owned(), TokenType::Pub)
+    ];
+}
@@ -1,2 +0,0 @@
-use crate::position::Position;
-
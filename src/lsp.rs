@@ -0,0 +1,567 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::process;
+use crate::json::{self, Json};
+use crate::keywords;
+use crate::commands::parse;
+use crate::position::Position;
+use crate::program::{CompileResult, Program, SourceFile};
+use crate::resolver::{Declaration, Resolution, Resolver};
+use crate::statements::{FunctionDeclarationStatement, Statement};
+use crate::testing::lower_tests;
+use crate::token::Token;
+use crate::type_checker::TypeChecker;
+use crate::types::Type;
+
+// The error codes of the protocol.
+const PARSE_ERROR: i64 = -32700;
+const INVALID_PARAMS: i64 = -32602;
+const METHOD_NOT_FOUND: i64 = -32601;
+
+// The severities of diagnostics.
+const ERROR_SEVERITY: i64 = 1;
+const WARNING_SEVERITY: i64 = 2;
+
+// The kinds of symbols and completion items, as numbered by the protocol.
+const CLASS_SYMBOL: i64 = 5;
+const METHOD_SYMBOL: i64 = 6;
+const PROPERTY_SYMBOL: i64 = 7;
+const CONSTRUCTOR_SYMBOL: i64 = 9;
+const ENUM_SYMBOL: i64 = 10;
+const INTERFACE_SYMBOL: i64 = 11;
+const FUNCTION_SYMBOL: i64 = 12;
+const VARIABLE_SYMBOL: i64 = 13;
+const ENUM_MEMBER_SYMBOL: i64 = 22;
+const KEYWORD_COMPLETION: i64 = 14;
+
+// What checking a document found out about it.
+struct Analysis {
+    files: Vec<SourceFile>, // The document, followed by the modules it imports.
+    statements: Vec<Statement>, // The statements of the document, as parsed.
+    types: HashMap<Position, Type>,
+    bindings: HashMap<Position, Declaration>,
+    diagnostics: Vec<Json>
+}
+
+// A language server, which speaks JSON-RPC over standard input and output. Documents are checked
+// like programs whenever they change, stopping at the first error of a stage: lexing and parsing,
+// loading the imported modules, then type checking. The warnings of the resolver are only given
+// for documents without errors, as it expects a checked program, and so are definitions.
+pub struct LanguageServer {
+    documents: HashMap<String, String>, // The text of the open documents, by their URI.
+    shut_down: bool
+}
+
+impl LanguageServer {
+    pub fn new() -> Self {
+        return Self {
+            documents: HashMap::new(),
+            shut_down: false
+        };
+    }
+
+    pub fn start(&mut self) -> () {
+        let stdin: io::Stdin = io::stdin();
+        let mut input: io::StdinLock = stdin.lock();
+
+        while let Some(content) = read_message(&mut input) {
+            let message: Json = match json::parse(&content) {
+                Ok(message) => message,
+                Err(error) => {
+                    send_error(Json::Null, PARSE_ERROR, &error);
+                    continue;
+                }
+            };
+            let method: &str = message.get("method").and_then(Json::as_str).unwrap_or_default();
+            let params: Json = message.get("params").cloned().unwrap_or(Json::Null);
+
+            if method == "exit" {
+                break;
+            }
+
+            let result: Option<Result<Json, (i64, String)>> = self.handle(method, &params);
+
+            // Only requests, which have an ID, are answered.
+            if let Some(id) = message.get("id") {
+                match result {
+                    Some(Ok(result)) => send(Json::object(vec![
+                        ("jsonrpc", Json::string("2.0")),
+                        ("id", id.to_owned()),
+                        ("result", result)
+                    ])),
+                    Some(Err((code, error))) => send_error(id.to_owned(), code, &error),
+                    None => send_error(
+                        id.to_owned(),
+                        METHOD_NOT_FOUND,
+                        &("Unknown method `".to_owned() + method + "`.")
+                    )
+                }
+            }
+        }
+
+        // Leaving without being shut down first is an error.
+        process::exit(if self.shut_down { 0 } else { 1 });
+    }
+
+    // Handle a request or notification, returning `None` for unknown methods.
+    fn handle(&mut self, method: &str, params: &Json) -> Option<Result<Json, (i64, String)>> {
+        let result: Option<Json> = match method {
+            "initialize" => Some(initialize_result()),
+            "initialized" => Some(Json::Null),
+            "shutdown" => {
+                self.shut_down = true;
+                Some(Json::Null)
+            },
+            "textDocument/didOpen" => self.open(params),
+            "textDocument/didChange" => self.change(params),
+            "textDocument/didClose" => self.close(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/definition" => self.definition(params),
+            "textDocument/documentSymbol" => self.symbols(params),
+            "textDocument/completion" => Some(completions()),
+            // Other notifications, which start with `$/`, can be ignored.
+            _ if method.starts_with("$/") => Some(Json::Null),
+            _ => return None
+        };
+
+        return Some(result.ok_or((INVALID_PARAMS, "Invalid parameters.".to_owned())));
+    }
+
+    fn open(&mut self, params: &Json) -> Option<Json> {
+        let document: &Json = params.get("textDocument")?;
+        let uri: &str = document.get("uri")?.as_str()?;
+        let text: &str = document.get("text")?.as_str()?;
+
+        self.documents.insert(uri.to_owned(), text.to_owned());
+        publish_diagnostics(uri, analyze(uri, text).diagnostics);
+
+        return Some(Json::Null);
+    }
+
+    // Documents are always sent whole, so the last change holds the new text.
+    fn change(&mut self, params: &Json) -> Option<Json> {
+        let uri: &str = params.get("textDocument")?.get("uri")?.as_str()?;
+        let text: &str = params.get("contentChanges")?.as_array()?.last()?.get("text")?.as_str()?;
+
+        self.documents.insert(uri.to_owned(), text.to_owned());
+        publish_diagnostics(uri, analyze(uri, text).diagnostics);
+
+        return Some(Json::Null);
+    }
+
+    fn close(&mut self, params: &Json) -> Option<Json> {
+        let uri: &str = params.get("textDocument")?.get("uri")?.as_str()?;
+
+        self.documents.remove(uri);
+        publish_diagnostics(uri, Vec::new());
+
+        return Some(Json::Null);
+    }
+
+    // The URI of the document a request is about, along with its analysis, which is `None` for
+    // documents which are not open.
+    fn analyze_document(&self, params: &Json) -> Option<(String, Option<Analysis>)> {
+        let uri: &str = params.get("textDocument")?.get("uri")?.as_str()?;
+        let analysis: Option<Analysis> = self.documents.get(uri).map(|text| analyze(uri, text));
+
+        return Some((uri.to_owned(), analysis));
+    }
+
+    fn hover(&self, params: &Json) -> Option<Json> {
+        let (_, analysis): (String, Option<Analysis>) = self.analyze_document(params)?;
+        let cursor: Json = params.get("position")?.to_owned();
+
+        let analysis: Analysis = match analysis {
+            Some(analysis) => analysis,
+            None => return Some(Json::Null)
+        };
+        let (line, column): (i32, i32) = from_protocol_position(&analysis.files[0], &cursor)?;
+
+        return Some(match find_at(analysis.types.keys(), line, column) {
+            Some(position) => Json::object(vec![
+                ("contents", Json::object(vec![
+                    ("kind", Json::string("plaintext")),
+                    ("value", Json::String(analysis.types[&position].to_string()))
+                ])),
+                ("range", to_range(&analysis.files, position))
+            ]),
+            None => Json::Null
+        });
+    }
+
+    fn definition(&self, params: &Json) -> Option<Json> {
+        let (uri, analysis): (String, Option<Analysis>) = self.analyze_document(params)?;
+        let cursor: Json = params.get("position")?.to_owned();
+
+        let analysis: Analysis = match analysis {
+            Some(analysis) => analysis,
+            None => return Some(Json::Null)
+        };
+        let (line, column): (i32, i32) = from_protocol_position(&analysis.files[0], &cursor)?;
+
+        return Some(match find_at(analysis.bindings.keys(), line, column) {
+            Some(identifier) => {
+                let declaration: Position = analysis.bindings[&identifier].position;
+                // Declarations in imported modules are in their own files.
+                let uri: String = match declaration.file {
+                    0 => uri,
+                    file => path_to_uri(&analysis.files[file].name)
+                };
+
+                Json::object(vec![
+                    ("uri", Json::String(uri)),
+                    ("range", to_range(&analysis.files, declaration))
+                ])
+            },
+            None => Json::Null
+        });
+    }
+
+    fn symbols(&self, params: &Json) -> Option<Json> {
+        let (_, analysis): (String, Option<Analysis>) = self.analyze_document(params)?;
+
+        let analysis: Analysis = match analysis {
+            Some(analysis) => analysis,
+            None => return Some(Json::Null)
+        };
+        let files: &[SourceFile] = &analysis.files;
+
+        let symbols: Vec<Json> = analysis.statements.iter()
+            .filter_map(|statement| match statement {
+                Statement::FunctionDeclaration(function) => {
+                    Some(symbol(files, &function.name.token, FUNCTION_SYMBOL, Vec::new()))
+                },
+                Statement::VariableDeclaration(variable) => {
+                    Some(symbol(files, &variable.identifier.token, VARIABLE_SYMBOL, Vec::new()))
+                },
+                Statement::ClassDeclaration(class) => {
+                    let method = |method: &FunctionDeclarationStatement, kind: i64| -> Json {
+                        return symbol(files, &method.name.token, kind, Vec::new());
+                    };
+                    let members: Vec<Json> = class.properties.iter()
+                        .map(|property| {
+                            symbol(files, &property.identifier.token, PROPERTY_SYMBOL, Vec::new())
+                        })
+                        .chain(class.constructor.iter()
+                            .map(|constructor| method(constructor, CONSTRUCTOR_SYMBOL)))
+                        .chain(class.methods.iter().map(|function| method(function, METHOD_SYMBOL)))
+                        .collect();
+
+                    Some(symbol(files, &class.name.token, CLASS_SYMBOL, members))
+                },
+                Statement::InterfaceDeclaration(interface) => {
+                    let methods: Vec<Json> = interface.methods.iter()
+                        .map(|method| symbol(files, &method.name.token, METHOD_SYMBOL, Vec::new()))
+                        .collect();
+
+                    Some(symbol(files, &interface.name.token, INTERFACE_SYMBOL, methods))
+                },
+                Statement::EnumDeclaration(enum_declaration) => {
+                    let variants: Vec<Json> = enum_declaration.variants.iter()
+                        .map(|variant| symbol(files, &variant.name, ENUM_MEMBER_SYMBOL, Vec::new()))
+                        .collect();
+
+                    Some(symbol(files, &enum_declaration.name.token, ENUM_SYMBOL, variants))
+                },
+                _ => None
+            })
+            .collect();
+
+        return Some(Json::Array(symbols));
+    }
+}
+
+fn initialize_result() -> Json {
+    return Json::object(vec![
+        ("capabilities", Json::object(vec![
+            ("textDocumentSync", Json::Number(1.0)), // Documents are sent whole.
+            ("hoverProvider", Json::Boolean(true)),
+            ("definitionProvider", Json::Boolean(true)),
+            ("documentSymbolProvider", Json::Boolean(true)),
+            ("completionProvider", Json::object(Vec::new()))
+        ])),
+        ("serverInfo", Json::object(vec![
+            ("name", Json::string("ribbit-rs")),
+            ("version", Json::string(env!("CARGO_PKG_VERSION")))
+        ]))
+    ]);
+}
+
+fn completions() -> Json {
+    return Json::Array(keywords().iter()
+        .map(|keyword| Json::object(vec![
+            ("label", Json::string(keyword.pattern())),
+            ("kind", Json::Number(KEYWORD_COMPLETION as f64))
+        ]))
+        .collect());
+}
+
+fn symbol(files: &[SourceFile], name: &Token, kind: i64, children: Vec<Json>) -> Json {
+    let range: Json = to_range(files, name.token_position);
+
+    return Json::object(vec![
+        ("name", Json::String(name.value())),
+        ("kind", Json::Number(kind as f64)),
+        ("range", range.to_owned()),
+        ("selectionRange", range),
+        ("children", Json::Array(children))
+    ]);
+}
+
+// Check a document the way a program is checked before it is run, keeping what was found.
+fn analyze(uri: &str, text: &str) -> Analysis {
+    let mut program: Program = Program::new(keywords(), text.to_owned());
+
+    program.set_print_errors(false);
+
+    // Imports are found relative to the document.
+    if let Some(path) = uri.strip_prefix("file://") {
+        program.set_path(percent_decode(path));
+    }
+
+    let mut analysis: Analysis = Analysis {
+        files: program.files().to_vec(),
+        statements: Vec::new(),
+        types: HashMap::new(),
+        bindings: HashMap::new(),
+        diagnostics: Vec::new()
+    };
+
+    let statements: Vec<Statement> = match parse(&mut program) {
+        Ok(statements) => statements,
+        Err(error) => {
+            analysis.diagnostics.push(diagnostic(&analysis.files, error, ERROR_SEVERITY));
+            return analysis;
+        }
+    };
+
+    analysis.statements = statements.to_owned();
+
+    let loaded: CompileResult<Vec<Statement>> = lower_tests(statements, None)
+        .and_then(|statements| program.create_module_loader().load(statements));
+
+    // The imported modules have been added to the files, even if one of them had an error.
+    analysis.files = program.files().to_vec();
+
+    let statements: Vec<Statement> = match loaded {
+        Ok(statements) => statements,
+        Err(error) => {
+            analysis.diagnostics.push(diagnostic(&analysis.files, error, ERROR_SEVERITY));
+            return analysis;
+        }
+    };
+
+    // The types found before an error are still worth showing.
+    let mut type_checker: TypeChecker = program.create_type_checker();
+    let checked: CompileResult<Vec<Statement>> = type_checker.check(statements);
+
+    analysis.types = type_checker.types().to_owned();
+
+    let statements: Vec<Statement> = match checked {
+        Ok(statements) => statements,
+        Err(error) => {
+            analysis.diagnostics.push(diagnostic(&analysis.files, error, ERROR_SEVERITY));
+            return analysis;
+        }
+    };
+
+    let resolution: Resolution = Resolver::new().resolve(&statements);
+
+    for warning in resolution.warnings {
+        // Warnings about imported modules belong to their own documents.
+        if warning.1.file == 0 {
+            analysis.diagnostics.push(diagnostic(&analysis.files, warning, WARNING_SEVERITY));
+        }
+    }
+
+    analysis.bindings = resolution.bindings;
+
+    // Errors in constant expressions, such as dividing by zero, are found while optimizing.
+    if let Err(error) = program.create_optimizer().optimize(statements) {
+        analysis.diagnostics.push(diagnostic(&analysis.files, error, ERROR_SEVERITY));
+    }
+
+    return analysis;
+}
+
+fn diagnostic(files: &[SourceFile], (message, position): (String, Position), severity: i64)
+        -> Json {
+    // Problems in imported modules are shown at the start of the document, naming the module.
+    let (message, range): (String, Json) = match position.file {
+        0 => (message, to_range(files, position)),
+        file => (
+            "In ".to_owned() + &files[file].name + " on line " + &position.line.to_string() +
+                ": " + &message,
+            to_range(files, Position::new_single(1, 1))
+        )
+    };
+
+    return Json::object(vec![
+        ("range", range),
+        ("severity", Json::Number(severity as f64)),
+        ("source", Json::string("ribbit")),
+        ("message", Json::String(message))
+    ]);
+}
+
+fn publish_diagnostics(uri: &str, diagnostics: Vec<Json>) -> () {
+    send(Json::object(vec![
+        ("jsonrpc", Json::string("2.0")),
+        ("method", Json::string("textDocument/publishDiagnostics")),
+        ("params", Json::object(vec![
+            ("uri", Json::string(uri)),
+            ("diagnostics", Json::Array(diagnostics))
+        ]))
+    ]));
+}
+
+// The narrowest of the positions which contain the given column of a line of the document.
+fn find_at<'a>(positions: impl Iterator<Item = &'a Position>, line: i32, column: i32)
+        -> Option<Position> {
+    return positions
+        .filter(|position| {
+            position.file == 0 && position.line == line && position.start <= column &&
+                column < position.end.max(position.start + 1)
+        })
+        .min_by_key(|position| position.end - position.start)
+        .copied();
+}
+
+// The protocol counts lines from 0, and columns in UTF-16 code units from 0, while positions count
+// both from 1, with columns in characters.
+fn to_protocol_position(files: &[SourceFile], line: i32, column: i32, file: usize) -> Json {
+    let text: &str = files[file].source.lines().nth((line - 1) as usize).unwrap_or_default();
+    let character: usize = text.chars()
+        .take((column - 1).max(0) as usize)
+        .map(char::len_utf16)
+        .sum();
+
+    return Json::object(vec![
+        ("line", Json::Number((line - 1) as f64)),
+        ("character", Json::Number(character as f64))
+    ]);
+}
+
+fn from_protocol_position(file: &SourceFile, position: &Json) -> Option<(i32, i32)> {
+    let line: i64 = position.get("line")?.as_i64()?;
+    let character: i64 = position.get("character")?.as_i64()?;
+    let text: &str = file.source.lines().nth(line as usize).unwrap_or_default();
+    let mut units: i64 = 0;
+    let mut column: i32 = 1;
+
+    for found in text.chars() {
+        units += found.len_utf16() as i64;
+
+        if units > character {
+            break;
+        }
+
+        column += 1;
+    }
+
+    return Some((line as i32 + 1, column));
+}
+
+fn to_range(files: &[SourceFile], position: Position) -> Json {
+    // Positions of a single character have the same start and end.
+    let end: i32 = position.end.max(position.start + 1);
+
+    return Json::object(vec![
+        ("start", to_protocol_position(files, position.line, position.start, position.file)),
+        ("end", to_protocol_position(files, position.line, end, position.file))
+    ]);
+}
+
+fn path_to_uri(path: &str) -> String {
+    let path: String = fs::canonicalize(path)
+        .map(|path| path.display().to_string())
+        .unwrap_or(path.to_owned());
+    let mut uri: String = "file://".to_owned();
+
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-_.~".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri += &format!("%{:02X}", byte);
+        }
+    }
+
+    return uri;
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes: &[u8] = text.as_bytes();
+    let mut decoded: Vec<u8> = Vec::new();
+    let mut index: usize = 0;
+
+    while index < bytes.len() {
+        let escaped: Option<u8> = bytes.get(index + 1..index + 3)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u8::from_str_radix(digits, 16).ok());
+
+        match escaped {
+            Some(byte) if bytes[index] == b'%' => {
+                decoded.push(byte);
+                index += 3;
+            },
+            _ => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    return String::from_utf8_lossy(&decoded).to_string();
+}
+
+// Read the content of a message, which follows headers giving its length. Returns `None` once the
+// input has ended.
+fn read_message(input: &mut impl BufRead) -> Option<String> {
+    let mut length: Option<usize> = None;
+
+    loop {
+        let mut header: String = String::new();
+
+        if input.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+
+        let header: &str = header.trim_end();
+
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse().ok();
+            }
+        }
+    }
+
+    let mut content: Vec<u8> = vec![0; length?];
+
+    input.read_exact(&mut content).ok()?;
+
+    return Some(String::from_utf8_lossy(&content).to_string());
+}
+
+fn send(message: Json) -> () {
+    let content: String = message.to_string();
+    let mut stdout: io::Stdout = io::stdout();
+
+    write!(stdout, "Content-Length: {}\r\n\r\n{}", content.len(), content).unwrap();
+    stdout.flush().unwrap();
+}
+
+fn send_error(id: Json, code: i64, message: &str) -> () {
+    send(Json::object(vec![
+        ("jsonrpc", Json::string("2.0")),
+        ("id", id),
+        ("error", Json::object(vec![
+            ("code", Json::Number(code as f64)),
+            ("message", Json::string(message))
+        ]))
+    ]));
+}
@@ -0,0 +1,707 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::expressions::{Expression, IdentifierExpression, MatchArmBody};
+use crate::parser::Parser;
+use crate::patterns::Pattern;
+use crate::position::Position;
+use crate::program::{CompileResult, Program, StackGuard};
+use crate::statements::{FunctionDeclarationStatement, ImportStatement, Statement};
+use crate::token::Token;
+use crate::token_type::TokenType;
+use crate::type_annotation::{TypeAnnotation, TypeParameterDeclaration};
+
+// The environment variable listing the directories imports are looked for in when they are not
+// next to the importing file.
+const SEARCH_PATH_VARIABLE: &str = "RIBBIT_PATH";
+
+// The declarations every program starts with, which it can use without importing them. `Error` is
+// the class everything thrown extends, and what the errors found while running are caught as.
+const PRELUDE: &str = "\
+class Error {
+    string message;
+
+    Error(string message) {
+        this.message = message;
+    }
+}
+";
+const PRELUDE_NAME: &str = "<prelude>";
+
+// A file of the program, along with what it declares at its top level.
+struct Module {
+    name: String, // The name the module was first imported as.
+    // What the names of its declarations are prefixed with once it is part of the program, which
+    // keeps them apart from those of other modules. The file the program starts with has none.
+    prefix: String,
+    declarations: HashMap<String, bool> // Whether each declaration is public.
+}
+
+impl Module {
+    fn qualify(&self, name: &str) -> String {
+        if self.prefix.is_empty() {
+            return name.to_owned();
+        }
+
+        return self.prefix.to_owned() + "." + name;
+    }
+}
+
+// Joins the modules a program imports into a single list of statements, which the rest of the
+// pipeline treats like a program made of one file. The declarations of each module are renamed to
+// start with the name of the module, such as `math.sqrt`, and uses of them are renamed to match,
+// both inside of the module and through its name in the files which import it. Each module is
+// included once, where it is first imported, so its top-level code runs before the code after
+// that import.
+pub struct ModuleLoader<'a> {
+    program: &'a mut Program,
+    modules: Vec<Module>,
+    loaded: HashMap<PathBuf, usize>, // The modules which have been loaded, by their full path.
+    loading: Vec<(PathBuf, String)>, // The files being loaded, each imported by the one before.
+    search_path: Vec<PathBuf>
+}
+
+impl<'a> ModuleLoader<'a> {
+    pub fn new(program: &'a mut Program) -> Self {
+        let search_path: Vec<PathBuf> = match env::var_os(SEARCH_PATH_VARIABLE) {
+            Some(paths) => env::split_paths(&paths).collect(),
+            None => Vec::new()
+        };
+
+        return Self {
+            program,
+            modules: Vec::new(),
+            loaded: HashMap::new(),
+            loading: Vec::new(),
+            search_path
+        };
+    }
+
+    // Load the modules imported by the statements of the file the program started with, and
+    // everything they import in turn.
+    pub fn load(&mut self, statements: Vec<Statement>) -> CompileResult<Vec<Statement>> {
+        let name: String = self.program.files()[0].name.to_owned();
+
+        if let Ok(path) = fs::canonicalize(&name) {
+            self.loading.push((path, name.to_owned()));
+        }
+
+        let mut linked: Vec<Statement> = self.load_prelude()?;
+
+        linked.extend(self.link(statements, 0, name, String::new())?);
+
+        return Ok(linked);
+    }
+
+    // The declarations of the prelude, which are added as a file of their own, but keep their
+    // names as they are.
+    fn load_prelude(&mut self) -> CompileResult<Vec<Statement>> {
+        let file: usize = self.program.add_file(PRELUDE_NAME.to_owned(), PRELUDE.to_owned());
+        let tokens: Vec<Token> = self.program.create_file_lexer(file).lex()?;
+
+        self.program.set_tokens(tokens);
+
+        return self.program.create_parser().parse();
+    }
+
+    // Rename the declarations of a file and the uses of other modules in it, replacing its imports
+    // by the modules they import if they have not been included yet.
+    fn link(&mut self, statements: Vec<Statement>, file: usize, name: String, prefix: String)
+        -> CompileResult<Vec<Statement>> {
+        // Tests are only run when their own file is tested, which turns them into functions before
+        // the modules are loaded.
+        let statements: Vec<Statement> = statements.into_iter()
+            .filter(|statement| !matches!(statement, Statement::Test(_)))
+            .collect();
+        let mut declarations: HashMap<String, bool> = HashMap::new();
+
+        for statement in &statements {
+            if let Some((token, public)) = declared_name(statement) {
+                declarations.insert(token.value(), public);
+            }
+        }
+
+        let module: usize = self.modules.len();
+
+        self.modules.push(Module {
+            name,
+            prefix,
+            declarations
+        });
+
+        let mut imports: HashMap<String, usize> = HashMap::new();
+        let mut included: Vec<Option<Vec<Statement>>> = Vec::new();
+
+        for statement in &statements {
+            included.push(match statement {
+                Statement::Import(import) => {
+                    let alias: String = import.name.value();
+
+                    if imports.contains_key(&alias)
+                        || self.modules[module].declarations.contains_key(&alias) {
+                        return Err((
+                            "`".to_owned() + &alias + "` is already declared.",
+                            import.name.token_position
+                        ));
+                    }
+
+                    let (imported, statements) = self.import(import, file)?;
+
+                    imports.insert(alias, imported);
+
+                    Some(statements)
+                },
+                _ => None
+            });
+        }
+
+        let mut renamer: Renamer = Renamer {
+            modules: &self.modules,
+            module,
+            imports,
+            scopes: Vec::new(),
+            type_parameters: Vec::new(),
+            stack: self.program.stack_guard()
+        };
+        let mut linked: Vec<Statement> = Vec::new();
+
+        for (mut statement, included) in statements.into_iter().zip(included) {
+            match included {
+                Some(statements) => linked.extend(statements),
+                None => {
+                    renamer.rename_statement(&mut statement)?;
+                    linked.push(statement);
+                }
+            }
+        }
+
+        return Ok(linked);
+    }
+
+    // Find the file an import refers to, first next to the importing file and then in each
+    // directory of the search path.
+    fn find(&self, import: &ImportStatement, file: usize) -> CompileResult<PathBuf> {
+        let importer: &Path = Path::new(&self.program.files()[file].name);
+        let directory: PathBuf = importer.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        return [directory].iter()
+            .chain(&self.search_path)
+            .map(|directory| directory.join(&import.path))
+            .find(|path| path.is_file())
+            .ok_or_else(|| (
+                "Cannot find the module `".to_owned() + &import.path + "`.",
+                import.name.token_position
+            ));
+    }
+
+    // Load the module an import refers to, returning its index along with its statements, which
+    // are empty if it has already been included.
+    fn import(&mut self, import: &ImportStatement, file: usize)
+        -> CompileResult<(usize, Vec<Statement>)> {
+        let path: PathBuf = self.find(import, file)?;
+        let name: String = path.display().to_string();
+        let full_path: PathBuf = fs::canonicalize(&path).unwrap_or(path.to_owned());
+
+        if let Some(start) = self.loading.iter().position(|(loading, _)| *loading == full_path) {
+            let cycle: Vec<String> = self.loading[start..].iter()
+                .map(|(_, name)| name.to_owned())
+                .chain([name])
+                .collect();
+
+            return Err((
+                "Import cycle: ".to_owned() + &cycle.join(" -> ") + ".",
+                import.name.token_position
+            ));
+        }
+
+        if let Some(module) = self.loaded.get(&full_path) {
+            return Ok((*module, Vec::new()));
+        }
+
+        let source: String = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(error) => return Err((
+                "Could not read `".to_owned() + &name + "`: " + &error.to_string() + ".",
+                import.name.token_position
+            ))
+        };
+        let imported: usize = self.program.add_file(name.to_owned(), source);
+        let tokens: Vec<Token> = self.program.create_file_lexer(imported).lex()?;
+
+        self.program.set_tokens(tokens);
+
+        let mut parser: Parser = self.program.create_parser();
+        let statements: Vec<Statement> = parser.parse()?;
+
+        // Modules imported under the same name by different files keep apart.
+        let mut prefix: String = import.name.value();
+        let mut count: usize = 1;
+
+        while self.modules.iter().any(|module| module.prefix == prefix) {
+            count += 1;
+            prefix = import.name.value() + "#" + &count.to_string();
+        }
+
+        self.loading.push((full_path.to_owned(), name));
+
+        let module: usize = self.modules.len();
+        let statements: Vec<Statement> = self.link(statements, imported,
+                                                   import.name.value(), prefix)?;
+
+        self.loading.pop();
+        self.loaded.insert(full_path, module);
+
+        return Ok((module, statements));
+    }
+}
+
+// The name declared by a statement at the top level of a file, and whether it is public.
+fn declared_name(statement: &Statement) -> Option<(&Token, bool)> {
+    return match statement {
+        Statement::VariableDeclaration(declaration) => {
+            Some((&declaration.identifier.token, declaration.public))
+        },
+        Statement::FunctionDeclaration(declaration) => {
+            Some((&declaration.name.token, declaration.public))
+        },
+        Statement::ClassDeclaration(declaration) => {
+            Some((&declaration.name.token, declaration.public))
+        },
+        Statement::InterfaceDeclaration(declaration) => {
+            Some((&declaration.name.token, declaration.public))
+        },
+        Statement::EnumDeclaration(declaration) => {
+            Some((&declaration.name.token, declaration.public))
+        },
+        _ => None
+    };
+}
+
+fn rename(token: &mut Token, name: String) -> () {
+    token.token_value = Some(name);
+}
+
+// Renames the declarations of a module and the uses of them in one of its files.
+struct Renamer<'r> {
+    modules: &'r [Module],
+    module: usize, // The module being renamed.
+    imports: HashMap<String, usize>, // The modules the file imports, by the names it uses.
+    scopes: Vec<HashSet<String>>, // The local variables, which hide declarations of the same name.
+    // The type parameters in scope, which hide types of the same name.
+    type_parameters: Vec<String>,
+    stack: StackGuard
+}
+
+impl<'r> Renamer<'r> {
+    fn is_local(&self, name: &str) -> bool {
+        return self.scopes.iter().any(|scope| scope.contains(name));
+    }
+
+    fn declare_local(&mut self, name: &Token) -> () {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.value());
+        }
+    }
+
+    // The name of a declaration of the module once it is part of the program.
+    fn find_declaration(&self, name: &str) -> Option<String> {
+        let module: &Module = &self.modules[self.module];
+
+        if module.declarations.contains_key(name) {
+            return Some(module.qualify(name));
+        }
+
+        return None;
+    }
+
+    // The name of a public declaration of an imported module, such as `vector` in `math.vector`,
+    // once it is part of the program.
+    fn find_member(&self, alias: &str, member: &str, position: Position)
+        -> CompileResult<Option<String>> {
+        let module: &Module = match self.imports.get(alias) {
+            Some(module) => &self.modules[*module],
+            None => return Ok(None)
+        };
+
+        return match module.declarations.get(member) {
+            Some(true) => Ok(Some(module.qualify(member))),
+            Some(false) => Err((
+                "`".to_owned() + member + "` is not public in module `" + &module.name + "`.",
+                position
+            )),
+            None => Err((
+                "Module `".to_owned() + &module.name + "` has no `" + member + "`.",
+                position
+            ))
+        };
+    }
+
+    // Rename the name of a type, which can be qualified by a module, as in `math.Vector`.
+    fn rename_type_name(&self, name: &mut Token) -> CompileResult<()> {
+        let value: String = name.value();
+
+        if self.type_parameters.contains(&value) {
+            return Ok(());
+        }
+
+        let renamed: Option<String> = match value.split_once('.') {
+            Some((alias, member)) => self.find_member(alias, member, name.token_position)?,
+            None => self.find_declaration(&value)
+        };
+
+        if let Some(renamed) = renamed {
+            rename(name, renamed);
+        }
+
+        return Ok(());
+    }
+
+    fn rename_type(&self, annotation: &mut TypeAnnotation) -> CompileResult<()> {
+        self.rename_type_name(&mut annotation.name)?;
+
+        for argument in &mut annotation.arguments {
+            self.rename_type(argument)?;
+        }
+
+        return Ok(());
+    }
+
+    // Bring type parameters into scope, renaming their bounds. Returns how many type parameters
+    // were in scope before, for removing them again afterwards.
+    fn declare_type_parameters(&mut self, type_parameters: &mut [TypeParameterDeclaration])
+        -> CompileResult<usize> {
+        let outer: usize = self.type_parameters.len();
+
+        self.type_parameters.extend(type_parameters.iter()
+            .map(|type_parameter| type_parameter.name.value()));
+
+        for type_parameter in type_parameters {
+            if let Some(bound) = &mut type_parameter.bound {
+                self.rename_type(bound)?;
+            }
+        }
+
+        return Ok(outer);
+    }
+
+    fn rename_declaration(&self, name: &mut Token) -> () {
+        rename(name, self.modules[self.module].qualify(&name.value()));
+    }
+
+    fn rename_block(&mut self, statements: &mut [Statement]) -> CompileResult<()> {
+        self.scopes.push(HashSet::new());
+
+        for statement in statements {
+            self.rename_statement(statement)?;
+        }
+
+        self.scopes.pop();
+
+        return Ok(());
+    }
+
+    fn rename_function(&mut self, declaration: &mut FunctionDeclarationStatement)
+        -> CompileResult<()> {
+        let outer: usize = self.declare_type_parameters(&mut declaration.type_parameters)?;
+
+        self.rename_type(&mut declaration.return_type)?;
+        self.scopes.push(HashSet::new());
+
+        for parameter in &mut declaration.parameters {
+            self.rename_type(&mut parameter.parameter_type)?;
+            self.declare_local(&parameter.name);
+        }
+
+        self.rename_block(&mut declaration.body)?;
+        self.scopes.pop();
+        self.type_parameters.truncate(outer);
+
+        return Ok(());
+    }
+
+    fn rename_statement(&mut self, statement: &mut Statement) -> CompileResult<()> {
+        self.stack.check(statement.position())?;
+
+        let top_level: bool = self.scopes.is_empty();
+
+        match statement {
+            Statement::Expression(expression_statement) => {
+                self.rename_expression(&mut expression_statement.expression)?;
+            },
+            Statement::VariableDeclaration(declaration) => {
+                self.rename_type(&mut declaration.variable_type)?;
+
+                if let Some(initialization) = &mut declaration.initialization {
+                    self.rename_expression(initialization)?;
+                }
+
+                if top_level {
+                    self.rename_declaration(&mut declaration.identifier.token);
+                } else {
+                    self.declare_local(&declaration.identifier.token);
+                }
+            },
+            Statement::Block(block) => self.rename_block(&mut block.statements)?,
+            Statement::If(if_statement) => {
+                self.rename_expression(&mut if_statement.condition)?;
+                self.rename_block(&mut if_statement.then_branch)?;
+                self.rename_block(&mut if_statement.else_branch)?;
+            },
+            Statement::While(while_statement) => {
+                self.rename_expression(&mut while_statement.condition)?;
+                self.rename_block(&mut while_statement.body)?;
+            },
+            Statement::Loop(loop_statement) => self.rename_block(&mut loop_statement.body)?,
+            Statement::For(for_statement) => {
+                self.scopes.push(HashSet::new());
+                self.rename_type(&mut for_statement.variable_type)?;
+                self.rename_expression(&mut for_statement.initializer)?;
+                self.declare_local(&for_statement.variable);
+                self.rename_expression(&mut for_statement.condition)?;
+                self.rename_expression(&mut for_statement.increment)?;
+                self.rename_block(&mut for_statement.body)?;
+                self.scopes.pop();
+            },
+            Statement::ForIn(for_in_statement) => {
+                self.rename_expression(&mut for_in_statement.iterable)?;
+                self.scopes.push(HashSet::new());
+
+                for variable in &for_in_statement.variables {
+                    self.declare_local(variable);
+                }
+
+                self.rename_block(&mut for_in_statement.body)?;
+                self.scopes.pop();
+            },
+            Statement::Break(_) | Statement::Continue(_) => {},
+            Statement::FunctionDeclaration(declaration) => {
+                if top_level {
+                    self.rename_declaration(&mut declaration.name.token);
+                } else {
+                    self.declare_local(&declaration.name.token);
+                }
+
+                self.rename_function(declaration)?;
+            },
+            Statement::Return(return_statement) => {
+                if let Some(value) = &mut return_statement.value {
+                    self.rename_expression(value)?;
+                }
+            },
+            Statement::ClassDeclaration(declaration) => {
+                self.rename_declaration(&mut declaration.name.token);
+
+                let outer: usize = self.declare_type_parameters(&mut declaration.type_parameters)?;
+
+                if let Some(superclass) = &mut declaration.superclass {
+                    self.rename_type_name(&mut superclass.token)?;
+                }
+
+                for argument in &mut declaration.superclass_arguments {
+                    self.rename_type(argument)?;
+                }
+
+                for interface in &mut declaration.interfaces {
+                    self.rename_type(interface)?;
+                }
+
+                // Property initializers can only see the top level.
+                for property in &mut declaration.properties {
+                    self.rename_type(&mut property.variable_type)?;
+
+                    if let Some(initialization) = &mut property.initialization {
+                        self.rename_expression(initialization)?;
+                    }
+                }
+
+                // The constructor keeps the name of the class as written, which is how it is
+                // told apart from the methods.
+                if let Some(constructor) = &mut declaration.constructor {
+                    self.rename_function(constructor)?;
+                }
+
+                for method in &mut declaration.methods {
+                    self.rename_function(method)?;
+                }
+
+                self.type_parameters.truncate(outer);
+            },
+            Statement::InterfaceDeclaration(declaration) => {
+                self.rename_declaration(&mut declaration.name.token);
+
+                let outer: usize = self.declare_type_parameters(&mut declaration.type_parameters)?;
+
+                for method in &mut declaration.methods {
+                    self.rename_function(method)?;
+                }
+
+                self.type_parameters.truncate(outer);
+            },
+            Statement::EnumDeclaration(declaration) => {
+                self.rename_declaration(&mut declaration.name.token);
+
+                for variant in &mut declaration.variants {
+                    for field in &mut variant.fields {
+                        self.rename_type(&mut field.parameter_type)?;
+                    }
+                }
+            },
+            Statement::Throw(throw_statement) => {
+                self.rename_expression(&mut throw_statement.value)?;
+            },
+            Statement::Try(try_statement) => {
+                self.rename_block(&mut try_statement.body)?;
+
+                for catch in &mut try_statement.catches {
+                    if let Some(error_type) = &mut catch.error_type {
+                        self.rename_type(error_type)?;
+                    }
+
+                    self.scopes.push(HashSet::new());
+                    self.declare_local(&catch.variable);
+                    self.rename_block(&mut catch.body)?;
+                    self.scopes.pop();
+                }
+
+                if let Some(finally) = &mut try_statement.finally {
+                    self.rename_block(finally)?;
+                }
+            },
+            Statement::Import(_) | Statement::Test(_) | Statement::Trivia(_) => unreachable!()
+        }
+
+        return Ok(());
+    }
+
+    fn rename_pattern(&mut self, pattern: &mut Pattern) -> CompileResult<()> {
+        self.stack.check(pattern.position())?;
+
+        match pattern {
+            Pattern::Binding(name) => self.declare_local(name),
+            Pattern::Variant(variant) => {
+                self.rename_type_name(&mut variant.enum_name)?;
+
+                for field in &mut variant.fields {
+                    self.rename_pattern(field)?;
+                }
+            },
+            Pattern::Literal(_) | Pattern::Wildcard(_) => {}
+        }
+
+        return Ok(());
+    }
+
+    fn rename_expression(&mut self, expression: &mut Expression) -> CompileResult<()> {
+        self.stack.check(expression.position())?;
+
+        match expression {
+            Expression::Literal(_) | Expression::This(_) | Expression::Super(_) => {},
+            Expression::Identifier(identifier) => {
+                let name: String = identifier.token.value();
+
+                if !self.is_local(&name) {
+                    if let Some(renamed) = self.find_declaration(&name) {
+                        rename(&mut identifier.token, renamed);
+                    }
+                }
+            },
+            Expression::Binary(binary) => {
+                self.rename_expression(&mut binary.left)?;
+                self.rename_expression(&mut binary.right)?;
+            },
+            Expression::Unary(unary) => self.rename_expression(&mut unary.operand)?,
+            Expression::Assignment(assignment) => {
+                self.rename_expression(&mut assignment.target)?;
+                self.rename_expression(&mut assignment.value)?;
+            },
+            Expression::FunctionCall(call) => {
+                match call.callee.as_mut() {
+                    // Called names are always functions or classes, even when a variable has the
+                    // same name.
+                    Expression::Identifier(identifier) => {
+                        if let Some(renamed) = self.find_declaration(&identifier.token.value()) {
+                            rename(&mut identifier.token, renamed);
+                        }
+                    },
+                    callee => self.rename_expression(callee)?
+                }
+
+                for argument in &mut call.arguments {
+                    self.rename_expression(argument)?;
+                }
+            },
+            Expression::Property(property) => {
+                // A declaration of an imported module, unless a variable hides the module.
+                if let (Expression::Identifier(object), false) =
+                    (property.object.as_ref(), property.safe) {
+                    let alias: String = object.token.value();
+
+                    if !self.is_local(&alias) {
+                        let position: Position = property.name.token_position;
+
+                        if let Some(renamed) = self.find_member(&alias, &property.name.value(),
+                                                                position)? {
+                            let start: Position = object.token.token_position;
+                            let position: Position = if start.line == position.line {
+                                Position::new(start.line, start.start, position.end)
+                                    .in_file(start.file)
+                            } else {
+                                position
+                            };
+
+                            *expression = Expression::Identifier(IdentifierExpression {
+                                token: Token::new(TokenType::Identifier, Some(renamed), position)
+                            });
+
+                            return Ok(());
+                        }
+                    }
+                }
+
+                self.rename_expression(&mut property.object)?;
+            },
+            Expression::Is(is) => {
+                self.rename_expression(&mut is.value)?;
+                self.rename_type_name(&mut is.class_name)?;
+            },
+            Expression::Map(map) => {
+                for (key, value) in &mut map.entries {
+                    self.rename_expression(key)?;
+                    self.rename_expression(value)?;
+                }
+            },
+            Expression::List(list) => {
+                for element in &mut list.elements {
+                    self.rename_expression(element)?;
+                }
+            },
+            Expression::Range(range) => {
+                self.rename_expression(&mut range.start)?;
+                self.rename_expression(&mut range.end)?;
+            },
+            Expression::Match(match_expression) => {
+                self.rename_expression(&mut match_expression.value)?;
+
+                for arm in &mut match_expression.arms {
+                    self.scopes.push(HashSet::new());
+                    self.rename_pattern(&mut arm.pattern)?;
+
+                    if let Some(guard) = &mut arm.guard {
+                        self.rename_expression(guard)?;
+                    }
+
+                    match &mut arm.body {
+                        MatchArmBody::Expression(body) => self.rename_expression(body)?,
+                        MatchArmBody::Block(body) => self.rename_block(body)?
+                    }
+
+                    self.scopes.pop();
+                }
+            },
+            Expression::Propagate(propagate) => self.rename_expression(&mut propagate.value)?
+        }
+
+        return Ok(());
+    }
+}
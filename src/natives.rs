@@ -0,0 +1,618 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::env;
+use std::f64::consts::PI;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{self, Output};
+use std::rc::Rc;
+use std::sync::OnceLock;
+use crate::expressions::{Expression, PropertyExpression};
+use crate::host::HostFunctionBody;
+use crate::program::Program;
+use crate::types::{FunctionType, Type};
+use crate::value::{ClassValue, FunctionCode, InstanceValue, Value};
+
+// Runs a native function with the values of its arguments, or returns why the program has to stop.
+// The type checker has already made sure the arguments have the declared types.
+pub type NativeFunction = fn(&Program, Vec<Value>) -> Result<Value, NativeError>;
+
+// Why a native function stopped the program: a runtime error with its message, which can be
+// caught, or a call to `exit` with its exit code, which ends the program wherever it is.
+pub enum NativeError {
+    Error(String),
+    Exit(i32)
+}
+
+impl From<String> for NativeError {
+    fn from(message: String) -> Self {
+        return Self::Error(message);
+    }
+}
+
+// A function which is built into the language and implemented in Rust.
+pub struct Native {
+    pub name: &'static str,
+    pub parameters: Vec<Option<Type>>, // `None` accepts a value of any type except `void`.
+    pub variadic: bool, // Whether the last parameter can be given any number of times, even none.
+    pub numeric: bool, // Whether it takes ints for its floats, returning an int when all are ints.
+    pub return_type: Type,
+    pub function: NativeFunction
+}
+
+impl Native {
+    // Whether the function can be called with the given number of arguments.
+    pub fn accepts(&self, count: usize) -> bool {
+        if self.variadic {
+            return count + 1 >= self.parameters.len();
+        }
+
+        return count == self.parameters.len();
+    }
+
+    // The type expected for each of the given number of arguments.
+    pub fn parameter_types(&self, count: usize) -> Vec<Option<Type>> {
+        return (0..count)
+            .map(|index| self.parameters[index.min(self.parameters.len() - 1)].to_owned())
+            .collect();
+    }
+}
+
+// A function given by a program embedding the language, which scripts call like any other.
+#[derive(Clone)]
+pub struct HostFunction {
+    pub name: String,
+    pub function_type: FunctionType,
+    pub function: HostFunctionBody
+}
+
+// A type of object defined by a program embedding the language. Its methods are given the object
+// before their arguments.
+#[derive(Clone)]
+pub struct HostType {
+    pub name: String,
+    pub methods: Vec<HostFunction>
+}
+
+// A global variable given its value by a program embedding the language.
+#[derive(Clone)]
+pub struct HostValue {
+    pub name: String,
+    pub value_type: Type,
+    pub value: Value
+}
+
+// A class which is built into the language. Programs cannot create its instances themselves;
+// natives return them.
+pub struct NativeClass {
+    pub name: &'static str,
+    pub properties: Vec<(&'static str, Type)>
+}
+
+// The modules grouping natives, which are called as in `fs.read(path)`.
+const MODULES: [&str; 2] = ["fs", "process"];
+
+const PROCESS_RESULT: &str = "ProcessResult";
+
+// `expect_error(expression)` is built in, but it is not a native function: rather than being given
+// the value of its argument, it evaluates the argument itself, making sure that fails.
+pub const EXPECT_ERROR: &str = "expect_error";
+
+// The class of errors, which the prelude declares. Errors found while a program runs are caught as
+// instances of it, and only instances of it can be thrown.
+pub const ERROR_CLASS: &str = "Error";
+
+// The enums which are built in, as their variants hold values of whatever types they are given:
+// `Result<T, E>` is either `Result.Ok(value)` or `Result.Err(error)`, and `Option<T>` is either
+// `Option.Some(value)` or `Option.None`.
+pub const RESULT_ENUM: &str = "Result";
+pub const OPTION_ENUM: &str = "Option";
+
+static NATIVES: OnceLock<Vec<Native>> = OnceLock::new();
+static NATIVE_CLASSES: OnceLock<Vec<NativeClass>> = OnceLock::new();
+
+// Every native function. Compiled programs refer to natives by their index in this list, so new
+// natives go at the end, and the bytecode version has to change when any are removed.
+pub fn natives() -> &'static [Native] {
+    return NATIVES.get_or_init(|| vec![
+        // Input and output.
+        native("print", vec![None], Type::Void, print),
+        native("println", vec![None], Type::Void, println),
+        native("input", vec![], nullable(Type::String), input),
+        // The process.
+        native("args", vec![], Type::List(Box::new(Type::String)), args),
+        native("env", vec![Some(Type::String)], nullable(Type::String), environment_variable),
+        native("exit", vec![Some(Type::Integer)], Type::Void, exit),
+        // Strings.
+        native("split", vec![Some(Type::String), Some(Type::String)],
+               Type::List(Box::new(Type::String)), split),
+        native("trim", vec![Some(Type::String)], Type::String, trim),
+        native("replace", vec![Some(Type::String), Some(Type::String), Some(Type::String)],
+               Type::String, replace),
+        native("find", vec![Some(Type::String), Some(Type::String)], nullable(Type::Integer),
+               find),
+        native("upper", vec![Some(Type::String)], Type::String, upper),
+        native("lower", vec![Some(Type::String)], Type::String, lower),
+        Native {
+            variadic: true,
+            ..native("format", vec![Some(Type::String), None], Type::String, format)
+        },
+        // Maths.
+        native("sqrt", vec![Some(Type::Float)], Type::Float, sqrt),
+        native("pow", vec![Some(Type::Float), Some(Type::Float)], Type::Float, pow),
+        native("floor", vec![Some(Type::Float)], Type::Integer, floor),
+        native("ceil", vec![Some(Type::Float)], Type::Integer, ceil),
+        native("round", vec![Some(Type::Float)], Type::Integer, round),
+        Native { numeric: true, ..native("abs", vec![Some(Type::Float)], Type::Float, abs) },
+        Native {
+            numeric: true,
+            ..native("min", vec![Some(Type::Float), Some(Type::Float)], Type::Float, min)
+        },
+        Native {
+            numeric: true,
+            ..native("max", vec![Some(Type::Float), Some(Type::Float)], Type::Float, max)
+        },
+        native("sin", vec![Some(Type::Float)], Type::Float, sin),
+        native("cos", vec![Some(Type::Float)], Type::Float, cos),
+        native("tan", vec![Some(Type::Float)], Type::Float, tan),
+        native("pi", vec![], Type::Float, pi),
+        // Conversions.
+        native("int", vec![None], Type::Integer, to_int),
+        native("float", vec![None], Type::Float, to_float),
+        native("to_string", vec![None], Type::String, to_string),
+        // Files.
+        native("fs.read", vec![Some(Type::String)], Type::String, read_file),
+        native("fs.write", vec![Some(Type::String), Some(Type::String)], Type::Void, write_file),
+        native("fs.exists", vec![Some(Type::String)], Type::Boolean, exists),
+        native("fs.list_dir", vec![Some(Type::String)], Type::List(Box::new(Type::String)),
+               list_dir),
+        Native {
+            variadic: true,
+            ..native("fs.join", vec![Some(Type::String), Some(Type::String)], Type::String,
+                     join_paths)
+        },
+        // Other processes.
+        native("process.run", vec![Some(Type::String), Some(Type::List(Box::new(Type::String)))],
+               Type::Class(PROCESS_RESULT.to_owned(), Vec::new()), run_process),
+        // Testing.
+        native("assert", vec![Some(Type::Boolean)], Type::Void, assert),
+        native("assert_eq", vec![None, None], Type::Void, assert_equal)
+    ]);
+}
+
+pub fn native_classes() -> &'static [NativeClass] {
+    return NATIVE_CLASSES.get_or_init(|| vec![
+        NativeClass {
+            name: PROCESS_RESULT,
+            properties: vec![
+                ("stdout", Type::String),
+                ("stderr", Type::String),
+                ("status", Type::Integer)
+            ]
+        }
+    ]);
+}
+
+// Find a native function by its name, along with its index.
+pub fn find_native(name: &str) -> Option<(usize, &'static Native)> {
+    return natives().iter().enumerate().find(|(_, native)| native.name == name);
+}
+
+// Find the native called by a call such as `fs.read(path)`, unless a variable hides the module.
+pub fn find_module_native(property: &PropertyExpression, is_variable: impl Fn(&str) -> bool)
+    -> Option<(usize, &'static Native)> {
+    return match property.object.as_ref() {
+        Expression::Identifier(identifier) => {
+            let module: String = identifier.token.value();
+
+            if is_module(&module) && !is_variable(&module) {
+                find_native(&(module + "." + &property.name.value()))
+            } else {
+                None
+            }
+        },
+        _ => None
+    };
+}
+
+pub fn is_module(name: &str) -> bool {
+    return MODULES.contains(&name);
+}
+
+pub fn is_built_in_enum(name: &str) -> bool {
+    return name == RESULT_ENUM || name == OPTION_ENUM;
+}
+
+pub fn is_native_class(name: &str) -> bool {
+    return native_classes().iter().any(|class| class.name == name);
+}
+
+fn native(name: &'static str, parameters: Vec<Option<Type>>, return_type: Type,
+          function: NativeFunction) -> Native {
+    return Native {
+        name,
+        parameters,
+        variadic: false,
+        numeric: false,
+        return_type,
+        function
+    };
+}
+
+fn nullable(inner: Type) -> Type {
+    return Type::Nullable(Box::new(inner));
+}
+
+fn string(value: &Value) -> String {
+    return match value {
+        Value::String(value) => value.to_owned(),
+        _ => unreachable!()
+    };
+}
+
+fn integer(value: &Value) -> i64 {
+    return match value {
+        Value::Integer(value) => *value,
+        _ => unreachable!()
+    };
+}
+
+// Integers can be given wherever floats are expected.
+fn number(value: &Value) -> f64 {
+    return match value {
+        Value::Integer(value) => *value as f64,
+        Value::Float(value) => *value,
+        _ => unreachable!()
+    };
+}
+
+fn list(values: Vec<Value>) -> Value {
+    return Value::List(Rc::new(RefCell::new(values)));
+}
+
+// Make an instance of a native class with the given fields.
+fn instance(class: &str, fields: Vec<(&str, Value)>) -> Value {
+    let class: Rc<ClassValue> = Rc::new(ClassValue {
+        name: class.to_owned(),
+        superclass: None,
+        initializer: FunctionCode::Native,
+        constructor: None,
+        methods: HashMap::new()
+    });
+    let fields: HashMap<String, Option<Value>> = fields.into_iter()
+        .map(|(name, value)| (name.to_owned(), Some(value)))
+        .collect();
+
+    return Value::Instance(Rc::new(RefCell::new(InstanceValue::new(class, fields))));
+}
+
+fn io_error(action: &str, path: &str, error: io::Error) -> NativeError {
+    return NativeError::Error(
+        "Could not ".to_owned() + action + " `" + path + "`: " + &error.to_string() + "."
+    );
+}
+
+// The output may have been closed, such as when it is piped into a program which stops early.
+fn output_error(error: io::Error) -> NativeError {
+    return NativeError::Error(
+        "Could not write the output: ".to_owned() + &error.to_string() + "."
+    );
+}
+
+fn print(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    let mut stdout: io::Stdout = io::stdout();
+
+    write!(stdout, "{}", arguments[0].to_string()).map_err(output_error)?;
+    stdout.flush().map_err(output_error)?;
+
+    return Ok(Value::Void);
+}
+
+fn println(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    writeln!(io::stdout(), "{}", arguments[0].to_string()).map_err(output_error)?;
+
+    return Ok(Value::Void);
+}
+
+// Read a line from standard input, without its line ending, or null at the end of the input.
+fn input(_: &Program, _: Vec<Value>) -> Result<Value, NativeError> {
+    let mut line: String = String::new();
+
+    return match io::stdin().read_line(&mut line) {
+        Ok(0) => Ok(Value::Null),
+        Ok(_) => Ok(Value::String(line.trim_end_matches(['\n', '\r']).to_owned())),
+        Err(error) => Err(NativeError::Error(
+            "Could not read the input: ".to_owned() + &error.to_string() + "."
+        ))
+    };
+}
+
+fn args(program: &Program, _: Vec<Value>) -> Result<Value, NativeError> {
+    return Ok(list(program.arguments().into_iter().map(Value::String).collect()));
+}
+
+fn environment_variable(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return Ok(match env::var(string(&arguments[0])) {
+        Ok(value) => Value::String(value),
+        Err(_) => Value::Null
+    });
+}
+
+// End the program, however deeply it is nested, which whoever is running it decides how to handle.
+// Exit codes are a single byte, so larger ones would be cut down to something else.
+fn exit(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    let code: i64 = integer(&arguments[0]);
+
+    if !(0..=255).contains(&code) {
+        return Err(NativeError::Error(
+            "Exit codes must be between 0 and 255, but got ".to_owned() + &code.to_string() + "."
+        ));
+    }
+
+    return Err(NativeError::Exit(code as i32));
+}
+
+fn split(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    let separator: String = string(&arguments[1]);
+
+    if separator.is_empty() {
+        return Err(NativeError::Error("Cannot split a string by an empty separator.".to_owned()));
+    }
+
+    return Ok(list(string(&arguments[0]).split(&separator)
+        .map(|part| Value::String(part.to_owned()))
+        .collect()));
+}
+
+fn trim(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return Ok(Value::String(string(&arguments[0]).trim().to_owned()));
+}
+
+fn replace(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    let from: String = string(&arguments[1]);
+
+    if from.is_empty() {
+        return Err(NativeError::Error("Cannot replace an empty string.".to_owned()));
+    }
+
+    return Ok(Value::String(string(&arguments[0]).replace(&from, &string(&arguments[2]))));
+}
+
+// The index of the first character where the second string appears in the first, or null.
+fn find(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    let text: String = string(&arguments[0]);
+
+    return Ok(match text.find(&string(&arguments[1])) {
+        Some(byte) => Value::Integer(text[..byte].chars().count() as i64),
+        None => Value::Null
+    });
+}
+
+fn upper(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return Ok(Value::String(string(&arguments[0]).to_uppercase()));
+}
+
+fn lower(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return Ok(Value::String(string(&arguments[0]).to_lowercase()));
+}
+
+// Replace each `{}` in the template with the next of the values.
+fn format(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    let template: String = string(&arguments[0]);
+    let parts: Vec<&str> = template.split("{}").collect();
+    let values: &[Value] = &arguments[1..];
+
+    if parts.len() - 1 != values.len() {
+        return Err(NativeError::Error(
+            "The template has ".to_owned() + &(parts.len() - 1).to_string() +
+                " placeholder(s), but " + &values.len().to_string() + " value(s) were given."
+        ));
+    }
+
+    let mut result: String = parts[0].to_owned();
+
+    for (value, part) in values.iter().zip(&parts[1..]) {
+        result += &(value.to_string() + part);
+    }
+
+    return Ok(Value::String(result));
+}
+
+fn sqrt(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    let value: f64 = number(&arguments[0]);
+
+    if value < 0.0 {
+        return Err(NativeError::Error(
+            "Cannot take the square root of a negative number.".to_owned()
+        ));
+    }
+
+    return Ok(Value::Float(value.sqrt()));
+}
+
+fn pow(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return Ok(Value::Float(number(&arguments[0]).powf(number(&arguments[1]))));
+}
+
+// Convert a whole number to an int, which fails like integer arithmetic does when it does not fit,
+// rather than saturating. `i64::MAX as f64` rounds up to 2^63, which is already too large.
+fn whole_number(value: f64) -> Result<Value, NativeError> {
+    if value.is_nan() || value < i64::MIN as f64 || value >= i64::MAX as f64 {
+        return Err(NativeError::Error("Integer overflow.".to_owned()));
+    }
+
+    return Ok(Value::Integer(value as i64));
+}
+
+fn floor(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return whole_number(number(&arguments[0]).floor());
+}
+
+fn ceil(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return whole_number(number(&arguments[0]).ceil());
+}
+
+fn round(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return whole_number(number(&arguments[0]).round());
+}
+
+fn abs(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return match &arguments[0] {
+        Value::Integer(value) => value.checked_abs()
+            .map(Value::Integer)
+            .ok_or_else(|| NativeError::Error("Integer overflow.".to_owned())),
+        value => Ok(Value::Float(number(value).abs()))
+    };
+}
+
+fn min(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return Ok(match (&arguments[0], &arguments[1]) {
+        (Value::Integer(left), Value::Integer(right)) => Value::Integer(*left.min(right)),
+        (left, right) => Value::Float(number(left).min(number(right)))
+    });
+}
+
+fn max(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return Ok(match (&arguments[0], &arguments[1]) {
+        (Value::Integer(left), Value::Integer(right)) => Value::Integer(*left.max(right)),
+        (left, right) => Value::Float(number(left).max(number(right)))
+    });
+}
+
+fn sin(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return Ok(Value::Float(number(&arguments[0]).sin()));
+}
+
+fn cos(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return Ok(Value::Float(number(&arguments[0]).cos()));
+}
+
+fn tan(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return Ok(Value::Float(number(&arguments[0]).tan()));
+}
+
+fn pi(_: &Program, _: Vec<Value>) -> Result<Value, NativeError> {
+    return Ok(Value::Float(PI));
+}
+
+fn to_int(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return match &arguments[0] {
+        Value::Integer(value) => Ok(Value::Integer(*value)),
+        Value::Float(value) => whole_number(value.trunc()),
+        Value::Char(value) => Ok(Value::Integer(*value as i64)),
+        Value::Boolean(value) => Ok(Value::Integer(*value as i64)),
+        Value::String(value) => match value.trim().parse::<i64>() {
+            Ok(value) => Ok(Value::Integer(value)),
+            Err(_) => Err(NativeError::Error(
+                "Cannot convert ".to_owned() + &arguments[0].to_repr() + " to an int."
+            ))
+        },
+        other => Err(NativeError::Error(
+            "Cannot convert ".to_owned() + &other.to_repr() + " to an int."
+        ))
+    };
+}
+
+fn to_float(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return match &arguments[0] {
+        Value::Integer(value) => Ok(Value::Float(*value as f64)),
+        Value::Float(value) => Ok(Value::Float(*value)),
+        Value::String(value) => match value.trim().parse::<f64>() {
+            Ok(value) => Ok(Value::Float(value)),
+            Err(_) => Err(NativeError::Error(
+                "Cannot convert ".to_owned() + &arguments[0].to_repr() + " to a float."
+            ))
+        },
+        other => Err(NativeError::Error(
+            "Cannot convert ".to_owned() + &other.to_repr() + " to a float."
+        ))
+    };
+}
+
+fn to_string(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return Ok(Value::String(arguments[0].to_string()));
+}
+
+fn read_file(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    let path: String = string(&arguments[0]);
+
+    return fs::read_to_string(&path)
+        .map(Value::String)
+        .map_err(|error| io_error("read", &path, error));
+}
+
+fn write_file(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    let path: String = string(&arguments[0]);
+
+    return fs::write(&path, string(&arguments[1]))
+        .map(|_| Value::Void)
+        .map_err(|error| io_error("write", &path, error));
+}
+
+fn exists(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    return Ok(Value::Boolean(PathBuf::from(string(&arguments[0])).exists()));
+}
+
+// The names of the entries in a directory, sorted so that the order does not depend on the system.
+fn list_dir(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    let path: String = string(&arguments[0]);
+    let mut names: Vec<String> = Vec::new();
+
+    for entry in fs::read_dir(&path).map_err(|error| io_error("list", &path, error))? {
+        let entry: fs::DirEntry = entry.map_err(|error| io_error("list", &path, error))?;
+
+        names.push(entry.file_name().to_string_lossy().into_owned());
+    }
+
+    names.sort();
+
+    return Ok(list(names.into_iter().map(Value::String).collect()));
+}
+
+fn join_paths(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    let path: PathBuf = arguments.iter().map(string).collect();
+
+    return Ok(Value::String(path.to_string_lossy().into_owned()));
+}
+
+// Run a program until it exits, collecting its output. The program is found through `PATH`, and
+// no shell is involved.
+fn run_process(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    let command: String = string(&arguments[0]);
+    let Value::List(args) = &arguments[1] else { unreachable!() };
+    let args: Vec<String> = args.borrow().iter().map(string).collect();
+
+    let output: Output = process::Command::new(&command)
+        .args(&args)
+        .output()
+        .map_err(|error| io_error("run", &command, error))?;
+
+    // Processes stopped by a signal have no exit status.
+    let status: i64 = output.status.code().unwrap_or(-1) as i64;
+
+    return Ok(instance(PROCESS_RESULT, vec![
+        ("stdout", Value::String(String::from_utf8_lossy(&output.stdout).into_owned())),
+        ("stderr", Value::String(String::from_utf8_lossy(&output.stderr).into_owned())),
+        ("status", Value::Integer(status))
+    ]));
+}
+
+fn assert(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    if arguments[0] != Value::Boolean(true) {
+        return Err(NativeError::Error("Assertion failed.".to_owned()));
+    }
+
+    return Ok(Value::Void);
+}
+
+fn assert_equal(_: &Program, arguments: Vec<Value>) -> Result<Value, NativeError> {
+    if arguments[0] != arguments[1] {
+        return Err(NativeError::Error(
+            "Assertion failed: `".to_owned() + &arguments[0].to_string() + "` is not equal to `" +
+                &arguments[1].to_string() + "`."
+        ));
+    }
+
+    return Ok(Value::Void);
+}
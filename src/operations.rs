@@ -0,0 +1,193 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use crate::expressions::{BinaryOperator, UnaryOperator};
+use crate::value::{MapKey, MapValue, Value};
+
+// The runtime behaviour of the operators and of the methods of built-in types, shared by every
+// backend. Logical `&&`, `||` and `??` are not handled here, as they short-circuit and so must be
+// evaluated by whatever is running the program.
+
+fn to_float(value: &Value) -> f64 {
+    return match value {
+        Value::Integer(value) => *value as f64,
+        Value::Float(value) => *value,
+        _ => unreachable!()
+    };
+}
+
+fn integer_result(result: Option<i64>) -> Result<Value, String> {
+    return match result {
+        Some(value) => Ok(Value::Integer(value)),
+        None => Err("Integer overflow.".to_owned())
+    };
+}
+
+fn arithmetic(operator: BinaryOperator, left: &Value, right: &Value) -> Result<Value, String> {
+    if let (Value::Integer(left), Value::Integer(right)) = (left, right) {
+        return match operator {
+            BinaryOperator::Add => integer_result(left.checked_add(*right)),
+            BinaryOperator::Subtract => integer_result(left.checked_sub(*right)),
+            BinaryOperator::Multiply => integer_result(left.checked_mul(*right)),
+            BinaryOperator::Divide | BinaryOperator::Remainder if *right == 0 => {
+                Err("Division by zero.".to_owned())
+            },
+            BinaryOperator::Divide => integer_result(left.checked_div(*right)),
+            BinaryOperator::Remainder => integer_result(left.checked_rem(*right)),
+            BinaryOperator::Power => {
+                if *right < 0 {
+                    return Err("Integers cannot be raised to a negative power.".to_owned());
+                }
+
+                let exponent: u32 = match u32::try_from(*right) {
+                    Ok(exponent) => exponent,
+                    Err(_) => return Err("Integer overflow.".to_owned())
+                };
+
+                integer_result(left.checked_pow(exponent))
+            },
+            _ => unreachable!()
+        };
+    }
+
+    let left: f64 = to_float(left);
+    let right: f64 = to_float(right);
+
+    return Ok(Value::Float(match operator {
+        BinaryOperator::Add => left + right,
+        BinaryOperator::Subtract => left - right,
+        BinaryOperator::Multiply => left * right,
+        BinaryOperator::Divide => left / right,
+        BinaryOperator::Remainder => left % right,
+        BinaryOperator::Power => left.powf(right),
+        _ => unreachable!()
+    }));
+}
+
+fn compare(operator: BinaryOperator, left: &Value, right: &Value) -> bool {
+    let ordering: Option<std::cmp::Ordering> = match (left, right) {
+        (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+        (Value::Char(left), Value::Char(right)) => Some(left.cmp(right)),
+        (Value::Integer(left), Value::Integer(right)) => Some(left.cmp(right)),
+        _ => to_float(left).partial_cmp(&to_float(right))
+    };
+
+    return match ordering {
+        Some(ordering) => match operator {
+            BinaryOperator::LessThan => ordering.is_lt(),
+            BinaryOperator::LessThanOrEqual => ordering.is_le(),
+            BinaryOperator::GreaterThan => ordering.is_gt(),
+            BinaryOperator::GreaterThanOrEqual => ordering.is_ge(),
+            _ => unreachable!()
+        },
+        // Comparisons involving NaN are always false.
+        None => false
+    };
+}
+
+pub fn binary_operation(operator: BinaryOperator, left: &Value, right: &Value)
+    -> Result<Value, String> {
+    return match operator {
+        BinaryOperator::Add => match (left, right) {
+            (Value::String(_), _) | (_, Value::String(_)) => {
+                Ok(Value::String(left.to_string() + &right.to_string()))
+            },
+            _ => arithmetic(operator, left, right)
+        },
+        BinaryOperator::Subtract | BinaryOperator::Multiply | BinaryOperator::Divide |
+        BinaryOperator::Remainder | BinaryOperator::Power => arithmetic(operator, left, right),
+        BinaryOperator::Equal => Ok(Value::Boolean(left == right)),
+        BinaryOperator::NotEqual => Ok(Value::Boolean(left != right)),
+        BinaryOperator::LessThan | BinaryOperator::LessThanOrEqual |
+        BinaryOperator::GreaterThan | BinaryOperator::GreaterThanOrEqual => {
+            Ok(Value::Boolean(compare(operator, left, right)))
+        },
+        BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Coalesce => unreachable!()
+    };
+}
+
+pub fn unary_operation(operator: UnaryOperator, operand: &Value) -> Result<Value, String> {
+    return match (operator, operand) {
+        (UnaryOperator::Negate, Value::Integer(value)) => integer_result(value.checked_neg()),
+        (UnaryOperator::Negate, Value::Float(value)) => Ok(Value::Float(-value)),
+        (UnaryOperator::Not, Value::Boolean(value)) => Ok(Value::Boolean(!value)),
+        (UnaryOperator::ToFloat, Value::Integer(value)) => Ok(Value::Float(*value as f64)),
+        // A nullable int can also be used as a nullable float.
+        (UnaryOperator::ToFloat, Value::Float(_) | Value::Null) => Ok(operand.to_owned()),
+        _ => unreachable!()
+    };
+}
+
+// The number of arguments a method of lists or maps takes, which is the same for both, or `None`
+// when neither has a method of that name.
+pub fn builtin_method_arity(name: &str) -> Option<usize> {
+    return match name {
+        "pop" | "size" => Some(0),
+        "push" | "get" | "remove" | "contains" => Some(1),
+        "set" => Some(2),
+        _ => None
+    };
+}
+
+pub fn map_method(map: &Rc<RefCell<MapValue>>, name: &str, arguments: &[Value])
+    -> Result<Value, String> {
+    let key: Option<MapKey> = arguments.first().and_then(MapKey::from_value);
+
+    return Ok(match name {
+        "get" => match map.borrow().get(key.as_ref().unwrap()) {
+            Some(value) => value,
+            None => return Err(
+                "Key ".to_owned() + &arguments[0].to_repr() + " is not in the map."
+            )
+        },
+        "set" => {
+            map.borrow_mut().set(key.unwrap(), arguments[1].to_owned());
+
+            Value::Void
+        },
+        "remove" => Value::Boolean(map.borrow_mut().remove(key.as_ref().unwrap())),
+        "contains" => Value::Boolean(map.borrow().contains(key.as_ref().unwrap())),
+        "size" => Value::Integer(map.borrow().len() as i64),
+        _ => unreachable!()
+    });
+}
+
+pub fn list_method(list: &Rc<RefCell<Vec<Value>>>, name: &str, arguments: &[Value])
+    -> Result<Value, String> {
+    let length: usize = list.borrow().len();
+
+    // Make sure an index argument refers to an element of the list.
+    let index: Option<usize> = match arguments.first() {
+        Some(Value::Integer(index)) if name == "get" || name == "set" => {
+            if *index < 0 || *index as usize >= length {
+                return Err(
+                    "Index ".to_owned() + &index.to_string() +
+                        " is out of range for a list of length " + &length.to_string() + "."
+                );
+            }
+
+            Some(*index as usize)
+        },
+        _ => None
+    };
+
+    return Ok(match name {
+        "push" => {
+            list.borrow_mut().push(arguments[0].to_owned());
+
+            Value::Void
+        },
+        "pop" => match list.borrow_mut().pop() {
+            Some(value) => value,
+            None => return Err("Cannot pop from an empty list.".to_owned())
+        },
+        "get" => list.borrow()[index.unwrap()].to_owned(),
+        "set" => {
+            list.borrow_mut()[index.unwrap()] = arguments[1].to_owned();
+
+            Value::Void
+        },
+        "contains" => Value::Boolean(list.borrow().contains(&arguments[0])),
+        "size" => Value::Integer(length as i64),
+        _ => unreachable!()
+    });
+}
@@ -0,0 +1,353 @@
+use crate::expressions::{BinaryOperator, Expression, LiteralExpression, LiteralValue, MatchArm,
+                         MatchArmBody};
+use crate::operations::{binary_operation, unary_operation};
+use crate::position::Position;
+use crate::program::{CompileResult, Program, StackGuard};
+use crate::statements::{BlockStatement, FunctionDeclarationStatement, Statement};
+use crate::value::Value;
+
+// Simplifies a type checked program before it is run, by evaluating expressions whose operands
+// are all literals and removing code which can never run.
+pub struct Optimizer {
+    stack: StackGuard
+}
+
+impl Optimizer {
+    pub fn new(program: &Program) -> Self {
+        return Self {
+            stack: program.stack_guard()
+        };
+    }
+
+    pub fn optimize(&mut self, statements: Vec<Statement>) -> CompileResult<Vec<Statement>> {
+        return self.optimize_statements(statements);
+    }
+
+    fn optimize_statements(&mut self, statements: Vec<Statement>) -> CompileResult<Vec<Statement>> {
+        let mut optimized: Vec<Statement> = Vec::new();
+
+        for statement in statements {
+            // Nothing after a `return`, `break` or `continue` can run.
+            let exits: bool = matches!(statement, Statement::Return(_) | Statement::Break(_) |
+                                                  Statement::Continue(_));
+
+            if let Some(statement) = self.optimize_statement(statement)? {
+                optimized.push(statement);
+            }
+
+            if exits {
+                break;
+            }
+        }
+
+        return Ok(optimized);
+    }
+
+    fn optimize_function(&mut self, function: FunctionDeclarationStatement)
+        -> CompileResult<FunctionDeclarationStatement> {
+        return Ok(FunctionDeclarationStatement {
+            body: self.optimize_statements(function.body)?,
+            ..function
+        });
+    }
+
+    // Optimize a statement, returning `None` if it can be removed entirely.
+    fn optimize_statement(&mut self, statement: Statement) -> CompileResult<Option<Statement>> {
+        self.stack.check(statement.position())?;
+
+        return Ok(Some(match statement {
+            Statement::Expression(mut expression_statement) => {
+                expression_statement.expression = self.optimize_expression(
+                    expression_statement.expression
+                )?;
+
+                Statement::Expression(expression_statement)
+            },
+            Statement::VariableDeclaration(mut declaration) => {
+                declaration.initialization = declaration.initialization
+                    .map(|initialization| self.optimize_expression(initialization))
+                    .transpose()?;
+
+                Statement::VariableDeclaration(declaration)
+            },
+            Statement::Block(block) => Statement::Block(BlockStatement {
+                statements: self.optimize_statements(block.statements)?,
+                position: block.position
+            }),
+            Statement::If(mut if_statement) => {
+                let condition: Expression = self.optimize_expression(if_statement.condition)?;
+
+                // Only the branch which is taken is kept, as a block so that it keeps its scope.
+                if let Some(taken) = Self::boolean_literal(&condition) {
+                    let branch: Vec<Statement> = if taken {
+                        if_statement.then_branch
+                    } else {
+                        if_statement.else_branch
+                    };
+
+                    return Ok(Some(Statement::Block(BlockStatement {
+                        statements: self.optimize_statements(branch)?,
+                        position: condition.position()
+                    })));
+                }
+
+                if_statement.condition = condition;
+                if_statement.then_branch = self.optimize_statements(if_statement.then_branch)?;
+                if_statement.else_branch = self.optimize_statements(if_statement.else_branch)?;
+
+                Statement::If(if_statement)
+            },
+            Statement::While(mut while_statement) => {
+                let condition: Expression = self.optimize_expression(while_statement.condition)?;
+
+                if Self::boolean_literal(&condition) == Some(false) {
+                    return Ok(None);
+                }
+
+                while_statement.condition = condition;
+                while_statement.body = self.optimize_statements(while_statement.body)?;
+
+                Statement::While(while_statement)
+            },
+            Statement::Loop(mut loop_statement) => {
+                loop_statement.body = self.optimize_statements(loop_statement.body)?;
+
+                Statement::Loop(loop_statement)
+            },
+            Statement::For(mut for_statement) => {
+                for_statement.initializer = self.optimize_expression(for_statement.initializer)?;
+                for_statement.condition = self.optimize_expression(for_statement.condition)?;
+                for_statement.increment = self.optimize_expression(for_statement.increment)?;
+                for_statement.body = self.optimize_statements(for_statement.body)?;
+
+                Statement::For(for_statement)
+            },
+            Statement::ForIn(mut for_in_statement) => {
+                for_in_statement.iterable = self.optimize_expression(for_in_statement.iterable)?;
+                for_in_statement.body = self.optimize_statements(for_in_statement.body)?;
+
+                Statement::ForIn(for_in_statement)
+            },
+            Statement::Return(mut return_statement) => {
+                return_statement.value = return_statement.value
+                    .map(|value| self.optimize_expression(value)).transpose()?;
+
+                Statement::Return(return_statement)
+            },
+            Statement::FunctionDeclaration(declaration) => {
+                Statement::FunctionDeclaration(self.optimize_function(declaration)?)
+            },
+            Statement::ClassDeclaration(mut declaration) => {
+                for property in declaration.properties.iter_mut() {
+                    property.initialization = property.initialization.take()
+                        .map(|initialization| self.optimize_expression(initialization))
+                        .transpose()?;
+                }
+
+                declaration.constructor = declaration.constructor
+                    .map(|constructor| self.optimize_function(constructor)).transpose()?;
+                declaration.methods = declaration.methods.into_iter()
+                    .map(|method| self.optimize_function(method))
+                    .collect::<CompileResult<_>>()?;
+
+                Statement::ClassDeclaration(declaration)
+            },
+            Statement::Throw(mut throw_statement) => {
+                throw_statement.value = self.optimize_expression(throw_statement.value)?;
+
+                Statement::Throw(throw_statement)
+            },
+            Statement::Try(mut try_statement) => {
+                try_statement.body = self.optimize_statements(try_statement.body)?;
+
+                for catch in try_statement.catches.iter_mut() {
+                    catch.body = self.optimize_statements(std::mem::take(&mut catch.body))?;
+                }
+
+                try_statement.finally = try_statement.finally
+                    .map(|finally| self.optimize_statements(finally)).transpose()?;
+
+                Statement::Try(try_statement)
+            },
+            Statement::Break(_) | Statement::Continue(_) | Statement::InterfaceDeclaration(_)
+            | Statement::EnumDeclaration(_) => statement,
+            Statement::Import(_) | Statement::Test(_) | Statement::Trivia(_) => unreachable!()
+        }));
+    }
+
+    fn boolean_literal(expression: &Expression) -> Option<bool> {
+        return match expression {
+            Expression::Literal(LiteralExpression { value: LiteralValue::Boolean(value), .. }) => {
+                Some(*value)
+            },
+            _ => None
+        };
+    }
+
+    fn literal(expression: &Expression) -> Option<Value> {
+        return match expression {
+            Expression::Literal(literal) => Some(Value::from_literal(&literal.value)),
+            _ => None
+        };
+    }
+
+    fn to_literal(value: Value, position: Position) -> Expression {
+        let value: LiteralValue = match value {
+            Value::Integer(value) => LiteralValue::Integer(value),
+            Value::Float(value) => LiteralValue::Float(value),
+            Value::String(value) => LiteralValue::String(value),
+            Value::Char(value) => LiteralValue::Char(value),
+            Value::Boolean(value) => LiteralValue::Boolean(value),
+            _ => unreachable!()
+        };
+
+        return Expression::Literal(LiteralExpression {
+            value,
+            position
+        });
+    }
+
+    fn optimize_expressions(&mut self, expressions: Vec<Expression>)
+        -> CompileResult<Vec<Expression>> {
+        return expressions.into_iter()
+            .map(|expression| self.optimize_expression(expression))
+            .collect();
+    }
+
+    fn optimize_expression(&mut self, expression: Expression) -> CompileResult<Expression> {
+        self.stack.check(expression.position())?;
+
+        return Ok(match expression {
+            Expression::Binary(mut binary) => {
+                let left: Expression = self.optimize_expression(*binary.left)?;
+                let right: Expression = self.optimize_expression(*binary.right)?;
+
+                // A literal on the left of `&&`, `||` or `??` decides whether the right side is
+                // used.
+                match (binary.operator, Self::literal(&left)) {
+                    (BinaryOperator::And, Some(Value::Boolean(true))) |
+                    (BinaryOperator::Or, Some(Value::Boolean(false))) |
+                    (BinaryOperator::Coalesce, Some(Value::Null)) => return Ok(right),
+                    (BinaryOperator::And | BinaryOperator::Or, Some(_)) => return Ok(left),
+                    (BinaryOperator::Coalesce, Some(_)) => return Ok(left),
+                    (BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Coalesce, None) => {
+                    },
+                    (_, Some(left_value)) => {
+                        if let Some(right_value) = Self::literal(&right) {
+                            return match binary_operation(binary.operator, &left_value,
+                                                          &right_value) {
+                                Ok(value) => Ok(Self::to_literal(value, binary.position)),
+                                Err(message) => Err((
+                                    message.trim_end_matches('.').to_owned() +
+                                        " in a constant expression.",
+                                    binary.position
+                                ))
+                            };
+                        }
+                    },
+                    _ => {}
+                }
+
+                binary.left = Box::new(left);
+                binary.right = Box::new(right);
+
+                Expression::Binary(binary)
+            },
+            Expression::Unary(mut unary) => {
+                let operand: Expression = self.optimize_expression(*unary.operand)?;
+
+                if let Some(value) = Self::literal(&operand) {
+                    return match unary_operation(unary.operator, &value) {
+                        Ok(value) => Ok(Self::to_literal(value, unary.position)),
+                        Err(message) => Err((
+                            message.trim_end_matches('.').to_owned() + " in a constant expression.",
+                            unary.position
+                        ))
+                    };
+                }
+
+                unary.operand = Box::new(operand);
+
+                Expression::Unary(unary)
+            },
+            Expression::Assignment(mut assignment) => {
+                assignment.value = Box::new(self.optimize_expression(*assignment.value)?);
+
+                if let Expression::Property(mut property) = *assignment.target {
+                    property.object = Box::new(self.optimize_expression(*property.object)?);
+                    assignment.target = Box::new(Expression::Property(property));
+                }
+
+                Expression::Assignment(assignment)
+            },
+            Expression::FunctionCall(mut call) => {
+                if let Expression::Property(mut property) = *call.callee {
+                    property.object = Box::new(self.optimize_expression(*property.object)?);
+                    call.callee = Box::new(Expression::Property(property));
+                }
+
+                call.arguments = self.optimize_expressions(call.arguments)?;
+
+                Expression::FunctionCall(call)
+            },
+            Expression::Property(mut property) => {
+                property.object = Box::new(self.optimize_expression(*property.object)?);
+
+                Expression::Property(property)
+            },
+            Expression::Is(mut is) => {
+                is.value = Box::new(self.optimize_expression(*is.value)?);
+
+                Expression::Is(is)
+            },
+            Expression::Map(mut map) => {
+                map.entries = map.entries.into_iter()
+                    .map(|(key, value)| {
+                        Ok((self.optimize_expression(key)?, self.optimize_expression(value)?))
+                    })
+                    .collect::<CompileResult<_>>()?;
+
+                Expression::Map(map)
+            },
+            Expression::List(mut list) => {
+                list.elements = self.optimize_expressions(list.elements)?;
+
+                Expression::List(list)
+            },
+            Expression::Range(mut range) => {
+                range.start = Box::new(self.optimize_expression(*range.start)?);
+                range.end = Box::new(self.optimize_expression(*range.end)?);
+
+                Expression::Range(range)
+            },
+            Expression::Match(mut match_expression) => {
+                match_expression.value = Box::new(self.optimize_expression(
+                    *match_expression.value
+                )?);
+                match_expression.arms = match_expression.arms.into_iter()
+                    .map(|arm| Ok(MatchArm {
+                        pattern: arm.pattern,
+                        guard: arm.guard.map(|guard| self.optimize_expression(guard)).transpose()?,
+                        body: match arm.body {
+                            MatchArmBody::Expression(body) => {
+                                MatchArmBody::Expression(self.optimize_expression(body)?)
+                            },
+                            MatchArmBody::Block(body) => {
+                                MatchArmBody::Block(self.optimize_statements(body)?)
+                            }
+                        }
+                    }))
+                    .collect::<CompileResult<_>>()?;
+
+                Expression::Match(match_expression)
+            },
+            Expression::Propagate(mut propagate) => {
+                propagate.value = Box::new(self.optimize_expression(*propagate.value)?);
+
+                Expression::Propagate(propagate)
+            },
+            Expression::Literal(_) | Expression::Identifier(_) | Expression::This(_) |
+            Expression::Super(_) => expression
+        });
+    }
+}
@@ -1,20 +1,1479 @@
-use crate::program::Program;
+use std::path::Path;
+use crate::expressions::{AssignmentExpression, BinaryExpression, BinaryOperator, Expression,
+                         FunctionCallExpression, IdentifierExpression, IsExpression,
+                         ListExpression, LiteralExpression, LiteralValue, MapExpression,
+                         MatchArm, MatchArmBody, MatchExpression, PropagateExpression,
+                         PropertyExpression, RangeExpression, SuperExpression, ThisExpression,
+                         UnaryExpression, UnaryOperator};
+use crate::patterns::{Pattern, VariantPattern};
+use crate::position::Position;
+use crate::program::{CompileResult, StackGuard};
+use crate::SOURCE_EXTENSION;
+use crate::statements::{BlockStatement, BreakStatement, CatchClause, ClassDeclarationStatement,
+                        Comment, ContinueStatement, EnumDeclarationStatement,
+                        EnumVariantDeclaration, ExpressionStatement, ForInStatement, ForStatement,
+                        FunctionDeclarationStatement, IfStatement, ImportStatement,
+                        InterfaceDeclarationStatement,
+                        LoopStatement, ParameterDeclaration, ReturnStatement, Statement,
+                        TestStatement, ThrowStatement, Trivia, TryStatement,
+                        VariableDeclarationStatement, WhileStatement};
 use crate::token::Token;
+use crate::token_type::TokenType;
+use crate::type_annotation::{TypeAnnotation, TypeParameterDeclaration};
 
-pub struct Parser<'a> {
-    program: &'a mut Program,
+// Join a name and a member of it, such as a module and one of its classes, into a single token.
+fn qualify(name: &Token, member: &Token) -> Token {
+    let start: Position = name.token_position;
+    let end: Position = member.token_position;
+    let position: Position = if start.line == end.line {
+        Position::new(start.line, start.start, end.end).in_file(start.file)
+    } else {
+        start
+    };
+
+    return Token::new(TokenType::Identifier, Some(name.value() + "." + &member.value()), position);
+}
+
+fn is_identifier(name: &str) -> bool {
+    return name.chars().next().is_some_and(|first| first.is_alphabetic() || first == '_')
+        && name.chars().all(|character| character.is_alphanumeric() || character == '_');
+}
+
+// The parser takes the tokens produced by the lexer and builds the statements that make up the
+// program.
+pub struct Parser {
     input: Vec<Token>,
     position: i32,
-    current_token: Option<Token>
+    current_token: Option<Token>,
+    // The comments and blank lines among the tokens, along with the index of the token after each,
+    // which are taken out as their statement, class, enum or match is parsed.
+    trivia: Vec<(usize, Option<Trivia>)>,
+    stack: StackGuard
 }
 
-impl<'a> Parser<'a> {
-    pub fn new(program: &'a mut Program, input: Vec<Token>) -> Self {
+impl Parser {
+    pub fn new(tokens: Vec<Token>, stack: StackGuard) -> Self {
+        let mut input: Vec<Token> = Vec::new();
+        let mut trivia: Vec<(usize, Option<Trivia>)> = Vec::new();
+
+        for token in tokens {
+            let item: Trivia = match token.token_type {
+                TokenType::Comment => Trivia::Comment(Comment {
+                    trailing: input.last().is_some_and(|previous: &Token| {
+                        previous.token_position.line == token.token_position.line
+                    }),
+                    text: token.value(),
+                    position: token.token_position
+                }),
+                TokenType::BlankLine => Trivia::BlankLine(token.token_position),
+                _ => {
+                    input.push(token);
+
+                    continue;
+                }
+            };
+
+            trivia.push((input.len(), Some(item)));
+        }
+
         return Self {
-            program,
             input,
             position: -1,
-            current_token: None
+            current_token: None,
+            trivia,
+            stack
+        };
+    }
+
+    fn advance(&mut self) -> () {
+        if self.position + 1 < self.input.len() as i32 {
+            self.position += 1;
+        }
+
+        self.current_token = self.input.get(self.position as usize).cloned();
+    }
+
+    // Move back to an earlier position, used when a speculative parse does not pan out.
+    fn rewind(&mut self, position: i32) -> () {
+        self.position = position;
+        self.current_token = self.input.get(self.position as usize).cloned();
+    }
+
+    fn peek(&self) -> &Token {
+        return self.current_token.as_ref().unwrap();
+    }
+
+    fn peek_ahead(&self, amount: i32) -> &Token {
+        let index: usize = ((self.position + amount) as usize).min(self.input.len() - 1);
+
+        return &self.input[index];
+    }
+
+    fn check(&self, token_type: TokenType) -> bool {
+        return self.peek().token_type == token_type;
+    }
+
+    // Move past the current token if it is of the given type, returning whether it was.
+    fn match_token(&mut self, token_type: TokenType) -> bool {
+        if self.check(token_type) {
+            self.advance();
+
+            return true;
+        }
+
+        return false;
+    }
+
+    // Move past the current token, failing with the given message if it is not of the given type.
+    fn expect(&mut self, token_type: TokenType, message: &str) -> CompileResult<Token> {
+        if !self.check(token_type) {
+            let found: String = self.peek().token_type.to_string();
+
+            return Err((
+                message.to_owned() + " Found " + &found + " instead.",
+                self.peek().token_position.to_owned()
+            ));
+        }
+
+        let token: Token = self.peek().to_owned();
+        self.advance();
+
+        return Ok(token);
+    }
+
+    // Take the trivia after the given token and before the current one which has not been taken
+    // yet, which is everything in a body up to here apart from what its own bodies took.
+    fn take_trivia(&mut self, after: i32) -> Vec<Trivia> {
+        let start: usize = self.trivia.partition_point(|(next, _)| *next as i32 <= after);
+        let end: usize = self.trivia.partition_point(|(next, _)| *next as i32 <= self.position);
+
+        return self.trivia[start..end].iter_mut()
+            .filter_map(|(_, trivia)| trivia.take())
+            .collect();
+    }
+
+    fn take_trivia_statements(&mut self, after: i32, statements: &mut Vec<Statement>) -> () {
+        statements.extend(self.take_trivia(after).into_iter().map(Statement::Trivia));
+    }
+
+    pub fn parse(&mut self) -> CompileResult<Vec<Statement>> {
+        let mut statements: Vec<Statement> = Vec::new();
+
+        // Go to the first token.
+        self.advance();
+
+        // Imports and public declarations can only be at the top level of a file.
+        loop {
+            self.take_trivia_statements(-1, &mut statements);
+
+            if self.check(TokenType::EOF) {
+                break;
+            }
+
+            if self.check(TokenType::Import) {
+                statements.push(Statement::Import(self.parse_import()?));
+            } else if self.is_test_declaration() {
+                statements.push(Statement::Test(self.parse_test_declaration()?));
+            } else if self.check(TokenType::Pub) {
+                statements.push(self.parse_public_declaration()?);
+            } else {
+                statements.push(self.parse_declaration()?);
+            }
+        }
+
+        return Ok(statements);
+    }
+
+    fn parse_import(&mut self) -> CompileResult<ImportStatement> {
+        self.advance(); // Move past the `import` keyword.
+
+        let token: Token = self.peek().to_owned();
+        let (path, name): (String, Token) = match token.token_type {
+            // `import "path/to/file.rbt";`, which is used through the name of the file.
+            TokenType::String => {
+                self.advance();
+
+                let path: String = token.value();
+                let stem: String = Path::new(&path).file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+
+                if !is_identifier(&stem) {
+                    return Err((
+                        "The file name `".to_owned() + &stem +
+                            "` cannot be used as the name of a module.",
+                        token.token_position
+                    ));
+                }
+
+                (path, Token::new(TokenType::Identifier, Some(stem), token.token_position))
+            },
+            // `import path.to.file;`, which is used through its last part.
+            TokenType::Identifier => {
+                let qualified: Token = self.parse_qualified_name("Expected a module name.")?;
+                let parts: Vec<String> = qualified.value().split('.').map(String::from).collect();
+                let path: String = parts.join("/") + "." + SOURCE_EXTENSION;
+
+                (path, Token::new(TokenType::Identifier, parts.last().cloned(),
+                                  qualified.token_position))
+            },
+            _ => return Err((
+                "Expected a module name or path after `import`.".to_owned(),
+                token.token_position
+            ))
+        };
+
+        self.expect(TokenType::Semicolon, "Expected `;` after import.")?;
+
+        return Ok(ImportStatement {
+            path,
+            name
+        });
+    }
+
+    // `test` is only a keyword when it is followed by the name of a test, so that it can still be
+    // used as a name.
+    fn is_test_declaration(&self) -> bool {
+        return self.check(TokenType::Identifier) && self.peek().value() == "test"
+            && self.peek_ahead(1).token_type == TokenType::String;
+    }
+
+    fn parse_test_declaration(&mut self) -> CompileResult<TestStatement> {
+        self.advance(); // Move past `test`.
+
+        let name: Token = self.peek().to_owned();
+
+        self.advance();
+
+        return Ok(TestStatement {
+            name,
+            body: self.parse_block()?
+        });
+    }
+
+    // Parse a declaration preceded by `pub`, which makes it visible to other modules.
+    fn parse_public_declaration(&mut self) -> CompileResult<Statement> {
+        let position: Position = self.peek().token_position;
+
+        self.advance(); // Move past the `pub` keyword.
+
+        if self.is_test_declaration() {
+            return Err(("Tests cannot be public.".to_owned(), position));
+        }
+
+        let mut statement: Statement = self.parse_declaration()?;
+
+        match &mut statement {
+            Statement::VariableDeclaration(declaration) => declaration.public = true,
+            Statement::FunctionDeclaration(declaration) => declaration.public = true,
+            Statement::ClassDeclaration(declaration) => declaration.public = true,
+            Statement::InterfaceDeclaration(declaration) => declaration.public = true,
+            Statement::EnumDeclaration(declaration) => declaration.public = true,
+            _ => return Err((
+                "Only declarations can be public.".to_owned(),
+                position
+            ))
+        }
+
+        return Ok(statement);
+    }
+
+    // Parse a name which may be qualified by the modules it is in, such as `math.Vector`, as a
+    // single token.
+    fn parse_qualified_name(&mut self, message: &str) -> CompileResult<Token> {
+        let mut name: Token = self.expect(TokenType::Identifier, message)?;
+
+        while self.check(TokenType::Period)
+            && self.peek_ahead(1).token_type == TokenType::Identifier {
+            self.advance(); // Move past the `.`.
+
+            name = qualify(&name, &self.peek().to_owned());
+            self.advance();
+        }
+
+        return Ok(name);
+    }
+
+    fn parse_declaration(&mut self) -> CompileResult<Statement> {
+        self.stack.check(self.peek().token_position)?;
+
+        if self.check(TokenType::Class) {
+            return Ok(Statement::ClassDeclaration(self.parse_class_declaration()?));
+        }
+
+        if self.check(TokenType::Interface) {
+            return Ok(Statement::InterfaceDeclaration(self.parse_interface_declaration()?));
+        }
+
+        if self.check(TokenType::Enum) {
+            return Ok(Statement::EnumDeclaration(self.parse_enum_declaration()?));
+        }
+
+        // Declarations start with a type followed by a name, e.g. `int x` or `map<K, V> f(`.
+        let start: i32 = self.position.to_owned();
+
+        if let Some(declared_type) = self.try_parse_type_annotation()? {
+            if self.check(TokenType::Identifier) {
+                if self.is_function_name() {
+                    return Ok(Statement::FunctionDeclaration(
+                        self.parse_function_declaration(declared_type)?
+                    ));
+                }
+
+                return Ok(Statement::VariableDeclaration(
+                    self.parse_variable_declaration(declared_type)?
+                ));
+            }
+        }
+
+        self.rewind(start);
+
+        return self.parse_statement();
+    }
+
+    // Attempt to parse a type annotation at the current position. Returns `None` (leaving the
+    // position wherever it stopped) if the tokens do not form a type.
+    fn try_parse_type_annotation(&mut self) -> CompileResult<Option<TypeAnnotation>> {
+        self.stack.check(self.peek().token_position)?;
+
+        if !self.check(TokenType::Identifier) {
+            return Ok(None);
+        }
+
+        let name: Token = self.parse_qualified_name("Expected a type.")?;
+        let mut arguments: Vec<TypeAnnotation> = Vec::new();
+
+        if self.match_token(TokenType::LessThan) {
+            loop {
+                match self.try_parse_type_annotation()? {
+                    Some(argument) => arguments.push(argument),
+                    None => return Ok(None)
+                }
+
+                if !self.match_token(TokenType::Comma) {
+                    break;
+                }
+            }
+
+            if !self.match_token(TokenType::GreaterThan) {
+                return Ok(None);
+            }
+        }
+
+        let nullable: bool = self.match_token(TokenType::QuestionMark);
+
+        return Ok(Some(TypeAnnotation {
+            name,
+            arguments,
+            nullable
+        }));
+    }
+
+    fn parse_type_annotation(&mut self) -> CompileResult<TypeAnnotation> {
+        let position: Position = self.peek().token_position.to_owned();
+
+        return match self.try_parse_type_annotation()? {
+            Some(type_annotation) => Ok(type_annotation),
+            None => Err(("Expected a type.".to_owned(), position))
         };
     }
+
+    fn parse_variable_declaration(&mut self, variable_type: TypeAnnotation)
+        -> CompileResult<VariableDeclarationStatement> {
+        let name: Token = self.expect(TokenType::Identifier, "Expected a variable name.")?;
+        let mut initialization: Option<Expression> = None;
+
+        if self.match_token(TokenType::Assign) {
+            initialization = Some(self.parse_expression()?);
+        }
+
+        self.expect(TokenType::Semicolon, "Expected `;` after variable declaration.")?;
+
+        return Ok(VariableDeclarationStatement {
+            variable_type,
+            identifier: IdentifierExpression { token: name },
+            initialization,
+            public: false
+        });
+    }
+
+    fn parse_parameters(&mut self) -> CompileResult<Vec<ParameterDeclaration>> {
+        let mut parameters: Vec<ParameterDeclaration> = Vec::new();
+
+        self.expect(TokenType::LeftParenthesis, "Expected `(` before parameters.")?;
+
+        if !self.check(TokenType::RightParenthesis) {
+            loop {
+                let parameter_type: TypeAnnotation = self.parse_type_annotation()?;
+                let name: Token = self.expect(TokenType::Identifier,
+                                              "Expected a parameter name.")?;
+
+                parameters.push(ParameterDeclaration {
+                    parameter_type,
+                    name
+                });
+
+                if !self.match_token(TokenType::Comma) {
+                    break;
+                }
+            }
+        }
+
+        self.expect(TokenType::RightParenthesis, "Expected `)` after parameters.")?;
+
+        return Ok(parameters);
+    }
+
+    // Whether the name at the current position is followed by parameters or type parameters,
+    // making it the name of a function rather than a variable.
+    fn is_function_name(&self) -> bool {
+        return matches!(self.peek_ahead(1).token_type,
+                        TokenType::LeftParenthesis | TokenType::LessThan);
+    }
+
+    // Parse the type parameters after the name of a generic function or class, if there are any.
+    fn parse_type_parameters(&mut self) -> CompileResult<Vec<TypeParameterDeclaration>> {
+        let mut type_parameters: Vec<TypeParameterDeclaration> = Vec::new();
+
+        if !self.match_token(TokenType::LessThan) {
+            return Ok(type_parameters);
+        }
+
+        loop {
+            let name: Token = self.expect(TokenType::Identifier,
+                                          "Expected a type parameter name.")?;
+            let mut bound: Option<TypeAnnotation> = None;
+
+            if self.match_token(TokenType::Extends) {
+                bound = Some(self.parse_type_annotation()?);
+            }
+
+            type_parameters.push(TypeParameterDeclaration {
+                name,
+                bound
+            });
+
+            if !self.match_token(TokenType::Comma) {
+                break;
+            }
+        }
+
+        self.expect(TokenType::GreaterThan, "Expected `>` after type parameters.")?;
+
+        return Ok(type_parameters);
+    }
+
+    fn parse_function_declaration(&mut self, return_type: TypeAnnotation)
+        -> CompileResult<FunctionDeclarationStatement> {
+        let name: Token = self.expect(TokenType::Identifier, "Expected a function name.")?;
+        let type_parameters: Vec<TypeParameterDeclaration> = self.parse_type_parameters()?;
+        let parameters: Vec<ParameterDeclaration> = self.parse_parameters()?;
+        let body: Vec<Statement> = self.parse_block()?;
+
+        return Ok(FunctionDeclarationStatement {
+            return_type,
+            name: IdentifierExpression { token: name },
+            type_parameters,
+            parameters,
+            body,
+            public: false
+        });
+    }
+
+    fn parse_class_declaration(&mut self) -> CompileResult<ClassDeclarationStatement> {
+        self.advance(); // Move past the `class` keyword.
+
+        let name: Token = self.expect(TokenType::Identifier, "Expected a class name.")?;
+        let type_parameters: Vec<TypeParameterDeclaration> = self.parse_type_parameters()?;
+        let mut superclass: Option<IdentifierExpression> = None;
+        let mut superclass_arguments: Vec<TypeAnnotation> = Vec::new();
+        let mut properties: Vec<VariableDeclarationStatement> = Vec::new();
+        let mut constructor: Option<FunctionDeclarationStatement> = None;
+        let mut methods: Vec<FunctionDeclarationStatement> = Vec::new();
+
+        if self.match_token(TokenType::Extends) {
+            let superclass_name: Token = self.parse_qualified_name("Expected a superclass name.")?;
+            superclass = Some(IdentifierExpression { token: superclass_name });
+
+            if self.match_token(TokenType::LessThan) {
+                loop {
+                    superclass_arguments.push(self.parse_type_annotation()?);
+
+                    if !self.match_token(TokenType::Comma) {
+                        break;
+                    }
+                }
+
+                self.expect(TokenType::GreaterThan, "Expected `>` after type arguments.")?;
+            }
+        }
+
+        let mut interfaces: Vec<TypeAnnotation> = Vec::new();
+
+        if self.match_token(TokenType::Implements) {
+            loop {
+                interfaces.push(self.parse_type_annotation()?);
+
+                if !self.match_token(TokenType::Comma) {
+                    break;
+                }
+            }
+        }
+
+        let body: i32 = self.position;
+        let mut trivia: Vec<Trivia> = Vec::new();
+
+        self.expect(TokenType::LeftBrace, "Expected `{` before class body.")?;
+
+        while !self.check(TokenType::RightBrace) && !self.check(TokenType::EOF) {
+            trivia.extend(self.take_trivia(body));
+
+            if self.check(TokenType::Identifier) && self.peek().value() == name.value()
+                && self.peek_ahead(1).token_type == TokenType::LeftParenthesis {
+                // Constructor.
+                let constructor_name: Token = self.peek().to_owned();
+
+                if constructor.is_some() {
+                    return Err((
+                        "A class can only have a single constructor.".to_owned(),
+                        constructor_name.token_position.to_owned()
+                    ));
+                }
+
+                // Constructors do not return anything.
+                let return_type: TypeAnnotation = TypeAnnotation {
+                    name: Token::new(
+                        TokenType::Identifier,
+                        Some("void".to_owned()),
+                        constructor_name.token_position.to_owned()
+                    ),
+                    arguments: Vec::new(),
+                    nullable: false
+                };
+
+                constructor = Some(self.parse_function_declaration(return_type)?);
+
+                continue;
+            }
+
+            let member_type: TypeAnnotation = self.parse_type_annotation()?;
+
+            if self.is_function_name() {
+                methods.push(self.parse_function_declaration(member_type)?);
+            } else {
+                properties.push(self.parse_variable_declaration(member_type)?);
+            }
+        }
+
+        trivia.extend(self.take_trivia(body));
+        self.expect(TokenType::RightBrace, "Expected `}` after class body.")?;
+
+        return Ok(ClassDeclarationStatement {
+            name: IdentifierExpression { token: name },
+            type_parameters,
+            superclass,
+            superclass_arguments,
+            interfaces,
+            properties,
+            constructor,
+            methods,
+            public: false,
+            trivia
+        });
+    }
+
+    fn parse_interface_declaration(&mut self) -> CompileResult<InterfaceDeclarationStatement> {
+        self.advance(); // Move past the `interface` keyword.
+
+        let name: Token = self.expect(TokenType::Identifier, "Expected an interface name.")?;
+        let type_parameters: Vec<TypeParameterDeclaration> = self.parse_type_parameters()?;
+        let mut methods: Vec<FunctionDeclarationStatement> = Vec::new();
+        let body: i32 = self.position;
+        let mut trivia: Vec<Trivia> = Vec::new();
+
+        self.expect(TokenType::LeftBrace, "Expected `{` before interface body.")?;
+
+        while !self.check(TokenType::RightBrace) && !self.check(TokenType::EOF) {
+            trivia.extend(self.take_trivia(body));
+
+            // Interface methods only have a signature, which ends with a `;`.
+            let return_type: TypeAnnotation = self.parse_type_annotation()?;
+            let method_name: Token = self.expect(TokenType::Identifier,
+                                                 "Expected a method name.")?;
+            let method_type_parameters: Vec<TypeParameterDeclaration> =
+                self.parse_type_parameters()?;
+            let parameters: Vec<ParameterDeclaration> = self.parse_parameters()?;
+
+            self.expect(TokenType::Semicolon, "Expected `;` after interface method.")?;
+
+            methods.push(FunctionDeclarationStatement {
+                return_type,
+                name: IdentifierExpression { token: method_name },
+                type_parameters: method_type_parameters,
+                parameters,
+                body: Vec::new(),
+                public: false
+            });
+        }
+
+        trivia.extend(self.take_trivia(body));
+        self.expect(TokenType::RightBrace, "Expected `}` after interface body.")?;
+
+        return Ok(InterfaceDeclarationStatement {
+            name: IdentifierExpression { token: name },
+            type_parameters,
+            methods,
+            public: false,
+            trivia
+        });
+    }
+
+    fn parse_enum_declaration(&mut self) -> CompileResult<EnumDeclarationStatement> {
+        self.advance(); // Move past the `enum` keyword.
+
+        let name: Token = self.expect(TokenType::Identifier, "Expected an enum name.")?;
+        let mut variants: Vec<EnumVariantDeclaration> = Vec::new();
+        let body: i32 = self.position;
+        let mut trivia: Vec<Trivia> = Vec::new();
+
+        self.expect(TokenType::LeftBrace, "Expected `{` before enum variants.")?;
+
+        while !self.check(TokenType::RightBrace) {
+            trivia.extend(self.take_trivia(body));
+
+            let variant_name: Token = self.expect(TokenType::Identifier,
+                                                  "Expected a variant name.")?;
+            let mut fields: Vec<ParameterDeclaration> = Vec::new();
+
+            if self.check(TokenType::LeftParenthesis) {
+                fields = self.parse_parameters()?;
+            }
+
+            variants.push(EnumVariantDeclaration {
+                name: variant_name,
+                fields
+            });
+
+            if !self.match_token(TokenType::Comma) {
+                break;
+            }
+        }
+
+        trivia.extend(self.take_trivia(body));
+        self.expect(TokenType::RightBrace, "Expected `}` after enum variants.")?;
+
+        return Ok(EnumDeclarationStatement {
+            name: IdentifierExpression { token: name },
+            variants,
+            public: false,
+            trivia
+        });
+    }
+
+    fn parse_block(&mut self) -> CompileResult<Vec<Statement>> {
+        let mut statements: Vec<Statement> = Vec::new();
+        let body: i32 = self.position;
+
+        self.expect(TokenType::LeftBrace, "Expected `{` before block.")?;
+
+        while !self.check(TokenType::RightBrace) && !self.check(TokenType::EOF) {
+            self.take_trivia_statements(body, &mut statements);
+            statements.push(self.parse_declaration()?);
+        }
+
+        self.take_trivia_statements(body, &mut statements);
+        self.expect(TokenType::RightBrace, "Expected `}` after block.")?;
+
+        return Ok(statements);
+    }
+
+    fn parse_statement(&mut self) -> CompileResult<Statement> {
+        let position: Position = self.peek().token_position.to_owned();
+
+        return Ok(match self.peek().token_type {
+            TokenType::LeftBrace => Statement::Block(BlockStatement {
+                statements: self.parse_block()?,
+                position
+            }),
+            TokenType::Import => return Err((
+                "Imports can only be at the top level of a file.".to_owned(),
+                position
+            )),
+            TokenType::Pub => return Err((
+                "Only declarations at the top level of a file can be public.".to_owned(),
+                position
+            )),
+            _ if self.is_test_declaration() => return Err((
+                "Tests can only be declared at the top level of a file.".to_owned(),
+                position
+            )),
+            TokenType::If => Statement::If(self.parse_if_statement()?),
+            TokenType::While => {
+                self.advance(); // Move past the `while` keyword.
+
+                let condition: Expression = self.parse_expression()?;
+                let body: Vec<Statement> = self.parse_block()?;
+
+                Statement::While(WhileStatement {
+                    condition,
+                    body
+                })
+            },
+            TokenType::Loop => {
+                self.advance(); // Move past the `loop` keyword.
+
+                Statement::Loop(LoopStatement {
+                    body: self.parse_block()?,
+                    position
+                })
+            },
+            TokenType::For => self.parse_for_statement()?,
+            TokenType::Match => {
+                let expression: Expression = self.parse_match()?;
+
+                // Like other block statements, a match on its own does not need a semicolon.
+                self.match_token(TokenType::Semicolon);
+
+                Statement::Expression(ExpressionStatement { expression })
+            },
+            TokenType::Break => {
+                self.advance(); // Move past the `break` keyword.
+                self.expect(TokenType::Semicolon, "Expected `;` after `break`.")?;
+
+                Statement::Break(BreakStatement { position })
+            },
+            TokenType::Continue => {
+                self.advance(); // Move past the `continue` keyword.
+                self.expect(TokenType::Semicolon, "Expected `;` after `continue`.")?;
+
+                Statement::Continue(ContinueStatement { position })
+            },
+            TokenType::Try => Statement::Try(self.parse_try_statement()?),
+            TokenType::Throw => {
+                self.advance(); // Move past the `throw` keyword.
+
+                let value: Expression = self.parse_expression()?;
+
+                self.expect(TokenType::Semicolon, "Expected `;` after thrown error.")?;
+
+                Statement::Throw(ThrowStatement {
+                    value,
+                    position
+                })
+            },
+            TokenType::Return => {
+                self.advance(); // Move past the `return` keyword.
+
+                let mut value: Option<Expression> = None;
+
+                if !self.check(TokenType::Semicolon) {
+                    value = Some(self.parse_expression()?);
+                }
+
+                self.expect(TokenType::Semicolon, "Expected `;` after return value.")?;
+
+                Statement::Return(ReturnStatement {
+                    value,
+                    position
+                })
+            },
+            _ => {
+                let expression: Expression = self.parse_expression()?;
+
+                self.expect(TokenType::Semicolon, "Expected `;` after expression.")?;
+
+                Statement::Expression(ExpressionStatement { expression })
+            }
+        });
+    }
+
+    fn parse_if_statement(&mut self) -> CompileResult<IfStatement> {
+        self.advance(); // Move past the `if` keyword.
+
+        let condition: Expression = self.parse_expression()?;
+        let then_branch: Vec<Statement> = self.parse_block()?;
+        let mut else_branch: Vec<Statement> = Vec::new();
+
+        if self.match_token(TokenType::Else) {
+            if self.check(TokenType::If) {
+                else_branch.push(Statement::If(self.parse_if_statement()?));
+            } else {
+                else_branch = self.parse_block()?;
+            }
+        }
+
+        return Ok(IfStatement {
+            condition,
+            then_branch,
+            else_branch
+        });
+    }
+
+    fn parse_try_statement(&mut self) -> CompileResult<TryStatement> {
+        let position: Position = self.peek().token_position.to_owned();
+
+        self.advance(); // Move past the `try` keyword.
+
+        let body: Vec<Statement> = self.parse_block()?;
+        let mut catches: Vec<CatchClause> = Vec::new();
+        let mut finally: Option<Vec<Statement>> = None;
+
+        while self.match_token(TokenType::Catch) {
+            self.expect(TokenType::LeftParenthesis, "Expected `(` after `catch`.")?;
+
+            let variable: Token = self.expect(TokenType::Identifier,
+                                              "Expected an error variable name.")?;
+            let mut error_type: Option<TypeAnnotation> = None;
+
+            if self.match_token(TokenType::Colon) {
+                error_type = Some(self.parse_type_annotation()?);
+            }
+
+            self.expect(TokenType::RightParenthesis, "Expected `)` after error variable.")?;
+
+            catches.push(CatchClause {
+                variable,
+                error_type,
+                body: self.parse_block()?
+            });
+        }
+
+        if self.match_token(TokenType::Finally) {
+            finally = Some(self.parse_block()?);
+        }
+
+        if catches.is_empty() && finally.is_none() {
+            return Err(("Expected `catch` or `finally` after `try` block.".to_owned(),
+                                self.peek().token_position.to_owned()));
+        }
+
+        return Ok(TryStatement {
+            body,
+            catches,
+            finally,
+            position
+        });
+    }
+
+    fn parse_for_statement(&mut self) -> CompileResult<Statement> {
+        self.advance(); // Move past the `for` keyword.
+
+        // `for item in items`.
+        if self.check(TokenType::Identifier) {
+            let variable: Token = self.peek().to_owned();
+
+            self.advance(); // Move past the loop variable.
+
+            return Ok(Statement::ForIn(self.parse_for_in_statement(vec![variable])?));
+        }
+
+        self.expect(TokenType::LeftParenthesis, "Expected `(` or a loop variable after `for`.")?;
+
+        // `for (key, value) in map`.
+        if self.check(TokenType::Identifier)
+            && self.peek_ahead(1).token_type == TokenType::Comma {
+            let mut variables: Vec<Token> = Vec::new();
+
+            loop {
+                variables.push(self.expect(TokenType::Identifier,
+                                           "Expected a loop variable name.")?);
+
+                if !self.match_token(TokenType::Comma) {
+                    break;
+                }
+            }
+
+            self.expect(TokenType::RightParenthesis, "Expected `)` after loop variables.")?;
+
+            return Ok(Statement::ForIn(self.parse_for_in_statement(variables)?));
+        }
+
+        // `for (int i = 0; i < 10; i++)`.
+        let variable_type: TypeAnnotation = self.parse_type_annotation()?;
+        let variable: Token = self.expect(TokenType::Identifier,
+                                          "Expected a loop variable name.")?;
+
+        self.expect(TokenType::Assign, "Expected `=` after loop variable.")?;
+
+        let initializer: Expression = self.parse_expression()?;
+
+        self.expect(TokenType::Semicolon, "Expected `;` after loop initializer.")?;
+
+        let condition: Expression = self.parse_expression()?;
+
+        self.expect(TokenType::Semicolon, "Expected `;` after loop condition.")?;
+
+        let increment: Expression = self.parse_expression()?;
+
+        self.expect(TokenType::RightParenthesis, "Expected `)` after loop increment.")?;
+
+        let body: Vec<Statement> = self.parse_block()?;
+
+        return Ok(Statement::For(ForStatement {
+            variable,
+            variable_type,
+            initializer,
+            condition,
+            increment,
+            body
+        }));
+    }
+
+    fn parse_for_in_statement(&mut self, variables: Vec<Token>) -> CompileResult<ForInStatement> {
+        self.expect(TokenType::In, "Expected `in` after loop variables.")?;
+
+        let iterable: Expression = self.parse_expression()?;
+        let body: Vec<Statement> = self.parse_block()?;
+
+        return Ok(ForInStatement {
+            variables,
+            iterable,
+            body
+        });
+    }
+
+    fn parse_expression(&mut self) -> CompileResult<Expression> {
+        return self.parse_assignment();
+    }
+
+    fn parse_assignment(&mut self) -> CompileResult<Expression> {
+        let target: Expression = self.parse_coalesce()?;
+        let position: Position = self.peek().token_position.to_owned();
+
+        let operator: Option<BinaryOperator> = match self.peek().token_type {
+            TokenType::Assign => None,
+            TokenType::PositionalAdd => Some(BinaryOperator::Add),
+            TokenType::PositionalSubtract => Some(BinaryOperator::Subtract),
+            TokenType::PositionalMultiply => Some(BinaryOperator::Multiply),
+            TokenType::PositionalDivide => Some(BinaryOperator::Divide),
+            TokenType::PositionalRemainder => Some(BinaryOperator::Remainder),
+            TokenType::PositionalPower => Some(BinaryOperator::Power),
+            _ => return Ok(target)
+        };
+
+        self.advance(); // Move past the assignment operator.
+
+        // Assignment is right-associative, so `a = b = c` assigns `c` to both.
+        let value: Expression = self.parse_assignment()?;
+
+        return self.make_assignment(target, operator, value, position);
+    }
+
+    fn make_assignment(&self, target: Expression, operator: Option<BinaryOperator>,
+                       value: Expression, position: Position) -> CompileResult<Expression> {
+        match target {
+            Expression::Identifier(_) => {},
+            Expression::Property(ref property) if !property.safe => {},
+            _ => return Err(("Invalid assignment target.".to_owned(), target.position()))
+        }
+
+        return Ok(Expression::Assignment(AssignmentExpression {
+            target: Box::new(target),
+            operator,
+            value: Box::new(value),
+            position
+        }));
+    }
+
+    // Parse a left-associative chain of binary operations, where the operands are parsed by the
+    // given function.
+    fn parse_binary(&mut self, operators: &[(TokenType, BinaryOperator)],
+                    operand: fn(&mut Self) -> CompileResult<Expression>)
+        -> CompileResult<Expression> {
+        let mut left: Expression = operand(self)?;
+
+        'outer: loop {
+            for (token_type, operator) in operators {
+                if self.check(token_type.to_owned()) {
+                    let position: Position = self.peek().token_position.to_owned();
+
+                    self.advance(); // Move past the operator.
+
+                    let right: Expression = operand(self)?;
+
+                    left = Expression::Binary(BinaryExpression {
+                        operator: operator.to_owned(),
+                        left: Box::new(left),
+                        right: Box::new(right),
+                        position
+                    });
+
+                    continue 'outer;
+                }
+            }
+
+            return Ok(left);
+        }
+    }
+
+    fn parse_coalesce(&mut self) -> CompileResult<Expression> {
+        let left: Expression = self.parse_or()?;
+
+        if !self.check(TokenType::DoubleQuestionMark) {
+            return Ok(left);
+        }
+
+        let position: Position = self.peek().token_position.to_owned();
+
+        self.advance(); // Move past the `??`.
+
+        // `??` is right-associative, so `a ?? b ?? c` tries `b` before `c`.
+        let right: Expression = self.parse_coalesce()?;
+
+        return Ok(Expression::Binary(BinaryExpression {
+            operator: BinaryOperator::Coalesce,
+            left: Box::new(left),
+            right: Box::new(right),
+            position
+        }));
+    }
+
+    fn parse_or(&mut self) -> CompileResult<Expression> {
+        return self.parse_binary(&[(TokenType::Or, BinaryOperator::Or)], Self::parse_and);
+    }
+
+    fn parse_and(&mut self) -> CompileResult<Expression> {
+        return self.parse_binary(&[(TokenType::And, BinaryOperator::And)], Self::parse_equality);
+    }
+
+    fn parse_equality(&mut self) -> CompileResult<Expression> {
+        return self.parse_binary(&[
+            (TokenType::Equal, BinaryOperator::Equal),
+            (TokenType::NotEqual, BinaryOperator::NotEqual)
+        ], Self::parse_comparison);
+    }
+
+    fn parse_comparison(&mut self) -> CompileResult<Expression> {
+        let mut expression: Expression = self.parse_binary(&[
+            (TokenType::LessThan, BinaryOperator::LessThan),
+            (TokenType::LessThanOrEqual, BinaryOperator::LessThanOrEqual),
+            (TokenType::GreaterThan, BinaryOperator::GreaterThan),
+            (TokenType::GreaterThanOrEqual, BinaryOperator::GreaterThanOrEqual)
+        ], Self::parse_range)?;
+
+        while self.match_token(TokenType::Is) {
+            let class_name: Token = self.parse_qualified_name("Expected a class name after `is`.")?;
+
+            expression = Expression::Is(IsExpression {
+                value: Box::new(expression),
+                class_name
+            });
+        }
+
+        return Ok(expression);
+    }
+
+    fn parse_range(&mut self) -> CompileResult<Expression> {
+        let start: Expression = self.parse_term()?;
+
+        if self.check(TokenType::DoublePeriod) {
+            let position: Position = self.peek().token_position.to_owned();
+
+            self.advance(); // Move past the `..`.
+
+            return Ok(Expression::Range(RangeExpression {
+                start: Box::new(start),
+                end: Box::new(self.parse_term()?),
+                position
+            }));
+        }
+
+        return Ok(start);
+    }
+
+    fn parse_term(&mut self) -> CompileResult<Expression> {
+        return self.parse_binary(&[
+            (TokenType::Plus, BinaryOperator::Add),
+            (TokenType::Minus, BinaryOperator::Subtract)
+        ], Self::parse_factor);
+    }
+
+    fn parse_factor(&mut self) -> CompileResult<Expression> {
+        return self.parse_binary(&[
+            (TokenType::Star, BinaryOperator::Multiply),
+            (TokenType::ForwardSlash, BinaryOperator::Divide),
+            (TokenType::Remainder, BinaryOperator::Remainder)
+        ], Self::parse_unary);
+    }
+
+    fn parse_unary(&mut self) -> CompileResult<Expression> {
+        let position: Position = self.peek().token_position.to_owned();
+
+        // Every expression is parsed through here, however deeply it is nested.
+        self.stack.check(position)?;
+
+        let operator: UnaryOperator = match self.peek().token_type {
+            TokenType::Minus => UnaryOperator::Negate,
+            TokenType::ExclamationMark => UnaryOperator::Not,
+            _ => return self.parse_power()
+        };
+
+        self.advance(); // Move past the operator.
+
+        return Ok(Expression::Unary(UnaryExpression {
+            operator,
+            operand: Box::new(self.parse_unary()?),
+            position
+        }));
+    }
+
+    fn parse_power(&mut self) -> CompileResult<Expression> {
+        let base: Expression = self.parse_postfix()?;
+
+        if self.check(TokenType::Power) {
+            let position: Position = self.peek().token_position.to_owned();
+
+            self.advance(); // Move past the `^`.
+
+            // Powers are right-associative and bind tighter than a unary operator on their left,
+            // so `-2 ^ 2 ^ 3` is `-(2 ^ (2 ^ 3))`.
+            return Ok(Expression::Binary(BinaryExpression {
+                operator: BinaryOperator::Power,
+                left: Box::new(base),
+                right: Box::new(self.parse_unary()?),
+                position
+            }));
+        }
+
+        return Ok(base);
+    }
+
+    fn parse_postfix(&mut self) -> CompileResult<Expression> {
+        let mut expression: Expression = self.parse_primary()?;
+
+        loop {
+            if self.check(TokenType::LeftParenthesis) {
+                let position: Position = expression.position();
+                let arguments: Vec<Expression> = self.parse_arguments()?;
+
+                expression = Expression::FunctionCall(FunctionCallExpression {
+                    callee: Box::new(expression),
+                    arguments,
+                    position
+                });
+            } else if self.check(TokenType::Period) || self.check(TokenType::QuestionPeriod) {
+                let safe: bool = self.check(TokenType::QuestionPeriod);
+
+                self.advance(); // Move past the `.` or `?.`.
+
+                let name: Token = self.expect(TokenType::Identifier,
+                                              "Expected a property name after `.`.")?;
+
+                expression = Expression::Property(PropertyExpression {
+                    object: Box::new(expression),
+                    name,
+                    safe
+                });
+            } else if self.check(TokenType::Increment) || self.check(TokenType::Decrement) {
+                let position: Position = self.peek().token_position.to_owned();
+                let operator: BinaryOperator = if self.check(TokenType::Increment) {
+                    BinaryOperator::Add
+                } else {
+                    BinaryOperator::Subtract
+                };
+
+                self.advance(); // Move past the `++` or `--`.
+
+                // `x++` is shorthand for `x += 1`.
+                let one: Expression = Expression::Literal(LiteralExpression {
+                    value: LiteralValue::Integer(1),
+                    position
+                });
+
+                expression = self.make_assignment(expression, Some(operator), one, position)?;
+            } else if self.check(TokenType::QuestionMark) {
+                let position: Position = self.peek().token_position.to_owned();
+
+                self.advance(); // Move past the `?`.
+
+                expression = Expression::Propagate(PropagateExpression {
+                    value: Box::new(expression),
+                    position
+                });
+            } else {
+                return Ok(expression);
+            }
+        }
+    }
+
+    fn parse_arguments(&mut self) -> CompileResult<Vec<Expression>> {
+        let mut arguments: Vec<Expression> = Vec::new();
+
+        self.advance(); // Move past the `(`.
+
+        if !self.check(TokenType::RightParenthesis) {
+            loop {
+                arguments.push(self.parse_expression()?);
+
+                if !self.match_token(TokenType::Comma) {
+                    break;
+                }
+            }
+        }
+
+        self.expect(TokenType::RightParenthesis, "Expected `)` after arguments.")?;
+
+        return Ok(arguments);
+    }
+
+    fn parse_primary(&mut self) -> CompileResult<Expression> {
+        let token: Token = self.peek().to_owned();
+        let position: Position = token.token_position.to_owned();
+
+        let literal: LiteralValue = match token.token_type {
+            TokenType::Integer => match token.value().parse::<i64>() {
+                Ok(value) => LiteralValue::Integer(value),
+                Err(_) => return Err(("Integer literal is too large.".to_owned(),
+                                              position))
+            },
+            TokenType::Float => LiteralValue::Float(token.value().parse::<f64>().unwrap()),
+            TokenType::String => LiteralValue::String(token.value()),
+            TokenType::Char => LiteralValue::Char(token.value().chars().next().unwrap()),
+            TokenType::Boolean => LiteralValue::Boolean(token.value() == "true"),
+            TokenType::Null => LiteralValue::Null,
+            TokenType::Identifier => {
+                self.advance(); // Move past the identifier.
+
+                return Ok(Expression::Identifier(IdentifierExpression { token }));
+            },
+            TokenType::This => {
+                self.advance(); // Move past the `this` keyword.
+
+                return Ok(Expression::This(ThisExpression { position }));
+            },
+            TokenType::Super => {
+                self.advance(); // Move past the `super` keyword.
+                self.expect(TokenType::Period, "Expected `.` after `super`.")?;
+
+                let method: Token = self.expect(TokenType::Identifier,
+                                                "Expected a method name after `super.`.")?;
+
+                return Ok(Expression::Super(SuperExpression { method }));
+            },
+            TokenType::LeftParenthesis => {
+                self.advance(); // Move past the `(`.
+
+                let expression: Expression = self.parse_expression()?;
+
+                self.expect(TokenType::RightParenthesis, "Expected `)` after expression.")?;
+
+                return Ok(expression);
+            },
+            TokenType::LeftBrace => return self.parse_map(),
+            TokenType::LeftBracket => return self.parse_list(),
+            TokenType::Match => return self.parse_match(),
+            _ => return Err((
+                "Expected an expression. Found ".to_owned() + &token.token_type.to_string() +
+                    " instead.",
+                position
+            ))
+        };
+
+        self.advance(); // Move past the literal.
+
+        return Ok(Expression::Literal(LiteralExpression {
+            value: literal,
+            position
+        }));
+    }
+
+    // Map literals, such as `{"a": 1, "b": 2}`.
+    fn parse_map(&mut self) -> CompileResult<Expression> {
+        let position: Position = self.peek().token_position.to_owned();
+        let mut entries: Vec<(Expression, Expression)> = Vec::new();
+
+        self.advance(); // Move past the `{`.
+
+        while !self.check(TokenType::RightBrace) {
+            let key: Expression = self.parse_expression()?;
+
+            self.expect(TokenType::Colon, "Expected `:` after map key.")?;
+
+            let value: Expression = self.parse_expression()?;
+
+            entries.push((key, value));
+
+            if !self.match_token(TokenType::Comma) {
+                break;
+            }
+        }
+
+        self.expect(TokenType::RightBrace, "Expected `}` after map entries.")?;
+
+        return Ok(Expression::Map(MapExpression {
+            entries,
+            position
+        }));
+    }
+
+    // List literals, such as `[1, 2, 3]`.
+    fn parse_list(&mut self) -> CompileResult<Expression> {
+        let position: Position = self.peek().token_position.to_owned();
+        let mut elements: Vec<Expression> = Vec::new();
+
+        self.advance(); // Move past the `[`.
+
+        while !self.check(TokenType::RightBracket) {
+            elements.push(self.parse_expression()?);
+
+            if !self.match_token(TokenType::Comma) {
+                break;
+            }
+        }
+
+        self.expect(TokenType::RightBracket, "Expected `]` after list elements.")?;
+
+        return Ok(Expression::List(ListExpression {
+            elements,
+            position
+        }));
+    }
+
+    fn parse_match(&mut self) -> CompileResult<Expression> {
+        let position: Position = self.peek().token_position.to_owned();
+        let mut arms: Vec<MatchArm> = Vec::new();
+
+        self.advance(); // Move past the `match` keyword.
+
+        let value: Expression = self.parse_expression()?;
+        let body: i32 = self.position;
+        let mut trivia: Vec<Trivia> = Vec::new();
+
+        self.expect(TokenType::LeftBrace, "Expected `{` before match arms.")?;
+
+        while !self.check(TokenType::RightBrace) && !self.check(TokenType::EOF) {
+            trivia.extend(self.take_trivia(body));
+
+            let pattern: Pattern = self.parse_pattern()?;
+            let mut guard: Option<Expression> = None;
+
+            if self.match_token(TokenType::If) {
+                guard = Some(self.parse_expression()?);
+            }
+
+            self.expect(TokenType::Arrow, "Expected `=>` after match pattern.")?;
+
+            let body: MatchArmBody = if self.check(TokenType::LeftBrace) {
+                let block: Vec<Statement> = self.parse_block()?;
+
+                self.match_token(TokenType::Comma);
+
+                MatchArmBody::Block(block)
+            } else {
+                let expression: Expression = self.parse_expression()?;
+
+                if !self.check(TokenType::RightBrace) {
+                    self.expect(TokenType::Comma, "Expected `,` after match arm.")?;
+                }
+
+                MatchArmBody::Expression(expression)
+            };
+
+            arms.push(MatchArm {
+                pattern,
+                guard,
+                body
+            });
+        }
+
+        trivia.extend(self.take_trivia(body));
+        self.expect(TokenType::RightBrace, "Expected `}` after match arms.")?;
+
+        return Ok(Expression::Match(MatchExpression {
+            value: Box::new(value),
+            arms,
+            position,
+            trivia
+        }));
+    }
+
+    fn parse_pattern(&mut self) -> CompileResult<Pattern> {
+        let token: Token = self.peek().to_owned();
+        let position: Position = token.token_position.to_owned();
+
+        self.stack.check(position)?;
+
+        match token.token_type {
+            TokenType::Integer | TokenType::Float | TokenType::String | TokenType::Char |
+            TokenType::Boolean | TokenType::Null => {
+                if let Expression::Literal(literal) = self.parse_primary()? {
+                    return Ok(Pattern::Literal(literal));
+                }
+
+                unreachable!();
+            },
+            TokenType::Minus => {
+                self.advance(); // Move past the `-`.
+
+                // Negative number literals.
+                if let Expression::Literal(literal) = self.parse_primary()? {
+                    match literal.value {
+                        LiteralValue::Integer(value) => {
+                            return Ok(Pattern::Literal(LiteralExpression {
+                                value: LiteralValue::Integer(-value),
+                                position
+                            }));
+                        },
+                        LiteralValue::Float(value) => {
+                            return Ok(Pattern::Literal(LiteralExpression {
+                                value: LiteralValue::Float(-value),
+                                position
+                            }));
+                        },
+                        _ => {}
+                    }
+                }
+
+                return Err(("Expected a number after `-` in pattern.".to_owned(),
+                                    position));
+            },
+            TokenType::Identifier => {
+                self.advance(); // Move past the identifier.
+
+                if token.value() == "_" {
+                    return Ok(Pattern::Wildcard(position));
+                }
+
+                if !self.match_token(TokenType::Period) {
+                    return Ok(Pattern::Binding(token));
+                }
+
+                let mut enum_name: Token = token;
+                let mut variant: Token = self.expect(TokenType::Identifier,
+                                                     "Expected a variant name after `.`.")?;
+                let mut fields: Vec<Pattern> = Vec::new();
+
+                // The enum of another module, as in `shapes.Shape.Circle`.
+                if self.match_token(TokenType::Period) {
+                    enum_name = qualify(&enum_name, &variant);
+                    variant = self.expect(TokenType::Identifier,
+                                          "Expected a variant name after `.`.")?;
+                }
+
+                if self.match_token(TokenType::LeftParenthesis) {
+                    while !self.check(TokenType::RightParenthesis) {
+                        fields.push(self.parse_pattern()?);
+
+                        if !self.match_token(TokenType::Comma) {
+                            break;
+                        }
+                    }
+
+                    self.expect(TokenType::RightParenthesis,
+                                "Expected `)` after variant patterns.")?;
+                }
+
+                return Ok(Pattern::Variant(VariantPattern {
+                    enum_name,
+                    variant,
+                    fields
+                }));
+            },
+            _ => return Err((
+                "Expected a pattern. Found ".to_owned() + &token.token_type.to_string() +
+                    " instead.",
+                position
+            ))
+        }
+    }
 }
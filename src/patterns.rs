@@ -0,0 +1,58 @@
+use crate::expressions::LiteralExpression;
+use crate::position::Position;
+use crate::token::Token;
+use crate::value::Value;
+
+// The patterns used by the arms of a `match` expression.
+#[derive(PartialEq, Clone)]
+pub enum Pattern {
+    Literal(LiteralExpression),
+    Binding(Token), // Matches anything, binding it to a variable.
+    Wildcard(Position), // `_`, which matches anything without binding it.
+    Variant(VariantPattern)
+}
+
+impl Pattern {
+    pub fn position(&self) -> Position {
+        return match self {
+            Pattern::Literal(literal) => literal.position,
+            Pattern::Binding(name) => name.token_position,
+            Pattern::Wildcard(position) => *position,
+            Pattern::Variant(variant) => variant.variant.token_position
+        };
+    }
+
+    // Whether the pattern matches every value, whatever its shape.
+    pub fn is_irrefutable(&self) -> bool {
+        return matches!(self, Pattern::Binding(_) | Pattern::Wildcard(_));
+    }
+}
+
+// `Shape.Circle(radius)`, or `Shape.Empty` for variants without a payload.
+#[derive(PartialEq, Clone)]
+pub struct VariantPattern {
+    pub enum_name: Token,
+    pub variant: Token,
+    pub fields: Vec<Pattern>
+}
+
+// Check whether a value matches a pattern, adding any variables it binds to the bindings.
+pub fn match_pattern(pattern: &Pattern, value: &Value, bindings: &mut Vec<(String, Value)>)
+    -> bool {
+    return match pattern {
+        Pattern::Literal(literal) => Value::from_literal(&literal.value) == *value,
+        Pattern::Binding(name) => {
+            bindings.push((name.value(), value.to_owned()));
+
+            true
+        },
+        Pattern::Wildcard(_) => true,
+        Pattern::Variant(variant_pattern) => match value {
+            Value::Enum(enum_value) if enum_value.variant == variant_pattern.variant.value() => {
+                variant_pattern.fields.iter().zip(enum_value.fields.iter())
+                    .all(|(field_pattern, field)| match_pattern(field_pattern, field, bindings))
+            },
+            _ => false
+        }
+    };
+}
@@ -1,5 +1,6 @@
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Position {
+    pub(crate) file: usize, // The index of the file in its program; the first file is 0.
     pub(crate) line: i32,
     pub(crate) start: i32,
     pub(crate) end: i32
@@ -8,6 +9,7 @@ pub struct Position {
 impl Position {
     pub fn new(line: i32, start: i32, end: i32) -> Self {
         return Self {
+            file: 0,
             line,
             start,
             end
@@ -17,6 +19,13 @@ impl Position {
     pub fn new_single(line: i32, position: i32) -> Self {
         return Position::new(line, position, position);
     }
+
+    pub fn in_file(self, file: usize) -> Self {
+        return Self {
+            file,
+            ..self
+        };
+    }
 }
 
 impl ToString for Position {
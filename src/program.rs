@@ -1,75 +1,369 @@
 use std::process;
+use crate::bytecode::CompiledProgram;
+use crate::interpreter::Interpreter;
+use crate::formatter::Formatter;
 use crate::keyword::Keyword;
 use crate::lexer::Lexer;
+use crate::modules::ModuleLoader;
+use crate::natives::{HostFunction, HostType, HostValue};
+use crate::optimizer::Optimizer;
 use crate::parser::Parser;
 use crate::position::Position;
+use crate::testing::is_test_function;
 use crate::token::Token;
+use crate::type_checker::TypeChecker;
+use crate::vm::VM;
+
+// The exit codes of programs which stop because of an error in their source, and because of an
+// error while they run.
+pub const ERROR_EXIT_CODE: i32 = 1;
+pub const RUNTIME_ERROR_EXIT_CODE: i32 = 70;
+
+// The most calls shown under a runtime error. Deep recursion would otherwise bury the error.
+const MAX_TRACE_LENGTH: usize = 10;
+
+// How much of the stack, in bytes, programs may use unless told otherwise. It is well within the
+// 2 MiB Rust gives new threads, so that embedding programs need not know how deeply the programs
+// they run recurse, nor how much stack each call takes in their build.
+pub const DEFAULT_STACK_LIMIT: usize = 1024 * 1024;
+
+// A file a program is made of, named by its path.
+#[derive(Clone)]
+pub struct SourceFile {
+    pub name: String,
+    pub source: String
+}
 
 pub struct Program {
     keywords: Vec<Keyword>,
     tokens: Vec<Token>,
-    source: String
+    // The files of the program, starting with the one it was started with, which has no name when
+    // it was not read from a file. The others are the modules it imports.
+    files: Vec<SourceFile>,
+    arguments: Vec<String>, // The arguments given to the program on the command line.
+    print_errors: bool, // Whether errors and warnings are printed when they are found.
+    // What a program embedding the language gives its scripts.
+    host_functions: Vec<HostFunction>,
+    host_values: Vec<HostValue>,
+    host_types: Vec<HostType>,
+    stack_limit: usize // How much of the stack, in bytes, the program may use while running.
+}
+
+// A call which was running when a runtime error happened: the function called, and where it was
+// called from.
+#[derive(Clone)]
+pub struct StackFrame {
+    pub function: String,
+    pub position: Position
+}
+
+// An error which stopped a program while it was running, along with the calls it happened in,
+// starting with the innermost one.
+#[derive(Clone)]
+pub struct RuntimeError {
+    pub message: String,
+    pub position: Position,
+    pub trace: Vec<StackFrame>
+}
+
+// Why a program stopped before reaching its end: an error while it was running, or a call to
+// `exit` with its exit code.
+pub enum Stop {
+    Error(RuntimeError),
+    Exit(i32)
+}
+
+// The result of lexing, parsing, loading or checking a program: what it gave, or the message of
+// the error found and where it was found.
+pub type CompileResult<T> = Result<T, (String, Position)>;
+
+// How much of the stack a pass over a program has used since it started. Passes recurse into
+// nested statements and expressions, so source nested deeply enough would otherwise overflow the
+// stack of whatever runs them instead of being reported.
+#[derive(Clone, Copy)]
+pub struct StackGuard {
+    start: usize,
+    limit: usize
+}
+
+impl StackGuard {
+    pub fn new(limit: usize) -> Self {
+        return Self {
+            start: stack_address(),
+            limit
+        };
+    }
+
+    pub fn check(&self, position: Position) -> CompileResult<()> {
+        if self.start.abs_diff(stack_address()) >= self.limit {
+            return Err(("Code is nested too deeply.".to_owned(), position));
+        }
+
+        return Ok(());
+    }
+}
+
+// The address of a variable on the stack, which tells how far the stack has grown between two
+// points, whichever way it grows.
+pub fn stack_address() -> usize {
+    let marker: u8 = 0;
+
+    return &marker as *const u8 as usize;
 }
 
 impl Program {
     pub fn new(keywords: Vec<Keyword>, source: String) -> Self {
+        return Self::from_files(keywords, vec![SourceFile {
+            name: String::new(),
+            source
+        }]);
+    }
+
+    pub fn from_files(keywords: Vec<Keyword>, files: Vec<SourceFile>) -> Self {
         let tokens: Vec<Token> = Vec::new();
 
         return Self {
             keywords,
             tokens,
-            source
+            files,
+            arguments: Vec::new(),
+            print_errors: true,
+            host_functions: Vec::new(),
+            host_values: Vec::new(),
+            host_types: Vec::new(),
+            stack_limit: DEFAULT_STACK_LIMIT
         };
     }
 
-    pub fn die_with_error(&self, message: String, position: Position) {
-        let source: String = self.source.to_owned();
+    // Name the file the program was started with by its path, which its imports are relative to.
+    pub fn set_path(&mut self, path: String) -> () {
+        self.files[0].name = path;
+    }
+
+    // Add a file to the program, returning its index.
+    pub fn add_file(&mut self, name: String, source: String) -> usize {
+        self.files.push(SourceFile {
+            name,
+            source
+        });
+
+        return self.files.len() - 1;
+    }
+
+    pub fn files(&self) -> &[SourceFile] {
+        return &self.files;
+    }
+
+    // Render a message about a position in the source, such as an error, followed by the line it
+    // is on with the position underlined.
+    pub fn render(&self, kind: &str, message: String, position: Position) -> String {
+        return kind.to_owned() + &self.locate(&position) + ": " + &message + "\n" +
+            &self.render_line(position);
+    }
+
+    // Where a position is, as in " on line 3", naming its file unless it is the one the program
+    // started with, which goes without saying.
+    fn locate(&self, position: &Position) -> String {
+        let mut location: String = String::new();
+
+        if position.file > 0 {
+            location += &(" in ".to_owned() + &self.files[position.file].name);
+        }
+
+        location += &(" on line ".to_owned() + &position.line.to_string());
+
+        return location;
+    }
+
+    // The line a position is on, with the position underlined.
+    fn render_line(&self, position: Position) -> String {
+        let source: String = self.files[position.file].source.to_owned();
         let line: i32 = position.to_owned().line;
         let lines: Vec<String> = source.to_owned().lines().map(|s| s.to_owned()).collect();
         let mut underline_amount: i32 = position.to_owned().end - position.to_owned().start;
 
-        if underline_amount == 0 {
+        if underline_amount <= 0 {
             underline_amount = 1;
-        } else if underline_amount == 1 {
-            underline_amount = 2;
         }
 
-        dbg!(position.to_string());
+        // Errors at the very end of the input can point just past the last line.
+        let source_line: String = lines.get((line - 1) as usize).cloned().unwrap_or_default();
+
+        let mut rendered: String = String::new();
+        rendered += &(" ".to_owned() + &line.to_owned().to_string() + " | " + &source_line);
+        rendered += "\n ";
+        rendered += &(" ".repeat(line.to_owned().to_string().chars().count()) + "   ");
+        rendered += &" ".repeat(position.to_owned().start as usize - 1);
+        rendered += &("^".repeat(underline_amount as usize) + " <- Here");
+
+        return rendered;
+    }
+
+    // Render a runtime error, followed by the calls it happened in, each with the line it was
+    // called on.
+    pub fn render_runtime_error(&self, error: &RuntimeError) -> String {
+        let mut rendered: String = self.render("Error", error.message.to_owned(),
+                                               error.position.to_owned());
+        // Tests are called by the test runner rather than by the program, so the calls to them
+        // are left out.
+        let trace: Vec<&StackFrame> = error.trace.iter()
+            .take_while(|frame| !is_test_function(&frame.function))
+            .collect();
+
+        for frame in trace.iter().take(MAX_TRACE_LENGTH) {
+            rendered += &("\nIn `".to_owned() + &frame.function + "`, called" +
+                &self.locate(&frame.position) + ":\n" +
+                &self.render_line(frame.position.to_owned()));
+        }
+
+        if trace.len() > MAX_TRACE_LENGTH {
+            rendered += &("\n... and ".to_owned() +
+                &(trace.len() - MAX_TRACE_LENGTH).to_string() + " more calls.");
+        }
 
-        // Create the error message.
-        let mut error_message: String = String::new();
-        error_message += &("Error on line ".to_owned() + &line.to_owned().to_string());
-        error_message += &(": ".to_owned() + message.as_str());
-        error_message += &("\n ".to_owned() + &line.to_owned().to_string() + " | " + lines.get(
-            line as usize).unwrap());
-        error_message += "\n ";
-        error_message += &(" ".repeat(line.to_owned().to_string().chars().count()) + "   ");
-        error_message += &" ".repeat(position.to_owned().start as usize - 1);
-        error_message += &("^".repeat(underline_amount as usize) + " <- Here");
+        return rendered;
+    }
 
+    pub fn die_with_error(&self, message: String, position: Position) -> ! {
         // Print the message.
-        eprintln!("{}", error_message);
+        self.report_error(message, position);
 
-        // Exit with the error code 1.
-        process::exit(1);
+        process::exit(ERROR_EXIT_CODE);
     }
 
-    pub fn create_lexer(&mut self) -> Lexer {
+    // Stop because of an error while the program was running, rather than one found before it
+    // started, which the exit code tells apart.
+    pub fn die_with_runtime_error(&self, error: RuntimeError) -> ! {
+        self.report_runtime_error(&error);
+
+        process::exit(RUNTIME_ERROR_EXIT_CODE);
+    }
+
+    // Print an error without stopping.
+    pub fn report_error(&self, message: String, position: Position) -> () {
+        if !self.print_errors {
+            return;
+        }
+
+        eprintln!("{}", self.render("Error", message, position));
+    }
+
+    pub fn report_runtime_error(&self, error: &RuntimeError) -> () {
+        if !self.print_errors {
+            return;
+        }
+
+        eprintln!("{}", self.render_runtime_error(error));
+    }
+
+    // Keep errors and warnings quiet, for tools which show them some other way.
+    pub fn set_print_errors(&mut self, print_errors: bool) -> () {
+        self.print_errors = print_errors;
+    }
+
+    // Print a warning, which does not stop the program.
+    pub fn warn(&self, message: String, position: Position) -> () {
+        if !self.print_errors {
+            return;
+        }
+
+        eprintln!("{}", self.render("Warning", message, position));
+    }
+
+    pub fn create_lexer(&self) -> Lexer {
+        return self.create_file_lexer(0);
+    }
+
+    pub fn create_file_lexer(&self, file: usize) -> Lexer {
+        let source: String = self.files[file].source.to_owned();
+        let keywords: Vec<Keyword> = self.keywords.clone();
+
         return Lexer::new(
-            self,
-            self.source.to_owned(),
-            self.keywords.clone()
+            source,
+            keywords,
+            file
         );
     }
 
+    // Let the program use more of the stack, when it runs on a thread with a larger one.
+    pub fn set_stack_limit(&mut self, stack_limit: usize) -> () {
+        self.stack_limit = stack_limit;
+    }
+
+    pub fn stack_limit(&self) -> usize {
+        return self.stack_limit;
+    }
+
+    // A guard for a pass over the program starting here.
+    pub fn stack_guard(&self) -> StackGuard {
+        return StackGuard::new(self.stack_limit);
+    }
+
+    pub fn set_arguments(&mut self, arguments: Vec<String>) -> () {
+        self.arguments = arguments;
+    }
+
+    pub fn arguments(&self) -> Vec<String> {
+        return self.arguments.to_owned();
+    }
+
+    pub fn set_host(&mut self, functions: Vec<HostFunction>, values: Vec<HostValue>,
+                    types: Vec<HostType>) -> () {
+        self.host_functions = functions;
+        self.host_values = values;
+        self.host_types = types;
+    }
+
+    pub fn find_host_function(&self, name: &str) -> Option<&HostFunction> {
+        return self.host_functions.iter().find(|function| function.name == name);
+    }
+
+    pub fn host_values(&self) -> &[HostValue] {
+        return &self.host_values;
+    }
+
+    pub fn host_types(&self) -> &[HostType] {
+        return &self.host_types;
+    }
+
+    pub fn find_host_method(&self, type_name: &str, name: &str) -> Option<&HostFunction> {
+        return self.host_types.iter()
+            .find(|host_type| host_type.name == type_name)?
+            .methods.iter()
+            .find(|method| method.name == name);
+    }
+
     pub fn set_tokens(&mut self, tokens: Vec<Token>) -> () {
         self.tokens = tokens;
     }
 
-    pub fn create_parser(&mut self) -> Parser {
-        return Parser::new(
-            self,
-            self.tokens.clone()
-        );
+    pub fn create_parser(&self) -> Parser {
+        let tokens: Vec<Token> = self.tokens.clone();
+
+        return Parser::new(tokens, self.stack_guard());
     }
-}
\ No newline at end of file
+
+    pub fn create_module_loader(&mut self) -> ModuleLoader<'_> {
+        return ModuleLoader::new(self);
+    }
+
+    pub fn create_type_checker(&mut self) -> TypeChecker<'_> {
+        return TypeChecker::new(self);
+    }
+
+    pub fn create_optimizer(&self) -> Optimizer {
+        return Optimizer::new(self);
+    }
+
+    pub fn create_interpreter(&mut self) -> Interpreter<'_> {
+        return Interpreter::new(self);
+    }
+
+    pub fn create_formatter(&self) -> Formatter<'_> {
+        return Formatter::new(self);
+    }
+
+    pub fn create_vm(&mut self, compiled: CompiledProgram) -> VM<'_> {
+        return VM::new(self, compiled);
+    }
+}
@@ -0,0 +1,505 @@
+use std::iter;
+use crate::bytecode::{ClassProto, CompiledProgram, Constant, FunctionProto, Instruction};
+use crate::natives::natives;
+use crate::operations::builtin_method_arity;
+use crate::position::Position;
+use crate::program::SourceFile;
+
+// The `.rbc` file format, which stores a compiled program so that it can be run without lexing,
+// parsing or checking it again. The source code of each file of the program is stored alongside the
+// bytecode so that runtime errors can still point at it.
+//
+// A file starts with the magic bytes and the format version, followed by the source files,
+// constants, functions, classes, globals and the index of the main function. Numbers are
+// little-endian, and strings and lists are prefixed with their length.
+
+const MAGIC: &[u8; 4] = b"RBC\0";
+// Bumped whenever the layout of the file or the meaning of an instruction changes.
+pub const VERSION: u16 = 8;
+
+const INTEGER_TAG: u8 = 0;
+const FLOAT_TAG: u8 = 1;
+const STRING_TAG: u8 = 2;
+const CHAR_TAG: u8 = 3;
+const BOOLEAN_TAG: u8 = 4;
+
+struct Writer {
+    bytes: Vec<u8>
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) -> () {
+        self.bytes.push(value);
+    }
+
+    fn u32(&mut self, value: usize) -> () {
+        self.bytes.extend((value as u32).to_le_bytes());
+    }
+
+    fn i32(&mut self, value: i32) -> () {
+        self.bytes.extend(value.to_le_bytes());
+    }
+
+    fn string(&mut self, value: &str) -> () {
+        self.u32(value.len());
+        self.bytes.extend(value.as_bytes());
+    }
+
+    fn constant(&mut self, constant: &Constant) -> () {
+        match constant {
+            Constant::Integer(value) => {
+                self.u8(INTEGER_TAG);
+                self.bytes.extend(value.to_le_bytes());
+            },
+            Constant::Float(value) => {
+                self.u8(FLOAT_TAG);
+                self.bytes.extend(value.to_bits().to_le_bytes());
+            },
+            Constant::String(value) => {
+                self.u8(STRING_TAG);
+                self.string(value);
+            },
+            Constant::Char(value) => {
+                self.u8(CHAR_TAG);
+                self.bytes.extend((*value as u32).to_le_bytes());
+            },
+            Constant::Boolean(value) => {
+                self.u8(BOOLEAN_TAG);
+                self.u8(*value as u8);
+            }
+        }
+    }
+
+    fn function(&mut self, function: &FunctionProto) -> () {
+        self.string(&function.name);
+        self.u32(function.arity);
+        self.u32(function.local_count);
+        self.u32(function.code.len());
+
+        for (instruction, position) in function.code.iter().zip(&function.positions) {
+            let (opcode, operands) = instruction.encode();
+
+            self.u8(opcode);
+
+            for operand in operands {
+                self.u32(operand);
+            }
+
+            self.u32(position.file);
+            self.i32(position.line);
+            self.i32(position.start);
+            self.i32(position.end);
+        }
+    }
+
+    fn class(&mut self, class: &ClassProto) -> () {
+        self.string(&class.name);
+        self.optional(class.superclass);
+        self.u32(class.initializer);
+        self.optional(class.constructor);
+        self.u32(class.methods.len());
+
+        for (name, method) in &class.methods {
+            self.string(name);
+            self.u32(*method);
+        }
+    }
+
+    fn optional(&mut self, value: Option<usize>) -> () {
+        match value {
+            Some(value) => {
+                self.u8(1);
+                self.u32(value);
+            },
+            None => self.u8(0)
+        }
+    }
+}
+
+pub fn write(program: &CompiledProgram, files: &[SourceFile]) -> Vec<u8> {
+    let mut writer: Writer = Writer {
+        bytes: Vec::new()
+    };
+
+    writer.bytes.extend(MAGIC);
+    writer.bytes.extend(VERSION.to_le_bytes());
+    writer.u32(files.len());
+
+    for file in files {
+        writer.string(&file.name);
+        writer.string(&file.source);
+    }
+
+    writer.u32(program.constants.len());
+
+    for constant in &program.constants {
+        writer.constant(constant);
+    }
+
+    writer.u32(program.functions.len());
+
+    for function in &program.functions {
+        writer.function(function);
+    }
+
+    writer.u32(program.classes.len());
+
+    for class in &program.classes {
+        writer.class(class);
+    }
+
+    writer.u32(program.globals.len());
+
+    for global in &program.globals {
+        writer.string(global);
+    }
+
+    writer.u32(program.main);
+
+    return writer.bytes;
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize
+}
+
+type Read<T> = Result<T, String>;
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, count: usize) -> Read<&'a [u8]> {
+        if self.bytes.len() - self.offset < count {
+            return Err("The file ends unexpectedly.".to_owned());
+        }
+
+        let bytes: &'a [u8] = &self.bytes[self.offset..self.offset + count];
+
+        self.offset += count;
+
+        return Ok(bytes);
+    }
+
+    fn u8(&mut self) -> Read<u8> {
+        return Ok(self.take(1)?[0]);
+    }
+
+    fn u32(&mut self) -> Read<usize> {
+        return Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()) as usize);
+    }
+
+    fn i32(&mut self) -> Read<i32> {
+        return Ok(i32::from_le_bytes(self.take(4)?.try_into().unwrap()));
+    }
+
+    fn u64(&mut self) -> Read<u64> {
+        return Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()));
+    }
+
+    fn string(&mut self) -> Read<String> {
+        let length: usize = self.u32()?;
+
+        return String::from_utf8(self.take(length)?.to_vec())
+            .map_err(|_| "A string is not valid UTF-8.".to_owned());
+    }
+
+    // Read a count of items, followed by the items themselves.
+    fn list<T>(&mut self, read: fn(&mut Self) -> Read<T>) -> Read<Vec<T>> {
+        let count: usize = self.u32()?;
+        let mut items: Vec<T> = Vec::new();
+
+        for _ in 0..count {
+            items.push(read(self)?);
+        }
+
+        return Ok(items);
+    }
+
+    fn constant(&mut self) -> Read<Constant> {
+        return Ok(match self.u8()? {
+            INTEGER_TAG => Constant::Integer(self.u64()? as i64),
+            FLOAT_TAG => Constant::Float(f64::from_bits(self.u64()?)),
+            STRING_TAG => Constant::String(self.string()?),
+            CHAR_TAG => match char::from_u32(self.u32()? as u32) {
+                Some(value) => Constant::Char(value),
+                None => return Err("A char constant is not a valid character.".to_owned())
+            },
+            BOOLEAN_TAG => Constant::Boolean(self.u8()? != 0),
+            tag => return Err("Unknown constant tag ".to_owned() + &tag.to_string() + ".")
+        });
+    }
+
+    fn function(&mut self) -> Read<FunctionProto> {
+        let name: String = self.string()?;
+        let arity: usize = self.u32()?;
+        let local_count: usize = self.u32()?;
+        let length: usize = self.u32()?;
+        let mut code: Vec<Instruction> = Vec::new();
+        let mut positions: Vec<Position> = Vec::new();
+
+        for _ in 0..length {
+            let opcode: u8 = self.u8()?;
+            let operand_count: usize = match Instruction::operand_count(opcode) {
+                Some(count) => count,
+                None => return Err("Unknown instruction ".to_owned() + &opcode.to_string() + ".")
+            };
+            let mut operands: Vec<usize> = Vec::new();
+
+            for _ in 0..operand_count {
+                operands.push(self.u32()?);
+            }
+
+            match Instruction::decode(opcode, &operands) {
+                Some(instruction) => code.push(instruction),
+                None => return Err("Invalid operand for an instruction.".to_owned())
+            }
+
+            let file: usize = self.u32()?;
+
+            positions.push(Position::new(self.i32()?, self.i32()?, self.i32()?).in_file(file));
+        }
+
+        return Ok(FunctionProto {
+            name,
+            arity,
+            local_count,
+            code,
+            positions
+        });
+    }
+
+    fn class(&mut self) -> Read<ClassProto> {
+        return Ok(ClassProto {
+            name: self.string()?,
+            superclass: self.optional()?,
+            initializer: self.u32()?,
+            constructor: self.optional()?,
+            methods: self.list(|reader| Ok((reader.string()?, reader.u32()?)))?
+        });
+    }
+
+    fn optional(&mut self) -> Read<Option<usize>> {
+        return Ok(match self.u8()? {
+            0 => None,
+            _ => Some(self.u32()?)
+        });
+    }
+}
+
+// Read a program written by `write`, along with its source files.
+pub fn read(bytes: &[u8]) -> Read<(CompiledProgram, Vec<SourceFile>)> {
+    let mut reader: Reader = Reader {
+        bytes,
+        offset: 0
+    };
+
+    if reader.take(MAGIC.len()).ok() != Some(MAGIC.as_slice()) {
+        return Err("This is not a compiled ribbit program.".to_owned());
+    }
+
+    let version: u16 = u16::from_le_bytes(reader.take(2)?.try_into().unwrap());
+
+    if version != VERSION {
+        return Err(
+            "This program was compiled for version ".to_owned() + &version.to_string() +
+                " of the bytecode format, but version " + &VERSION.to_string() +
+                " is required; compile it again."
+        );
+    }
+
+    let files: Vec<SourceFile> = reader.list(|reader| Ok(SourceFile {
+        name: reader.string()?,
+        source: reader.string()?
+    }))?;
+    let program: CompiledProgram = CompiledProgram {
+        constants: reader.list(Reader::constant)?,
+        functions: reader.list(Reader::function)?,
+        classes: reader.list(Reader::class)?,
+        globals: reader.list(Reader::string)?,
+        main: reader.u32()?
+    };
+
+    if reader.offset != bytes.len() {
+        return Err("There is unexpected data at the end of the file.".to_owned());
+    }
+
+    if files.is_empty() {
+        return Err("The program has no source files.".to_owned());
+    }
+
+    validate(&program, files.len())?;
+
+    return Ok((program, files));
+}
+
+// Make sure that everything the program refers to exists, and that each function is called with
+// the arguments it takes and finds the values it works with on the stack, so that a damaged file
+// cannot send the virtual machine out of bounds.
+fn validate(program: &CompiledProgram, file_count: usize) -> Read<()> {
+    let is_name = |index: usize| -> bool {
+        return matches!(program.constants.get(index), Some(Constant::String(_)));
+    };
+    let invalid = |function: &FunctionProto| -> Read<()> {
+        return Err("The function `".to_owned() + &function.name + "` is invalid.");
+    };
+    let functions: &Vec<FunctionProto> = &program.functions;
+    let takes = |function: usize, arity: usize| -> bool {
+        return functions.get(function).is_some_and(|function| function.arity == arity);
+    };
+    let classes: usize = program.classes.len();
+
+    for class in &program.classes {
+        let valid: bool = class.superclass.is_none_or(|superclass| superclass < classes)
+            && takes(class.initializer, 1)
+            && class.constructor.is_none_or(|constructor| {
+                functions.get(constructor).is_some_and(|constructor| constructor.arity >= 1)
+            })
+            && class.methods.iter().all(|(_, method)| {
+                functions.get(*method).is_some_and(|method| method.arity >= 1)
+            })
+            && superclasses(program, class).count() <= classes;
+
+        if !valid {
+            return Err("The class `".to_owned() + &class.name + "` is invalid.");
+        }
+    }
+
+    for function in functions {
+        if function.code.last() != Some(&Instruction::Return)
+            || function.positions.iter().any(|position| position.file >= file_count)
+            || function.local_count < function.arity
+            || function.local_count > function.arity.max(function.used_slots()) {
+            return invalid(function);
+        }
+
+        for instruction in &function.code {
+            let valid: bool = match *instruction {
+                Instruction::Constant(index) => index < program.constants.len(),
+                Instruction::GetLocal(slot) | Instruction::SetLocal(slot) => {
+                    slot < function.local_count
+                },
+                Instruction::GetGlobal(slot) | Instruction::SetGlobal(slot) => {
+                    slot < program.globals.len()
+                },
+                Instruction::GetField(name) | Instruction::SetField(name) | Instruction::Is(name) |
+                Instruction::IsVariant(name) => is_name(name),
+                Instruction::CallMethod(name, count) => match program.constants.get(name) {
+                    Some(Constant::String(name)) => {
+                        builtin_method_arity(name) == Some(count) || program.classes.iter()
+                            .flat_map(|class| &class.methods)
+                            .any(|(method, index)| method == name && takes(*index, count + 1))
+                    },
+                    _ => false
+                },
+                Instruction::MakeEnum(enum_name, variant, _) => {
+                    is_name(enum_name) && is_name(variant)
+                },
+                Instruction::Jump(target) | Instruction::JumpIfFalse(target) |
+                Instruction::JumpIfFalseOrPop(target) | Instruction::JumpIfTrueOrPop(target) |
+                Instruction::JumpIfNull(target) | Instruction::JumpIfNotNull(target) |
+                Instruction::JumpIfPresent(target) | Instruction::IterNext(target, _) |
+                Instruction::PushHandler(target) => {
+                    target < function.code.len()
+                },
+                Instruction::Call(index, count) => takes(index, count),
+                // Classes without a constructor of their own use the one they inherit, if any.
+                Instruction::New(index, count) => program.classes.get(index).is_some_and(|class| {
+                    superclasses(program, class)
+                        .find_map(|class| class.constructor)
+                        .map_or(count == 0, |constructor| takes(constructor, count + 1))
+                }),
+                Instruction::CallNative(index, count) => {
+                    natives().get(index).is_some_and(|native| native.accepts(count))
+                },
+                _ => true
+            };
+
+            if !valid {
+                return invalid(function);
+            }
+        }
+
+        if !check_stack(function) {
+            return invalid(function);
+        }
+    }
+
+    if !takes(program.main, 0) {
+        return Err("The program has no main function.".to_owned());
+    }
+
+    return Ok(());
+}
+
+// A class followed by its superclasses, stopping early should they go around in a circle.
+fn superclasses<'p>(program: &'p CompiledProgram, class: &'p ClassProto)
+    -> impl Iterator<Item = &'p ClassProto> {
+    return iter::successors(Some(class), |class| {
+        class.superclass.and_then(|superclass| program.classes.get(superclass))
+    }).take(program.classes.len() + 1);
+}
+
+// Follow every path through a function, making sure that each instruction finds the values it
+// takes on the stack, that loops are only advanced and ended while they are being iterated over,
+// and that paths meeting at an instruction agree on both.
+fn check_stack(function: &FunctionProto) -> bool {
+    // The height of the stack and the number of loops being iterated over before each instruction
+    // reached so far.
+    let mut states: Vec<Option<(usize, usize)>> = vec![None; function.code.len()];
+    let mut pending: Vec<(usize, (usize, usize))> = vec![(0, (0, 0))];
+
+    while let Some((ip, state)) = pending.pop() {
+        match states[ip] {
+            Some(seen) if seen == state => continue,
+            Some(_) => return false,
+            None => states[ip] = Some(state)
+        }
+
+        let (height, loops): (usize, usize) = state;
+        let instruction: Instruction = function.code[ip];
+
+        if height < instruction.stack_inputs() {
+            return false;
+        }
+
+        let next: usize = (height as isize + instruction.stack_effect()) as usize;
+
+        match instruction {
+            Instruction::Return | Instruction::Throw => {},
+            Instruction::Jump(target) => pending.push((target, state)),
+            Instruction::JumpIfFalse(target) => {
+                pending.push((target, (next, loops)));
+                pending.push((ip + 1, (next, loops)));
+            },
+            Instruction::JumpIfFalseOrPop(target) | Instruction::JumpIfTrueOrPop(target) |
+            Instruction::JumpIfNull(target) | Instruction::JumpIfNotNull(target) |
+            Instruction::JumpIfPresent(target) => {
+                pending.push((target, state));
+                pending.push((ip + 1, (next, loops)));
+            },
+            // An error caught by the handler leaves the stack as it was, with the error on top.
+            Instruction::PushHandler(target) => {
+                pending.push((target, (height + 1, loops)));
+                pending.push((ip + 1, state));
+            },
+            Instruction::IterStart => pending.push((ip + 1, (next, loops + 1))),
+            Instruction::IterNext(target, count) => {
+                if loops == 0 || !(1..=2).contains(&count) {
+                    return false;
+                }
+
+                pending.push((target, state));
+                pending.push((ip + 1, (next, loops)));
+            },
+            Instruction::IterEnd => {
+                if loops == 0 {
+                    return false;
+                }
+
+                pending.push((ip + 1, (next, loops - 1)));
+            },
+            _ => pending.push((ip + 1, (next, loops)))
+        }
+    }
+
+    return true;
+}
@@ -0,0 +1,280 @@
+use std::io::{self, BufRead, Write};
+use std::process;
+use crate::ast::describe_statements;
+use crate::commands::{parse, STACK_LIMIT};
+use crate::interpreter::Interpreter;
+use crate::keywords;
+use crate::lexer::Lexer;
+use crate::optimizer::Optimizer;
+use crate::parser::Parser;
+use crate::program::{CompileResult, Program, SourceFile, Stop};
+use crate::statements::Statement;
+use crate::token::Token;
+use crate::type_checker::TypeChecker;
+use crate::types::Type;
+
+const PROMPT: &str = "> ";
+const CONTINUATION_PROMPT: &str = ". ";
+
+const HELP: &str = "\
+Enter statements to run them, or an expression to see its value and type.
+  :type <expression>   Show the type of an expression without running it.
+  :ast <code>          Show the syntax tree of some code.
+  :tokens <code>       Show the tokens of some code.
+  :cancel              Discard an entry which is not finished.
+  :help                Show this message.
+  :quit                Leave the REPL.";
+
+// An entry which has been checked along with the entries before it.
+struct Analysis {
+    source: String, // The source of every entry so far, ending with this one.
+    files: Vec<SourceFile>, // That source, followed by the modules it imports.
+    statement_count: usize, // The number of statements in that source.
+    statements: Vec<Statement>, // The optimized statements of this entry.
+    result_type: Option<Type> // The type of the entry, if it ends with an expression.
+}
+
+// The interactive prompt, which runs each entry as if it were appended to the program made of the
+// entries before it. Every entry is checked along with those entries, so that it can use what
+// they declared, but only its own statements are run. Entries with errors are forgotten.
+pub struct Repl {
+    source: String, // The entries which have run without errors.
+    statement_count: usize
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        return Self {
+            source: String::new(),
+            statement_count: 0
+        };
+    }
+
+    pub fn start(&mut self) -> () {
+        // The interpreter is kept between entries, so that variables keep their values. It never
+        // reports errors itself, so its program does not need a source.
+        let mut session: Program = Program::new(keywords(), String::new());
+
+        session.set_stack_limit(STACK_LIMIT);
+
+        let mut interpreter: Interpreter = session.create_interpreter();
+        let stdin: io::Stdin = io::stdin();
+        let mut lines = stdin.lock().lines();
+
+        println!("Ribbit {} - type `:help` for help.", env!("CARGO_PKG_VERSION"));
+
+        loop {
+            // Keep reading lines until the entry is complete. Commands can be used while it is
+            // not, which leaves `:cancel` to give up on it.
+            let mut entry: String = String::new();
+
+            loop {
+                print!("{}", if entry.is_empty() { PROMPT } else { CONTINUATION_PROMPT });
+                io::stdout().flush().unwrap();
+
+                let line: String = match lines.next() {
+                    Some(Ok(line)) => line,
+                    _ => {
+                        if !entry.trim().is_empty() {
+                            eprintln!("The last entry was not finished, so it has not been run.");
+                        }
+
+                        return;
+                    }
+                };
+
+                if let Some(command) = line.trim().strip_prefix(':') {
+                    let (name, argument) = command.split_once(char::is_whitespace)
+                        .unwrap_or((command, ""));
+
+                    match name {
+                        "quit" | "q" => return,
+                        "cancel" => entry.clear(),
+                        _ => self.run_command(name, argument.trim())
+                    }
+
+                    continue;
+                }
+
+                entry += &(line + "\n");
+
+                if is_complete(&entry) {
+                    break;
+                }
+            }
+
+            let entry: &str = entry.trim();
+
+            if entry.is_empty() {
+                continue;
+            }
+
+            self.run_entry(&mut interpreter, entry);
+        }
+    }
+
+    fn run_command(&mut self, name: &str, argument: &str) -> () {
+        match name {
+            "help" => println!("{}", HELP),
+            "type" => self.show_type(argument),
+            "ast" => self.show_ast(argument),
+            "tokens" => self.show_tokens(argument),
+            _ => eprintln!("Unknown command `:{}`; type `:help` for help.", name)
+        }
+    }
+
+    fn run_entry(&mut self, interpreter: &mut Interpreter, entry: &str) -> () {
+        let analysis: Analysis = match self.analyze(entry) {
+            Some(analysis) => analysis,
+            None => return
+        };
+        let mut statements: Vec<Statement> = analysis.statements;
+
+        // An entry ending with an expression shows its value, unless it has no value.
+        let result: Option<(Statement, Type)> = match (statements.last(), analysis.result_type) {
+            (Some(Statement::Expression(_)), Some(result_type)) if result_type != Type::Void => {
+                Some((statements.pop().unwrap(), result_type))
+            },
+            _ => None
+        };
+        let mut outcome: Result<(), Stop> = interpreter.run(&statements);
+
+        if let (Ok(()), Some((Statement::Expression(expression_statement), result_type))) =
+            (&outcome, result) {
+            outcome = interpreter.evaluate_expression(&expression_statement.expression)
+                .map(|value| println!("{}: {}", value.to_repr(), result_type.to_string()));
+        }
+
+        match outcome {
+            Ok(()) => {
+                self.source = analysis.source;
+                self.statement_count = analysis.statement_count;
+            },
+            Err(Stop::Error(error)) => {
+                Program::from_files(keywords(), analysis.files).report_runtime_error(&error);
+            },
+            // Exiting ends the session along with the program.
+            Err(Stop::Exit(code)) => process::exit(code)
+        }
+    }
+
+    // Lex, parse, check and optimize an entry along with the entries before it, returning `None`
+    // if it has an error, which has already been reported.
+    fn analyze(&self, entry: &str) -> Option<Analysis> {
+        let source: String = self.source.to_owned() + &with_semicolon(entry) + "\n";
+        let mut program: Program = Program::new(keywords(), source.to_owned());
+        let analysis: CompileResult<Analysis> = self.analyze_program(&mut program, source);
+
+        return reported(&program, analysis);
+    }
+
+    fn analyze_program(&self, program: &mut Program, source: String) -> CompileResult<Analysis> {
+        let mut lexer: Lexer = program.create_lexer();
+        let tokens: Vec<Token> = lexer.lex()?;
+
+        program.set_tokens(tokens);
+
+        let mut parser: Parser = program.create_parser();
+        let statements: Vec<Statement> = parser.parse()?;
+        let statements: Vec<Statement> = program.create_module_loader().load(statements)?;
+
+        let mut type_checker: TypeChecker = program.create_type_checker();
+        let mut statements: Vec<Statement> = type_checker.check(statements)?;
+
+        let statement_count: usize = statements.len();
+        let new_statements: Vec<Statement> = statements.split_off(self.statement_count);
+        let result_type: Option<Type> = match new_statements.last() {
+            Some(Statement::Expression(expression_statement)) => {
+                Some(type_checker.expression_type(&expression_statement.expression)?)
+            },
+            _ => None
+        };
+
+        let files: Vec<SourceFile> = program.files().to_vec();
+        let mut optimizer: Optimizer = program.create_optimizer();
+
+        return Ok(Analysis {
+            source,
+            files,
+            statement_count,
+            statements: optimizer.optimize(new_statements)?,
+            result_type
+        });
+    }
+
+    fn show_type(&self, expression: &str) -> () {
+        if let Some(Analysis { result_type: Some(result_type), .. }) = self.analyze(expression) {
+            println!("{}", result_type.to_string());
+        }
+    }
+
+    fn show_ast(&self, code: &str) -> () {
+        let mut program: Program = Program::new(keywords(), with_semicolon(code));
+        let statements: CompileResult<Vec<Statement>> = parse(&mut program);
+
+        if let Some(statements) = reported(&program, statements) {
+            print!("{}", describe_statements(&statements));
+        }
+    }
+
+    fn show_tokens(&self, code: &str) -> () {
+        let program: Program = Program::new(keywords(), code.to_owned());
+        let tokens: CompileResult<Vec<Token>> = program.create_lexer().lex();
+
+        for token in reported(&program, tokens).unwrap_or_default() {
+            println!("{}", token.to_string());
+        }
+    }
+}
+
+// What lexing, parsing or checking an entry gave, or `None` once the error found is reported.
+fn reported<T>(program: &Program, result: CompileResult<T>) -> Option<T> {
+    return match result {
+        Ok(value) => Some(value),
+        Err((message, position)) => {
+            program.report_error(message, position);
+
+            None
+        }
+    };
+}
+
+// Entries can leave out the `;` at the end of their last statement.
+fn with_semicolon(entry: &str) -> String {
+    let entry: &str = entry.trim_end();
+
+    if entry.ends_with(';') || entry.ends_with('}') {
+        return entry.to_owned();
+    }
+
+    return entry.to_owned() + ";";
+}
+
+// Whether an entry is complete, rather than having brackets or a string which are still open.
+fn is_complete(entry: &str) -> bool {
+    let mut depth: i32 = 0;
+    let mut characters = entry.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        match character {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            '"' | '\'' => loop {
+                match characters.next() {
+                    Some('\\') => {
+                        characters.next();
+                    },
+                    Some(other) if other == character => break,
+                    Some(_) => {},
+                    None => return false
+                }
+            },
+            '/' if characters.peek() == Some(&'/') => {
+                while characters.next_if(|next| *next != '\n').is_some() {}
+            },
+            _ => {}
+        }
+    }
+
+    return depth <= 0;
+}
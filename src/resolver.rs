@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use crate::expressions::{Expression, MatchArmBody};
+use crate::patterns::Pattern;
+use crate::position::Position;
+use crate::statements::{FunctionDeclarationStatement, Statement};
+use crate::token::Token;
+
+// What a name was declared as.
+#[derive(Clone, Copy, PartialEq)]
+pub enum DeclarationKind {
+    Variable,
+    Parameter,
+    Function,
+    Class,
+    Interface,
+    Enum
+}
+
+impl ToString for DeclarationKind {
+    fn to_string(&self) -> String {
+        return match self {
+            DeclarationKind::Variable => String::from("Variable"),
+            DeclarationKind::Parameter => String::from("Parameter"),
+            DeclarationKind::Function => String::from("Function"),
+            DeclarationKind::Class => String::from("Class"),
+            DeclarationKind::Interface => String::from("Interface"),
+            DeclarationKind::Enum => String::from("Enum")
+        };
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Declaration {
+    pub kind: DeclarationKind,
+    pub position: Position // The position of the declared name.
+}
+
+// A variable which is in scope.
+struct Variable {
+    name: String,
+    declaration: Declaration,
+    used: bool
+}
+
+// What the resolver found out about the names in a program.
+pub struct Resolution {
+    // The declaration each identifier refers to, by the position of the identifier.
+    pub bindings: HashMap<Position, Declaration>,
+    pub warnings: Vec<(String, Position)>
+}
+
+// Binds every identifier in a type checked program to the declaration it refers to, following the
+// same scoping rules as the type checker. Undefined names and duplicate declarations are errors
+// reported by the type checker, so the resolver looks for code which is valid but suspicious:
+// variables which shadow others, and variables and parameters which are never used. Names starting
+// with `_` are never reported as unused.
+pub struct Resolver {
+    scopes: Vec<Vec<Variable>>, // The first scope holds the global variables.
+    // Functions, classes and enums, which can be used before they are declared.
+    items: HashMap<String, Declaration>,
+    resolution: Resolution
+}
+
+impl Resolver {
+    pub fn new() -> Self {
+        return Self {
+            scopes: Vec::new(),
+            items: HashMap::new(),
+            resolution: Resolution {
+                bindings: HashMap::new(),
+                warnings: Vec::new()
+            }
+        };
+    }
+
+    pub fn resolve(mut self, statements: &[Statement]) -> Resolution {
+        for statement in statements {
+            let (name, kind): (&Token, DeclarationKind) = match statement {
+                Statement::FunctionDeclaration(declaration) => {
+                    (&declaration.name.token, DeclarationKind::Function)
+                },
+                Statement::ClassDeclaration(declaration) => {
+                    (&declaration.name.token, DeclarationKind::Class)
+                },
+                Statement::InterfaceDeclaration(declaration) => {
+                    (&declaration.name.token, DeclarationKind::Interface)
+                },
+                Statement::EnumDeclaration(declaration) => {
+                    (&declaration.name.token, DeclarationKind::Enum)
+                },
+                _ => continue
+            };
+
+            self.items.insert(name.value(), Declaration {
+                kind,
+                position: name.token_position
+            });
+        }
+
+        self.push_scope();
+        self.resolve_statements(statements);
+        self.pop_scope();
+
+        self.resolution.warnings.sort_by_key(|(_, position)| (position.line, position.start));
+
+        return self.resolution;
+    }
+
+    fn warn(&mut self, message: String, position: Position) -> () {
+        self.resolution.warnings.push((message, position));
+    }
+
+    fn push_scope(&mut self) -> () {
+        self.scopes.push(Vec::new());
+    }
+
+    fn pop_scope(&mut self) -> () {
+        for variable in self.scopes.pop().unwrap() {
+            if !variable.used && !variable.name.starts_with('_') {
+                self.warn(
+                    variable.declaration.kind.to_string() + " `" + &variable.name +
+                        "` is never used.",
+                    variable.declaration.position
+                );
+            }
+        }
+    }
+
+    fn declare(&mut self, name: &Token, kind: DeclarationKind) -> () {
+        let name_value: String = name.value();
+        let shadowed: Option<Position> = self.scopes.iter().rev()
+            .flat_map(|scope| scope.iter().rev())
+            .find(|variable| variable.name == name_value)
+            .map(|variable| variable.declaration.position);
+
+        if let Some(shadowed) = shadowed {
+            self.warn(
+                "`".to_owned() + &name_value + "` shadows the variable declared on line " +
+                    &shadowed.line.to_string() + ".",
+                name.token_position
+            );
+        }
+
+        let declaration: Declaration = Declaration {
+            kind,
+            position: name.token_position
+        };
+
+        self.resolution.bindings.insert(name.token_position, declaration);
+        self.scopes.last_mut().unwrap().push(Variable {
+            name: name_value,
+            declaration,
+            used: false
+        });
+    }
+
+    // Bind an identifier to what it refers to, marking variables as used unless they are only
+    // being assigned to.
+    fn bind(&mut self, name: &Token, is_use: bool) -> () {
+        let name_value: String = name.value();
+        let variable: Option<&mut Variable> = self.scopes.iter_mut().rev()
+            .flat_map(|scope| scope.iter_mut().rev())
+            .find(|variable| variable.name == name_value);
+
+        let declaration: Option<Declaration> = match variable {
+            Some(variable) => {
+                variable.used |= is_use;
+
+                Some(variable.declaration)
+            },
+            None => self.items.get(&name_value).copied()
+        };
+
+        if let Some(declaration) = declaration {
+            self.resolution.bindings.insert(name.token_position, declaration);
+        }
+    }
+
+    fn resolve_statements(&mut self, statements: &[Statement]) -> () {
+        for statement in statements {
+            self.resolve_statement(statement);
+        }
+    }
+
+    fn resolve_block(&mut self, statements: &[Statement]) -> () {
+        self.push_scope();
+        self.resolve_statements(statements);
+        self.pop_scope();
+    }
+
+    fn resolve_function(&mut self, declaration: &FunctionDeclarationStatement) -> () {
+        self.push_scope();
+
+        for parameter in &declaration.parameters {
+            self.declare(&parameter.name, DeclarationKind::Parameter);
+        }
+
+        self.resolve_block(&declaration.body);
+        self.pop_scope();
+    }
+
+    fn resolve_statement(&mut self, statement: &Statement) -> () {
+        match statement {
+            Statement::Expression(expression_statement) => {
+                self.resolve_expression(&expression_statement.expression);
+            },
+            Statement::VariableDeclaration(declaration) => {
+                if let Some(initialization) = &declaration.initialization {
+                    self.resolve_expression(initialization);
+                }
+
+                self.declare(&declaration.identifier.token, DeclarationKind::Variable);
+            },
+            Statement::Block(block) => self.resolve_block(&block.statements),
+            Statement::If(if_statement) => {
+                self.resolve_expression(&if_statement.condition);
+                self.resolve_block(&if_statement.then_branch);
+                self.resolve_block(&if_statement.else_branch);
+            },
+            Statement::While(while_statement) => {
+                self.resolve_expression(&while_statement.condition);
+                self.resolve_block(&while_statement.body);
+            },
+            Statement::Loop(loop_statement) => self.resolve_block(&loop_statement.body),
+            Statement::For(for_statement) => {
+                self.push_scope();
+                self.resolve_expression(&for_statement.initializer);
+                self.declare(&for_statement.variable, DeclarationKind::Variable);
+                self.resolve_expression(&for_statement.condition);
+                self.resolve_block(&for_statement.body);
+                self.resolve_expression(&for_statement.increment);
+                self.pop_scope();
+            },
+            Statement::ForIn(for_in_statement) => {
+                self.resolve_expression(&for_in_statement.iterable);
+                self.push_scope();
+
+                for variable in &for_in_statement.variables {
+                    self.declare(variable, DeclarationKind::Variable);
+                }
+
+                self.resolve_block(&for_in_statement.body);
+                self.pop_scope();
+            },
+            Statement::Return(return_statement) => {
+                if let Some(value) = &return_statement.value {
+                    self.resolve_expression(value);
+                }
+            },
+            Statement::FunctionDeclaration(declaration) => self.resolve_function(declaration),
+            Statement::ClassDeclaration(declaration) => {
+                if let Some(superclass) = &declaration.superclass {
+                    self.bind(&superclass.token, true);
+                }
+
+                // Property initializers can only see the globals.
+                for property in &declaration.properties {
+                    if let Some(initialization) = &property.initialization {
+                        self.resolve_expression(initialization);
+                    }
+                }
+
+                if let Some(constructor) = &declaration.constructor {
+                    self.resolve_function(constructor);
+                }
+
+                for method in &declaration.methods {
+                    self.resolve_function(method);
+                }
+            },
+            Statement::Throw(throw_statement) => self.resolve_expression(&throw_statement.value),
+            Statement::Try(try_statement) => {
+                self.resolve_block(&try_statement.body);
+
+                for catch in &try_statement.catches {
+                    self.push_scope();
+                    self.declare(&catch.variable, DeclarationKind::Variable);
+                    self.resolve_block(&catch.body);
+                    self.pop_scope();
+                }
+
+                if let Some(finally) = &try_statement.finally {
+                    self.resolve_block(finally);
+                }
+            },
+            Statement::Break(_) | Statement::Continue(_) | Statement::InterfaceDeclaration(_)
+            | Statement::EnumDeclaration(_) => {},
+            Statement::Import(_) | Statement::Test(_) | Statement::Trivia(_) => unreachable!()
+        }
+    }
+
+    fn resolve_expressions(&mut self, expressions: &[Expression]) -> () {
+        for expression in expressions {
+            self.resolve_expression(expression);
+        }
+    }
+
+    fn resolve_expression(&mut self, expression: &Expression) -> () {
+        match expression {
+            Expression::Identifier(identifier) => self.bind(&identifier.token, true),
+            Expression::Binary(binary) => {
+                self.resolve_expression(&binary.left);
+                self.resolve_expression(&binary.right);
+            },
+            Expression::Unary(unary) => self.resolve_expression(&unary.operand),
+            Expression::Assignment(assignment) => {
+                self.resolve_expression(&assignment.value);
+
+                match assignment.target.as_ref() {
+                    // Compound assignments such as `+=` read the variable too.
+                    Expression::Identifier(identifier) => {
+                        self.bind(&identifier.token, assignment.operator.is_some());
+                    },
+                    target => self.resolve_expression(target)
+                }
+            },
+            Expression::FunctionCall(call) => {
+                match call.callee.as_ref() {
+                    // Called names are always functions or classes, even when a variable has the
+                    // same name.
+                    Expression::Identifier(identifier) => {
+                        if let Some(declaration) = self.items.get(&identifier.token.value()) {
+                            self.resolution.bindings.insert(identifier.token.token_position,
+                                                            *declaration);
+                        }
+                    },
+                    callee => self.resolve_expression(callee)
+                }
+
+                self.resolve_expressions(&call.arguments);
+            },
+            Expression::Property(property) => self.resolve_expression(&property.object),
+            Expression::Is(is) => {
+                self.resolve_expression(&is.value);
+                self.bind(&is.class_name, true);
+            },
+            Expression::Map(map) => {
+                for (key, value) in &map.entries {
+                    self.resolve_expression(key);
+                    self.resolve_expression(value);
+                }
+            },
+            Expression::List(list) => self.resolve_expressions(&list.elements),
+            Expression::Range(range) => {
+                self.resolve_expression(&range.start);
+                self.resolve_expression(&range.end);
+            },
+            Expression::Match(match_expression) => {
+                self.resolve_expression(&match_expression.value);
+
+                for arm in &match_expression.arms {
+                    self.push_scope();
+                    self.declare_pattern(&arm.pattern);
+
+                    if let Some(guard) = &arm.guard {
+                        self.resolve_expression(guard);
+                    }
+
+                    match &arm.body {
+                        MatchArmBody::Expression(body) => self.resolve_expression(body),
+                        MatchArmBody::Block(body) => self.resolve_block(body)
+                    }
+
+                    self.pop_scope();
+                }
+            },
+            Expression::Propagate(propagate) => self.resolve_expression(&propagate.value),
+            Expression::Literal(_) | Expression::This(_) | Expression::Super(_) => {}
+        }
+    }
+
+    fn declare_pattern(&mut self, pattern: &Pattern) -> () {
+        match pattern {
+            Pattern::Binding(name) => self.declare(name, DeclarationKind::Variable),
+            Pattern::Variant(variant) => {
+                self.bind(&variant.enum_name, true);
+
+                for field in &variant.fields {
+                    self.declare_pattern(field);
+                }
+            },
+            Pattern::Literal(_) | Pattern::Wildcard(_) => {}
+        }
+    }
+}
@@ -1,75 +1,264 @@
 use crate::expressions::{Expression, IdentifierExpression};
+use crate::position::Position;
 use crate::token::Token;
+use crate::type_annotation::{TypeAnnotation, TypeParameterDeclaration};
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone)]
 pub enum Statement {
     Expression(ExpressionStatement),
     VariableDeclaration(VariableDeclarationStatement),
-    Assignment(AssignmentStatement),
     Block(BlockStatement),
     If(IfStatement),
     While(WhileStatement),
+    Loop(LoopStatement),
     For(ForStatement),
+    ForIn(ForInStatement),
+    Break(BreakStatement),
+    Continue(ContinueStatement),
     FunctionDeclaration(FunctionDeclarationStatement),
     Return(ReturnStatement),
     ClassDeclaration(ClassDeclarationStatement),
-    Lambda(LambdaExpression)
+    InterfaceDeclaration(InterfaceDeclarationStatement),
+    EnumDeclaration(EnumDeclarationStatement),
+    Import(ImportStatement),
+    Test(TestStatement),
+    Throw(ThrowStatement),
+    Try(TryStatement),
+    Trivia(Trivia)
 }
 
+#[derive(PartialEq, Clone)]
 pub struct ExpressionStatement {
     pub expression: Expression
 }
 
+#[derive(PartialEq, Clone)]
 pub struct VariableDeclarationStatement {
+    pub variable_type: TypeAnnotation,
     pub identifier: IdentifierExpression,
-    pub initialization: Option<Expression>
-}
-
-pub struct AssignmentStatement {
-    pub identifier: IdentifierExpression,
-    pub value: Expression
+    pub initialization: Option<Expression>,
+    pub public: bool // Whether other modules can use the variable, for those at the top level.
 }
 
+#[derive(PartialEq, Clone)]
 pub struct BlockStatement {
-
+    pub statements: Vec<Statement>,
+    pub position: Position // The position of the `{` opening the block.
 }
 
+#[derive(PartialEq, Clone)]
 pub struct IfStatement {
     pub condition: Expression,
     pub then_branch: Vec<Statement>,
     pub else_branch: Vec<Statement>
 }
 
+#[derive(PartialEq, Clone)]
 pub struct WhileStatement {
     pub condition: Expression,
     pub body: Vec<Statement>
 }
 
+#[derive(PartialEq, Clone)]
+pub struct LoopStatement {
+    pub body: Vec<Statement>,
+    pub position: Position
+}
+
+#[derive(PartialEq, Clone)]
 pub struct ForStatement {
     pub variable: Token,
+    pub variable_type: TypeAnnotation,
     pub initializer: Expression,
     pub condition: Expression,
     pub increment: Expression,
     pub body: Vec<Statement>
 }
 
+// `for item in items` and `for (key, value) in map`. The types of the variables come from what is
+// being iterated over.
+#[derive(PartialEq, Clone)]
+pub struct ForInStatement {
+    pub variables: Vec<Token>,
+    pub iterable: Expression,
+    pub body: Vec<Statement>
+}
+
+#[derive(PartialEq, Clone)]
+pub struct BreakStatement {
+    pub position: Position
+}
+
+#[derive(PartialEq, Clone)]
+pub struct ContinueStatement {
+    pub position: Position
+}
+
+#[derive(PartialEq, Clone)]
+pub struct ParameterDeclaration {
+    pub parameter_type: TypeAnnotation,
+    pub name: Token
+}
+
+#[derive(PartialEq, Clone)]
 pub struct FunctionDeclarationStatement {
+    pub return_type: TypeAnnotation,
     pub name: IdentifierExpression,
-    pub parameters: Vec<Token>,
-    pub body: Vec<Statement>
+    pub type_parameters: Vec<TypeParameterDeclaration>,
+    pub parameters: Vec<ParameterDeclaration>,
+    pub body: Vec<Statement>,
+    pub public: bool // Whether other modules can call the function, for those at the top level.
 }
 
+#[derive(PartialEq, Clone)]
 pub struct ReturnStatement {
-    pub value: Option<Expression>
+    pub value: Option<Expression>,
+    pub position: Position
 }
 
+#[derive(PartialEq, Clone)]
 pub struct ClassDeclarationStatement {
     pub name: IdentifierExpression,
+    pub type_parameters: Vec<TypeParameterDeclaration>,
+    pub superclass: Option<IdentifierExpression>,
+    pub superclass_arguments: Vec<TypeAnnotation>, // Such as the `int` in `extends Base<int>`.
+    pub interfaces: Vec<TypeAnnotation>, // The interfaces the class implements.
     pub properties: Vec<VariableDeclarationStatement>,
-    pub methods: Vec<FunctionDeclarationStatement>
+    // The constructor is declared like a method named after the class, without a return type.
+    pub constructor: Option<FunctionDeclarationStatement>,
+    pub methods: Vec<FunctionDeclarationStatement>,
+    pub public: bool,
+    pub trivia: Vec<Trivia> // The comments and blank lines between the members.
 }
 
-pub struct LambdaExpression {
-    pub parameters: Vec<Token>,
+#[derive(PartialEq, Clone)]
+pub struct InterfaceDeclarationStatement {
+    pub name: IdentifierExpression,
+    pub type_parameters: Vec<TypeParameterDeclaration>,
+    // The methods which classes implementing the interface must have, declared without a body.
+    pub methods: Vec<FunctionDeclarationStatement>,
+    pub public: bool,
+    pub trivia: Vec<Trivia> // The comments and blank lines between the methods.
+}
+
+#[derive(PartialEq, Clone)]
+pub struct EnumDeclarationStatement {
+    pub name: IdentifierExpression,
+    pub variants: Vec<EnumVariantDeclaration>,
+    pub public: bool,
+    pub trivia: Vec<Trivia> // The comments and blank lines between the variants.
+}
+
+// A variant of an enum, such as `Circle(float radius)`, or `Empty` for one without a payload.
+#[derive(PartialEq, Clone)]
+pub struct EnumVariantDeclaration {
+    pub name: Token,
+    pub fields: Vec<ParameterDeclaration>
+}
+
+// `import "path/to/file.rbt";` or `import path.to.file;`, which makes the public declarations of
+// another file available through its name, such as `file.function()`.
+#[derive(PartialEq, Clone)]
+pub struct ImportStatement {
+    pub path: String, // The path of the file, relative to the importing file or the search path.
+    // The name the module is used through, which is the name of its file, positioned at the path.
+    pub name: Token
+}
+
+// `test "name" { ... }`, which declares a test. Tests are checked along with the rest of their
+// file, but only run by the test runner, once the top level of the file has run.
+#[derive(PartialEq, Clone)]
+pub struct TestStatement {
+    pub name: Token, // The string naming the test.
+    pub body: Vec<Statement>
+}
+
+// `throw error;`, which stops with an error that a `try` around it can catch.
+#[derive(PartialEq, Clone)]
+pub struct ThrowStatement {
+    pub value: Expression,
+    pub position: Position
+}
+
+// `try { ... } catch (e: NotFound) { ... } finally { ... }`. An error is handled by the first catch
+// it is an instance of the class of, and the finally block runs however the others are left.
+#[derive(PartialEq, Clone)]
+pub struct TryStatement {
+    pub body: Vec<Statement>,
+    pub catches: Vec<CatchClause>,
+    pub finally: Option<Vec<Statement>>,
+    pub position: Position
+}
+
+// A catch without a class, as in `catch (e)`, catches every error.
+#[derive(PartialEq, Clone)]
+pub struct CatchClause {
+    pub variable: Token,
+    pub error_type: Option<TypeAnnotation>,
     pub body: Vec<Statement>
-}
\ No newline at end of file
+}
+
+// The comments and blank lines of the source, which the parser only sees when a program is being
+// formatted. Those found inside an expression are kept after the statement around it.
+#[derive(PartialEq, Clone)]
+pub enum Trivia {
+    Comment(Comment),
+    BlankLine(Position)
+}
+
+impl Statement {
+    // The position used when reporting an error about this statement as a whole.
+    pub fn position(&self) -> Position {
+        return match self {
+            Statement::Expression(statement) => statement.expression.position(),
+            Statement::VariableDeclaration(declaration) => {
+                declaration.identifier.token.token_position
+            },
+            Statement::Block(block) => block.position,
+            Statement::If(if_statement) => if_statement.condition.position(),
+            Statement::While(while_statement) => while_statement.condition.position(),
+            Statement::Loop(loop_statement) => loop_statement.position,
+            Statement::For(for_statement) => for_statement.variable.token_position,
+            Statement::ForIn(for_in) => for_in.iterable.position(),
+            Statement::Break(break_statement) => break_statement.position,
+            Statement::Continue(continue_statement) => continue_statement.position,
+            Statement::FunctionDeclaration(declaration) => declaration.name.token.token_position,
+            Statement::Return(return_statement) => return_statement.position,
+            Statement::ClassDeclaration(declaration) => declaration.name.token.token_position,
+            Statement::InterfaceDeclaration(declaration) => declaration.name.token.token_position,
+            Statement::EnumDeclaration(declaration) => declaration.name.token.token_position,
+            Statement::Import(import) => import.name.token_position,
+            Statement::Test(test) => test.name.token_position,
+            Statement::Throw(throw) => throw.position,
+            Statement::Try(try_statement) => try_statement.position,
+            Statement::Trivia(trivia) => trivia.position()
+        };
+    }
+}
+
+impl ClassDeclarationStatement {
+    // The superclass as a type, along with its type arguments, such as `Base<int>`.
+    pub fn superclass_type(&self) -> Option<TypeAnnotation> {
+        return self.superclass.as_ref().map(|superclass| TypeAnnotation {
+            name: superclass.token.to_owned(),
+            arguments: self.superclass_arguments.to_owned(),
+            nullable: false
+        });
+    }
+}
+
+impl Trivia {
+    pub fn position(&self) -> Position {
+        return match self {
+            Trivia::Comment(comment) => comment.position,
+            Trivia::BlankLine(position) => *position
+        };
+    }
+}
+
+#[derive(PartialEq, Clone)]
+pub struct Comment {
+    pub text: String, // The whole comment, including the `//`.
+    pub position: Position,
+    pub trailing: bool // Whether it follows some code on the same line.
+}
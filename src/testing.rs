@@ -0,0 +1,92 @@
+use std::collections::HashSet;
+use crate::expressions::{Expression, FunctionCallExpression, IdentifierExpression};
+use crate::position::Position;
+use crate::program::CompileResult;
+use crate::statements::{ExpressionStatement, FunctionDeclarationStatement, Statement,
+                        TestStatement};
+use crate::token::Token;
+use crate::token_type::TokenType;
+use crate::type_annotation::TypeAnnotation;
+
+// The names of the tests declared by the statements of a file, in order.
+pub fn test_names(statements: &[Statement]) -> Vec<String> {
+    return statements.iter()
+        .filter_map(|statement| match statement {
+            Statement::Test(test) => Some(test.name.value()),
+            _ => None
+        })
+        .collect();
+}
+
+// The name of the function a test is turned into, which cannot be called by the program as it is
+// not an identifier.
+fn function_name(test: &str) -> String {
+    return "test \"".to_owned() + test + "\"";
+}
+
+// Whether a function is a test turned into a function.
+pub fn is_test_function(name: &str) -> bool {
+    return name.starts_with("test \"");
+}
+
+// Turn the tests of a file into functions, so that they are checked like any other code. They
+// are moved after the rest of the top level, so that they can use every global of the file, and
+// when a test is selected, it is called last, once everything it uses has been set up.
+pub fn lower_tests(statements: Vec<Statement>, selected: Option<&str>)
+        -> CompileResult<Vec<Statement>> {
+    let mut names: HashSet<String> = HashSet::new();
+    let mut lowered: Vec<Statement> = Vec::new();
+    let mut functions: Vec<Statement> = Vec::new();
+    let mut call: Option<Statement> = None;
+
+    for statement in statements {
+        let test: TestStatement = match statement {
+            Statement::Test(test) => test,
+            _ => {
+                lowered.push(statement);
+                continue;
+            }
+        };
+        let name: String = test.name.value();
+        let position: Position = test.name.token_position;
+
+        if !names.insert(name.to_owned()) {
+            return Err(("Test `".to_owned() + &name + "` is already declared.", position));
+        }
+
+        let function: Token = Token::new(TokenType::Identifier, Some(function_name(&name)),
+                                         position);
+
+        if selected == Some(name.as_str()) {
+            call = Some(Statement::Expression(ExpressionStatement {
+                expression: Expression::FunctionCall(FunctionCallExpression {
+                    callee: Box::new(Expression::Identifier(IdentifierExpression {
+                        token: function.to_owned()
+                    })),
+                    arguments: Vec::new(),
+                    position
+                })
+            }));
+        }
+
+        functions.push(Statement::FunctionDeclaration(FunctionDeclarationStatement {
+            return_type: TypeAnnotation {
+                name: Token::new(TokenType::Identifier, Some("void".to_owned()), position),
+                arguments: Vec::new(),
+                nullable: false
+            },
+            name: IdentifierExpression {
+                token: function
+            },
+            type_parameters: Vec::new(),
+            parameters: Vec::new(),
+            body: test.body,
+            public: false
+        }));
+    }
+
+    lowered.extend(functions);
+    lowered.extend(call);
+
+    return Ok(lowered);
+}
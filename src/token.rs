@@ -1,10 +1,11 @@
 use crate::position::Position;
 use crate::token_type::TokenType;
 
+#[derive(Clone, PartialEq)]
 pub struct Token {
-    token_type: TokenType,
-    token_value: Option<String>,
-    token_position: Position
+    pub(crate) token_type: TokenType,
+    pub(crate) token_value: Option<String>,
+    pub(crate) token_position: Position
 }
 
 impl Token {
@@ -25,6 +26,11 @@ impl Token {
             token_position
         };
     }
+
+    // The value of the token, or an empty string for tokens that do not carry one.
+    pub fn value(&self) -> String {
+        return self.token_value.clone().unwrap_or_default();
+    }
 }
 
 impl ToString for Token {
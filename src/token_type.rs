@@ -29,6 +29,11 @@ pub enum TokenType {
     Equal, // Double equal, used for equality check.
     And,
     Or,
+    DoublePeriod, // `..`, used for ranges.
+    Arrow, // `=>`, used by match arms.
+    QuestionMark, // `?`, used by nullable types.
+    QuestionPeriod, // `?.`, used for member access on values which might be null.
+    DoubleQuestionMark, // `??`, used to replace null values.
 
     // Operation tokens.
     Increment, // ++
@@ -52,6 +57,7 @@ pub enum TokenType {
     String,
     Char, // Single character, can be an escape sequence like a newline, etc.
     Boolean,
+    Null,
     Integer, // Whole number.
     Float, // Floating point number.
 
@@ -63,8 +69,11 @@ pub enum TokenType {
     Super, // Calls to the parent class are routed through the `super` keyword.
     This,
     Extends,
+    Interface,
+    Implements,
     // Loops.
     For,
+    In,
     While,
     Loop,
     Break,
@@ -72,11 +81,27 @@ pub enum TokenType {
     // Logical.
     If,
     Else,
+    Match,
+    // Enums.
+    Enum,
+    // Errors.
+    Try,
+    Catch,
+    Finally,
+    Throw,
+
+    // Modules.
+    Import,
+    Pub, // Makes a declaration visible to the modules which import it.
 
     // Other.
     Return,
     Is,
 
+    // Trivia, which the lexer only keeps for the formatter.
+    Comment,
+    BlankLine,
+
     // End of file.
     EOF
 }
@@ -106,6 +131,11 @@ impl ToString for TokenType {
             TokenType::Equal => String::from("Equal"),
             TokenType::And => String::from("And"),
             TokenType::Or => String::from("Or"),
+            TokenType::DoublePeriod => String::from("DoublePeriod"),
+            TokenType::Arrow => String::from("Arrow"),
+            TokenType::QuestionMark => String::from("QuestionMark"),
+            TokenType::QuestionPeriod => String::from("QuestionPeriod"),
+            TokenType::DoubleQuestionMark => String::from("DoubleQuestionMark"),
             TokenType::Increment => String::from("Increment"),
             TokenType::Decrement => String::from("Decrement"),
             TokenType::Plus => String::from("Plus"),
@@ -123,21 +153,35 @@ impl ToString for TokenType {
             TokenType::String => String::from("String"),
             TokenType::Char => String::from("Char"),
             TokenType::Boolean => String::from("Boolean"),
+            TokenType::Null => String::from("Null"),
             TokenType::Integer => String::from("Integer"),
             TokenType::Float => String::from("Float"),
             TokenType::Class => String::from("Class"),
             TokenType::Super => String::from("Super"),
             TokenType::This => String::from("This"),
             TokenType::Extends => String::from("Extends"),
+            TokenType::Interface => String::from("Interface"),
+            TokenType::Implements => String::from("Implements"),
+            TokenType::Import => String::from("Import"),
+            TokenType::Pub => String::from("Pub"),
             TokenType::For => String::from("For"),
+            TokenType::In => String::from("In"),
             TokenType::While => String::from("While"),
             TokenType::Loop => String::from("Loop"),
             TokenType::Break => String::from("Break"),
             TokenType::Continue => String::from("Continue"),
             TokenType::If => String::from("If"),
             TokenType::Else => String::from("Else"),
+            TokenType::Match => String::from("Match"),
+            TokenType::Enum => String::from("Enum"),
+            TokenType::Try => String::from("Try"),
+            TokenType::Catch => String::from("Catch"),
+            TokenType::Finally => String::from("Finally"),
+            TokenType::Throw => String::from("Throw"),
             TokenType::Return => String::from("Return"),
             TokenType::Is => String::from("Is"),
+            TokenType::Comment => String::from("Comment"),
+            TokenType::BlankLine => String::from("BlankLine"),
             TokenType::EOF => String::from("EOF")
         };
     }
@@ -0,0 +1,67 @@
+use crate::position::Position;
+use crate::token::Token;
+
+// A type as written in the source, such as `int` or `map<string, int>`. These are resolved into
+// actual types by the type checker.
+#[derive(PartialEq, Clone)]
+pub struct TypeAnnotation {
+    pub name: Token,
+    pub arguments: Vec<TypeAnnotation>,
+    pub nullable: bool // Whether the type is followed by a `?`, such as `string?`.
+}
+
+impl TypeAnnotation {
+    pub fn position(&self) -> Position {
+        return self.name.token_position.to_owned();
+    }
+}
+
+impl ToString for TypeAnnotation {
+    fn to_string(&self) -> String {
+        let mut result: String = self.name.value();
+
+        if !self.arguments.is_empty() {
+            let arguments: Vec<String> = self.arguments.iter()
+                .map(|argument| argument.to_string())
+                .collect();
+
+            result += &("<".to_owned() + &arguments.join(", ") + ">");
+        }
+
+        if self.nullable {
+            result.push('?');
+        }
+
+        return result;
+    }
+}
+
+// A type parameter of a generic function or class, such as the `T extends Shape` in
+// `class Box<T extends Shape>`.
+#[derive(PartialEq, Clone)]
+pub struct TypeParameterDeclaration {
+    pub name: Token,
+    pub bound: Option<TypeAnnotation> // The type that the type arguments must be assignable to.
+}
+
+impl ToString for TypeParameterDeclaration {
+    fn to_string(&self) -> String {
+        return match &self.bound {
+            Some(bound) => self.name.value() + " extends " + &bound.to_string(),
+            None => self.name.value()
+        };
+    }
+}
+
+// The type parameters of a declaration as written after its name, which is empty when it has none.
+pub fn type_parameters_to_string(type_parameters: &[TypeParameterDeclaration]) -> String {
+    if type_parameters.is_empty() {
+        return String::new();
+    }
+
+    let type_parameters: Vec<String> = type_parameters.iter()
+        .map(|type_parameter| type_parameter.to_string())
+        .collect();
+
+    return "<".to_owned() + &type_parameters.join(", ") + ">";
+}
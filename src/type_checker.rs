@@ -0,0 +1,3309 @@
+use std::collections::{HashMap, HashSet};
+use std::mem;
+use crate::conversions::Converter;
+use crate::expressions::{AssignmentExpression, BinaryExpression, BinaryOperator, Expression,
+                         FunctionCallExpression, IdentifierExpression, ListExpression,
+                         LiteralValue, MapExpression, MatchArmBody, MatchExpression,
+                         PropagateExpression, PropertyExpression, UnaryExpression, UnaryOperator};
+use crate::patterns::Pattern;
+use crate::position::Position;
+use crate::program::{CompileResult, Program, StackGuard};
+use crate::statements::{ClassDeclarationStatement, EnumDeclarationStatement, ForInStatement,
+                        FunctionDeclarationStatement, InterfaceDeclarationStatement, Statement,
+                        TryStatement, VariableDeclarationStatement};
+use crate::token::Token;
+use crate::type_annotation::{TypeAnnotation, TypeParameterDeclaration};
+use crate::natives::{find_module_native, find_native, is_built_in_enum, is_module, is_native_class,
+                     native_classes, Native, ERROR_CLASS, EXPECT_ERROR, OPTION_ENUM, RESULT_ENUM};
+use crate::types::{ClassType, EnumType, FunctionType, Type, TypeParameter};
+
+// What is known about variables at some point of the program, which depends on the path taken to
+// reach it. Variables are identified by their name and the index of the scope declaring them.
+#[derive(Clone, Default)]
+struct FlowState {
+    non_null: HashSet<(String, usize)>, // Nullable variables which are known not to be null.
+    unassigned: HashSet<(String, usize)>, // Variables which might not have been given a value.
+    // Properties of `this` which the constructor being checked might not have given a value.
+    unassigned_properties: HashSet<String>
+}
+
+impl FlowState {
+    // What is known after reaching the same point through either of two paths.
+    fn join(&self, other: &FlowState) -> FlowState {
+        return FlowState {
+            non_null: self.non_null.intersection(&other.non_null).cloned().collect(),
+            unassigned: self.unassigned.union(&other.unassigned).cloned().collect(),
+            unassigned_properties: self.unassigned_properties
+                .union(&other.unassigned_properties)
+                .cloned()
+                .collect()
+        };
+    }
+}
+
+// What running some code can change: the variables it assigns to, and the names of the functions
+// and methods it calls, which can assign to globals.
+#[derive(Default)]
+struct Effects {
+    assigned: HashSet<String>,
+    called: HashSet<String>
+}
+
+// The type checker walks the statements of the program before it is run, making sure that every
+// expression is used with the correct types.
+pub struct TypeChecker<'a> {
+    program: &'a mut Program,
+    functions: HashMap<String, FunctionType>,
+    classes: HashMap<String, ClassType>,
+    enums: HashMap<String, EnumType>,
+    scopes: Vec<HashMap<String, Type>>, // The first scope holds the global variables.
+    return_type: Option<Type>, // The return type of the function being checked, if any.
+    current_class: Option<String>,
+    // The type parameters of the generic functions and classes being checked, innermost last.
+    type_parameters: Vec<TypeParameter>,
+    loop_depth: i32,
+    // The loop depth where the finally block being checked starts, which nothing can jump out of.
+    finally_loop_depth: Option<i32>,
+    flow: FlowState,
+    // The type of every expression and declared variable checked so far, by its position.
+    types: HashMap<Position, Type>,
+    // The positions of the int expressions accepted as floats, which are converted once the
+    // program is checked.
+    conversions: HashSet<Position>,
+    stack: StackGuard
+}
+
+impl<'a> TypeChecker<'a> {
+    pub fn new(program: &'a mut Program) -> Self {
+        let mut classes: HashMap<String, ClassType> = native_classes().iter()
+            .map(|class| (class.name.to_owned(), ClassType {
+                interface: false,
+                type_parameters: Vec::new(),
+                superclass: None,
+                superclass_arguments: Vec::new(),
+                interfaces: Vec::new(),
+                properties: class.properties.iter()
+                    .map(|(name, property_type)| (name.to_string(), property_type.to_owned()))
+                    .collect(),
+                methods: HashMap::new(),
+                constructor: None
+            }))
+            .collect();
+
+        // The types defined by a program embedding the language only have methods.
+        for host_type in program.host_types() {
+            classes.insert(host_type.name.to_owned(), ClassType {
+                interface: false,
+                type_parameters: Vec::new(),
+                superclass: None,
+                superclass_arguments: Vec::new(),
+                interfaces: Vec::new(),
+                properties: HashMap::new(),
+                methods: host_type.methods.iter()
+                    .map(|method| (method.name.to_owned(), method.function_type.to_owned()))
+                    .collect(),
+                constructor: None
+            });
+        }
+
+        // The values given by a program embedding the language are globals from the start.
+        let globals: HashMap<String, Type> = program.host_values().iter()
+            .map(|value| (value.name.to_owned(), value.value_type.to_owned()))
+            .collect();
+
+        let stack: StackGuard = program.stack_guard();
+
+        return Self {
+            program,
+            functions: HashMap::new(),
+            classes,
+            enums: HashMap::new(),
+            scopes: vec![globals],
+            return_type: None,
+            current_class: None,
+            type_parameters: Vec::new(),
+            loop_depth: 0,
+            finally_loop_depth: None,
+            flow: FlowState::default(),
+            types: HashMap::new(),
+            conversions: HashSet::new(),
+            stack
+        };
+    }
+
+    // The types found by checking, by the position of the expression or variable declaration
+    // they belong to.
+    pub fn types(&self) -> &HashMap<Position, Type> {
+        return &self.types;
+    }
+
+    // Check a program, returning it with the ints it uses as floats converted to floats.
+    pub fn check(&mut self, statements: Vec<Statement>) -> CompileResult<Vec<Statement>> {
+        // Classes, enums and functions can be used before they are declared, so their signatures
+        // are collected before anything is checked.
+        self.declare_enums(&statements)?;
+        self.declare_classes(&statements)?;
+        self.define_enums(&statements)?;
+        self.declare_functions(&statements)?;
+
+        for statement in &statements {
+            self.check_statement(statement)?;
+        }
+
+        let mut converter: Converter = Converter::new(mem::take(&mut self.conversions),
+                                                      self.program.stack_guard());
+
+        return converter.convert(statements);
+    }
+
+    // Register the name of a class or interface, along with the names of its type parameters.
+    fn declare_class_name(&mut self, name: &Token, type_parameters: &[TypeParameterDeclaration],
+                          interface: bool) -> CompileResult<()> {
+        let name_value: String = name.value();
+
+        if self.classes.contains_key(&name_value) || self.enums.contains_key(&name_value)
+            || is_built_in_enum(&name_value) {
+            return Err((
+                "`".to_owned() + &name_value + "` is already declared.",
+                name.token_position.to_owned()
+            ));
+        }
+
+        self.classes.insert(name_value, ClassType {
+            interface,
+            type_parameters: type_parameters.iter()
+                .map(|type_parameter| TypeParameter {
+                    name: type_parameter.name.value(),
+                    bound: None
+                })
+                .collect(),
+            superclass: None,
+            superclass_arguments: Vec::new(),
+            interfaces: Vec::new(),
+            properties: HashMap::new(),
+            methods: HashMap::new(),
+            constructor: None
+        });
+
+        return Ok(());
+    }
+
+    fn declare_classes(&mut self, statements: &[Statement]) -> CompileResult<()> {
+        let declarations: Vec<&ClassDeclarationStatement> = statements.iter()
+            .filter_map(|statement| match statement {
+                Statement::ClassDeclaration(declaration) => Some(declaration),
+                _ => None
+            })
+            .collect();
+        let interfaces: Vec<&InterfaceDeclarationStatement> = statements.iter()
+            .filter_map(|statement| match statement {
+                Statement::InterfaceDeclaration(declaration) => Some(declaration),
+                _ => None
+            })
+            .collect();
+
+        // Register the names first, so that members can refer to any class or interface.
+        for declaration in &declarations {
+            self.declare_class_name(&declaration.name.token, &declaration.type_parameters, false)?;
+        }
+
+        for interface in &interfaces {
+            self.declare_class_name(&interface.name.token, &interface.type_parameters, true)?;
+        }
+
+        for declaration in &declarations {
+            if let Some(superclass_name) = &declaration.superclass {
+                let superclass_value: String = superclass_name.token.value();
+
+                if !self.classes.contains_key(&superclass_value) {
+                    return Err((
+                        "Unknown class `".to_owned() + &superclass_value + "`.",
+                        superclass_name.token.token_position.to_owned()
+                    ));
+                }
+
+                if self.is_built_in_class(&superclass_value) {
+                    return Err((
+                        "Cannot extend the built-in class `".to_owned() + &superclass_value +
+                            "`.",
+                        superclass_name.token.token_position.to_owned()
+                    ));
+                }
+
+                if self.classes[&superclass_value].interface {
+                    return Err((
+                        "Cannot extend the interface `".to_owned() + &superclass_value +
+                            "`; implement it instead.",
+                        superclass_name.token.token_position.to_owned()
+                    ));
+                }
+
+                self.classes.get_mut(&declaration.name.token.value()).unwrap().superclass =
+                    Some(superclass_value);
+            }
+        }
+
+        for declaration in &declarations {
+            let name: String = declaration.name.token.value();
+            let mut seen: Vec<String> = vec![name.to_owned()];
+            let mut current: Option<String> = self.classes[&name].superclass.to_owned();
+
+            // Make sure the inheritance chain does not loop back on itself.
+            while let Some(superclass) = current {
+                if seen.contains(&superclass) {
+                    return Err((
+                        "Class `".to_owned() + &name + "` inherits from itself.",
+                        declaration.name.token.token_position.to_owned()
+                    ));
+                }
+
+                current = self.classes[&superclass].superclass.to_owned();
+                seen.push(superclass);
+            }
+        }
+
+        // The bounds of the type parameters are resolved before any type arguments are given.
+        let generics: Vec<(&IdentifierExpression, &Vec<TypeParameterDeclaration>)> = declarations
+            .iter()
+            .map(|declaration| (&declaration.name, &declaration.type_parameters))
+            .chain(interfaces.iter().map(|interface| (&interface.name, &interface.type_parameters)))
+            .collect();
+
+        for (name, type_parameters) in generics {
+            let type_parameters: Vec<TypeParameter> =
+                self.declare_type_parameters(type_parameters)?;
+
+            self.type_parameters.clear();
+            self.classes.get_mut(&name.token.value()).unwrap().type_parameters = type_parameters;
+        }
+
+        for declaration in &declarations {
+            let name: String = declaration.name.token.value();
+
+            self.type_parameters = self.classes[&name].type_parameters.to_owned();
+
+            if let Some(superclass) = declaration.superclass_type() {
+                let superclass_arguments: Vec<Type> = match self.resolve_type_name(&superclass)? {
+                    Type::Class(_, arguments) => arguments,
+                    // A type parameter with the name of the superclass hides it.
+                    _ => return Err((
+                        "Unknown class `".to_owned() + &superclass.name.value() + "`.",
+                        superclass.position()
+                    ))
+                };
+
+                self.classes.get_mut(&name).unwrap().superclass_arguments = superclass_arguments;
+            }
+
+            for annotation in &declaration.interfaces {
+                let interface: (String, Vec<Type>) = match self.resolve_type_name(annotation)? {
+                    Type::Class(interface, arguments) if self.classes[&interface].interface => {
+                        (interface, arguments)
+                    },
+                    other => return Err((
+                        "Only interfaces can be implemented, but found `".to_owned() +
+                            &other.to_string() + "`.",
+                        annotation.position()
+                    ))
+                };
+                let implemented: bool = self.classes[&name].interfaces.iter()
+                    .any(|(implemented, _)| *implemented == interface.0);
+
+                if implemented {
+                    return Err((
+                        "Interface `".to_owned() + &interface.0 + "` is already implemented.",
+                        annotation.position()
+                    ));
+                }
+
+                self.classes.get_mut(&name).unwrap().interfaces.push(interface);
+            }
+
+            self.type_parameters.clear();
+        }
+
+        for declaration in &declarations {
+            let name: String = declaration.name.token.value();
+            let mut properties: HashMap<String, Type> = HashMap::new();
+            let mut methods: HashMap<String, FunctionType> = HashMap::new();
+
+            self.type_parameters = self.classes[&name].type_parameters.to_owned();
+
+            for property in &declaration.properties {
+                let property_name: String = property.identifier.token.value();
+                let property_type: Type = self.resolve_variable_type(&property.variable_type)?;
+
+                if properties.insert(property_name.to_owned(), property_type).is_some() {
+                    return Err((
+                        "Property `".to_owned() + &property_name + "` is already declared.",
+                        property.identifier.token.token_position.to_owned()
+                    ));
+                }
+            }
+
+            for method in &declaration.methods {
+                let method_name: String = method.name.token.value();
+                let method_type: FunctionType = self.resolve_function_type(method)?;
+
+                if properties.contains_key(&method_name)
+                    || methods.insert(method_name.to_owned(), method_type).is_some() {
+                    return Err((
+                        "Member `".to_owned() + &method_name + "` is already declared.",
+                        method.name.token.token_position.to_owned()
+                    ));
+                }
+            }
+
+            let constructor: Option<FunctionType> = declaration.constructor.as_ref()
+                .map(|constructor| self.resolve_function_type(constructor))
+                .transpose()?;
+
+            let class_type: &mut ClassType = self.classes.get_mut(&name).unwrap();
+
+            class_type.properties = properties;
+            class_type.methods = methods;
+            class_type.constructor = constructor;
+
+            self.type_parameters.clear();
+        }
+
+        for interface in &interfaces {
+            let name: String = interface.name.token.value();
+            let mut methods: HashMap<String, FunctionType> = HashMap::new();
+
+            self.type_parameters = self.classes[&name].type_parameters.to_owned();
+
+            for method in &interface.methods {
+                let method_name: String = method.name.token.value();
+                let method_type: FunctionType = self.resolve_function_type(method)?;
+
+                if methods.insert(method_name.to_owned(), method_type).is_some() {
+                    return Err((
+                        "Member `".to_owned() + &method_name + "` is already declared.",
+                        method.name.token.token_position.to_owned()
+                    ));
+                }
+            }
+
+            self.classes.get_mut(&name).unwrap().methods = methods;
+            self.type_parameters.clear();
+        }
+
+        return Ok(());
+    }
+
+    fn enum_declarations(statements: &[Statement]) -> Vec<&EnumDeclarationStatement> {
+        return statements.iter()
+            .filter_map(|statement| match statement {
+                Statement::EnumDeclaration(declaration) => Some(declaration),
+                _ => None
+            })
+            .collect();
+    }
+
+    // Register the names of enums, so that any type can refer to them.
+    fn declare_enums(&mut self, statements: &[Statement]) -> CompileResult<()> {
+        for declaration in Self::enum_declarations(statements) {
+            let name: String = declaration.name.token.value();
+
+            if self.enums.contains_key(&name) || is_built_in_enum(&name) {
+                return Err((
+                    "`".to_owned() + &name + "` is already declared.",
+                    declaration.name.token.token_position.to_owned()
+                ));
+            }
+
+            self.enums.insert(name, EnumType {
+                variants: Vec::new()
+            });
+        }
+
+        return Ok(());
+    }
+
+    // Resolve the fields of the variants of every enum, once all class and enum names are known.
+    fn define_enums(&mut self, statements: &[Statement]) -> CompileResult<()> {
+        for declaration in Self::enum_declarations(statements) {
+            let mut variants: Vec<(String, Vec<Type>)> = Vec::new();
+
+            for variant in &declaration.variants {
+                let variant_name: String = variant.name.value();
+
+                if variants.iter().any(|(existing, _)| *existing == variant_name) {
+                    return Err((
+                        "Variant `".to_owned() + &variant_name + "` is already declared.",
+                        variant.name.token_position.to_owned()
+                    ));
+                }
+
+                let fields: Vec<Type> = variant.fields.iter()
+                    .map(|field| self.resolve_variable_type(&field.parameter_type))
+                    .collect::<CompileResult<_>>()?;
+
+                variants.push((variant_name, fields));
+            }
+
+            self.enums.get_mut(&declaration.name.token.value()).unwrap().variants = variants;
+        }
+
+        return Ok(());
+    }
+
+    // The constructor used to create instances of a class with the given type arguments, which
+    // may be inherited. Classes without any constructor take no arguments.
+    fn find_constructor(&self, class: &str, arguments: &[Type]) -> FunctionType {
+        if let Some(constructor) = &self.classes[class].constructor {
+            return constructor.substitute(&self.class_bindings(class, arguments));
+        }
+
+        return match self.find_superclass(class, arguments) {
+            Some((superclass, superclass_arguments)) => {
+                self.find_constructor(&superclass, &superclass_arguments)
+            },
+            None => FunctionType {
+                type_parameters: Vec::new(),
+                parameters: Vec::new(),
+                return_type: Type::Void
+            }
+        };
+    }
+
+    fn declare_functions(&mut self, statements: &[Statement]) -> CompileResult<()> {
+        for statement in statements {
+            if let Statement::FunctionDeclaration(declaration) = statement {
+                let name: String = declaration.name.token.value();
+                let function_type: FunctionType = self.resolve_function_type(declaration)?;
+
+                if self.classes.contains_key(&name) || self.enums.contains_key(&name)
+                    || self.functions.insert(name.to_owned(), function_type).is_some() {
+                    return Err((
+                        "`".to_owned() + &name + "` is already declared.",
+                        declaration.name.token.token_position.to_owned()
+                    ));
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn resolve_type(&self, annotation: &TypeAnnotation) -> CompileResult<Type> {
+        let resolved: Type = self.resolve_type_name(annotation)?;
+
+        if !annotation.nullable {
+            return Ok(resolved);
+        }
+
+        if resolved == Type::Void {
+            return Err(("Type `void` cannot be nullable.".to_owned(),
+                                annotation.position()));
+        }
+
+        return Ok(resolved.to_nullable());
+    }
+
+    fn resolve_type_name(&self, annotation: &TypeAnnotation) -> CompileResult<Type> {
+        let name: String = annotation.name.value();
+        let is_type_parameter: bool = self.find_type_parameter(&name).is_some();
+        let expected_arguments: usize = match name.as_str() {
+            _ if is_type_parameter => 0,
+            "map" | RESULT_ENUM => 2,
+            "list" | OPTION_ENUM => 1,
+            _ => self.classes.get(&name).map_or(0, |class| class.type_parameters.len())
+        };
+
+        if annotation.arguments.len() != expected_arguments {
+            return Err((
+                "Type `".to_owned() + &name + "` expects " + &expected_arguments.to_string() +
+                    " type argument(s), but " + &annotation.arguments.len().to_string() +
+                    " were given.",
+                annotation.position()
+            ));
+        }
+
+        return Ok(match name.as_str() {
+            _ if is_type_parameter => Type::Parameter(name),
+            "int" => Type::Integer,
+            "float" => Type::Float,
+            "string" => Type::String,
+            "char" => Type::Char,
+            "bool" => Type::Boolean,
+            "void" => Type::Void,
+            "map" => {
+                let key: Type = self.resolve_variable_type(&annotation.arguments[0])?;
+                let value: Type = self.resolve_variable_type(&annotation.arguments[1])?;
+
+                if !key.is_hashable() {
+                    return Err((
+                        "Type `".to_owned() + &key.to_string() + "` cannot be used as a map key.",
+                        annotation.arguments[0].position()
+                    ));
+                }
+
+                Type::Map(Box::new(key), Box::new(value))
+            },
+            "list" => Type::List(Box::new(self.resolve_variable_type(&annotation.arguments[0])?)),
+            "range" => Type::Range,
+            RESULT_ENUM => Type::Result(
+                Box::new(self.resolve_variable_type(&annotation.arguments[0])?),
+                Box::new(self.resolve_variable_type(&annotation.arguments[1])?)
+            ),
+            OPTION_ENUM => {
+                Type::Option(Box::new(self.resolve_variable_type(&annotation.arguments[0])?))
+            },
+            _ if self.enums.contains_key(&name) => Type::Enum(name),
+            _ => {
+                if !self.classes.contains_key(&name) {
+                    return Err((
+                        "Unknown type `".to_owned() + &name + "`.",
+                        annotation.position()
+                    ));
+                }
+
+                let arguments: Vec<Type> = annotation.arguments.iter()
+                    .map(|argument| self.resolve_variable_type(argument))
+                    .collect::<CompileResult<_>>()?;
+                let bindings: HashMap<String, Type> = self.class_bindings(&name, &arguments);
+
+                for (type_parameter, argument) in self.classes[&name].type_parameters.iter()
+                    .zip(&annotation.arguments) {
+                    self.check_bound(type_parameter, &bindings, &name, argument.position())?;
+                }
+
+                Type::Class(name, arguments)
+            }
+        });
+    }
+
+    // Resolve the type of something which holds a value, which cannot be `void`.
+    fn resolve_variable_type(&self, annotation: &TypeAnnotation) -> CompileResult<Type> {
+        let resolved: Type = self.resolve_type(annotation)?;
+
+        if resolved == Type::Void {
+            return Err((
+                "Only functions can have the type `void`.".to_owned(),
+                annotation.position()
+            ));
+        }
+
+        return Ok(resolved);
+    }
+
+    fn resolve_function_type(&mut self, declaration: &FunctionDeclarationStatement)
+        -> CompileResult<FunctionType> {
+        let outer: usize = self.type_parameters.len();
+        let type_parameters: Vec<TypeParameter> = self.declare_type_parameters(
+            &declaration.type_parameters
+        )?;
+        let parameters: Vec<Type> = declaration.parameters.iter()
+            .map(|parameter| self.resolve_variable_type(&parameter.parameter_type))
+            .collect::<CompileResult<_>>()?;
+        let return_type: Type = self.resolve_type(&declaration.return_type)?;
+
+        self.type_parameters.truncate(outer);
+
+        return Ok(FunctionType {
+            type_parameters,
+            parameters,
+            return_type
+        });
+    }
+
+    // Bring the type parameters of a generic function or class into scope, returning them along
+    // with their bounds, which can refer to any of them.
+    fn declare_type_parameters(&mut self, declarations: &[TypeParameterDeclaration])
+        -> CompileResult<Vec<TypeParameter>> {
+        let start: usize = self.type_parameters.len();
+
+        for declaration in declarations {
+            let name: String = declaration.name.value();
+
+            if self.find_type_parameter(&name).is_some() {
+                return Err((
+                    "Type parameter `".to_owned() + &name + "` is already declared.",
+                    declaration.name.token_position.to_owned()
+                ));
+            }
+
+            self.type_parameters.push(TypeParameter {
+                name,
+                bound: None
+            });
+        }
+
+        for (index, declaration) in declarations.iter().enumerate() {
+            if let Some(annotation) = &declaration.bound {
+                let bound: Type = self.resolve_type(annotation)?;
+
+                if !matches!(bound, Type::Class(..)) {
+                    return Err((
+                        "The bound of a type parameter must be a class, but found `".to_owned() +
+                            &bound.to_string() + "`.",
+                        annotation.position()
+                    ));
+                }
+
+                self.type_parameters[start + index].bound = Some(bound);
+            }
+        }
+
+        return Ok(self.type_parameters[start..].to_vec());
+    }
+
+    fn find_type_parameter(&self, name: &str) -> Option<&TypeParameter> {
+        return self.type_parameters.iter().rev()
+            .find(|type_parameter| type_parameter.name == name);
+    }
+
+    // The type parameters of a generic declaration used as types, as they are inside of it.
+    fn parameter_types(type_parameters: &[TypeParameter]) -> Vec<Type> {
+        return type_parameters.iter()
+            .map(|type_parameter| Type::Parameter(type_parameter.name.to_owned()))
+            .collect();
+    }
+
+    // The type arguments given to each type parameter of a class.
+    fn class_bindings(&self, class: &str, arguments: &[Type]) -> HashMap<String, Type> {
+        return self.classes[class].type_parameters.iter()
+            .map(|type_parameter| type_parameter.name.to_owned())
+            .zip(arguments.iter().cloned())
+            .collect();
+    }
+
+    // Check that the type argument bound to a type parameter is within its bound.
+    fn check_bound(&self, type_parameter: &TypeParameter, bindings: &HashMap<String, Type>,
+                   name: &str, position: Position) -> CompileResult<()> {
+        let (bound, argument): (&Type, &Type) = match (&type_parameter.bound,
+                                                        bindings.get(&type_parameter.name)) {
+            (Some(bound), Some(argument)) => (bound, argument),
+            _ => return Ok(())
+        };
+        let bound: Type = bound.substitute(bindings);
+
+        if !self.is_assignable(argument, &bound) {
+            return Err((
+                "`".to_owned() + &type_parameter.name + "` of `" + name + "` must be a `" +
+                    &bound.to_string() + "`, but found `" + &argument.to_string() + "`.",
+                position
+            ));
+        }
+
+        return Ok(());
+    }
+
+    // The superclass of a class with the given type arguments, along with the type arguments the
+    // class gives it.
+    fn find_superclass(&self, class: &str, arguments: &[Type]) -> Option<(String, Vec<Type>)> {
+        let class_type: &ClassType = &self.classes[class];
+        let bindings: HashMap<String, Type> = self.class_bindings(class, arguments);
+
+        return class_type.superclass.as_ref().map(|superclass| (
+            superclass.to_owned(),
+            class_type.superclass_arguments.iter()
+                .map(|argument| argument.substitute(&bindings))
+                .collect()
+        ));
+    }
+
+    // The interfaces a class with the given type arguments implements itself, along with the type
+    // arguments it gives them.
+    fn find_interfaces(&self, class: &str, arguments: &[Type]) -> Vec<(String, Vec<Type>)> {
+        let bindings: HashMap<String, Type> = self.class_bindings(class, arguments);
+
+        return self.classes[class].interfaces.iter()
+            .map(|(interface, interface_arguments)| (
+                interface.to_owned(),
+                interface_arguments.iter()
+                    .map(|argument| argument.substitute(&bindings))
+                    .collect()
+            ))
+            .collect();
+    }
+
+    // The type arguments of an ancestor of a class with the given type arguments, such as `int`
+    // for `Base` when `class Derived extends Base<int>`. Returns `None` if it is not an ancestor.
+    // The interfaces a class implements, or that its superclasses implement, are its ancestors too.
+    fn as_ancestor(&self, class: &str, arguments: &[Type], ancestor: &str) -> Option<Vec<Type>> {
+        if class == ancestor {
+            return Some(arguments.to_vec());
+        }
+
+        for (interface, interface_arguments) in self.find_interfaces(class, arguments) {
+            if interface == ancestor {
+                return Some(interface_arguments);
+            }
+        }
+
+        let (superclass, superclass_arguments): (String, Vec<Type>) = self.find_superclass(
+            class, arguments
+        )?;
+
+        return self.as_ancestor(&superclass, &superclass_arguments, ancestor);
+    }
+
+    // The closest class which instances of both types are instances of, if they are classes.
+    fn common_ancestor(&self, left: &Type, right: &Type) -> Option<Type> {
+        return match (left, right) {
+            (Type::Class(left, left_arguments), Type::Class(right, right_arguments)) => {
+                let mut current: Option<(String, Vec<Type>)> = Some((left.to_owned(),
+                                                                     left_arguments.to_owned()));
+
+                while let Some((class, arguments)) = current {
+                    if self.as_ancestor(right, right_arguments, &class).as_ref()
+                        == Some(&arguments) {
+                        return Some(Type::Class(class, arguments));
+                    }
+
+                    current = self.find_superclass(&class, &arguments);
+                }
+
+                None
+            },
+            _ => None
+        };
+    }
+
+    fn is_subclass(&self, class: &str, ancestor: &str) -> bool {
+        let mut current: Option<String> = Some(class.to_owned());
+
+        while let Some(name) = current {
+            if name == ancestor {
+                return true;
+            }
+
+            current = self.classes[&name].superclass.to_owned();
+        }
+
+        return false;
+    }
+
+    // Whether a value of type `from` can be stored somewhere expecting type `to`.
+    fn is_assignable(&self, from: &Type, to: &Type) -> bool {
+        return match (from, to) {
+            // The value is converted where it is stored, which `is_conversion` tells.
+            (Type::Integer, Type::Float) => true,
+            // Type arguments have to match exactly, as the values of a class can change.
+            (Type::Class(from, from_arguments), Type::Class(to, to_arguments)) => {
+                self.as_ancestor(from, from_arguments, to).as_ref() == Some(to_arguments)
+            },
+            // The values of enums never change, so a `Result` or `Option` of a subclass can be
+            // used as one of its superclass. Their ints are not converted, so they cannot be used
+            // as floats.
+            (Type::Result(from_value, from_error), Type::Result(to_value, to_error)) => {
+                self.is_assignable_unconverted(from_value, to_value)
+                    && self.is_assignable_unconverted(from_error, to_error)
+            },
+            (Type::Option(from), Type::Option(to)) => self.is_assignable_unconverted(from, to),
+            (Type::Null, Type::Nullable(_)) => true,
+            (Type::Nullable(from), Type::Nullable(to)) => self.is_assignable(from, to),
+            (_, Type::Nullable(to)) => self.is_assignable(from, to),
+            // A type parameter can be used as its bound.
+            (Type::Parameter(name), _) if from != to => match self.find_type_parameter(name) {
+                Some(TypeParameter { bound: Some(bound), .. }) => self.is_assignable(bound, to),
+                _ => false
+            },
+            _ => from == to
+        };
+    }
+
+    // Whether storing a value of type `from` somewhere expecting type `to` turns an int into a
+    // float.
+    fn is_conversion(from: &Type, to: &Type) -> bool {
+        return match (from, to) {
+            (Type::Integer, Type::Float) => true,
+            (Type::Nullable(from), Type::Nullable(to)) => Self::is_conversion(from, to),
+            (_, Type::Nullable(to)) => Self::is_conversion(from, to),
+            _ => false
+        };
+    }
+
+    fn is_assignable_unconverted(&self, from: &Type, to: &Type) -> bool {
+        return self.is_assignable(from, to) && !Self::is_conversion(from, to);
+    }
+
+    // Find a property of a class with the given type arguments, or of one of its ancestors.
+    fn find_property(&self, class: &str, arguments: &[Type], name: &str) -> Option<Type> {
+        if let Some(property) = self.classes[class].properties.get(name) {
+            return Some(property.substitute(&self.class_bindings(class, arguments)));
+        }
+
+        let (superclass, superclass_arguments): (String, Vec<Type>) = self.find_superclass(
+            class, arguments
+        )?;
+
+        return self.find_property(&superclass, &superclass_arguments, name);
+    }
+
+    // Whether a class is given to programs rather than declared by them, in which case they
+    // cannot create its instances or extend it.
+    fn is_built_in_class(&self, name: &str) -> bool {
+        return is_native_class(name)
+            || self.program.host_types().iter().any(|host_type| host_type.name == name);
+    }
+
+    fn find_method(&self, class: &str, arguments: &[Type], name: &str) -> Option<FunctionType> {
+        if let Some(method) = self.classes[class].methods.get(name) {
+            return Some(method.substitute(&self.class_bindings(class, arguments)));
+        }
+
+        let (superclass, superclass_arguments): (String, Vec<Type>) = self.find_superclass(
+            class, arguments
+        )?;
+
+        return self.find_method(&superclass, &superclass_arguments, name);
+    }
+
+    fn map_method(key: &Type, value: &Type, name: &str) -> Option<FunctionType> {
+        let (parameters, return_type): (Vec<Type>, Type) = match name {
+            "get" => (vec![key.to_owned()], value.to_owned()),
+            "set" => (vec![key.to_owned(), value.to_owned()], Type::Void),
+            "remove" => (vec![key.to_owned()], Type::Boolean),
+            "contains" => (vec![key.to_owned()], Type::Boolean),
+            "size" => (Vec::new(), Type::Integer),
+            _ => return None
+        };
+
+        return Some(FunctionType {
+            type_parameters: Vec::new(),
+            parameters,
+            return_type
+        });
+    }
+
+    fn list_method(element: &Type, name: &str) -> Option<FunctionType> {
+        let (parameters, return_type): (Vec<Type>, Type) = match name {
+            "push" => (vec![element.to_owned()], Type::Void),
+            "pop" => (Vec::new(), element.to_owned()),
+            "get" => (vec![Type::Integer], element.to_owned()),
+            "set" => (vec![Type::Integer, element.to_owned()], Type::Void),
+            "contains" => (vec![element.to_owned()], Type::Boolean),
+            "size" => (Vec::new(), Type::Integer),
+            _ => return None
+        };
+
+        return Some(FunctionType {
+            type_parameters: Vec::new(),
+            parameters,
+            return_type
+        });
+    }
+
+    // A method which takes no arguments and returns a value, used by the iteration protocol.
+    fn find_getter(&self, class: &str, arguments: &[Type], name: &str) -> Option<Type> {
+        return self.find_method(class, arguments, name)
+            .filter(|method| method.parameters.is_empty() && method.return_type != Type::Void)
+            .map(|method| method.return_type);
+    }
+
+    // The type of the items produced when iterating over an instance of a class. Classes can be
+    // iterated over if they have `has_next()` and `next()` methods, or an `iterator()` method
+    // returning an instance of a class which does.
+    fn iterator_item_type(&self, class: &str, arguments: &[Type]) -> Option<Type> {
+        let (iterator_class, iterator_arguments): (String, Vec<Type>) = match self.find_getter(
+            class, arguments, "iterator"
+        ) {
+            Some(Type::Class(iterator_class, iterator_arguments)) => {
+                (iterator_class, iterator_arguments)
+            },
+            Some(_) => return None,
+            None => (class.to_owned(), arguments.to_vec())
+        };
+
+        if self.find_getter(&iterator_class, &iterator_arguments, "has_next")
+            != Some(Type::Boolean) {
+            return None;
+        }
+
+        return self.find_getter(&iterator_class, &iterator_arguments, "next");
+    }
+
+    fn push_scope(&mut self) -> () {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn pop_scope(&mut self) -> () {
+        self.scopes.pop();
+
+        // Forget what was known about the variables of the scope.
+        let depth: usize = self.scopes.len();
+
+        self.flow.non_null.retain(|(_, scope)| *scope < depth);
+        self.flow.unassigned.retain(|(_, scope)| *scope < depth);
+    }
+
+    fn declare_variable(&mut self, name: String, variable_type: Type, position: Position)
+        -> CompileResult<()> {
+        let scope: &mut HashMap<String, Type> = self.scopes.last_mut().unwrap();
+
+        if scope.contains_key(&name) {
+            return Err((
+                "Variable `".to_owned() + &name + "` is already declared in this scope.",
+                position
+            ));
+        }
+
+        scope.insert(name, variable_type.to_owned());
+        self.types.insert(position, variable_type);
+
+        return Ok(());
+    }
+
+    fn lookup_variable(&self, name: &str) -> Option<Type> {
+        return self.find_variable(name).map(|(_, variable_type)| variable_type);
+    }
+
+    // Find the declared type of a variable, along with the index of the scope declaring it.
+    fn find_variable(&self, name: &str) -> Option<(usize, Type)> {
+        for (index, scope) in self.scopes.iter().enumerate().rev() {
+            if let Some(variable_type) = scope.get(name) {
+                return Some((index, variable_type.to_owned()));
+            }
+        }
+
+        return None;
+    }
+
+    // Record that a variable has been given a value of the given type.
+    fn assign_variable(&mut self, name: &str, value_type: &Type) -> () {
+        let (scope, variable_type): (usize, Type) = self.find_variable(name).unwrap();
+        let key: (String, usize) = (name.to_owned(), scope);
+
+        self.flow.unassigned.remove(&key);
+
+        if matches!(variable_type, Type::Nullable(_)) && !value_type.is_nullable() {
+            self.flow.non_null.insert(key);
+        } else {
+            self.flow.non_null.remove(&key);
+        }
+    }
+
+    // Stop treating variables which are assigned to in the given statements as not null. Loops
+    // use this, as a later iteration can see a value assigned by an earlier one.
+    fn forget_assigned(&mut self, statements: &[Statement], expressions: &[&Expression]) -> () {
+        let mut effects: Effects = Effects::default();
+
+        assigned_in_statements(statements, &mut effects);
+
+        for expression in expressions {
+            assigned_in_expression(expression, &mut effects);
+        }
+
+        if effects.called.iter().any(|name| self.runs_user_code(name)) {
+            self.forget_globals();
+        }
+
+        self.flow.non_null.retain(|(name, _)| !effects.assigned.contains(name));
+    }
+
+    // Stop treating globals as not null, after calling a function or method which might assign to
+    // them.
+    fn forget_globals(&mut self) -> () {
+        self.flow.non_null.retain(|(_, scope)| *scope != 0);
+    }
+
+    // Whether calling a function or method with the given name might run code of the program,
+    // rather than a native.
+    fn runs_user_code(&self, name: &str) -> bool {
+        return self.functions.contains_key(name)
+            || (self.classes.contains_key(name) && !self.is_built_in_class(name))
+            || self.classes.values().any(|class| class.methods.contains_key(name));
+    }
+
+    // Make sure that the constructor being checked, if any, has given every property which needs
+    // one a value before it leaves at the given position.
+    fn check_properties_assigned(&self, position: Position) -> CompileResult<()> {
+        return match self.flow.unassigned_properties.iter().min() {
+            Some(property) => Err((
+                "Property `".to_owned() + property + "` might not be given a value by the \
+                    constructor.",
+                position
+            )),
+            None => Ok(())
+        };
+    }
+
+    fn is_top_level(&self) -> bool {
+        return self.scopes.len() == 1 && self.return_type.is_none();
+    }
+
+    fn check_block(&mut self, statements: &[Statement]) -> CompileResult<()> {
+        self.push_scope();
+
+        for statement in statements {
+            self.check_statement(statement)?;
+        }
+
+        self.pop_scope();
+
+        return Ok(());
+    }
+
+    fn check_statement(&mut self, statement: &Statement) -> CompileResult<()> {
+        self.stack.check(statement.position())?;
+
+        match statement {
+            Statement::Expression(expression_statement) => {
+                self.check_expression(&expression_statement.expression, None)?;
+            },
+            Statement::VariableDeclaration(declaration) => {
+                self.check_variable_declaration(declaration)?;
+            },
+            Statement::Block(block) => self.check_block(&block.statements)?,
+            Statement::If(if_statement) => {
+                let (when_true, when_false): (FlowState, FlowState) =
+                    self.check_branch_condition(&if_statement.condition)?;
+
+                self.flow = when_true;
+                self.check_block(&if_statement.then_branch)?;
+
+                let after_then: FlowState = std::mem::replace(&mut self.flow, when_false);
+
+                self.check_block(&if_statement.else_branch)?;
+
+                // A branch which always leaves, like an early return, does not continue below.
+                match (Self::always_exits(&if_statement.then_branch),
+                       Self::always_exits(&if_statement.else_branch)) {
+                    (true, _) => {},
+                    (false, true) => self.flow = after_then,
+                    (false, false) => self.flow = after_then.join(&self.flow)
+                }
+            },
+            Statement::While(while_statement) => {
+                self.forget_assigned(&while_statement.body, &[&while_statement.condition]);
+
+                let before: FlowState = self.flow.to_owned();
+                let (when_true, when_false): (FlowState, FlowState) =
+                    self.check_branch_condition(&while_statement.condition)?;
+
+                self.flow = when_true;
+                self.check_loop_body(&while_statement.body)?;
+
+                // The body might not run at all, and a `break` skips the final condition.
+                self.flow = if Self::contains_break(&while_statement.body) {
+                    before
+                } else {
+                    when_false
+                };
+            },
+            Statement::Loop(loop_statement) => {
+                self.forget_assigned(&loop_statement.body, &[]);
+
+                let before: FlowState = self.flow.to_owned();
+
+                self.check_loop_body(&loop_statement.body)?;
+
+                self.flow = before;
+            },
+            Statement::For(for_statement) => {
+                self.push_scope();
+
+                let variable_type: Type = self.resolve_variable_type(
+                    &for_statement.variable_type
+                )?;
+
+                let initializer_type: Type = self.check_expression_assignable(
+                    &for_statement.initializer,
+                    &variable_type
+                )?;
+
+                self.declare_variable(
+                    for_statement.variable.value(),
+                    variable_type,
+                    for_statement.variable.token_position.to_owned()
+                )?;
+                self.assign_variable(&for_statement.variable.value(), &initializer_type);
+                self.forget_assigned(&for_statement.body,
+                                     &[&for_statement.condition, &for_statement.increment]);
+
+                let before: FlowState = self.flow.to_owned();
+                let (when_true, when_false): (FlowState, FlowState) =
+                    self.check_branch_condition(&for_statement.condition)?;
+
+                self.flow = when_true;
+                self.check_loop_body(&for_statement.body)?;
+                self.check_expression(&for_statement.increment, None)?;
+
+                self.flow = if Self::contains_break(&for_statement.body) {
+                    before
+                } else {
+                    when_false
+                };
+
+                self.pop_scope();
+            },
+            Statement::ForIn(for_in_statement) => self.check_for_in(for_in_statement)?,
+            Statement::Break(break_statement) => {
+                if self.loop_depth == 0 {
+                    return Err(("`break` can only be used inside of a loop.".to_owned(),
+                                        break_statement.position.to_owned()));
+                }
+
+                self.check_not_leaving_finally("break", break_statement.position.to_owned())?;
+            },
+            Statement::Continue(continue_statement) => {
+                if self.loop_depth == 0 {
+                    return Err((
+                        "`continue` can only be used inside of a loop.".to_owned(),
+                        continue_statement.position.to_owned()
+                    ));
+                }
+
+                self.check_not_leaving_finally("continue", continue_statement.position.to_owned())?;
+            },
+            Statement::FunctionDeclaration(declaration) => {
+                if !self.is_top_level() {
+                    return Err((
+                        "Functions can only be declared at the top level.".to_owned(),
+                        declaration.name.token.token_position.to_owned()
+                    ));
+                }
+
+                let function_type: FunctionType = self.functions[&declaration.name.token.value()]
+                    .to_owned();
+
+                self.check_function(declaration, &function_type, HashSet::new())?;
+            },
+            Statement::Return(return_statement) => {
+                let return_type: Type = match &self.return_type {
+                    Some(return_type) => return_type.to_owned(),
+                    None => return Err((
+                        "`return` can only be used inside of a function.".to_owned(),
+                        return_statement.position.to_owned()
+                    ))
+                };
+
+                if self.finally_loop_depth.is_some() {
+                    return Err(("Cannot return from a `finally` block.".to_owned(),
+                                        return_statement.position.to_owned()));
+                }
+
+                match &return_statement.value {
+                    Some(value) => {
+                        if return_type == Type::Void {
+                            return Err((
+                                "A `void` function cannot return a value.".to_owned(),
+                                value.position()
+                            ));
+                        }
+
+                        self.check_expression_expecting(value, &return_type)?;
+                    },
+                    None => {
+                        if return_type != Type::Void {
+                            return Err((
+                                "Expected a return value of type `".to_owned() +
+                                    &return_type.to_string() + "`.",
+                                return_statement.position.to_owned()
+                            ));
+                        }
+                    }
+                }
+
+                self.check_properties_assigned(return_statement.position)?;
+            },
+            Statement::ClassDeclaration(declaration) => {
+                if !self.is_top_level() {
+                    return Err((
+                        "Classes can only be declared at the top level.".to_owned(),
+                        declaration.name.token.token_position.to_owned()
+                    ));
+                }
+
+                self.check_class(declaration)?;
+            },
+            Statement::InterfaceDeclaration(declaration) => {
+                if !self.is_top_level() {
+                    return Err((
+                        "Interfaces can only be declared at the top level.".to_owned(),
+                        declaration.name.token.token_position.to_owned()
+                    ));
+                }
+            },
+            Statement::EnumDeclaration(declaration) => {
+                if !self.is_top_level() {
+                    return Err((
+                        "Enums can only be declared at the top level.".to_owned(),
+                        declaration.name.token.token_position.to_owned()
+                    ));
+                }
+            },
+            Statement::Throw(throw_statement) => {
+                let thrown: Type = self.check_expression(&throw_statement.value, None)?;
+
+                if !self.is_error_type(&thrown) {
+                    return Err((
+                        "Only errors can be thrown, but found `".to_owned() +
+                            &thrown.to_string() + "`.",
+                        throw_statement.value.position()
+                    ));
+                }
+            },
+            Statement::Try(try_statement) => self.check_try(try_statement)?,
+            // Imports are replaced by the modules they import before a program is checked, tests
+            // are turned into functions, and trivia is only parsed by the formatter.
+            Statement::Import(_) | Statement::Test(_) | Statement::Trivia(_) => unreachable!()
+        }
+
+        return Ok(());
+    }
+
+    // Whether values of a type can be thrown, which is the case for instances of `Error` and of
+    // the classes extending it.
+    fn is_error_type(&self, value_type: &Type) -> bool {
+        return match value_type {
+            Type::Class(name, _) => self.is_subclass(name, ERROR_CLASS),
+            _ => false
+        };
+    }
+
+    fn check_not_leaving_finally(&self, keyword: &str, position: Position) -> CompileResult<()> {
+        if self.finally_loop_depth == Some(self.loop_depth) {
+            return Err((
+                "Cannot leave a `finally` block with `".to_owned() + keyword + "`.",
+                position
+            ));
+        }
+
+        return Ok(());
+    }
+
+    fn check_try(&mut self, try_statement: &TryStatement) -> CompileResult<()> {
+        // An error can stop the body anywhere, so the catches and the finally block cannot count
+        // on what it does.
+        self.forget_assigned(&try_statement.body, &[]);
+
+        let before: FlowState = self.flow.to_owned();
+        let mut after: Vec<FlowState> = Vec::new();
+
+        self.check_block(&try_statement.body)?;
+
+        if !Self::always_exits(&try_statement.body) {
+            after.push(self.flow.to_owned());
+        }
+
+        for catch in &try_statement.catches {
+            let error_type: Type = match &catch.error_type {
+                Some(annotation) => self.resolve_type(annotation)?,
+                None => Type::Class(ERROR_CLASS.to_owned(), Vec::new())
+            };
+
+            if !self.is_error_type(&error_type) {
+                return Err((
+                    "Only errors can be caught, but found `".to_owned() +
+                        &error_type.to_string() + "`.",
+                    catch.error_type.as_ref().unwrap().name.token_position.to_owned()
+                ));
+            }
+
+            self.flow = before.to_owned();
+            self.push_scope();
+            self.declare_variable(catch.variable.value(), error_type.to_owned(),
+                                  catch.variable.token_position.to_owned())?;
+            self.assign_variable(&catch.variable.value(), &error_type);
+            self.check_block(&catch.body)?;
+            self.pop_scope();
+
+            if !Self::always_exits(&catch.body) {
+                after.push(self.flow.to_owned());
+            }
+        }
+
+        let joined: FlowState = match after.split_first() {
+            Some((first, rest)) => rest.iter().fold(first.to_owned(), |flow, other| flow.join(other)),
+            None => before.to_owned()
+        };
+
+        if let Some(finally) = &try_statement.finally {
+            let enclosing: Option<i32> = self.finally_loop_depth.replace(self.loop_depth);
+
+            // The finally block also runs when an error stops the body or a catch early.
+            self.flow = before.join(&joined);
+            self.check_block(finally)?;
+            self.finally_loop_depth = enclosing;
+        }
+
+        self.flow = joined;
+
+        if let Some(finally) = &try_statement.finally {
+            self.forget_assigned(finally, &[]);
+        }
+
+        return Ok(());
+    }
+
+    fn check_variable_declaration(&mut self, declaration: &VariableDeclarationStatement)
+        -> CompileResult<()> {
+        let name: String = declaration.identifier.token.value();
+        let variable_type: Type = self.resolve_variable_type(&declaration.variable_type)?;
+        let initialization_type: Option<Type> = declaration.initialization.as_ref()
+            .map(|initialization| self.check_expression_assignable(initialization,
+                                                                   &variable_type))
+            .transpose()?;
+
+        self.declare_variable(
+            name.to_owned(),
+            variable_type,
+            declaration.identifier.token.token_position.to_owned()
+        )?;
+
+        match initialization_type {
+            Some(initialization_type) => self.assign_variable(&name, &initialization_type),
+            None => {
+                self.flow.unassigned.insert((name, self.scopes.len() - 1));
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn check_for_in(&mut self, for_in_statement: &ForInStatement) -> CompileResult<()> {
+        let iterable: Type = self.check_expression(&for_in_statement.iterable, None)?;
+
+        let variable_types: Vec<Type> = match &iterable {
+            Type::Range => vec![Type::Integer],
+            Type::List(element) => vec![element.as_ref().to_owned()],
+            // Maps can be iterated over by key, or by key and value.
+            Type::Map(key, value) if for_in_statement.variables.len() == 2 => {
+                vec![key.as_ref().to_owned(), value.as_ref().to_owned()]
+            },
+            Type::Map(key, _) => vec![key.as_ref().to_owned()],
+            Type::Nullable(_) => {
+                return Err(self.null_error(&iterable, for_in_statement.iterable.position()))
+            },
+            Type::Class(class, arguments) => match self.iterator_item_type(class, arguments) {
+                Some(item) => vec![item],
+                None => return Err((
+                    "Class `".to_owned() + class + "` cannot be iterated over; it needs \
+                        `has_next()` and `next()` methods, or an `iterator()` method.",
+                    for_in_statement.iterable.position()
+                ))
+            },
+            _ => return Err((
+                "Type `".to_owned() + &iterable.to_string() + "` cannot be iterated over.",
+                for_in_statement.iterable.position()
+            ))
+        };
+
+        if for_in_statement.variables.len() != variable_types.len() {
+            return Err((
+                "Iterating over `".to_owned() + &iterable.to_string() + "` produces " +
+                    &variable_types.len().to_string() + " value(s), but " +
+                    &for_in_statement.variables.len().to_string() + " variable(s) were given.",
+                for_in_statement.variables[0].token_position.to_owned()
+            ));
+        }
+
+        // The methods of an iterable class run between iterations.
+        if matches!(iterable, Type::Class(..)) {
+            self.forget_globals();
+        }
+
+        self.forget_assigned(&for_in_statement.body, &[]);
+
+        let before: FlowState = self.flow.to_owned();
+
+        self.push_scope();
+
+        for (variable, variable_type) in for_in_statement.variables.iter()
+            .zip(variable_types) {
+            self.declare_variable(variable.value(), variable_type,
+                                  variable.token_position.to_owned())?;
+        }
+
+        self.check_loop_body(&for_in_statement.body)?;
+
+        self.pop_scope();
+
+        self.flow = before;
+
+        return Ok(());
+    }
+
+    fn check_condition(&mut self, condition: &Expression) -> CompileResult<()> {
+        self.check_expression_expecting(condition, &Type::Boolean)?;
+
+        return Ok(());
+    }
+
+    // Check a condition, returning what is known when it is true and when it is false.
+    fn check_branch_condition(&mut self, condition: &Expression)
+        -> CompileResult<(FlowState, FlowState)> {
+        self.check_condition(condition)?;
+
+        let mut when_true: FlowState = self.flow.to_owned();
+        let mut when_false: FlowState = self.flow.to_owned();
+
+        when_true.non_null.extend(self.null_checks(condition, true));
+        when_false.non_null.extend(self.null_checks(condition, false));
+
+        return Ok((when_true, when_false));
+    }
+
+    // The nullable variables which cannot be null when the condition has the given result, such
+    // as `x` in `x != null`.
+    fn null_checks(&self, condition: &Expression, result: bool) -> Vec<(String, usize)> {
+        return match condition {
+            Expression::Binary(binary) => match (binary.operator, result) {
+                (BinaryOperator::NotEqual, true) | (BinaryOperator::Equal, false) => {
+                    let checked: &Expression = match (binary.left.as_ref(), binary.right.as_ref()) {
+                        (checked, Expression::Literal(literal))
+                            | (Expression::Literal(literal), checked)
+                            if literal.value == LiteralValue::Null => checked,
+                        _ => return Vec::new()
+                    };
+
+                    match checked {
+                        Expression::Identifier(identifier) => {
+                            let name: String = identifier.token.value();
+
+                            match self.find_variable(&name) {
+                                Some((scope, Type::Nullable(_))) => vec![(name, scope)],
+                                _ => Vec::new()
+                            }
+                        },
+                        _ => Vec::new()
+                    }
+                },
+                // Both sides of `&&` are true when it is, and both sides of `||` are false when
+                // it is.
+                (BinaryOperator::And, true) | (BinaryOperator::Or, false) => {
+                    let mut checks: Vec<(String, usize)> = self.null_checks(&binary.left, result);
+
+                    checks.extend(self.null_checks(&binary.right, result));
+
+                    checks
+                },
+                _ => Vec::new()
+            },
+            Expression::Unary(unary) if unary.operator == UnaryOperator::Not => {
+                self.null_checks(&unary.operand, !result)
+            },
+            _ => Vec::new()
+        };
+    }
+
+    fn check_loop_body(&mut self, body: &[Statement]) -> CompileResult<()> {
+        self.loop_depth += 1;
+        self.check_block(body)?;
+        self.loop_depth -= 1;
+
+        return Ok(());
+    }
+
+    // Check a function, method or constructor. Constructors are given the properties they have
+    // to assign to before they return.
+    fn check_function(&mut self, declaration: &FunctionDeclarationStatement,
+                      function_type: &FunctionType, unassigned_properties: HashSet<String>)
+        -> CompileResult<()> {
+        let enclosing_loop_depth: i32 = self.loop_depth;
+        // Functions can be called from anywhere, so nothing is known about the variables outside
+        // of them.
+        let enclosing_flow: FlowState = std::mem::take(&mut self.flow);
+
+        self.flow.unassigned_properties = unassigned_properties;
+
+        let outer: usize = self.type_parameters.len();
+
+        self.type_parameters.extend(function_type.type_parameters.iter().cloned());
+        self.return_type = Some(function_type.return_type.to_owned());
+        self.loop_depth = 0;
+        self.push_scope();
+
+        for (parameter, parameter_type) in declaration.parameters.iter()
+            .zip(function_type.parameters.iter()) {
+            self.declare_variable(
+                parameter.name.value(),
+                parameter_type.to_owned(),
+                parameter.name.token_position.to_owned()
+            )?;
+        }
+
+        self.check_block(&declaration.body)?;
+
+        if !Self::always_exits(&declaration.body) {
+            self.check_properties_assigned(declaration.name.token.token_position)?;
+        }
+
+        self.pop_scope();
+        self.type_parameters.truncate(outer);
+        self.return_type = None;
+        self.loop_depth = enclosing_loop_depth;
+        self.flow = enclosing_flow;
+
+        if function_type.return_type != Type::Void && !Self::always_returns(&declaration.body) {
+            return Err((
+                "Function `".to_owned() + &declaration.name.token.value() +
+                    "` does not return a value on every path.",
+                declaration.name.token.token_position.to_owned()
+            ));
+        }
+
+        return Ok(());
+    }
+
+    fn check_class(&mut self, declaration: &ClassDeclarationStatement) -> CompileResult<()> {
+        let name: String = declaration.name.token.value();
+        // Property initializers run whenever an instance is created.
+        let enclosing_flow: FlowState = std::mem::take(&mut self.flow);
+
+        self.type_parameters = self.classes[&name].type_parameters.to_owned();
+        self.check_implementations(declaration)?;
+
+        // Property initializers run before the constructor, without access to `this`.
+        for property in &declaration.properties {
+            if let Some(initialization) = &property.initialization {
+                let property_type: Type = self.classes[&name].properties
+                    [&property.identifier.token.value()].to_owned();
+
+                self.check_expression_expecting(initialization, &property_type)?;
+            }
+        }
+
+        self.current_class = Some(name.to_owned());
+
+        // Properties which would otherwise be used before they are given a value, since they are
+        // neither nullable nor initialized, have to be assigned to by the constructor.
+        let unassigned_properties: Vec<&VariableDeclarationStatement> = declaration.properties
+            .iter()
+            .filter(|property| {
+                property.initialization.is_none() && !self.classes[&name].properties
+                    [&property.identifier.token.value()].is_nullable()
+            })
+            .collect();
+
+        match &declaration.constructor {
+            Some(constructor) => {
+                let constructor_type: FunctionType = self.find_constructor(
+                    &name,
+                    &Self::parameter_types(&self.type_parameters)
+                );
+                let unassigned_properties: HashSet<String> = unassigned_properties.iter()
+                    .map(|property| property.identifier.token.value())
+                    .collect();
+
+                self.check_function(constructor, &constructor_type, unassigned_properties)?;
+            },
+            None => if let Some(property) = unassigned_properties.first() {
+                return Err((
+                    "Property `".to_owned() + &property.identifier.token.value() +
+                        "` is never given a value; initialize it, or assign to it in a \
+                        constructor.",
+                    property.identifier.token.token_position
+                ));
+            }
+        }
+
+        for method in &declaration.methods {
+            let method_type: FunctionType = self.classes[&name].methods
+                [&method.name.token.value()].to_owned();
+
+            self.check_function(method, &method_type, HashSet::new())?;
+        }
+
+        self.current_class = None;
+        self.type_parameters.clear();
+        self.flow = enclosing_flow;
+
+        return Ok(());
+    }
+
+    // Check that a class has every method of the interfaces it implements, either declared by it or
+    // inherited, with the parameters the interface gives them. Methods can return a more specific
+    // type than the interface does.
+    fn check_implementations(&self, declaration: &ClassDeclarationStatement) -> CompileResult<()> {
+        let name: String = declaration.name.token.value();
+        let arguments: Vec<Type> = Self::parameter_types(&self.type_parameters);
+        let interfaces: Vec<(String, Vec<Type>)> = self.classes[&name].interfaces.to_owned();
+
+        for ((interface, interface_arguments), annotation) in interfaces.iter()
+            .zip(&declaration.interfaces) {
+            let bindings: HashMap<String, Type> = self.class_bindings(interface,
+                                                                      interface_arguments);
+            let mut method_names: Vec<&String> = self.classes[interface].methods.keys().collect();
+
+            method_names.sort();
+
+            for method_name in method_names {
+                let required: FunctionType = self.classes[interface].methods[method_name]
+                    .substitute(&bindings);
+                let interface_type: Type = Type::Class(interface.to_owned(),
+                                                       interface_arguments.to_owned());
+
+                let method: FunctionType = match self.find_method(&name, &arguments, method_name) {
+                    Some(method) => method,
+                    None => return Err((
+                        "Class `".to_owned() + &name + "` does not implement the method `" +
+                            method_name + "` of `" + &interface_type.to_string() + "`.",
+                        annotation.position()
+                    ))
+                };
+
+                if !self.implements_method(&method, &required) {
+                    let position: Position = declaration.methods.iter()
+                        .find(|declared| declared.name.token.value() == *method_name)
+                        .map_or(annotation.position(),
+                                |declared| declared.name.token.token_position.to_owned());
+
+                    return Err((
+                        "Method `".to_owned() + method_name + "` of class `" + &name +
+                            "` must match `" + &required.signature(method_name) + "` of `" +
+                            &interface_type.to_string() + "`.",
+                        position
+                    ));
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    // Whether a method can be called wherever an interface method with the required type is.
+    fn implements_method(&self, method: &FunctionType, required: &FunctionType) -> bool {
+        if method.type_parameters.len() != required.type_parameters.len() {
+            return false;
+        }
+
+        // The type parameters of the two methods only differ by their names.
+        let renames: HashMap<String, Type> = required.type_parameters.iter()
+            .zip(&method.type_parameters)
+            .map(|(required, own)| (required.name.to_owned(), Type::Parameter(own.name.to_owned())))
+            .collect();
+        let required: FunctionType = required.substitute(&renames);
+        let same_bounds: bool = required.type_parameters.iter()
+            .zip(&method.type_parameters)
+            .all(|(required, own)| required.bound == own.bound);
+
+        return same_bounds && required.parameters == method.parameters
+            && self.is_assignable_unconverted(&method.return_type, &required.return_type);
+    }
+
+    // Whether running the given statements is guaranteed to end in a `return`, or in a `throw`
+    // which leaves the function just the same.
+    fn always_returns(statements: &[Statement]) -> bool {
+        return statements.iter().any(|statement| match statement {
+            Statement::Return(_) | Statement::Throw(_) => true,
+            Statement::Try(try_statement) => {
+                (Self::always_returns(&try_statement.body)
+                    && try_statement.catches.iter().all(|catch| Self::always_returns(&catch.body)))
+                    || try_statement.finally.as_ref()
+                        .is_some_and(|finally| Self::always_returns(finally))
+            },
+            Statement::Block(block) => Self::always_returns(&block.statements),
+            Statement::If(if_statement) => {
+                Self::always_returns(&if_statement.then_branch)
+                    && Self::always_returns(&if_statement.else_branch)
+            },
+            // A loop which is never broken out of can only be left by returning.
+            Statement::Loop(loop_statement) => !Self::contains_break(&loop_statement.body),
+            // Matches are exhaustive, so one returns if all of its arms do.
+            Statement::Expression(expression_statement) => match &expression_statement.expression {
+                Expression::Match(match_expression) => match_expression.arms.iter()
+                    .all(|arm| match &arm.body {
+                        MatchArmBody::Block(body) => Self::always_returns(body),
+                        MatchArmBody::Expression(_) => false
+                    }),
+                _ => false
+            },
+            _ => false
+        });
+    }
+
+    // Whether running the given statements is guaranteed to leave them early, by returning or by
+    // moving on to another iteration of a loop.
+    fn always_exits(statements: &[Statement]) -> bool {
+        return Self::always_returns(statements) || statements.iter().any(|statement| match statement {
+            Statement::Break(_) | Statement::Continue(_) => true,
+            Statement::Block(block) => Self::always_exits(&block.statements),
+            Statement::Try(try_statement) => {
+                Self::always_exits(&try_statement.body)
+                    && try_statement.catches.iter().all(|catch| Self::always_exits(&catch.body))
+            },
+            Statement::If(if_statement) => {
+                Self::always_exits(&if_statement.then_branch)
+                    && Self::always_exits(&if_statement.else_branch)
+            },
+            _ => false
+        });
+    }
+
+    // Whether the statements contain a `break` which applies to the enclosing loop.
+    fn contains_break(statements: &[Statement]) -> bool {
+        return statements.iter().any(|statement| match statement {
+            Statement::Break(_) => true,
+            Statement::Block(block) => Self::contains_break(&block.statements),
+            Statement::Try(try_statement) => {
+                Self::contains_break(&try_statement.body)
+                    || try_statement.catches.iter().any(|catch| Self::contains_break(&catch.body))
+            },
+            Statement::If(if_statement) => {
+                Self::contains_break(&if_statement.then_branch)
+                    || Self::contains_break(&if_statement.else_branch)
+            },
+            Statement::Expression(expression_statement) => match &expression_statement.expression {
+                Expression::Match(match_expression) => match_expression.arms.iter()
+                    .any(|arm| match &arm.body {
+                        MatchArmBody::Block(body) => Self::contains_break(body),
+                        MatchArmBody::Expression(_) => false
+                    }),
+                _ => false
+            },
+            _ => false
+        });
+    }
+
+    fn check_expression_expecting(&mut self, expression: &Expression, expected: &Type)
+        -> CompileResult<()> {
+        self.check_expression_assignable(expression, expected)?;
+
+        return Ok(());
+    }
+
+    // Check that an expression can be stored somewhere expecting the given type, returning the
+    // type it actually has.
+    fn check_expression_assignable(&mut self, expression: &Expression, expected: &Type)
+        -> CompileResult<Type> {
+        let found: Type = self.check_expression(expression, Some(expected))?;
+
+        self.check_assignable(&found, expected, expression.position())?;
+
+        return Ok(found);
+    }
+
+    // Check that a value of the found type can be stored somewhere expecting the given type.
+    fn check_assignable(&mut self, found: &Type, expected: &Type, position: Position)
+        -> CompileResult<()> {
+        if Self::is_conversion(found, expected) {
+            self.conversions.insert(position);
+        }
+
+        if !self.is_assignable(found, expected) {
+            if let Type::Nullable(inner) = found {
+                if self.is_assignable(inner, expected) {
+                    return Err(self.null_error(found, position));
+                }
+            }
+
+            return Err((
+                "Expected a value of type `".to_owned() + &expected.to_string() +
+                    "`, but found `" + &found.to_string() + "`.",
+                position
+            ));
+        }
+
+        return Ok(());
+    }
+
+    fn null_error(&self, value_type: &Type, position: Position) -> (String, Position) {
+        return (
+            "A value of type `".to_owned() + &value_type.to_string() + "` might be null; compare \
+                it to `null` first, or use `?.` or `??`.",
+            position
+        );
+    }
+
+    fn check_arguments(&mut self, arguments: &[Expression], parameters: &[Type], name: &str,
+                       position: Position) -> CompileResult<()> {
+        self.check_argument_count(arguments, parameters, name, position)?;
+
+        for (argument, parameter) in arguments.iter().zip(parameters.iter()) {
+            self.check_expression_expecting(argument, parameter)?;
+        }
+
+        return Ok(());
+    }
+
+    fn check_argument_count(&self, arguments: &[Expression], parameters: &[Type], name: &str,
+                            position: Position) -> CompileResult<()> {
+        if arguments.len() != parameters.len() {
+            return Err((
+                "`".to_owned() + name + "` expects " + &parameters.len().to_string() +
+                    " argument(s), but " + &arguments.len().to_string() + " were given.",
+                position
+            ));
+        }
+
+        return Ok(());
+    }
+
+    // Check a call to a function, method or constructor, returning the type of its result. The
+    // type parameters of a generic function are inferred from the types of the arguments, and
+    // failing that from the type expected of the result.
+    fn check_function_call(&mut self, arguments: &[Expression], function_type: &FunctionType,
+                           name: &str, position: Position, expected: Option<&Type>)
+        -> CompileResult<Type> {
+        if function_type.type_parameters.is_empty() {
+            self.check_arguments(arguments, &function_type.parameters, name, position)?;
+
+            return Ok(function_type.return_type.to_owned());
+        }
+
+        self.check_argument_count(arguments, &function_type.parameters, name, position)?;
+
+        let names: Vec<String> = function_type.type_parameters.iter()
+            .map(|type_parameter| type_parameter.name.to_owned())
+            .collect();
+        let mut hints: HashMap<String, Type> = HashMap::new();
+        let mut bindings: HashMap<String, Type> = HashMap::new();
+        let mut argument_types: Vec<Type> = Vec::new();
+
+        if let Some(expected) = expected {
+            self.infer(&function_type.return_type, expected, &names, &mut hints);
+        }
+
+        for (argument, parameter) in arguments.iter().zip(&function_type.parameters) {
+            // Arguments such as empty lists need to know the type of their parameter, which they
+            // can once its type parameters are known.
+            let mut known: HashMap<String, Type> = hints.to_owned();
+
+            known.extend(bindings.to_owned());
+
+            let parameter_expected: Option<Type> = names.iter()
+                .all(|name| !parameter.mentions(name) || known.contains_key(name))
+                .then(|| parameter.substitute(&known));
+            let argument_type: Type = self.check_expression(argument, parameter_expected.as_ref())?;
+
+            self.infer(parameter, &argument_type, &names, &mut bindings);
+            argument_types.push(argument_type);
+        }
+
+        for type_parameter in &function_type.type_parameters {
+            if !bindings.contains_key(&type_parameter.name) {
+                let hint: Type = match hints.get(&type_parameter.name) {
+                    Some(hint) => hint.to_owned(),
+                    None => return Err((
+                        "Cannot infer the type parameter `".to_owned() + &type_parameter.name +
+                            "` of `" + name + "`.",
+                        position
+                    ))
+                };
+
+                bindings.insert(type_parameter.name.to_owned(), hint);
+            }
+        }
+
+        // The expected type is used where the arguments allow it, so that `Box<Shape> box =
+        // Box(circle)` gives a `Box<Shape>` rather than a `Box<Circle>`.
+        let mut preferred: HashMap<String, Type> = bindings.to_owned();
+
+        for (name, hint) in &hints {
+            if self.is_assignable(&bindings[name], hint) {
+                preferred.insert(name.to_owned(), hint.to_owned());
+            }
+        }
+
+        let fits: bool = argument_types.iter()
+            .zip(&function_type.parameters)
+            .all(|(argument_type, parameter)| {
+                self.is_assignable(argument_type, &parameter.substitute(&preferred))
+            });
+
+        if fits {
+            bindings = preferred;
+        }
+
+        for type_parameter in &function_type.type_parameters {
+            self.check_bound(type_parameter, &bindings, name, position)?;
+        }
+
+        for ((argument, parameter), argument_type) in arguments.iter()
+            .zip(&function_type.parameters)
+            .zip(&argument_types) {
+            self.check_assignable(argument_type, &parameter.substitute(&bindings),
+                                  argument.position())?;
+        }
+
+        return Ok(function_type.return_type.substitute(&bindings));
+    }
+
+    // Bind the type parameters with the given names which appear in a type by matching it against
+    // the type of a value given for it. A type parameter given values of different types is bound
+    // to one that all of them can be assigned to, so `1` and `2.5` give a `float`.
+    fn infer(&self, pattern: &Type, found: &Type, names: &[String],
+             bindings: &mut HashMap<String, Type>) -> () {
+        match (pattern, found) {
+            (Type::Parameter(name), _) if names.contains(name) => {
+                let bound: Type = match bindings.get(name) {
+                    None => found.to_owned(),
+                    Some(bound) if self.is_assignable(found, bound) => bound.to_owned(),
+                    Some(bound) if self.is_assignable(bound, found) => found.to_owned(),
+                    Some(bound) if *bound == Type::Null => found.to_nullable(),
+                    Some(bound) if *found == Type::Null => bound.to_nullable(),
+                    // The arguments which do not fit are reported once everything is inferred.
+                    Some(bound) => self.common_ancestor(bound, found)
+                        .unwrap_or(bound.to_owned())
+                };
+
+                bindings.insert(name.to_owned(), bound);
+            },
+            (Type::Nullable(_), Type::Null) => {},
+            (Type::Nullable(pattern), Type::Nullable(found))
+                | (Type::List(pattern), Type::List(found))
+                | (Type::Option(pattern), Type::Option(found)) => {
+                self.infer(pattern, found, names, bindings);
+            },
+            (Type::Nullable(pattern), _) => self.infer(pattern, found, names, bindings),
+            (Type::Map(pattern_key, pattern_value), Type::Map(found_key, found_value))
+                | (Type::Result(pattern_key, pattern_value),
+                   Type::Result(found_key, found_value)) => {
+                self.infer(pattern_key, found_key, names, bindings);
+                self.infer(pattern_value, found_value, names, bindings);
+            },
+            // The pattern can also be found as one of its ancestors, such as when an instance of
+            // a generic class is stored as an interface it implements.
+            (Type::Class(pattern_class, patterns), Type::Class(class, arguments)) => {
+                if let Some(arguments) = self.as_ancestor(class, arguments, pattern_class) {
+                    for (pattern, found) in patterns.iter().zip(&arguments) {
+                        self.infer(pattern, found, names, bindings);
+                    }
+                } else if let Some(patterns) = self.as_ancestor(pattern_class, patterns, class) {
+                    for (pattern, found) in patterns.iter().zip(arguments) {
+                        self.infer(pattern, found, names, bindings);
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+
+    // `expect_error` takes an expression of any type, even `void`, as its value is never used.
+    fn check_expect_error(&mut self, call: &FunctionCallExpression) -> CompileResult<Type> {
+        if call.arguments.len() != 1 {
+            return Err((
+                "`".to_owned() + EXPECT_ERROR + "` expects 1 argument(s), but " +
+                    &call.arguments.len().to_string() + " were given.",
+                call.position
+            ));
+        }
+
+        self.check_expression(&call.arguments[0], None)?;
+
+        return Ok(Type::Void);
+    }
+
+    // The values given to `assert_eq` have to be comparable with `==`.
+    fn check_comparable(&self, left: &Type, right: &Type, position: Position) -> CompileResult<()> {
+        if self.binary_result(BinaryOperator::Equal, left, right).is_none() {
+            return Err((
+                "Values of types `".to_owned() + &left.to_string() + "` and `" +
+                    &right.to_string() + "` cannot be compared.",
+                position
+            ));
+        }
+
+        return Ok(());
+    }
+
+    // Check the arguments of a call to a native function, returning their types.
+    fn check_native_arguments(&mut self, arguments: &[Expression], native: &Native,
+                              position: Position) -> CompileResult<Vec<Type>> {
+        if !native.accepts(arguments.len()) {
+            let at_least: &str = if native.variadic { "at least " } else { "" };
+            let expected: usize = native.parameters.len() - native.variadic as usize;
+
+            return Err((
+                "`".to_owned() + native.name + "` expects " + at_least + &expected.to_string() +
+                    " argument(s), but " + &arguments.len().to_string() + " were given.",
+                position
+            ));
+        }
+
+        let mut types: Vec<Type> = Vec::new();
+
+        for (argument, parameter) in arguments.iter()
+            .zip(native.parameter_types(arguments.len())) {
+            let argument_type: Type = match &parameter {
+                // Ints are kept as they are, so the native can tell that it was given only ints.
+                Some(parameter) if native.numeric => {
+                    let found: Type = self.check_expression(argument, Some(parameter))?;
+
+                    if found != Type::Integer {
+                        self.check_assignable(&found, parameter, argument.position())?;
+                    }
+
+                    found
+                },
+                Some(parameter) => self.check_expression_assignable(argument, parameter)?,
+                None => self.check_expression(argument, None)?
+            };
+
+            if parameter.is_none() && argument_type == Type::Void {
+                return Err((
+                    "Cannot pass a `void` value to `".to_owned() + native.name + "`.",
+                    argument.position()
+                ));
+            }
+
+            types.push(argument_type);
+        }
+
+        return Ok(types);
+    }
+
+    // What a call to a native function with arguments of the given types produces.
+    fn native_return_type(native: &Native, types: &[Type]) -> Type {
+        if native.numeric && types.iter().all(|argument| *argument == Type::Integer) {
+            return Type::Integer;
+        }
+
+        return native.return_type.to_owned();
+    }
+
+    // The type of an expression at the top level of the program.
+    pub fn expression_type(&mut self, expression: &Expression) -> CompileResult<Type> {
+        return self.check_expression(expression, None);
+    }
+
+    // Check an expression and return its type. The expected type, if known, is used to give a
+    // type to empty map literals and to the variants of `Result` and `Option`.
+    fn check_expression(&mut self, expression: &Expression, expected: Option<&Type>)
+        -> CompileResult<Type> {
+        self.stack.check(expression.position())?;
+
+        let expression_type: Type = self.check_expression_type(expression, expected)?;
+
+        // When expressions share a position, it keeps the type of the innermost one, which is
+        // checked first.
+        self.types.entry(expression.position()).or_insert(expression_type.to_owned());
+
+        return Ok(expression_type);
+    }
+
+    fn check_expression_type(&mut self, expression: &Expression, expected: Option<&Type>)
+        -> CompileResult<Type> {
+        return Ok(match expression {
+            Expression::Literal(literal) => Self::literal_type(&literal.value),
+            Expression::Identifier(identifier) => {
+                let name: String = identifier.token.value();
+
+                match self.find_variable(&name) {
+                    Some((scope, variable_type)) => {
+                        let key: (String, usize) = (name.to_owned(), scope);
+
+                        if self.flow.unassigned.contains(&key) {
+                            return Err((
+                                "Variable `".to_owned() + &name +
+                                    "` might not have been given a value.",
+                                identifier.token.token_position.to_owned()
+                            ));
+                        }
+
+                        match variable_type {
+                            Type::Nullable(inner) if self.flow.non_null.contains(&key) => *inner,
+                            _ => variable_type
+                        }
+                    },
+                    None => {
+                        let message: String = if self.functions.contains_key(&name)
+                            || self.classes.contains_key(&name) || find_native(&name).is_some()
+                            || self.program.find_host_function(&name).is_some() {
+                            "`".to_owned() + &name + "` must be called."
+                        } else if self.enums.contains_key(&name) {
+                            "Enum `".to_owned() + &name + "` can only be used through its variants."
+                        } else if is_module(&name) {
+                            "Module `".to_owned() + &name +
+                                "` can only be used through its functions."
+                        } else {
+                            "Undefined variable `".to_owned() + &name + "`."
+                        };
+
+                        return Err((message, identifier.token.token_position.to_owned()))
+                    }
+                }
+            },
+            Expression::Binary(binary) => self.check_binary(binary)?,
+            Expression::Unary(unary) => self.check_unary(unary)?,
+            Expression::Assignment(assignment) => self.check_assignment(assignment)?,
+            Expression::FunctionCall(call) => self.check_call(call, expected)?,
+            Expression::Property(property) => {
+                let built_in: Option<(String, Type, Vec<Type>)> = self.find_built_in_variant(
+                    property, expected
+                )?;
+
+                if let Some((enum_name, value_type, fields)) = built_in {
+                    if !fields.is_empty() {
+                        return Err((
+                            "Variant `".to_owned() + &enum_name + "." + &property.name.value() +
+                                "` must be called with its fields.",
+                            property.name.token_position.to_owned()
+                        ));
+                    }
+
+                    return Ok(value_type);
+                }
+
+                if let Some((enum_name, fields)) = self.find_variant(property)? {
+                    if !fields.is_empty() {
+                        return Err((
+                            "Variant `".to_owned() + &enum_name + "." + &property.name.value() +
+                                "` must be called with its fields.",
+                            property.name.token_position.to_owned()
+                        ));
+                    }
+
+                    return Ok(Type::Enum(enum_name));
+                }
+
+                let object_type: Type = self.check_member_object(property)?;
+                let name: String = property.name.value();
+
+                if let Type::Class(class, arguments) = &object_type {
+                    if let Some(property_type) = self.find_property(class, arguments, &name) {
+                        return Ok(if property.safe {
+                            property_type.to_nullable()
+                        } else {
+                            property_type
+                        });
+                    }
+
+                    if self.find_method(class, arguments, &name).is_some() {
+                        return Err((
+                            "Method `".to_owned() + &name + "` must be called.",
+                            property.name.token_position.to_owned()
+                        ));
+                    }
+                }
+
+                return Err((
+                    "Type `".to_owned() + &object_type.to_string() + "` has no property `" +
+                        &name + "`.",
+                    property.name.token_position.to_owned()
+                ))
+            },
+            Expression::This(this) => match &self.current_class {
+                Some(class) if self.return_type.is_some() => Type::Class(
+                    class.to_owned(),
+                    Self::parameter_types(&self.classes[class].type_parameters)
+                ),
+                _ => return Err((
+                    "`this` can only be used inside of a method.".to_owned(),
+                    this.position.to_owned()
+                ))
+            },
+            Expression::Super(super_expression) => return Err((
+                "`super` can only be used to call a method.".to_owned(),
+                super_expression.method.token_position.to_owned()
+            )),
+            Expression::Is(is) => {
+                let value_type: Type = self.check_expression(&is.value, None)?;
+                let class_name: String = is.class_name.value();
+
+                if matches!(value_type, Type::Nullable(_)) {
+                    return Err(self.null_error(&value_type, is.value.position()));
+                }
+
+                if !matches!(value_type, Type::Class(..)) {
+                    return Err((
+                        "Only class instances can be checked with `is`.".to_owned(),
+                        is.value.position()
+                    ));
+                }
+
+                if !self.classes.contains_key(&class_name) {
+                    return Err((
+                        "Unknown class `".to_owned() + &class_name + "`.",
+                        is.class_name.token_position.to_owned()
+                    ));
+                }
+
+                // Instances only know their classes once the program runs.
+                if self.classes[&class_name].interface {
+                    return Err((
+                        "The interface `".to_owned() + &class_name +
+                            "` cannot be checked with `is`.",
+                        is.class_name.token_position.to_owned()
+                    ));
+                }
+
+                Type::Boolean
+            },
+            Expression::Map(map) => self.check_map(map, expected)?,
+            Expression::List(list) => self.check_list(list, expected)?,
+            Expression::Range(range) => {
+                self.check_expression_expecting(&range.start, &Type::Integer)?;
+                self.check_expression_expecting(&range.end, &Type::Integer)?;
+
+                Type::Range
+            },
+            Expression::Match(match_expression) => self.check_match(match_expression, expected)?,
+            Expression::Propagate(propagate) => self.check_propagate(propagate)?
+        });
+    }
+
+    fn literal_type(literal: &LiteralValue) -> Type {
+        return match literal {
+            LiteralValue::Integer(_) => Type::Integer,
+            LiteralValue::Float(_) => Type::Float,
+            LiteralValue::String(_) => Type::String,
+            LiteralValue::Char(_) => Type::Char,
+            LiteralValue::Boolean(_) => Type::Boolean,
+            LiteralValue::Null => Type::Null
+        };
+    }
+
+    // Check the object of a property access or method call, returning the type whose members are
+    // used. Only `?.` can be used on values which might be null.
+    fn check_member_object(&mut self, property: &PropertyExpression) -> CompileResult<Type> {
+        let object_type: Type = self.check_expression(&property.object, None)?;
+
+        let object_type: Type = match object_type {
+            Type::Nullable(inner) if property.safe => *inner,
+            Type::Nullable(_) | Type::Null => {
+                return Err(self.null_error(&object_type, property.object.position()))
+            },
+            _ => object_type
+        };
+
+        // A type parameter has the members of its bound.
+        return Ok(match &object_type {
+            Type::Parameter(name) => self.find_type_parameter(name)
+                .and_then(|type_parameter| type_parameter.bound.to_owned())
+                .unwrap_or(object_type),
+            _ => object_type
+        });
+    }
+
+    // Find the native called by a call such as `fs.read(path)`, unless a variable hides the module.
+    // Modules cannot be used as values, so calling a function they do not have is an error.
+    fn find_module_native(&self, property: &PropertyExpression)
+        -> CompileResult<Option<&'static Native>> {
+        let is_variable = |name: &str| self.lookup_variable(name).is_some();
+
+        if let Some((_, native)) = find_module_native(property, is_variable) {
+            return Ok(Some(native));
+        }
+
+        if let Expression::Identifier(identifier) = property.object.as_ref() {
+            let module: String = identifier.token.value();
+
+            if is_module(&module) && !is_variable(&module) {
+                return Err((
+                    "Module `".to_owned() + &module + "` has no function `" +
+                        &property.name.value() + "`.",
+                    property.name.token_position.to_owned()
+                ));
+            }
+        }
+
+        return Ok(None);
+    }
+
+    // If the property names a variant of an enum, such as `Shape.Circle`, return the name of the
+    // enum and the types of the variant's fields.
+    fn find_variant(&self, property: &PropertyExpression)
+        -> CompileResult<Option<(String, Vec<Type>)>> {
+        let enum_name: String = match property.object.as_ref() {
+            Expression::Identifier(identifier) => identifier.token.value(),
+            _ => return Ok(None)
+        };
+
+        if self.lookup_variable(&enum_name).is_some() {
+            return Ok(None);
+        }
+
+        let enum_type: &EnumType = match self.enums.get(&enum_name) {
+            Some(enum_type) => enum_type,
+            None => return Ok(None)
+        };
+
+        return match enum_type.find_variant(&property.name.value()) {
+            Some(fields) => Ok(Some((enum_name, fields.to_owned()))),
+            None => Err((
+                "Enum `".to_owned() + &enum_name + "` has no variant `" +
+                    &property.name.value() + "`.",
+                property.name.token_position.to_owned()
+            ))
+        };
+    }
+
+    // If the property names a variant of `Result` or `Option`, such as `Result.Ok`, return the
+    // name of the enum, the type of the value and the types of the variant's fields. As with empty
+    // lists, which `Result` or `Option` it is comes from where the value goes.
+    fn find_built_in_variant(&self, property: &PropertyExpression, expected: Option<&Type>)
+        -> CompileResult<Option<(String, Type, Vec<Type>)>> {
+        let enum_name: String = match property.object.as_ref() {
+            Expression::Identifier(identifier) => identifier.token.value(),
+            _ => return Ok(None)
+        };
+
+        if !is_built_in_enum(&enum_name) || self.lookup_variable(&enum_name).is_some() {
+            return Ok(None);
+        }
+
+        let variant_name: String = property.name.value();
+        let expected: Option<&Type> = match expected {
+            Some(Type::Nullable(inner)) => Some(inner),
+            _ => expected
+        };
+        let (value_type, enum_type): (Type, EnumType) = match expected {
+            Some(expected) => match self.find_enum_type(expected) {
+                Some((name, enum_type)) if name == enum_name => (expected.to_owned(), enum_type),
+                _ => return Err((
+                    "Expected a value of type `".to_owned() + &expected.to_string() +
+                        "`, but found `" + &enum_name + "." + &variant_name + "`.",
+                    property.name.token_position.to_owned()
+                ))
+            },
+            None => return Err((
+                "Cannot infer the type of `".to_owned() + &enum_name + "." + &variant_name +
+                    "`; declare its type instead.",
+                property.name.token_position.to_owned()
+            ))
+        };
+
+        return Ok(match enum_type.find_variant(&variant_name) {
+            Some(fields) => Some((enum_name, value_type, fields.to_owned())),
+            None => return Err((
+                "Enum `".to_owned() + &enum_name + "` has no variant `" + &variant_name + "`.",
+                property.name.token_position.to_owned()
+            ))
+        });
+    }
+
+    // The name and variants of an enum type. The fields of the variants of `Result` and `Option`
+    // have the types they were given.
+    fn find_enum_type(&self, value_type: &Type) -> Option<(String, EnumType)> {
+        return match value_type {
+            Type::Enum(name) => Some((name.to_owned(), self.enums[name].to_owned())),
+            Type::Result(value, error) => Some((RESULT_ENUM.to_owned(), EnumType {
+                variants: vec![
+                    (String::from("Ok"), vec![value.as_ref().to_owned()]),
+                    (String::from("Err"), vec![error.as_ref().to_owned()])
+                ]
+            })),
+            Type::Option(value) => Some((OPTION_ENUM.to_owned(), EnumType {
+                variants: vec![
+                    (String::from("Some"), vec![value.as_ref().to_owned()]),
+                    (String::from("None"), Vec::new())
+                ]
+            })),
+            _ => None
+        };
+    }
+
+    // `value?` gives the value of a `Result.Ok` or `Option.Some`, and otherwise returns the
+    // `Result.Err` or `Option.None` as it is, so the function has to return the same kind of
+    // value, with room for the error.
+    fn check_propagate(&mut self, propagate: &PropagateExpression) -> CompileResult<Type> {
+        let value_type: Type = self.check_expression(&propagate.value, None)?;
+        let return_type: Type = match &self.return_type {
+            Some(return_type) => return_type.to_owned(),
+            None => return Err((
+                "`?` can only be used inside of a function.".to_owned(),
+                propagate.position.to_owned()
+            ))
+        };
+
+        if self.finally_loop_depth.is_some() {
+            return Err(("Cannot return from a `finally` block with `?`.".to_owned(),
+                                propagate.position.to_owned()));
+        }
+
+        let value: Type = match &value_type {
+            Type::Result(value, _) | Type::Option(value) => value.as_ref().to_owned(),
+            _ => return Err((
+                "`?` can only be used on a `Result` or an `Option`, but found `".to_owned() +
+                    &value_type.to_string() + "`.",
+                propagate.value.position()
+            ))
+        };
+        let returned: &Type = match &return_type {
+            Type::Nullable(inner) => inner,
+            _ => &return_type
+        };
+        let returnable: bool = match (&value_type, returned) {
+            (Type::Result(_, error), Type::Result(_, returned_error)) => {
+                self.is_assignable(error, returned_error)
+            },
+            (Type::Option(_), Type::Option(_)) => true,
+            _ => false
+        };
+
+        if !returnable {
+            return Err((
+                "Cannot use `?` on `".to_owned() + &value_type.to_string() +
+                    "` in a function returning `" + &return_type.to_string() + "`.",
+                propagate.position.to_owned()
+            ));
+        }
+
+        return Ok(value);
+    }
+
+    fn check_binary(&mut self, binary: &BinaryExpression) -> CompileResult<Type> {
+        if binary.operator == BinaryOperator::Coalesce {
+            return self.check_coalesce(binary);
+        }
+
+        let left: Type = self.check_expression(&binary.left, None)?;
+        let right: Type = match binary.operator {
+            // The right side of `&&` and `||` only runs for one result of the left side, so it
+            // might not run at all.
+            BinaryOperator::And | BinaryOperator::Or => {
+                let before: FlowState = self.flow.to_owned();
+                let checks: Vec<(String, usize)> = self.null_checks(
+                    &binary.left,
+                    binary.operator == BinaryOperator::And
+                );
+
+                self.flow.non_null.extend(checks);
+
+                let right: Type = self.check_expression(&binary.right, None)?;
+
+                self.flow = before.join(&self.flow);
+
+                right
+            },
+            // Values like `Option.None`, whose type depends on where they go, take the type of
+            // what they are compared to.
+            BinaryOperator::Equal | BinaryOperator::NotEqual => {
+                self.check_expression(&binary.right, Some(&left))?
+            },
+            _ => self.check_expression(&binary.right, None)?
+        };
+
+        return Ok(match self.binary_result(binary.operator, &left, &right) {
+            Some(result) => result,
+            None if matches!(left, Type::Nullable(_)) => {
+                return Err(self.null_error(&left, binary.left.position()))
+            },
+            None if matches!(right, Type::Nullable(_)) => {
+                return Err(self.null_error(&right, binary.right.position()))
+            },
+            None => return Err((
+                "Operator `".to_owned() + &binary.operator.to_string() +
+                    "` cannot be applied to `" + &left.to_string() + "` and `" +
+                    &right.to_string() + "`.",
+                binary.position.to_owned()
+            ))
+        });
+    }
+
+    fn check_coalesce(&mut self, binary: &BinaryExpression) -> CompileResult<Type> {
+        let left: Type = self.check_expression(&binary.left, None)?;
+
+        // Values which are known not to be null are left as they are.
+        let inner: Type = match left {
+            Type::Nullable(inner) => *inner,
+            Type::Null => return Err((
+                "The left side of `??` is always null.".to_owned(),
+                binary.left.position()
+            )),
+            _ => left
+        };
+
+        // The right side only runs when the left side is null.
+        let before: FlowState = self.flow.to_owned();
+        let right: Type = self.check_expression(&binary.right, Some(&inner))?;
+
+        self.flow = before.join(&self.flow);
+
+        if !self.is_assignable(&right, &inner.to_nullable()) {
+            return Err((
+                "Expected a value of type `".to_owned() + &inner.to_string() + "`, but found `" +
+                    &right.to_string() + "`.",
+                binary.right.position()
+            ));
+        }
+
+        if Self::is_conversion(&right, &inner.to_nullable()) {
+            self.conversions.insert(binary.right.position());
+        }
+
+        // The result can still be null if the replacement can be.
+        return Ok(if right.is_nullable() {
+            inner.to_nullable()
+        } else {
+            inner
+        });
+    }
+
+    // The type produced by a binary operation, or `None` if the operand types are not supported.
+    fn binary_result(&self, operator: BinaryOperator, left: &Type, right: &Type) -> Option<Type> {
+        let numeric_result: Type = if *left == Type::Float || *right == Type::Float {
+            Type::Float
+        } else {
+            Type::Integer
+        };
+        let is_printable = |value_type: &Type| matches!(
+            value_type,
+            Type::Integer | Type::Float | Type::String | Type::Char | Type::Boolean
+        );
+
+        return match operator {
+            BinaryOperator::Add if (*left == Type::String && is_printable(right))
+                || (*right == Type::String && is_printable(left)) => Some(Type::String),
+            BinaryOperator::Add | BinaryOperator::Subtract | BinaryOperator::Multiply |
+            BinaryOperator::Divide | BinaryOperator::Remainder | BinaryOperator::Power
+                if left.is_numeric() && right.is_numeric() => Some(numeric_result),
+            // Anything can be compared to `null`, including variables known not to be null.
+            BinaryOperator::Equal | BinaryOperator::NotEqual
+                if (left.is_numeric() && right.is_numeric()) || self.is_assignable(left, right)
+                    || self.is_assignable(right, left) || *left == Type::Null
+                    || *right == Type::Null => Some(Type::Boolean),
+            BinaryOperator::LessThan | BinaryOperator::LessThanOrEqual |
+            BinaryOperator::GreaterThan | BinaryOperator::GreaterThanOrEqual
+                if (left.is_numeric() && right.is_numeric())
+                    || (left == right && matches!(left, Type::String | Type::Char)) => {
+                Some(Type::Boolean)
+            },
+            BinaryOperator::And | BinaryOperator::Or
+                if *left == Type::Boolean && *right == Type::Boolean => Some(Type::Boolean),
+            _ => None
+        };
+    }
+
+    fn check_unary(&mut self, unary: &UnaryExpression) -> CompileResult<Type> {
+        let operand: Type = self.check_expression(&unary.operand, None)?;
+
+        // Only checked programs have conversions, which the REPL checks again to find the type of
+        // an entry.
+        if unary.operator == UnaryOperator::ToFloat {
+            return Ok(if operand.is_nullable() { Type::Float.to_nullable() } else { Type::Float });
+        }
+
+        if matches!(operand, Type::Nullable(_)) {
+            return Err(self.null_error(&operand, unary.operand.position()));
+        }
+
+        let valid: bool = match unary.operator {
+            UnaryOperator::Negate => operand.is_numeric(),
+            UnaryOperator::Not => operand == Type::Boolean,
+            UnaryOperator::ToFloat => unreachable!()
+        };
+
+        if !valid {
+            return Err((
+                "Operator `".to_owned() + &unary.operator.to_string() +
+                    "` cannot be applied to `" + &operand.to_string() + "`.",
+                unary.position.to_owned()
+            ));
+        }
+
+        return Ok(operand);
+    }
+
+    fn check_assignment(&mut self, assignment: &AssignmentExpression) -> CompileResult<Type> {
+        // Plain assignments to variables give them a value, without using their current one.
+        if let (Expression::Identifier(identifier), None) = (assignment.target.as_ref(),
+                                                             assignment.operator) {
+            let name: String = identifier.token.value();
+
+            let target: Type = match self.lookup_variable(&name) {
+                Some(target) => target,
+                None => return self.check_expression(&assignment.target, None)
+            };
+            let value: Type = self.check_expression_assignable(&assignment.value, &target)?;
+
+            self.assign_variable(&name, &value);
+
+            return Ok(target);
+        }
+
+        let target: Type = self.check_expression(&assignment.target, None)?;
+
+        match assignment.operator {
+            Some(operator) => {
+                let value: Type = self.check_expression(&assignment.value, None)?;
+                let result: Option<Type> = self.binary_result(operator, &target, &value);
+
+                if !result.is_some_and(|result| self.is_assignable(&result, &target)) {
+                    return Err((
+                        "Operator `".to_owned() + &operator.to_string() +
+                            "=` cannot be applied to `" + &target.to_string() + "` and `" +
+                            &value.to_string() + "`.",
+                        assignment.position.to_owned()
+                    ));
+                }
+            },
+            None => {
+                self.check_expression_expecting(&assignment.value, &target)?;
+
+                if let Expression::Property(property) = assignment.target.as_ref() {
+                    if matches!(property.object.as_ref(), Expression::This(_)) {
+                        self.flow.unassigned_properties.remove(&property.name.value());
+                    }
+                }
+            }
+        }
+
+        return Ok(target);
+    }
+
+    fn check_call(&mut self, call: &FunctionCallExpression, expected: Option<&Type>)
+        -> CompileResult<Type> {
+        match call.callee.as_ref() {
+            Expression::Identifier(identifier) if self.lookup_variable(
+                &identifier.token.value()
+            ).is_none() => {
+                let name: String = identifier.token.value();
+
+                if let Some(function_type) = self.functions.get(&name) {
+                    let function_type: FunctionType = function_type.to_owned();
+                    let return_type: Type = self.check_function_call(
+                        &call.arguments, &function_type, &name, call.position.to_owned(), expected
+                    )?;
+
+                    self.forget_globals();
+
+                    return Ok(return_type);
+                }
+
+                if self.is_built_in_class(&name) {
+                    return Err((
+                        "Instances of the built-in class `".to_owned() + &name +
+                            "` cannot be created.",
+                        identifier.token.token_position.to_owned()
+                    ));
+                }
+
+                if self.classes.get(&name).is_some_and(|class| class.interface) {
+                    return Err((
+                        "Instances of the interface `".to_owned() + &name + "` cannot be created.",
+                        identifier.token.token_position.to_owned()
+                    ));
+                }
+
+                if self.classes.contains_key(&name) {
+                    let type_parameters: Vec<TypeParameter> = self.classes[&name].type_parameters
+                        .to_owned();
+                    let class_type: Type = Type::Class(name.to_owned(),
+                                                       Self::parameter_types(&type_parameters));
+                    // The type arguments of a generic class are inferred as if its constructor
+                    // were a generic function returning the new instance.
+                    let constructor: FunctionType = FunctionType {
+                        parameters: self.find_constructor(
+                            &name,
+                            &Self::parameter_types(&type_parameters)
+                        ).parameters,
+                        type_parameters,
+                        return_type: class_type
+                    };
+                    let instance_type: Type = self.check_function_call(
+                        &call.arguments, &constructor, &name, call.position.to_owned(), expected
+                    )?;
+
+                    self.forget_globals();
+
+                    return Ok(instance_type);
+                }
+
+                if name == EXPECT_ERROR {
+                    return self.check_expect_error(call);
+                }
+
+                if let Some((_, native)) = find_native(&name) {
+                    let types: Vec<Type> = self.check_native_arguments(&call.arguments, native,
+                                                                       call.position)?;
+
+                    if name == "assert_eq" {
+                        self.check_comparable(&types[0], &types[1], call.arguments[1].position())?;
+                    }
+
+                    return Ok(Self::native_return_type(native, &types));
+                }
+
+                if let Some(function) = self.program.find_host_function(&name) {
+                    let function_type: FunctionType = function.function_type.to_owned();
+
+                    return self.check_function_call(&call.arguments, &function_type, &name,
+                                                    call.position.to_owned(), expected);
+                }
+
+                return Err((
+                    "Undefined function `".to_owned() + &name + "`.",
+                    identifier.token.token_position.to_owned()
+                ));
+            },
+            Expression::Property(property) => {
+                if let Some(native) = self.find_module_native(property)? {
+                    let types: Vec<Type> = self.check_native_arguments(&call.arguments, native,
+                                                                       call.position)?;
+
+                    return Ok(Self::native_return_type(native, &types));
+                }
+
+                if let Some((enum_name, value_type, fields)) = self.find_built_in_variant(
+                    property, expected
+                )? {
+                    self.check_arguments(&call.arguments, &fields,
+                                         &(enum_name + "." + &property.name.value()),
+                                         call.position.to_owned())?;
+
+                    return Ok(value_type);
+                }
+
+                if let Some((enum_name, fields)) = self.find_variant(property)? {
+                    self.check_arguments(
+                        &call.arguments,
+                        &fields,
+                        &(enum_name.to_owned() + "." + &property.name.value()),
+                        call.position.to_owned()
+                    )?;
+
+                    return Ok(Type::Enum(enum_name));
+                }
+
+                let object_type: Type = self.check_member_object(property)?;
+                let name: String = property.name.value();
+
+                let method: Option<FunctionType> = match &object_type {
+                    Type::Map(key, value) => Self::map_method(key, value, &name),
+                    Type::List(element) => Self::list_method(element, &name),
+                    Type::Class(class, arguments) => self.find_method(class, arguments, &name),
+                    _ => None
+                };
+
+                return Ok(match method {
+                    Some(method) => {
+                        let return_type: Type = self.check_function_call(
+                            &call.arguments, &method, &name, call.position.to_owned(), expected
+                        )?;
+
+                        if matches!(object_type, Type::Class(..)) {
+                            self.forget_globals();
+                        }
+
+                        if property.safe {
+                            return_type.to_nullable()
+                        } else {
+                            return_type
+                        }
+                    },
+                    None => return Err((
+                        "Type `".to_owned() + &object_type.to_string() + "` has no method `" +
+                            &name + "`.",
+                        property.name.token_position.to_owned()
+                    ))
+                });
+            },
+            Expression::Super(super_expression) => {
+                let name: String = super_expression.method.value();
+                let superclass: Option<(String, Vec<Type>)> = match &self.current_class {
+                    Some(class) if self.return_type.is_some() => self.find_superclass(
+                        class,
+                        &Self::parameter_types(&self.classes[class].type_parameters)
+                    ),
+                    _ => return Err((
+                        "`super` can only be used inside of a method.".to_owned(),
+                        super_expression.method.token_position.to_owned()
+                    ))
+                };
+
+                let method: Option<FunctionType> = match &superclass {
+                    Some((superclass, arguments)) => self.find_method(superclass, arguments, &name),
+                    None => return Err((
+                        "`super` can only be used in a class which extends another.".to_owned(),
+                        super_expression.method.token_position.to_owned()
+                    ))
+                };
+
+                return Ok(match method {
+                    Some(method) => {
+                        let return_type: Type = self.check_function_call(
+                            &call.arguments, &method, &name, call.position.to_owned(), expected
+                        )?;
+
+                        self.forget_globals();
+
+                        return_type
+                    },
+                    None => return Err((
+                        "Superclass has no method `".to_owned() + &name + "`.",
+                        super_expression.method.token_position.to_owned()
+                    ))
+                });
+            },
+            _ => {}
+        }
+
+        self.check_expression(&call.callee, None)?;
+
+        return Err((
+            "Only functions and methods can be called.".to_owned(),
+            call.callee.position()
+        ));
+    }
+
+    fn check_map(&mut self, map: &MapExpression, expected: Option<&Type>) -> CompileResult<Type> {
+        let (mut key_type, mut value_type): (Option<Type>, Option<Type>) = match expected {
+            Some(Type::Map(key, value)) => {
+                (Some(key.as_ref().to_owned()), Some(value.as_ref().to_owned()))
+            },
+            _ => (None, None)
+        };
+
+        if map.entries.is_empty() && key_type.is_none() {
+            return Err((
+                "Cannot infer the type of an empty map; declare its type instead.".to_owned(),
+                map.position.to_owned()
+            ));
+        }
+
+        // Without an expected type, the first entry decides the type of the map.
+        for (key, value) in &map.entries {
+            match &key_type {
+                Some(expected_key) => self.check_expression_expecting(key, expected_key)?,
+                None => key_type = Some(self.check_expression(key, None)?)
+            }
+
+            match &value_type {
+                Some(expected_value) => self.check_expression_expecting(value, expected_value)?,
+                None => value_type = Some(self.check_expression(value, None)?)
+            }
+        }
+
+        let key_type: Type = key_type.unwrap();
+        let value_type: Type = value_type.unwrap();
+
+        if !key_type.is_hashable() {
+            return Err((
+                "Type `".to_owned() + &key_type.to_string() + "` cannot be used as a map key.",
+                map.entries[0].0.position()
+            ));
+        }
+
+        if value_type == Type::Void {
+            return Err(("Map values cannot be `void`.".to_owned(),
+                                map.entries[0].1.position()));
+        }
+
+        return Ok(Type::Map(Box::new(key_type), Box::new(value_type)));
+    }
+
+    fn check_list(&mut self, list: &ListExpression, expected: Option<&Type>)
+        -> CompileResult<Type> {
+        let mut element_type: Option<Type> = match expected {
+            Some(Type::List(element)) => Some(element.as_ref().to_owned()),
+            _ => None
+        };
+
+        if list.elements.is_empty() && element_type.is_none() {
+            return Err((
+                "Cannot infer the type of an empty list; declare its type instead.".to_owned(),
+                list.position.to_owned()
+            ));
+        }
+
+        // Without an expected type, the first element decides the type of the list.
+        for element in &list.elements {
+            match &element_type {
+                Some(expected_element) => self.check_expression_expecting(element,
+                                                                          expected_element)?,
+                None => element_type = Some(self.check_expression(element, None)?)
+            }
+        }
+
+        let element_type: Type = element_type.unwrap();
+
+        if element_type == Type::Void {
+            return Err(("List elements cannot be `void`.".to_owned(),
+                                list.elements[0].position()));
+        }
+
+        return Ok(Type::List(Box::new(element_type)));
+    }
+
+    fn check_match(&mut self, match_expression: &MatchExpression, expected: Option<&Type>)
+        -> CompileResult<Type> {
+        let value_type: Type = self.check_expression(&match_expression.value, None)?;
+
+        if match_expression.arms.is_empty() {
+            return Err(("A match needs at least one arm.".to_owned(),
+                                match_expression.position.to_owned()));
+        }
+
+        // Without an expected type, the first arm decides the type of the match. Arms with a
+        // block produce nothing, so they cannot be mixed with arms which produce a value.
+        let mut result_type: Option<Type> = expected.cloned();
+        let before: FlowState = self.flow.to_owned();
+        let mut after: Option<FlowState> = None;
+
+        for arm in &match_expression.arms {
+            self.flow = before.to_owned();
+            self.push_scope();
+            self.check_pattern(&arm.pattern, &value_type)?;
+
+            if let Some(guard) = &arm.guard {
+                self.flow = self.check_branch_condition(guard)?.0;
+            }
+
+            let arm_type: Type = match &arm.body {
+                MatchArmBody::Expression(body) => match &result_type {
+                    Some(result_type) => {
+                        self.check_expression_expecting(body, result_type)?;
+
+                        result_type.to_owned()
+                    },
+                    None => self.check_expression(body, None)?
+                },
+                MatchArmBody::Block(body) => {
+                    if result_type.as_ref().is_some_and(|result_type| *result_type != Type::Void) {
+                        return Err((
+                            "Expected this arm to produce a value of type `".to_owned() +
+                                &result_type.unwrap().to_string() + "`.",
+                            arm.pattern.position()
+                        ));
+                    }
+
+                    self.check_block(body)?;
+
+                    Type::Void
+                }
+            };
+
+            self.pop_scope();
+
+            let exits: bool = match &arm.body {
+                MatchArmBody::Block(body) => Self::always_exits(body),
+                MatchArmBody::Expression(_) => false
+            };
+
+            if !exits {
+                after = Some(match after {
+                    Some(after) => after.join(&self.flow),
+                    None => self.flow.to_owned()
+                });
+            }
+
+            if result_type.is_none() {
+                result_type = Some(arm_type);
+            }
+        }
+
+        self.flow = after.unwrap_or(before);
+
+        let rows: Vec<Vec<Option<&Pattern>>> = match_expression.arms.iter()
+            .filter(|arm| arm.guard.is_none()) // A guard might fail, so it covers nothing.
+            .map(|arm| vec![Self::refutable(&arm.pattern)])
+            .collect();
+        let missing: Vec<String> = match self.constructors(&value_type) {
+            // Name every missing variant, rather than only the first one.
+            Some(constructors) => constructors.iter()
+                .filter_map(|constructor| self.find_unmatched_constructor(
+                    &rows, &[value_type.to_owned()], constructor
+                ))
+                .map(|mut witness| witness.remove(0))
+                .collect(),
+            None => self.find_unmatched(&rows, &[value_type.to_owned()])
+                .map(|mut witness| witness.remove(0))
+                .into_iter()
+                .collect()
+        };
+
+        if !missing.is_empty() {
+            return Err((
+                "Match is not exhaustive; missing: ".to_owned() + &missing.join(", "),
+                match_expression.position.to_owned()
+            ));
+        }
+
+        return Ok(result_type.unwrap());
+    }
+
+    // Check a pattern against the type of the value it matches, declaring the variables it binds.
+    fn check_pattern(&mut self, pattern: &Pattern, value_type: &Type) -> CompileResult<()> {
+        self.stack.check(pattern.position())?;
+
+        match pattern {
+            Pattern::Literal(literal) => {
+                let literal_type: Type = Self::literal_type(&literal.value);
+
+                let matchable: bool = self.is_assignable(&literal_type, value_type)
+                    || (literal_type.is_numeric() && value_type.is_numeric());
+
+                if !matchable {
+                    return Err((
+                        "A pattern of type `".to_owned() + &literal_type.to_string() +
+                            "` cannot match a value of type `" + &value_type.to_string() + "`.",
+                        literal.position.to_owned()
+                    ));
+                }
+            },
+            Pattern::Binding(name) => {
+                self.declare_variable(name.value(), value_type.to_owned(),
+                                      name.token_position.to_owned())?;
+            },
+            Pattern::Wildcard(_) => {},
+            Pattern::Variant(variant_pattern) => {
+                let enum_name: String = variant_pattern.enum_name.value();
+                let variant_name: String = variant_pattern.variant.value();
+
+                let enum_type: EnumType = match self.find_enum_type(value_type) {
+                    Some((name, enum_type)) if name == enum_name => enum_type,
+                    _ => return Err((
+                        "A pattern of type `".to_owned() + &enum_name +
+                            "` cannot match a value of type `" + &value_type.to_string() + "`.",
+                        variant_pattern.enum_name.token_position.to_owned()
+                    ))
+                };
+
+                let fields: Vec<Type> = match enum_type.find_variant(&variant_name) {
+                    Some(fields) => fields.to_owned(),
+                    None => return Err((
+                        "Enum `".to_owned() + &enum_name + "` has no variant `" + &variant_name +
+                            "`.",
+                        variant_pattern.variant.token_position.to_owned()
+                    ))
+                };
+
+                if variant_pattern.fields.len() != fields.len() {
+                    return Err((
+                        "Variant `".to_owned() + &enum_name + "." + &variant_name + "` has " +
+                            &fields.len().to_string() + " field(s), but the pattern has " +
+                            &variant_pattern.fields.len().to_string() + ".",
+                        variant_pattern.variant.token_position.to_owned()
+                    ));
+                }
+
+                for (field_pattern, field_type) in variant_pattern.fields.iter().zip(&fields) {
+                    self.check_pattern(field_pattern, field_type)?;
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    // Patterns which match anything are treated as missing by the exhaustiveness check.
+    fn refutable(pattern: &Pattern) -> Option<&Pattern> {
+        return if pattern.is_irrefutable() {
+            None
+        } else {
+            Some(pattern)
+        };
+    }
+
+    // Every shape a value of the given type can have, as a name and the types of its fields, if
+    // there are few enough to list. Other values, like numbers, can only be covered by a pattern
+    // which matches anything.
+    fn constructors(&self, value_type: &Type) -> Option<Vec<(String, Vec<Type>)>> {
+        return match value_type {
+            Type::Boolean => Some(vec![
+                (String::from("true"), Vec::new()),
+                (String::from("false"), Vec::new())
+            ]),
+            _ => self.find_enum_type(value_type).map(|(name, enum_type)| enum_type.variants
+                .into_iter()
+                .map(|(variant, fields)| (name.to_owned() + "." + &variant, fields))
+                .collect())
+        };
+    }
+
+    // Find values which none of the rows of patterns match, one column per type. If there is
+    // one, it is returned as a description of the pattern in each column.
+    fn find_unmatched(&self, rows: &[Vec<Option<&Pattern>>], types: &[Type])
+        -> Option<Vec<String>> {
+        if types.is_empty() {
+            return if rows.is_empty() {
+                Some(Vec::new())
+            } else {
+                None
+            };
+        }
+
+        // When no row looks inside the first column, splitting it by constructor finds nothing
+        // new, and would never end for recursive enums.
+        let constructors: Option<Vec<(String, Vec<Type>)>> = match rows.iter()
+            .any(|row| row[0].is_some()) {
+            true => self.constructors(&types[0]),
+            false => None
+        };
+
+        return match constructors {
+            Some(constructors) => constructors.iter()
+                .find_map(|constructor| self.find_unmatched_constructor(rows, types, constructor)),
+            None => {
+                // Only the rows matching anything in the first column can cover it.
+                let remaining: Vec<Vec<Option<&Pattern>>> = rows.iter()
+                    .filter(|row| row[0].is_none())
+                    .map(|row| row[1..].to_vec())
+                    .collect();
+
+                self.find_unmatched(&remaining, &types[1..]).map(|mut witness| {
+                    witness.insert(0, String::from("_"));
+
+                    witness
+                })
+            }
+        };
+    }
+
+    // Find values with the given constructor in the first column which none of the rows match.
+    fn find_unmatched_constructor(&self, rows: &[Vec<Option<&Pattern>>], types: &[Type],
+                                  constructor: &(String, Vec<Type>)) -> Option<Vec<String>> {
+        let (name, fields): &(String, Vec<Type>) = constructor;
+
+        // Replace the first column by the fields of the rows which match the constructor.
+        let specialized: Vec<Vec<Option<&Pattern>>> = rows.iter()
+            .filter_map(|row| {
+                let mut columns: Vec<Option<&Pattern>> = match row[0] {
+                    None => vec![None; fields.len()],
+                    Some(Pattern::Literal(literal)) => match &literal.value {
+                        LiteralValue::Boolean(value) if value.to_string() == *name => Vec::new(),
+                        _ => return None
+                    },
+                    Some(Pattern::Variant(variant)) => {
+                        if variant.enum_name.value() + "." + &variant.variant.value() != *name {
+                            return None;
+                        }
+
+                        variant.fields.iter().map(Self::refutable).collect()
+                    },
+                    Some(_) => return None
+                };
+
+                columns.extend_from_slice(&row[1..]);
+
+                Some(columns)
+            })
+            .collect();
+        let mut specialized_types: Vec<Type> = fields.to_owned();
+
+        specialized_types.extend_from_slice(&types[1..]);
+
+        let mut witness: Vec<String> = self.find_unmatched(&specialized, &specialized_types)?;
+        let field_witnesses: Vec<String> = witness.drain(..fields.len()).collect();
+
+        witness.insert(0, if fields.is_empty() {
+            name.to_owned()
+        } else {
+            name.to_owned() + "(" + &field_witnesses.join(", ") + ")"
+        });
+
+        return Some(witness);
+    }
+}
+
+// Collect the names of the variables assigned to by the statements.
+fn assigned_in_statements(statements: &[Statement], effects: &mut Effects) -> () {
+    for statement in statements {
+        match statement {
+            Statement::Expression(expression_statement) => {
+                assigned_in_expression(&expression_statement.expression, effects);
+            },
+            Statement::VariableDeclaration(declaration) => {
+                if let Some(initialization) = &declaration.initialization {
+                    assigned_in_expression(initialization, effects);
+                }
+            },
+            Statement::Block(block) => assigned_in_statements(&block.statements, effects),
+            Statement::If(if_statement) => {
+                assigned_in_expression(&if_statement.condition, effects);
+                assigned_in_statements(&if_statement.then_branch, effects);
+                assigned_in_statements(&if_statement.else_branch, effects);
+            },
+            Statement::While(while_statement) => {
+                assigned_in_expression(&while_statement.condition, effects);
+                assigned_in_statements(&while_statement.body, effects);
+            },
+            Statement::Loop(loop_statement) => {
+                assigned_in_statements(&loop_statement.body, effects);
+            },
+            Statement::For(for_statement) => {
+                assigned_in_expression(&for_statement.initializer, effects);
+                assigned_in_expression(&for_statement.condition, effects);
+                assigned_in_expression(&for_statement.increment, effects);
+                assigned_in_statements(&for_statement.body, effects);
+            },
+            Statement::ForIn(for_in_statement) => {
+                assigned_in_expression(&for_in_statement.iterable, effects);
+                assigned_in_statements(&for_in_statement.body, effects);
+            },
+            Statement::Return(return_statement) => {
+                if let Some(value) = &return_statement.value {
+                    assigned_in_expression(value, effects);
+                }
+            },
+            Statement::Throw(throw_statement) => {
+                assigned_in_expression(&throw_statement.value, effects);
+            },
+            Statement::Try(try_statement) => {
+                assigned_in_statements(&try_statement.body, effects);
+
+                for catch in &try_statement.catches {
+                    assigned_in_statements(&catch.body, effects);
+                }
+
+                if let Some(finally) = &try_statement.finally {
+                    assigned_in_statements(finally, effects);
+                }
+            },
+            Statement::Break(_) | Statement::Continue(_) | Statement::FunctionDeclaration(_) |
+            Statement::ClassDeclaration(_) | Statement::InterfaceDeclaration(_)
+            | Statement::EnumDeclaration(_) | Statement::Import(_) | Statement::Test(_)
+            | Statement::Trivia(_) => {}
+        }
+    }
+}
+
+fn assigned_in_expression(expression: &Expression, effects: &mut Effects) -> () {
+    match expression {
+        Expression::Assignment(assignment) => {
+            if let Expression::Identifier(identifier) = assignment.target.as_ref() {
+                effects.assigned.insert(identifier.token.value());
+            }
+
+            assigned_in_expression(&assignment.target, effects);
+            assigned_in_expression(&assignment.value, effects);
+        },
+        Expression::Binary(binary) => {
+            assigned_in_expression(&binary.left, effects);
+            assigned_in_expression(&binary.right, effects);
+        },
+        Expression::Unary(unary) => assigned_in_expression(&unary.operand, effects),
+        Expression::FunctionCall(call) => {
+            match call.callee.as_ref() {
+                Expression::Identifier(identifier) => {
+                    effects.called.insert(identifier.token.value());
+                },
+                Expression::Property(property) => {
+                    effects.called.insert(property.name.value());
+                },
+                Expression::Super(super_expression) => {
+                    effects.called.insert(super_expression.method.value());
+                },
+                _ => {}
+            }
+
+            assigned_in_expression(&call.callee, effects);
+
+            for argument in &call.arguments {
+                assigned_in_expression(argument, effects);
+            }
+        },
+        Expression::Property(property) => assigned_in_expression(&property.object, effects),
+        Expression::Is(is) => assigned_in_expression(&is.value, effects),
+        Expression::Map(map) => {
+            for (key, value) in &map.entries {
+                assigned_in_expression(key, effects);
+                assigned_in_expression(value, effects);
+            }
+        },
+        Expression::List(list) => {
+            for element in &list.elements {
+                assigned_in_expression(element, effects);
+            }
+        },
+        Expression::Range(range) => {
+            assigned_in_expression(&range.start, effects);
+            assigned_in_expression(&range.end, effects);
+        },
+        Expression::Match(match_expression) => {
+            assigned_in_expression(&match_expression.value, effects);
+
+            for arm in &match_expression.arms {
+                if let Some(guard) = &arm.guard {
+                    assigned_in_expression(guard, effects);
+                }
+
+                match &arm.body {
+                    MatchArmBody::Expression(body) => assigned_in_expression(body, effects),
+                    MatchArmBody::Block(body) => assigned_in_statements(body, effects)
+                }
+            }
+        },
+        Expression::Propagate(propagate) => assigned_in_expression(&propagate.value, effects),
+        Expression::Literal(_) | Expression::Identifier(_) | Expression::This(_) |
+        Expression::Super(_) => {}
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+// The static types known to the type checker.
+#[derive(PartialEq, Clone)]
+pub enum Type {
+    Integer,
+    Float,
+    String,
+    Char,
+    Boolean,
+    Void,
+    Map(Box<Type>, Box<Type>), // Key type and value type.
+    List(Box<Type>),
+    Range,
+    Class(String, Vec<Type>), // The name of the class and its type arguments.
+    Enum(String),
+    Result(Box<Type>, Box<Type>), // The built-in `Result`, with its value type and error type.
+    Option(Box<Type>), // The built-in `Option`, with its value type.
+    Nullable(Box<Type>), // A value of the inner type, or null.
+    Null, // The type of the `null` literal.
+    Parameter(String) // A type parameter, inside of the generic function or class declaring it.
+}
+
+impl Type {
+    pub fn is_numeric(&self) -> bool {
+        return matches!(self, Type::Integer | Type::Float);
+    }
+
+    // Whether values of this type can be used as map keys.
+    pub fn is_hashable(&self) -> bool {
+        return matches!(self, Type::Integer | Type::String | Type::Char | Type::Boolean);
+    }
+
+    pub fn is_nullable(&self) -> bool {
+        return matches!(self, Type::Nullable(_) | Type::Null);
+    }
+
+    // The type of this value when it might also be null.
+    pub fn to_nullable(&self) -> Type {
+        return match self {
+            Type::Nullable(_) | Type::Null | Type::Void => self.to_owned(),
+            _ => Type::Nullable(Box::new(self.to_owned()))
+        };
+    }
+
+    // Replace the type parameters with the types they are bound to. Parameters without a binding
+    // are left as they are.
+    pub fn substitute(&self, bindings: &HashMap<String, Type>) -> Type {
+        let substitute = |inner: &Type| Box::new(inner.substitute(bindings));
+
+        return match self {
+            Type::Map(key, value) => Type::Map(substitute(key), substitute(value)),
+            Type::List(element) => Type::List(substitute(element)),
+            Type::Class(name, arguments) => Type::Class(
+                name.to_owned(),
+                arguments.iter().map(|argument| argument.substitute(bindings)).collect()
+            ),
+            Type::Result(value, error) => Type::Result(substitute(value), substitute(error)),
+            Type::Option(value) => Type::Option(substitute(value)),
+            // A parameter bound to a nullable type must not become doubly nullable.
+            Type::Nullable(inner) => inner.substitute(bindings).to_nullable(),
+            Type::Parameter(name) => match bindings.get(name) {
+                Some(bound) => bound.to_owned(),
+                None => self.to_owned()
+            },
+            _ => self.to_owned()
+        };
+    }
+
+    // Whether the type parameter with the given name appears anywhere in this type.
+    pub fn mentions(&self, parameter: &str) -> bool {
+        return match self {
+            Type::Map(key, value) | Type::Result(key, value) => {
+                key.mentions(parameter) || value.mentions(parameter)
+            },
+            Type::List(inner) | Type::Option(inner) | Type::Nullable(inner) => {
+                inner.mentions(parameter)
+            },
+            Type::Class(_, arguments) => {
+                arguments.iter().any(|argument| argument.mentions(parameter))
+            },
+            Type::Parameter(name) => name == parameter,
+            _ => false
+        };
+    }
+}
+
+impl ToString for Type {
+    fn to_string(&self) -> String {
+        return match self {
+            Type::Integer => String::from("int"),
+            Type::Float => String::from("float"),
+            Type::String => String::from("string"),
+            Type::Char => String::from("char"),
+            Type::Boolean => String::from("bool"),
+            Type::Void => String::from("void"),
+            Type::Map(key, value) => "map<".to_owned() + &key.to_string() + ", " +
+                &value.to_string() + ">",
+            Type::List(element) => "list<".to_owned() + &element.to_string() + ">",
+            Type::Range => String::from("range"),
+            Type::Class(name, arguments) if arguments.is_empty() => name.to_owned(),
+            Type::Class(name, arguments) => {
+                let arguments: Vec<String> = arguments.iter()
+                    .map(|argument| argument.to_string())
+                    .collect();
+
+                name.to_owned() + "<" + &arguments.join(", ") + ">"
+            },
+            Type::Enum(name) => name.to_owned(),
+            Type::Result(value, error) => "Result<".to_owned() + &value.to_string() + ", " +
+                &error.to_string() + ">",
+            Type::Option(value) => "Option<".to_owned() + &value.to_string() + ">",
+            Type::Nullable(inner) => inner.to_string() + "?",
+            Type::Null => String::from("null"),
+            Type::Parameter(name) => name.to_owned()
+        };
+    }
+}
+
+// A type parameter of a generic function or class, with the type its arguments must be
+// assignable to.
+#[derive(Clone)]
+pub struct TypeParameter {
+    pub name: String,
+    pub bound: Option<Type>
+}
+
+#[derive(Clone)]
+pub struct FunctionType {
+    pub type_parameters: Vec<TypeParameter>, // Which are inferred from the arguments of each call.
+    pub parameters: Vec<Type>,
+    pub return_type: Type
+}
+
+impl FunctionType {
+    // The function type with the type parameters of its class replaced by the types they are
+    // bound to. Its own type parameters are left for each call to infer.
+    pub fn substitute(&self, bindings: &HashMap<String, Type>) -> FunctionType {
+        return FunctionType {
+            type_parameters: self.type_parameters.iter()
+                .map(|type_parameter| TypeParameter {
+                    name: type_parameter.name.to_owned(),
+                    bound: type_parameter.bound.as_ref().map(|bound| bound.substitute(bindings))
+                })
+                .collect(),
+            parameters: self.parameters.iter()
+                .map(|parameter| parameter.substitute(bindings))
+                .collect(),
+            return_type: self.return_type.substitute(bindings)
+        };
+    }
+
+    // The signature of a function with the given name, such as `int compare(Dog)`.
+    pub fn signature(&self, name: &str) -> String {
+        let mut signature: String = self.return_type.to_string() + " " + name;
+
+        if !self.type_parameters.is_empty() {
+            let type_parameters: Vec<String> = self.type_parameters.iter()
+                .map(|type_parameter| match &type_parameter.bound {
+                    Some(bound) => type_parameter.name.to_owned() + " extends " +
+                        &bound.to_string(),
+                    None => type_parameter.name.to_owned()
+                })
+                .collect();
+
+            signature += &("<".to_owned() + &type_parameters.join(", ") + ">");
+        }
+
+        let parameters: Vec<String> = self.parameters.iter()
+            .map(|parameter| parameter.to_string())
+            .collect();
+
+        return signature + "(" + &parameters.join(", ") + ")";
+    }
+}
+
+// A class, or an interface, which only has methods.
+pub struct ClassType {
+    pub interface: bool,
+    pub type_parameters: Vec<TypeParameter>,
+    pub superclass: Option<String>,
+    pub superclass_arguments: Vec<Type>, // In terms of the type parameters of this class.
+    // The interfaces the class implements, with their type arguments in terms of the type
+    // parameters of this class.
+    pub interfaces: Vec<(String, Vec<Type>)>,
+    pub properties: HashMap<String, Type>,
+    pub methods: HashMap<String, FunctionType>,
+    pub constructor: Option<FunctionType> // Classes without a constructor inherit one.
+}
+
+// The variants of an enum, in the order they are declared, with the types of their fields.
+#[derive(Clone)]
+pub struct EnumType {
+    pub variants: Vec<(String, Vec<Type>)>
+}
+
+impl EnumType {
+    pub fn find_variant(&self, name: &str) -> Option<&Vec<Type>> {
+        return self.variants.iter()
+            .find(|(variant, _)| variant == name)
+            .map(|(_, fields)| fields);
+    }
+}
@@ -0,0 +1,405 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::expressions::LiteralValue;
+use crate::natives::{ERROR_CLASS, OPTION_ENUM, RESULT_ENUM};
+use crate::program::RuntimeError;
+use crate::statements::FunctionDeclarationStatement;
+use crate::types::Type;
+
+// A value produced while running a program.
+#[derive(Clone)]
+pub enum Value {
+    Integer(i64),
+    Float(f64),
+    String(String),
+    Char(char),
+    Boolean(bool),
+    Map(Rc<RefCell<MapValue>>),
+    List(Rc<RefCell<Vec<Value>>>),
+    Range(i64, i64), // The start (inclusive) and end (exclusive).
+    Instance(Rc<RefCell<InstanceValue>>),
+    Enum(Rc<EnumValue>),
+    Host(HostObject),
+    Null,
+    Void
+}
+
+impl Value {
+    pub fn from_literal(literal: &LiteralValue) -> Value {
+        return match literal {
+            LiteralValue::Integer(value) => Value::Integer(*value),
+            LiteralValue::Float(value) => Value::Float(*value),
+            LiteralValue::String(value) => Value::String(value.to_owned()),
+            LiteralValue::Char(value) => Value::Char(*value),
+            LiteralValue::Boolean(value) => Value::Boolean(*value),
+            LiteralValue::Null => Value::Null
+        };
+    }
+
+    // The representation of the value when it is nested inside of another value, where strings
+    // and chars are quoted.
+    pub fn to_repr(&self) -> String {
+        return match self {
+            Value::String(value) => format!("{:?}", value),
+            Value::Char(value) => format!("{:?}", value),
+            _ => self.to_string()
+        };
+    }
+
+    // Whether the value is of the given type, which values given by a program embedding the
+    // language are checked with, since nothing else makes sure of it.
+    pub fn has_type(&self, value_type: &Type) -> bool {
+        return match (self, value_type) {
+            (Value::Integer(_), Type::Integer) | (Value::Float(_), Type::Float) |
+            (Value::String(_), Type::String) | (Value::Char(_), Type::Char) |
+            (Value::Boolean(_), Type::Boolean) | (Value::Range(_, _), Type::Range) |
+            (Value::Null, Type::Nullable(_) | Type::Null) | (Value::Void, Type::Void) => true,
+            (value, Type::Nullable(inner)) => value.has_type(inner),
+            (Value::List(list), Type::List(element_type)) => {
+                list.borrow().iter().all(|element| element.has_type(element_type))
+            },
+            (Value::Map(map), Type::Map(key_type, value_type)) => {
+                map.borrow().entries().iter().all(|(key, value)| {
+                    key.to_value().has_type(key_type) && value.has_type(value_type)
+                })
+            },
+            (Value::Instance(instance), Type::Class(name, _)) => {
+                let mut class: Option<Rc<ClassValue>> = Some(instance.borrow().class.to_owned());
+
+                while let Some(current) = class {
+                    if current.name == *name {
+                        return true;
+                    }
+
+                    class = current.superclass.to_owned();
+                }
+
+                false
+            },
+            (Value::Host(object), Type::Class(name, _)) => object.type_name == *name,
+            (Value::Enum(enum_value), Type::Enum(name)) => enum_value.enum_name == *name,
+            (Value::Enum(enum_value), Type::Result(value_type, error_type)) => {
+                let field_type: &Type = if enum_value.variant == "Ok" {
+                    value_type
+                } else {
+                    error_type
+                };
+
+                enum_value.enum_name == RESULT_ENUM &&
+                    enum_value.fields.iter().all(|field| field.has_type(field_type))
+            },
+            (Value::Enum(enum_value), Type::Option(value_type)) => {
+                enum_value.enum_name == OPTION_ENUM &&
+                    enum_value.fields.iter().all(|field| field.has_type(value_type))
+            },
+            _ => false
+        };
+    }
+}
+
+impl ToString for Value {
+    fn to_string(&self) -> String {
+        return match self {
+            Value::Integer(value) => value.to_string(),
+            Value::Float(value) => {
+                if value.fract() == 0.0 && value.is_finite() {
+                    format!("{:.1}", value)
+                } else {
+                    value.to_string()
+                }
+            },
+            Value::String(value) => value.to_owned(),
+            Value::Char(value) => value.to_string(),
+            Value::Boolean(value) => value.to_string(),
+            Value::Map(map) => {
+                let entries: Vec<String> = map.borrow().entries().iter()
+                    .map(|(key, value)| key.to_value().to_repr() + ": " + &value.to_repr())
+                    .collect();
+
+                "{".to_owned() + &entries.join(", ") + "}"
+            },
+            Value::List(list) => {
+                let elements: Vec<String> = list.borrow().iter()
+                    .map(|element| element.to_repr())
+                    .collect();
+
+                "[".to_owned() + &elements.join(", ") + "]"
+            },
+            Value::Range(start, end) => start.to_string() + ".." + &end.to_string(),
+            Value::Instance(instance) => "<".to_owned() + &instance.borrow().class.name + ">",
+            Value::Host(object) => "<".to_owned() + &object.type_name + ">",
+            Value::Enum(enum_value) => {
+                let name: String = enum_value.enum_name.to_owned() + "." + &enum_value.variant;
+
+                if enum_value.fields.is_empty() {
+                    name
+                } else {
+                    let fields: Vec<String> = enum_value.fields.iter()
+                        .map(|field| field.to_repr())
+                        .collect();
+
+                    name + "(" + &fields.join(", ") + ")"
+                }
+            },
+            Value::Null => String::from("null"),
+            Value::Void => String::from("void")
+        };
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        return match (self, other) {
+            (Value::Integer(left), Value::Integer(right)) => left == right,
+            (Value::Float(left), Value::Float(right)) => left == right,
+            (Value::Integer(left), Value::Float(right)) => (*left as f64) == *right,
+            (Value::Float(left), Value::Integer(right)) => *left == (*right as f64),
+            (Value::String(left), Value::String(right)) => left == right,
+            (Value::Char(left), Value::Char(right)) => left == right,
+            (Value::Boolean(left), Value::Boolean(right)) => left == right,
+            (Value::Map(left), Value::Map(right)) => {
+                Rc::ptr_eq(left, right) || left.borrow().equals(&right.borrow())
+            },
+            (Value::List(left), Value::List(right)) => {
+                Rc::ptr_eq(left, right) || *left.borrow() == *right.borrow()
+            },
+            (Value::Range(left_start, left_end), Value::Range(right_start, right_end)) => {
+                left_start == right_start && left_end == right_end
+            },
+            (Value::Instance(left), Value::Instance(right)) => Rc::ptr_eq(left, right),
+            (Value::Host(left), Value::Host(right)) => Rc::ptr_eq(&left.object, &right.object),
+            (Value::Enum(left), Value::Enum(right)) => {
+                left.enum_name == right.enum_name && left.variant == right.variant
+                    && left.fields == right.fields
+            },
+            (Value::Null, Value::Null) => true,
+            (Value::Void, Value::Void) => true,
+            _ => false
+        };
+    }
+}
+
+// The subset of values that can be used as map keys.
+#[derive(PartialEq, Eq, Hash, Clone)]
+pub enum MapKey {
+    Integer(i64),
+    String(String),
+    Char(char),
+    Boolean(bool)
+}
+
+impl MapKey {
+    pub fn from_value(value: &Value) -> Option<MapKey> {
+        return match value {
+            Value::Integer(value) => Some(MapKey::Integer(*value)),
+            Value::String(value) => Some(MapKey::String(value.to_owned())),
+            Value::Char(value) => Some(MapKey::Char(*value)),
+            Value::Boolean(value) => Some(MapKey::Boolean(*value)),
+            _ => None
+        };
+    }
+
+    pub fn to_value(&self) -> Value {
+        return match self {
+            MapKey::Integer(value) => Value::Integer(*value),
+            MapKey::String(value) => Value::String(value.to_owned()),
+            MapKey::Char(value) => Value::Char(*value),
+            MapKey::Boolean(value) => Value::Boolean(*value)
+        };
+    }
+}
+
+// A map which remembers the order its keys were first inserted in, so that printing and
+// iterating over it is deterministic.
+pub struct MapValue {
+    entries: Vec<(MapKey, Value)>,
+    indices: HashMap<MapKey, usize> // The index of each key within the entries.
+}
+
+impl Default for MapValue {
+    fn default() -> Self {
+        return Self::new();
+    }
+}
+
+impl MapValue {
+    pub fn new() -> Self {
+        return Self {
+            entries: Vec::new(),
+            indices: HashMap::new()
+        };
+    }
+
+    pub fn get(&self, key: &MapKey) -> Option<Value> {
+        return self.indices.get(key).map(|index| self.entries[*index].1.to_owned());
+    }
+
+    // Set the value of a key. Existing keys keep their original position.
+    pub fn set(&mut self, key: MapKey, value: Value) -> () {
+        match self.indices.get(&key) {
+            Some(index) => self.entries[*index].1 = value,
+            None => {
+                self.indices.insert(key.to_owned(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    // Remove a key, returning whether it was present.
+    pub fn remove(&mut self, key: &MapKey) -> bool {
+        let index: usize = match self.indices.remove(key) {
+            Some(index) => index,
+            None => return false
+        };
+
+        self.entries.remove(index);
+
+        // Everything after the removed entry has moved down by one.
+        for other_index in self.indices.values_mut() {
+            if *other_index > index {
+                *other_index -= 1;
+            }
+        }
+
+        return true;
+    }
+
+    pub fn contains(&self, key: &MapKey) -> bool {
+        return self.indices.contains_key(key);
+    }
+
+    pub fn len(&self) -> usize {
+        return self.entries.len();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.entries.is_empty();
+    }
+
+    pub fn entries(&self) -> &Vec<(MapKey, Value)> {
+        return &self.entries;
+    }
+
+    // Maps are equal when they have the same entries, regardless of insertion order.
+    pub fn equals(&self, other: &MapValue) -> bool {
+        return self.len() == other.len() && self.entries.iter()
+            .all(|(key, value)| other.get(key).is_some_and(|other_value| other_value == *value));
+    }
+}
+
+// The code of a function, which depends on the backend running the program.
+#[derive(Clone)]
+pub enum FunctionCode {
+    Declaration(Rc<FunctionDeclarationStatement>), // Run by the tree-walking interpreter.
+    Compiled(usize), // The index of a function in a compiled program.
+    Native // Classes built into the language have no code; natives make their instances.
+}
+
+pub struct ClassValue {
+    pub name: String,
+    pub superclass: Option<Rc<ClassValue>>,
+    // Gives the properties declared by the class itself their initial values. For the tree-walking
+    // interpreter, its body is made up of the property declarations.
+    pub initializer: FunctionCode,
+    pub constructor: Option<FunctionCode>,
+    pub methods: HashMap<String, FunctionCode>
+}
+
+impl ClassValue {
+    // Find a method on this class or its superclasses, along with the class that declares it.
+    pub fn find_method(self: &Rc<Self>, name: &str) -> Option<(Rc<ClassValue>, FunctionCode)> {
+        if let Some(method) = self.methods.get(name) {
+            return Some((self.to_owned(), method.to_owned()));
+        }
+
+        return self.superclass.as_ref()?.find_method(name);
+    }
+
+    // The constructor used when creating an instance, which may be inherited.
+    pub fn find_constructor(self: &Rc<Self>) -> Option<(Rc<ClassValue>, FunctionCode)> {
+        if let Some(constructor) = &self.constructor {
+            return Some((self.to_owned(), constructor.to_owned()));
+        }
+
+        return self.superclass.as_ref()?.find_constructor();
+    }
+
+    pub fn is_subclass_of(&self, name: &str) -> bool {
+        if self.name == name {
+            return true;
+        }
+
+        return match &self.superclass {
+            Some(superclass) => superclass.is_subclass_of(name),
+            None => false
+        };
+    }
+}
+
+pub struct InstanceValue {
+    pub class: Rc<ClassValue>,
+    pub fields: HashMap<String, Option<Value>>, // Fields without an initializer start out unset.
+    // For an error which has been caught, the error it was caught from, so that throwing it again
+    // reports where it started rather than where it was thrown again.
+    pub origin: Option<RuntimeError>
+}
+
+impl InstanceValue {
+    pub fn new(class: Rc<ClassValue>, fields: HashMap<String, Option<Value>>) -> Self {
+        return Self {
+            class,
+            fields,
+            origin: None
+        };
+    }
+
+    // An instance of `Error` standing for an error found while running, which is what catching
+    // the error gives.
+    pub fn error(class: Rc<ClassValue>, message: String) -> Value {
+        let fields: HashMap<String, Option<Value>> = HashMap::from([
+            ("message".to_owned(), Some(Value::String(message)))
+        ]);
+
+        return Value::Instance(Rc::new(RefCell::new(Self::new(class, fields))));
+    }
+
+    // What an error is reported with when nothing catches it: its message, after the name of its
+    // class unless that is `Error` itself.
+    pub fn error_message(&self) -> String {
+        let message: Option<String> = match self.fields.get("message") {
+            Some(Some(Value::String(message))) => Some(message.to_owned()),
+            _ => None
+        };
+
+        return match message {
+            Some(message) if self.class.name == ERROR_CLASS => message,
+            Some(message) => self.class.name.to_owned() + ": " + &message,
+            None => self.class.name.to_owned()
+        };
+    }
+}
+
+// An object of a type defined by a program embedding the language, which scripts can only pass
+// around and call the methods of.
+#[derive(Clone)]
+pub struct HostObject {
+    pub type_name: String,
+    pub object: Rc<dyn Any> // A `RefCell` holding the Rust value.
+}
+
+// A variant of an enum, along with its payload.
+pub struct EnumValue {
+    pub enum_name: String,
+    pub variant: String,
+    pub fields: Vec<Value>
+}
+
+impl EnumValue {
+    // Whether this is a `Result.Ok` or an `Option.Some`, whose value `?` takes out.
+    pub fn is_present(&self) -> bool {
+        return matches!((self.enum_name.as_str(), self.variant.as_str()),
+                        (RESULT_ENUM, "Ok") | (OPTION_ENUM, "Some"));
+    }
+}
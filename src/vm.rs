@@ -0,0 +1,716 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::process;
+use std::rc::Rc;
+use crate::bytecode::{ClassProto, CompiledProgram, Constant, FunctionProto, Instruction};
+use crate::natives::{natives, Native, NativeError, ERROR_CLASS};
+use crate::operations::{binary_operation, list_method, map_method, unary_operation};
+use crate::position::Position;
+use crate::program::{Program, RuntimeError, StackFrame};
+use crate::value::{ClassValue, EnumValue, FunctionCode, InstanceValue, MapKey, MapValue, Value};
+
+// The deepest that function calls can be nested before the program is stopped. Frames are kept on
+// the heap, so this is far deeper than the tree walker allows.
+const MAX_CALL_DEPTH: usize = 100_000;
+
+// The deepest that calls made from inside of an instruction, such as constructors and iterator
+// methods, can be nested. Each of these runs the machine again on the Rust stack, so they are
+// limited like the tree walker's calls are.
+const MAX_NESTED_CALLS: usize = 1000;
+
+// A runtime error, along with where it happened.
+type Execution<T> = Result<T, (String, Position)>;
+
+// The progress of a `for ... in` loop. Lists and maps are copied before iterating, so changing
+// them inside of the loop does not affect which items are visited.
+enum Iteration {
+    Range(i64, i64), // The next value, and the end.
+    List(Vec<Value>, usize),
+    Map(Vec<(MapKey, Value)>, usize),
+    Instance(Value) // The iterator, which has `has_next` and `next` methods.
+}
+
+// A single function call.
+struct Frame {
+    function: usize,
+    ip: usize, // The next instruction to run.
+    locals: Vec<Value>,
+    base: usize, // The height of the stack when the call started.
+    iterations: Vec<Iteration>
+}
+
+// Where to go when a runtime error happens, and what to go back to.
+struct Handler {
+    frame: usize, // The index of the frame which pushed the handler.
+    stack: usize,
+    iterations: usize,
+    target: usize
+}
+
+// The virtual machine, which runs a program compiled to bytecode.
+pub struct VM<'a> {
+    program: &'a mut Program,
+    compiled: CompiledProgram,
+    classes: Vec<Rc<ClassValue>>,
+    globals: Vec<Option<Value>>, // Globals declared without a value are unset.
+    stack: Vec<Value>,
+    frames: Vec<Frame>,
+    handlers: Vec<Handler>,
+    thrown: Option<Value>, // The object an error was thrown with, while it goes back up.
+    nested: usize, // How many calls from inside of instructions are running.
+    // The code `exit` was called with. It ends the program like an error which nothing catches.
+    exit: Option<i32>
+}
+
+impl<'a> VM<'a> {
+    pub fn new(program: &'a mut Program, compiled: CompiledProgram) -> Self {
+        let mut classes: Vec<Option<Rc<ClassValue>>> = vec![None; compiled.classes.len()];
+
+        for index in 0..compiled.classes.len() {
+            Self::create_class(&compiled.classes, &mut classes, index);
+        }
+
+        let globals: Vec<Option<Value>> = vec![None; compiled.globals.len()];
+
+        return Self {
+            program,
+            compiled,
+            classes: classes.into_iter().map(|class| class.unwrap()).collect(),
+            globals,
+            stack: Vec::new(),
+            frames: Vec::new(),
+            handlers: Vec::new(),
+            thrown: None,
+            nested: 0,
+            exit: None
+        };
+    }
+
+    // The superclass has to exist before its subclasses can.
+    fn create_class(protos: &[ClassProto], classes: &mut Vec<Option<Rc<ClassValue>>>,
+                    index: usize) -> Rc<ClassValue> {
+        if let Some(class) = &classes[index] {
+            return class.to_owned();
+        }
+
+        let proto: &ClassProto = &protos[index];
+        let superclass: Option<Rc<ClassValue>> = proto.superclass
+            .map(|superclass| Self::create_class(protos, classes, superclass));
+        let class: Rc<ClassValue> = Rc::new(ClassValue {
+            name: proto.name.to_owned(),
+            superclass,
+            initializer: FunctionCode::Compiled(proto.initializer),
+            constructor: proto.constructor.map(FunctionCode::Compiled),
+            methods: proto.methods.iter()
+                .map(|(name, method)| (name.to_owned(), FunctionCode::Compiled(*method)))
+                .collect()
+        });
+
+        classes[index] = Some(class.to_owned());
+
+        return class;
+    }
+
+    pub fn run(&mut self) -> () {
+        let main: usize = self.compiled.main;
+
+        self.push_frame(main, Vec::new());
+
+        if let Err((message, position)) = self.execute(0) {
+            if let Some(code) = self.exit {
+                process::exit(code);
+            }
+
+            // An error which has been caught before started somewhere else.
+            let origin: Option<RuntimeError> = match self.thrown.take() {
+                Some(Value::Instance(instance)) => instance.borrow().origin.to_owned(),
+                _ => None
+            };
+            let error: RuntimeError = origin.unwrap_or_else(|| RuntimeError {
+                message,
+                position,
+                trace: self.trace()
+            });
+
+            self.program.die_with_runtime_error(error);
+        }
+    }
+
+    // The calls which were running when an error stopped the program, innermost first. Frames are
+    // left in place by errors, and each caller is stopped just after the instruction making the
+    // call.
+    fn trace(&self) -> Vec<StackFrame> {
+        return self.frames.windows(2).rev()
+            .map(|frames| {
+                let name: &str = &self.compiled.functions[frames[1].function].name;
+                let caller: &FunctionProto = &self.compiled.functions[frames[0].function];
+
+                StackFrame {
+                    // Errors in initializers are shown as happening in the class.
+                    function: name.strip_suffix(".<init>").unwrap_or(name).to_owned(),
+                    position: caller.positions[frames[0].ip - 1].to_owned()
+                }
+            })
+            .collect();
+    }
+
+    fn push_frame(&mut self, function: usize, mut arguments: Vec<Value>) -> () {
+        arguments.resize(self.compiled.functions[function].local_count, Value::Void);
+
+        self.frames.push(Frame {
+            function,
+            ip: 0,
+            locals: arguments,
+            base: self.stack.len(),
+            iterations: Vec::new()
+        });
+    }
+
+    fn push_call(&mut self, function: usize, arguments: Vec<Value>, position: Position)
+        -> Execution<()> {
+        if self.frames.len() >= MAX_CALL_DEPTH {
+            return Err((
+                "Stack overflow; functions are nested too deeply.".to_owned(),
+                position
+            ));
+        }
+
+        self.push_frame(function, arguments);
+
+        return Ok(());
+    }
+
+    // Call a function from inside of an instruction, running it to completion.
+    fn call(&mut self, function: usize, arguments: Vec<Value>, position: Position)
+        -> Execution<Value> {
+        if self.nested >= MAX_NESTED_CALLS {
+            return Err((
+                "Stack overflow; functions are nested too deeply.".to_owned(),
+                position
+            ));
+        }
+
+        self.push_call(function, arguments, position)?;
+        self.nested += 1;
+
+        let result: Execution<Value> = self.execute(self.frames.len() - 1);
+
+        self.nested -= 1;
+
+        return result;
+    }
+
+    fn call_method(&mut self, object: Value, name: &str, arguments: Vec<Value>,
+                   position: Position) -> Execution<Value> {
+        let method: usize = self.find_method(&object, name);
+        let mut method_arguments: Vec<Value> = vec![object];
+
+        method_arguments.extend(arguments);
+
+        return self.call(method, method_arguments, position);
+    }
+
+    fn find_method(&self, object: &Value, name: &str) -> usize {
+        return match object {
+            Value::Instance(instance) => match instance.borrow().class.find_method(name) {
+                Some((_, FunctionCode::Compiled(method))) => method,
+                _ => unreachable!()
+            },
+            _ => unreachable!()
+        };
+    }
+
+    fn constant(&self, index: usize) -> Value {
+        return match &self.compiled.constants[index] {
+            Constant::Integer(value) => Value::Integer(*value),
+            Constant::Float(value) => Value::Float(*value),
+            Constant::String(value) => Value::String(value.to_owned()),
+            Constant::Char(value) => Value::Char(*value),
+            Constant::Boolean(value) => Value::Boolean(*value)
+        };
+    }
+
+    fn name(&self, index: usize) -> String {
+        return match &self.compiled.constants[index] {
+            Constant::String(name) => name.to_owned(),
+            _ => unreachable!()
+        };
+    }
+
+    fn pop(&mut self) -> Value {
+        return self.stack.pop().unwrap();
+    }
+
+    fn peek(&self) -> &Value {
+        return self.stack.last().unwrap();
+    }
+
+    fn pop_many(&mut self, count: usize) -> Vec<Value> {
+        return self.stack.split_off(self.stack.len() - count);
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        return self.frames.last_mut().unwrap();
+    }
+
+    fn jump(&mut self, target: usize) -> () {
+        self.frame().ip = target;
+    }
+
+    // Run instructions until the frame at the given depth returns, producing its result. Errors
+    // are caught by the innermost handler, unless it belongs to an outer call to this.
+    fn execute(&mut self, depth: usize) -> Execution<Value> {
+        loop {
+            let error: (String, Position) = match self.run_instructions(depth) {
+                Ok(value) => return Ok(value),
+                Err(error) => error
+            };
+            let handler: Handler = match self.handlers.last() {
+                Some(handler) if handler.frame >= depth && self.exit.is_none() => {
+                    self.handlers.pop().unwrap()
+                },
+                _ => return Err(error)
+            };
+            let error: Value = self.catch(error);
+
+            self.frames.truncate(handler.frame + 1);
+            self.stack.truncate(handler.stack);
+            self.frame().iterations.truncate(handler.iterations);
+            self.stack.push(error);
+            self.jump(handler.target);
+        }
+    }
+
+    // The error object for an error which is being caught, which is the one it was thrown with,
+    // or an `Error` for one found while running. The first time it is caught, it remembers the
+    // error along with the calls it happened in, in case it is thrown again.
+    fn catch(&mut self, (message, position): (String, Position)) -> Value {
+        let error: Value = self.thrown.take().unwrap_or_else(|| {
+            let class: &Rc<ClassValue> = self.classes.iter()
+                .find(|class| class.name == ERROR_CLASS)
+                .unwrap();
+
+            InstanceValue::error(class.to_owned(), message.to_owned())
+        });
+
+        if let Value::Instance(instance) = &error {
+            if instance.borrow().origin.is_none() {
+                instance.borrow_mut().origin = Some(RuntimeError {
+                    message,
+                    position,
+                    trace: self.trace()
+                });
+            }
+        }
+
+        return error;
+    }
+
+    // Start an error with an error object, which carries on from where it started if it has been
+    // caught before.
+    fn throw(&mut self, error: Value, position: Position) -> (String, Position) {
+        let thrown: (String, Position) = match &error {
+            Value::Instance(instance) => match &instance.borrow().origin {
+                Some(origin) => (origin.message.to_owned(), origin.position.to_owned()),
+                None => (instance.borrow().error_message(), position)
+            },
+            _ => unreachable!()
+        };
+
+        self.thrown = Some(error);
+
+        return thrown;
+    }
+
+    fn run_instructions(&mut self, depth: usize) -> Execution<Value> {
+        loop {
+            let frame: &mut Frame = self.frames.last_mut().unwrap();
+            let function: usize = frame.function;
+            let ip: usize = frame.ip;
+
+            frame.ip += 1;
+
+            let instruction: Instruction = self.compiled.functions[function].code[ip];
+            let position: Position = self.compiled.functions[function].positions[ip];
+
+            match instruction {
+                Instruction::Constant(index) => {
+                    let value: Value = self.constant(index);
+
+                    self.stack.push(value);
+                },
+                Instruction::Null => self.stack.push(Value::Null),
+                Instruction::Void => self.stack.push(Value::Void),
+                Instruction::Pop => {
+                    self.pop();
+                },
+                Instruction::Swap => {
+                    let length: usize = self.stack.len();
+
+                    self.stack.swap(length - 1, length - 2);
+                },
+                Instruction::GetLocal(slot) => {
+                    let value: Value = self.frame().locals[slot].to_owned();
+
+                    self.stack.push(value);
+                },
+                Instruction::SetLocal(slot) => {
+                    let value: Value = self.peek().to_owned();
+
+                    self.frame().locals[slot] = value;
+                },
+                Instruction::GetGlobal(slot) => match &self.globals[slot] {
+                    Some(value) => self.stack.push(value.to_owned()),
+                    None => return Err((
+                        "Variable `".to_owned() + &self.compiled.globals[slot] +
+                            "` is used before it is given a value.",
+                        position
+                    ))
+                },
+                Instruction::SetGlobal(slot) => self.globals[slot] = Some(self.peek().to_owned()),
+                Instruction::GetField(name) => {
+                    let name: String = self.name(name);
+                    let instance: Rc<RefCell<InstanceValue>> = match self.pop() {
+                        Value::Instance(instance) => instance,
+                        Value::Null => return Err((
+                            "Cannot get property `".to_owned() + &name + "` of null.",
+                            position
+                        )),
+                        _ => unreachable!()
+                    };
+                    let value: Option<Value> = instance.borrow().fields.get(&name).cloned()
+                        .flatten();
+
+                    match value {
+                        Some(value) => self.stack.push(value),
+                        None => return Err((
+                            "Property `".to_owned() + &name +
+                                "` is used before it is given a value.",
+                            position
+                        ))
+                    }
+                },
+                Instruction::SetField(name) => {
+                    let name: String = self.name(name);
+                    let object: Value = self.pop();
+                    let value: Value = self.peek().to_owned();
+
+                    match object {
+                        Value::Instance(instance) => {
+                            instance.borrow_mut().fields.insert(name, Some(value));
+                        },
+                        _ => unreachable!()
+                    }
+                },
+                Instruction::Binary(operator) => {
+                    let right: Value = self.pop();
+                    let left: Value = self.pop();
+                    let result: Value = binary_operation(operator, &left, &right)
+                        .map_err(|message| (message, position))?;
+
+                    self.stack.push(result);
+                },
+                Instruction::Unary(operator) => {
+                    let operand: Value = self.pop();
+                    let result: Value = unary_operation(operator, &operand)
+                        .map_err(|message| (message, position))?;
+
+                    self.stack.push(result);
+                },
+                Instruction::Jump(target) => self.jump(target),
+                Instruction::JumpIfFalse(target) => {
+                    if let Value::Boolean(false) = self.pop() {
+                        self.jump(target);
+                    }
+                },
+                Instruction::JumpIfFalseOrPop(target) => {
+                    if let Value::Boolean(false) = self.peek() {
+                        self.jump(target);
+                    } else {
+                        self.pop();
+                    }
+                },
+                Instruction::JumpIfTrueOrPop(target) => {
+                    if let Value::Boolean(true) = self.peek() {
+                        self.jump(target);
+                    } else {
+                        self.pop();
+                    }
+                },
+                Instruction::JumpIfNull(target) => {
+                    if let Value::Null = self.peek() {
+                        self.jump(target);
+                    }
+                },
+                Instruction::JumpIfNotNull(target) => {
+                    if !matches!(self.peek(), Value::Null) {
+                        self.jump(target);
+                    }
+                },
+                Instruction::JumpIfPresent(target) => {
+                    if matches!(self.peek(), Value::Enum(enum_value) if enum_value.is_present()) {
+                        self.jump(target);
+                    }
+                },
+                Instruction::Call(function, count) => {
+                    let arguments: Vec<Value> = self.pop_many(count);
+
+                    self.push_call(function, arguments, position)?;
+                },
+                Instruction::CallMethod(name, count) => {
+                    let name: String = self.name(name);
+                    let arguments: Vec<Value> = self.pop_many(count);
+                    let object: Value = self.pop();
+
+                    let result: Value = match &object {
+                        Value::Map(map) => map_method(map, &name, &arguments)
+                            .map_err(|message| (message, position))?,
+                        Value::List(list) => list_method(list, &name, &arguments)
+                            .map_err(|message| (message, position))?,
+                        Value::Instance(_) => {
+                            let method: usize = self.find_method(&object, &name);
+                            let mut method_arguments: Vec<Value> = vec![object];
+
+                            method_arguments.extend(arguments);
+                            self.push_call(method, method_arguments, position)?;
+
+                            continue;
+                        },
+                        Value::Null => return Err((
+                            "Cannot call method `".to_owned() + &name + "` of null.",
+                            position
+                        )),
+                        _ => unreachable!()
+                    };
+
+                    self.stack.push(result);
+                },
+                Instruction::New(class, count) => {
+                    let arguments: Vec<Value> = self.pop_many(count);
+                    let instance: Value = self.instantiate(class, arguments, position)?;
+
+                    self.stack.push(instance);
+                },
+                Instruction::CallNative(native, count) => {
+                    let arguments: Vec<Value> = self.pop_many(count);
+                    let native: &Native = &natives()[native];
+                    let result: Value = match (native.function)(self.program, arguments) {
+                        Ok(result) => result,
+                        Err(NativeError::Error(message)) => return Err((message, position)),
+                        Err(NativeError::Exit(code)) => {
+                            self.exit = Some(code);
+
+                            return Err((String::new(), position));
+                        }
+                    };
+
+                    self.stack.push(result);
+                },
+                Instruction::PushHandler(target) => {
+                    let handler: Handler = Handler {
+                        frame: self.frames.len() - 1,
+                        stack: self.stack.len(),
+                        iterations: self.frame().iterations.len(),
+                        target
+                    };
+
+                    self.handlers.push(handler);
+                },
+                Instruction::PopHandler => {
+                    self.handlers.pop();
+                },
+                Instruction::Throw => match self.pop() {
+                    Value::String(message) => return Err((message, position)),
+                    error => return Err(self.throw(error, position))
+                },
+                Instruction::Return => {
+                    let value: Value = self.pop();
+                    let frame: Frame = self.frames.pop().unwrap();
+                    let frames: usize = self.frames.len();
+
+                    self.stack.truncate(frame.base);
+                    self.handlers.retain(|handler| handler.frame < frames);
+
+                    if self.frames.len() == depth {
+                        return Ok(value);
+                    }
+
+                    self.stack.push(value);
+                },
+                Instruction::List(count) => {
+                    let elements: Vec<Value> = self.pop_many(count);
+
+                    self.stack.push(Value::List(Rc::new(RefCell::new(elements))));
+                },
+                Instruction::Map(count) => {
+                    let values: Vec<Value> = self.pop_many(count * 2);
+                    let mut map: MapValue = MapValue::new();
+
+                    for entry in values.chunks(2) {
+                        map.set(MapKey::from_value(&entry[0]).unwrap(), entry[1].to_owned());
+                    }
+
+                    self.stack.push(Value::Map(Rc::new(RefCell::new(map))));
+                },
+                Instruction::Range => {
+                    let end: Value = self.pop();
+                    let start: Value = self.pop();
+
+                    match (start, end) {
+                        (Value::Integer(start), Value::Integer(end)) => {
+                            self.stack.push(Value::Range(start, end));
+                        },
+                        _ => unreachable!()
+                    }
+                },
+                Instruction::Is(class_name) => {
+                    let class_name: String = self.name(class_name);
+
+                    match self.pop() {
+                        Value::Instance(instance) => self.stack.push(Value::Boolean(
+                            instance.borrow().class.is_subclass_of(&class_name)
+                        )),
+                        _ => unreachable!()
+                    }
+                },
+                Instruction::MakeEnum(enum_name, variant, count) => {
+                    let fields: Vec<Value> = self.pop_many(count);
+                    let value: Value = Value::Enum(Rc::new(EnumValue {
+                        enum_name: self.name(enum_name),
+                        variant: self.name(variant),
+                        fields
+                    }));
+
+                    self.stack.push(value);
+                },
+                Instruction::IsVariant(variant) => {
+                    let variant: String = self.name(variant);
+                    let is_variant: bool = match self.pop() {
+                        Value::Enum(enum_value) => enum_value.variant == variant,
+                        _ => false
+                    };
+
+                    self.stack.push(Value::Boolean(is_variant));
+                },
+                Instruction::VariantField(index) => match self.pop() {
+                    Value::Enum(enum_value) => self.stack.push(enum_value.fields[index].to_owned()),
+                    _ => unreachable!()
+                },
+                Instruction::IterStart => {
+                    let iterable: Value = self.pop();
+                    let iteration: Iteration = self.start_iteration(iterable, position)?;
+
+                    self.frame().iterations.push(iteration);
+                },
+                Instruction::IterNext(target, count) => {
+                    match self.next_item(count, position)? {
+                        Some(values) => self.stack.extend(values),
+                        None => self.jump(target)
+                    }
+                },
+                Instruction::IterEnd => {
+                    self.frame().iterations.pop();
+                }
+            }
+        }
+    }
+
+    fn instantiate(&mut self, class: usize, arguments: Vec<Value>, position: Position)
+        -> Execution<Value> {
+        let class: Rc<ClassValue> = self.classes[class].to_owned();
+        let instance: Value = Value::Instance(Rc::new(RefCell::new(
+            InstanceValue::new(class.to_owned(), HashMap::new())
+        )));
+        let mut chain: Vec<Rc<ClassValue>> = Vec::new();
+        let mut current: Option<Rc<ClassValue>> = Some(class.to_owned());
+
+        while let Some(class) = current {
+            current = class.superclass.to_owned();
+            chain.push(class);
+        }
+
+        // Superclass properties are initialized first.
+        for class in chain.iter().rev() {
+            let initializer: usize = match class.initializer {
+                FunctionCode::Compiled(initializer) => initializer,
+                FunctionCode::Declaration(_) | FunctionCode::Native => unreachable!()
+            };
+
+            self.call(initializer, vec![instance.to_owned()], position.to_owned())?;
+        }
+
+        if let Some((_, FunctionCode::Compiled(constructor))) = class.find_constructor() {
+            let mut constructor_arguments: Vec<Value> = vec![instance.to_owned()];
+
+            constructor_arguments.extend(arguments);
+            self.call(constructor, constructor_arguments, position)?;
+        }
+
+        return Ok(instance);
+    }
+
+    fn start_iteration(&mut self, iterable: Value, position: Position) -> Execution<Iteration> {
+        return Ok(match iterable {
+            Value::Range(start, end) => Iteration::Range(start, end),
+            Value::List(list) => Iteration::List(list.borrow().to_owned(), 0),
+            Value::Map(map) => Iteration::Map(map.borrow().entries().to_owned(), 0),
+            Value::Instance(ref instance) => {
+                let class: Rc<ClassValue> = instance.borrow().class.to_owned();
+
+                if class.find_method("iterator").is_some() {
+                    Iteration::Instance(self.call_method(iterable, "iterator", Vec::new(),
+                                                         position)?)
+                } else {
+                    Iteration::Instance(iterable)
+                }
+            },
+            _ => unreachable!()
+        });
+    }
+
+    // Advance the innermost `for ... in` loop, producing the values of its variables, or `None`
+    // once it is finished.
+    fn next_item(&mut self, count: usize, position: Position) -> Execution<Option<Vec<Value>>> {
+        let iteration: &mut Iteration = self.frame().iterations.last_mut().unwrap();
+
+        return Ok(match iteration {
+            Iteration::Range(next, end) => {
+                if *next >= *end {
+                    return Ok(None);
+                }
+
+                *next += 1;
+
+                Some(vec![Value::Integer(*next - 1)])
+            },
+            Iteration::List(elements, index) => {
+                let element: Option<Value> = elements.get(*index).cloned();
+
+                *index += 1;
+
+                element.map(|element| vec![element])
+            },
+            Iteration::Map(entries, index) => {
+                let entry: Option<(MapKey, Value)> = entries.get(*index).cloned();
+
+                *index += 1;
+
+                entry.map(|(key, value)| if count == 2 {
+                    vec![key.to_value(), value]
+                } else {
+                    vec![key.to_value()]
+                })
+            },
+            Iteration::Instance(iterator) => {
+                let iterator: Value = iterator.to_owned();
+
+                match self.call_method(iterator.to_owned(), "has_next", Vec::new(), position)? {
+                    Value::Boolean(true) => {
+                        Some(vec![self.call_method(iterator, "next", Vec::new(), position)?])
+                    },
+                    _ => None
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use common::{directory, programs};
+
+// Every program in `tests/programs` is run on each backend, and has to produce exactly the output
+// stored next to it: `name.out` holds what it prints, `name.err` holds the warnings it produces
+// and the error it stops with, if any, and `name.status` holds its exit code when that is not
+// the one implied by `name.err`. Programs are run from that directory, so that the paths of the
+// modules they import appear the same in errors wherever the repository is. Setting `RIBBIT_BLESS`
+// writes what the first backend produces to those files instead, for the other backends to be
+// checked against.
+const BACKENDS: [&str; 2] = ["tree", "vm"];
+
+fn blessing() -> bool {
+    return env::var_os("RIBBIT_BLESS").is_some();
+}
+
+// Write the file, or remove it when there is nothing to put in it, since it is optional.
+fn write_expected(path: &Path, contents: &str) -> () {
+    if contents.is_empty() {
+        if path.exists() {
+            fs::remove_file(path).unwrap();
+        }
+    } else {
+        fs::write(path, contents).unwrap();
+    }
+}
+
+fn ribbit(args: &[&str]) -> Output {
+    return Command::new(env!("CARGO_BIN_EXE_ribbit-rs"))
+        .args(args)
+        .current_dir(directory())
+        .output()
+        .unwrap();
+}
+
+fn run(program: &Path, backend: &str) -> Output {
+    // Compiled programs are always run by the virtual machine.
+    if backend == "rbc" {
+        return ribbit(&["run", program.to_str().unwrap()]);
+    }
+
+    let name: &str = program.file_name().unwrap().to_str().unwrap();
+
+    return ribbit(&[&("--backend=".to_owned() + backend), name]);
+}
+
+// Compile each program to a `.rbc` file in the given directory, along with the warnings printed
+// while compiling it. Programs which are rejected before they run have to be rejected with their
+// expected error, and are left out.
+fn compile_programs(directory: &Path) -> Vec<(PathBuf, PathBuf, String)> {
+    fs::create_dir_all(directory).unwrap();
+
+    return programs().into_iter()
+        .filter_map(|program| {
+            let compiled: PathBuf = directory.join(program.file_name().unwrap())
+                .with_extension("rbc");
+            let name: &str = program.file_name().unwrap().to_str().unwrap();
+            let output: Output = ribbit(&["compile", name, compiled.to_str().unwrap()]);
+            let stderr: String = String::from_utf8_lossy(&output.stderr).to_string();
+
+            if output.status.success() {
+                return Some((program, compiled, stderr));
+            }
+
+            let expected_stderr: String = fs::read_to_string(program.with_extension("err"))
+                .unwrap_or_default();
+
+            assert_eq!(stderr, expected_stderr, "could not compile {}", program.display());
+
+            return None;
+        })
+        .collect();
+}
+
+// Run every program with each of the given backends, returning what did not match. Each program
+// comes with the warnings which were already printed before it was run, if it was compiled first.
+fn check_programs(programs: Vec<(PathBuf, PathBuf, String)>, backends: &[&str]) -> Vec<String> {
+    let mut failures: Vec<String> = Vec::new();
+
+    for (program, input, printed) in programs {
+        let expected_stdout: String = fs::read_to_string(program.with_extension("out"))
+            .unwrap_or_default();
+        let expected_stderr: String = fs::read_to_string(program.with_extension("err"))
+            .unwrap_or_default();
+        // Warnings alone do not stop a program.
+        let expected_status: i32 = match fs::read_to_string(program.with_extension("status")) {
+            Ok(status) => status.trim().parse().unwrap(),
+            Err(_) => {
+                let errors: bool = expected_stderr.lines().any(|line| line.starts_with("Error"));
+
+                if errors { 1 } else { 0 }
+            }
+        };
+
+        for backend in backends {
+            let output: Output = run(&input, backend);
+            let name: String = program.file_name().unwrap().to_string_lossy().to_string();
+
+            if String::from_utf8_lossy(&output.stdout) != expected_stdout {
+                failures.push(name.to_owned() + " (" + backend + "): unexpected output");
+            }
+
+            if printed.to_owned() + &String::from_utf8_lossy(&output.stderr) != expected_stderr {
+                failures.push(name.to_owned() + " (" + backend + "): unexpected error");
+            }
+
+            if output.status.code() != Some(expected_status) {
+                failures.push(name + " (" + backend + "): unexpected exit code");
+            }
+        }
+    }
+
+    return failures;
+}
+
+// Store what the program produces as what it is expected to produce.
+fn bless(program: &Path) -> () {
+    let output: Output = run(program, BACKENDS[0]);
+    let stderr: String = String::from_utf8_lossy(&output.stderr).to_string();
+    let code: i32 = output.status.code().unwrap();
+    let implied: i32 = if stderr.lines().any(|line| line.starts_with("Error")) { 1 } else { 0 };
+    let status: String = if code == implied { String::new() } else { code.to_string() + "\n" };
+
+    fs::write(program.with_extension("out"), &*String::from_utf8_lossy(&output.stdout)).unwrap();
+    write_expected(&program.with_extension("err"), &stderr);
+    write_expected(&program.with_extension("status"), &status);
+}
+
+#[test]
+fn backends_match_expected_output() -> () {
+    if blessing() {
+        programs().iter().for_each(|program| bless(program));
+    }
+
+    let programs: Vec<(PathBuf, PathBuf, String)> = programs().into_iter()
+        .map(|program| (program.to_owned(), program, String::new()))
+        .collect();
+    let failures: Vec<String> = check_programs(programs, &BACKENDS);
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn compiled_programs_match_expected_output() -> () {
+    // The expected files may be in the middle of being rewritten.
+    if blessing() {
+        return;
+    }
+
+    let directory: PathBuf = env::temp_dir().join("ribbit-rs-tests-".to_owned() +
+        &std::process::id().to_string());
+    let failures: Vec<String> = check_programs(compile_programs(&directory), &["rbc"]);
+
+    fs::remove_dir_all(directory).unwrap();
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
@@ -0,0 +1,186 @@
+mod common;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Child, Command, Output, Stdio};
+use common::ribbit;
+
+fn stdout(output: &Output) -> String {
+    return String::from_utf8_lossy(&output.stdout).to_string();
+}
+
+#[test]
+fn inline_code_sees_its_arguments() -> () {
+    for backend in ["--backend=tree", "--backend=vm"] {
+        let output: Output = ribbit(&[backend, "-e", "println(args());", "a", "--b"], "");
+
+        assert_eq!(stdout(&output), "[\"a\", \"--b\"]\n");
+    }
+}
+
+#[test]
+fn programs_can_be_read_from_stdin() -> () {
+    let output: Output = ribbit(&["run", "-", "--", "x"], "println(args().get(0));");
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "x\n");
+}
+
+#[test]
+fn missing_files_are_reported() -> () {
+    let output: Output = ribbit(&["check", "does-not-exist.rbt"], "");
+
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .starts_with("Could not read `does-not-exist.rbt`:"));
+}
+
+#[test]
+fn version_and_unknown_options() -> () {
+    let version: Output = ribbit(&["--version"], "");
+    let unknown: Output = ribbit(&["--unknown"], "");
+
+    assert_eq!(stdout(&version), "ribbit-rs ".to_owned() + env!("CARGO_PKG_VERSION") + "\n");
+    assert_eq!(unknown.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&unknown.stderr).starts_with("Unknown option `--unknown`."));
+}
+
+#[test]
+fn programs_see_the_environment() -> () {
+    let output: Output = Command::new(env!("CARGO_BIN_EXE_ribbit-rs"))
+        .args(["-e", "println(env(\"RIBBIT_TEST_VALUE\") ?? \"unset\");"])
+        .env("RIBBIT_TEST_VALUE", "set")
+        .output()
+        .unwrap();
+
+    assert_eq!(stdout(&output), "set\n");
+}
+
+#[test]
+fn programs_use_files_and_processes() -> () {
+    const PROGRAM: &str = "\
+        string directory = args().get(0) ?? \"\";
+        fs.write(fs.join(directory, \"b.txt\"), \"bee\");
+        fs.write(fs.join(directory, \"a.txt\"), \"ay\");
+        println(fs.list_dir(directory));
+        println(fs.read(fs.join(directory, \"a.txt\")));
+        println(fs.exists(fs.join(directory, \"c.txt\")));
+        ProcessResult result = process.run(\"sh\", [\"-c\", \"echo out; echo err >&2; exit 3\"]);
+        print(result.stdout + result.stderr);
+        println(result.status);
+        fs.read(fs.join(directory, \"c.txt\"));";
+
+    for backend in ["tree", "vm"] {
+        let directory: PathBuf = env::temp_dir().join("ribbit-rs-files-".to_owned() + backend +
+            "-" + &std::process::id().to_string());
+
+        fs::create_dir_all(&directory).unwrap();
+
+        let option: String = "--backend=".to_owned() + backend;
+        let path: String = directory.display().to_string();
+        let output: Output = ribbit(&[&option, "-e", PROGRAM, &path], "");
+
+        fs::remove_dir_all(&directory).unwrap();
+
+        assert_eq!(stdout(&output), "[\"a.txt\", \"b.txt\"]\nay\nfalse\nout\nerr\n3\n");
+        assert_eq!(output.status.code(), Some(70));
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Could not read `"));
+    }
+}
+
+#[test]
+fn imports_are_found_on_the_search_path() -> () {
+    let directory: PathBuf = env::temp_dir().join("ribbit-rs-modules-".to_owned() +
+        &std::process::id().to_string());
+
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(directory.join("greetings.rbt"), "pub string hello() { return \"hello\"; }").unwrap();
+
+    let output: Output = Command::new(env!("CARGO_BIN_EXE_ribbit-rs"))
+        .args(["-e", "import greetings; println(greetings.hello());"])
+        .env("RIBBIT_PATH", &directory)
+        .output()
+        .unwrap();
+
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(stdout(&output), "hello\n");
+}
+
+#[test]
+fn the_vm_nests_calls_deeper_than_the_tree_walker() -> () {
+    const PROGRAM: &str = "\
+        int sum(int n) { if n == 0 { return 0; } return n + sum(n - 1); }
+        println(sum(5000));";
+    let output: Output = ribbit(&["--backend=vm", "-e", PROGRAM], "");
+
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "12502500\n");
+}
+
+#[test]
+fn constructors_nested_too_deeply_are_stopped() -> () {
+    const PROGRAM: &str = "\
+        class Node { Node next = Node(); }
+        Node node = Node();";
+    let output: Output = ribbit(&["--backend=vm", "-e", PROGRAM], "");
+
+    assert_eq!(output.status.code(), Some(70));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("Stack overflow; functions are nested too deeply."));
+}
+
+#[test]
+fn damaged_compiled_programs_are_refused() -> () {
+    let directory: PathBuf = env::temp_dir().join("ribbit-rs-damaged-".to_owned() +
+        &std::process::id().to_string());
+    let source: PathBuf = directory.join("add.rbt");
+    let compiled: PathBuf = directory.join("add.rbc");
+
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(&source, "int add(int a, int b) { return a + b; }\nprintln(add(1, 2));").unwrap();
+    ribbit(&["compile", source.to_str().unwrap(), compiled.to_str().unwrap()], "");
+
+    let bytes: Vec<u8> = fs::read(&compiled).unwrap();
+    // The function is stored as its name, its arity, its number of locals and its code, which
+    // starts with `GetLocal 0`.
+    let name: usize = bytes.windows(7).position(|window| window == b"\x03\0\0\0add").unwrap();
+    let damages: [(usize, &[u8]); 3] = [
+        (name + 7, &[3, 0, 0, 0]), // Called with fewer arguments than it takes.
+        (name + 11, &[0xF0, 0xFF, 0xFF, 0xFF]), // More locals than could ever be allocated.
+        (name + 19, &[12]) // `Unary 0`, which negates a value that is not on the stack.
+    ];
+
+    for (offset, damage) in damages {
+        let mut damaged: Vec<u8> = bytes.to_owned();
+
+        damaged[offset..offset + damage.len()].copy_from_slice(damage);
+        fs::write(&compiled, damaged).unwrap();
+
+        let output: Output = ribbit(&["run", compiled.to_str().unwrap()], "");
+
+        assert_eq!(output.status.code(), Some(1));
+        assert!(String::from_utf8_lossy(&output.stderr)
+            .ends_with(": The function `add` is invalid.\n"));
+    }
+
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn closed_output_is_a_runtime_error() -> () {
+    let mut child: Child = Command::new(env!("CARGO_BIN_EXE_ribbit-rs"))
+        .args(["-e", "for i in 0..100000 { println(i); }"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    drop(child.stdout.take());
+
+    let output: Output = child.wait_with_output().unwrap();
+
+    assert_eq!(output.status.code(), Some(70));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Could not write the output:"));
+}
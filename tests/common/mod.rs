@@ -0,0 +1,42 @@
+// Helpers shared by the integration tests. Each test file is a crate of its own which includes this
+// module, and not all of them use every helper.
+#![allow(dead_code)]
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+
+// The directory of example programs, along with the files holding what they are expected to
+// produce.
+pub fn directory() -> PathBuf {
+    return Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("programs");
+}
+
+// Every example program, in order of name.
+pub fn programs() -> Vec<PathBuf> {
+    let mut programs: Vec<PathBuf> = fs::read_dir(directory()).unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "rbt"))
+        .collect();
+
+    programs.sort();
+
+    return programs;
+}
+
+// Run the command line interface with the given arguments, writing the given text to its standard
+// input.
+pub fn ribbit(args: &[&str], stdin: &str) -> Output {
+    let mut child: Child = Command::new(env!("CARGO_BIN_EXE_ribbit-rs"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+
+    return child.wait_with_output().unwrap();
+}
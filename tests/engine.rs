@@ -0,0 +1,265 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::thread;
+use ribbit_rs::{Engine, Error, ErrorKind, HostClass, Object, ScriptValue, Type, Value};
+
+fn greeting_engine() -> Engine {
+    let mut engine: Engine = Engine::new();
+
+    engine.register_function("greet", |name: String| {
+        if name.is_empty() {
+            return Err("Nobody to greet.".to_owned());
+        }
+
+        return Ok("Hello, ".to_owned() + &name + "!");
+    });
+
+    return engine;
+}
+
+#[test]
+fn scripts_produce_the_value_they_end_with() -> () {
+    let engine: Engine = Engine::new();
+
+    assert!(engine.run("int x = 20; x * 2 + 2;").unwrap() == Value::Integer(42));
+    assert!(engine.run("int x = 1;").unwrap() == Value::Void);
+    assert!(engine.run("").unwrap() == Value::Void);
+    assert_eq!(engine.run("float half(float x) { return x / 2; } half(1);").unwrap().to_string(),
+               "0.5");
+}
+
+#[test]
+fn registered_functions_are_checked_and_called() -> () {
+    let engine: Engine = greeting_engine();
+
+    assert_eq!(engine.run("greet(\"ribbit\");").unwrap().to_string(), "Hello, ribbit!");
+
+    let compile_error: Error = engine.run("string s = \"a\";\ngreet(1);").err().unwrap();
+
+    assert_eq!(compile_error.kind, ErrorKind::Compile);
+    assert_eq!((compile_error.line, compile_error.column), (2, 7));
+    assert_eq!(compile_error.to_string(), "\
+Error on line 2: Expected a value of type `string`, but found `int`.
+ 2 | greet(1);
+           ^ <- Here");
+
+    let runtime_error: Error = engine.run("println(\"before\");\ngreet(\"\");").err().unwrap();
+
+    assert_eq!(runtime_error.kind, ErrorKind::Runtime);
+    assert_eq!(runtime_error.message, "Nobody to greet.");
+    assert_eq!(runtime_error.line, 2);
+    assert!(runtime_error.trace.is_empty());
+}
+
+#[test]
+fn exiting_stops_the_script_but_not_the_host() -> () {
+    let engine: Engine = Engine::new();
+    let error: Error = engine.run("\
+void stop() {
+    try {
+        exit(3);
+    } catch (e) {
+        println(\"caught\");
+    }
+}
+stop();
+1;").err().unwrap();
+
+    assert_eq!(error.kind, ErrorKind::Exit(3));
+    assert_eq!(error.to_string(), "The script exited with code 3.");
+}
+
+#[test]
+fn deep_recursion_is_stopped_before_the_stack_runs_out() -> () {
+    let engine: Engine = Engine::new();
+    let error: Error = engine.run("int forever(int n) { return forever(n + 1); }\nforever(0);")
+        .err().unwrap();
+
+    assert_eq!(error.kind, ErrorKind::Runtime);
+    assert_eq!(error.message, "Stack overflow; functions are nested too deeply.");
+}
+
+#[test]
+fn deeply_nested_source_is_an_error_on_a_small_stack() -> () {
+    // Rust gives new threads 2 MiB of stack, which is what embedding programs usually run on.
+    let thread = thread::Builder::new().stack_size(2 * 1024 * 1024).spawn(|| {
+        let engine: Engine = Engine::new();
+        let sum: String = "(x + ".repeat(5000) + "x" + &")".repeat(5000);
+        let negation: String = "!".repeat(5000) + "true";
+        let blocks: String = "{".repeat(5000) + &"}".repeat(5000);
+
+        for source in ["int x = 1; ".to_owned() + &sum + ";", negation + ";", blocks] {
+            let error: Error = engine.run(&source).err().unwrap();
+
+            assert_eq!(error.kind, ErrorKind::Compile);
+            assert_eq!(error.message, "Code is nested too deeply.");
+        }
+
+        let shallow: String = "(x + ".repeat(20) + "x" + &")".repeat(20);
+
+        assert!(engine.run(&("int x = 1; ".to_owned() + &shallow + ";")).unwrap() ==
+                Value::Integer(21));
+    }).unwrap();
+
+    thread.join().unwrap();
+}
+
+#[test]
+fn runtime_errors_carry_the_calls_they_happened_in() -> () {
+    let engine: Engine = greeting_engine();
+    let error: Error = engine.run("\
+string welcome(string name) { return greet(name); }
+string welcome_all(list<string> names) { return welcome(names.get(0)); }
+welcome_all([\"\"]);").err().unwrap();
+    let calls: Vec<(String, i32, i32)> = error.trace.iter()
+        .map(|call| (call.function.to_owned(), call.line, call.column))
+        .collect();
+
+    assert_eq!((error.line, error.column), (1, 38));
+    assert_eq!(calls, vec![("welcome".to_owned(), 2, 49), ("welcome_all".to_owned(), 3, 1)]);
+    assert_eq!(error.to_string(), "\
+Error on line 1: Nobody to greet.
+ 1 | string welcome(string name) { return greet(name); }
+                                          ^^^^^ <- Here
+In `welcome`, called on line 2:
+ 2 | string welcome_all(list<string> names) { return welcome(names.get(0)); }
+                                                     ^^^^^^^ <- Here
+In `welcome_all`, called on line 3:
+ 3 | welcome_all([\"\"]);
+     ^^^^^^^^^^^ <- Here");
+}
+
+#[test]
+fn registered_values_are_shared_globals() -> () {
+    let mut engine: Engine = Engine::new();
+    let names: Rc<RefCell<Vec<Value>>> = Rc::new(RefCell::new(vec![Value::String("a".to_owned())]));
+
+    engine.register_dynamic_value("names", Type::List(Box::new(Type::String)),
+                                  Value::List(names.clone())).unwrap();
+    engine.register_value("limit", 3);
+
+    let script = engine.compile("names.push(\"b\"); limit = limit - names.size(); limit;").unwrap();
+
+    assert!(engine.execute(&script).unwrap() == Value::Integer(1));
+    // The list is shared, but the integer is given again on each run.
+    assert!(engine.execute(&script).unwrap() == Value::Integer(0));
+    assert_eq!(names.borrow().len(), 3);
+
+    let error: Error = engine.run("string limit = \"x\";").err().unwrap();
+
+    assert_eq!(error.message, "Variable `limit` is already declared in this scope.");
+}
+
+#[test]
+fn dynamic_values_must_have_their_declared_types() -> () {
+    let mut engine: Engine = Engine::new();
+    let numbers: Value = Value::List(Rc::new(RefCell::new(vec![Value::Integer(1)])));
+
+    assert_eq!(engine.register_dynamic_value("v", Type::Integer, Value::Boolean(true)),
+               Err("The value of `v` is not of type `int`.".to_owned()));
+    assert!(engine.register_dynamic_value("v", Type::List(Box::new(Type::String)), numbers)
+        .is_err());
+    assert!(engine.register_dynamic_value("v", Type::Nullable(Box::new(Type::Integer)),
+                                          Value::Null).is_ok());
+
+    engine.register_dynamic_function("g", Vec::new(), Type::Integer, |_| {
+        return Ok(Value::String("x".to_owned()));
+    });
+
+    let error: Error = engine.run("g() + 1;").err().unwrap();
+
+    assert_eq!(error.kind, ErrorKind::Runtime);
+    assert_eq!(error.message, "`g` returned a value which is not of type `int`.");
+}
+
+#[test]
+fn values_are_converted_both_ways() -> () {
+    let mut engine: Engine = Engine::new();
+
+    engine.register_function("total", |numbers: Vec<i64>, scale: f64| {
+        return Ok(numbers.iter().sum::<i64>() as f64 * scale);
+    });
+    engine.register_function("first_word", |text: String| {
+        return Ok(text.split_whitespace().next().map(|word| word.to_owned()));
+    });
+    engine.register_dynamic_function("describe", vec![Type::Integer], Type::String, |arguments| {
+        return Ok(Value::String("<".to_owned() + &arguments[0].to_string() + ">"));
+    });
+
+    let value: Value = engine.run("[total([1, 2, 3], 2), total([], 1)];").unwrap();
+
+    assert_eq!(Vec::<f64>::from_value(value), Some(vec![12.0, 0.0]));
+
+    let value: Value = engine.run("first_word(\"  \") ?? describe(7);").unwrap();
+
+    assert_eq!(String::from_value(value), Some("<7>".to_owned()));
+    assert_eq!(engine.run("first_word(\"a b\");").unwrap().to_string(), "a");
+    assert!(engine.run("total([1.5], 1);").is_err());
+}
+
+struct Counter {
+    count: i64
+}
+
+impl HostClass for Counter {
+    const NAME: &'static str = "Counter";
+}
+
+#[test]
+fn host_classes_are_used_through_their_methods() -> () {
+    let mut engine: Engine = Engine::new();
+    let counter: Object<Counter> = Object::new(Counter { count: 0 });
+
+    engine.register_class::<Counter>();
+    engine.register_method("increment", |counter: Object<Counter>, by: i64| {
+        counter.borrow_mut().count += by;
+
+        return Ok(counter.borrow().count);
+    });
+    engine.register_function("make_counter", || Ok(Object::new(Counter { count: 10 })));
+    engine.register_value("shared", counter.clone());
+
+    let value: Value = engine.run("\
+        shared.increment(2);
+        Counter other = make_counter();
+        other.increment(1);
+        [shared is Counter, shared == other, shared == shared];").unwrap();
+
+    assert_eq!(value.to_string(), "[true, false, true]");
+    assert_eq!(counter.borrow().count, 2);
+
+    let error: Error = engine.run("Counter c = Counter();").err().unwrap();
+
+    assert_eq!(error.message, "Instances of the built-in class `Counter` cannot be created.");
+
+    let error: Error = engine.run("shared.count;").err().unwrap();
+
+    assert_eq!(error.kind, ErrorKind::Compile);
+}
+
+#[test]
+fn scripts_are_only_run_with_what_they_were_checked_against() -> () {
+    let mut engine: Engine = Engine::new();
+
+    engine.register_function("double", |x: i64| Ok(x * 2));
+
+    let script = engine.compile("double(21);").unwrap();
+
+    // Registering a function again with the same types keeps the script runnable.
+    engine.register_function("double", |x: i64| Ok(x + x));
+    assert!(engine.execute(&script).unwrap() == Value::Integer(42));
+
+    engine.register_function("double", |text: String| Ok(text.repeat(2)));
+
+    let error: Error = engine.execute(&script).err().unwrap();
+
+    assert_eq!(error.kind, ErrorKind::Compile);
+    assert_eq!(error.to_string(), "Error: `int double(int)` is no longer registered as it was \
+        when the script was compiled.");
+
+    let error: Error = Engine::new().execute(&script).err().unwrap();
+
+    assert_eq!(error.kind, ErrorKind::Compile);
+    assert_eq!(error.message, "`int double(int)` is no longer registered as it was when the \
+        script was compiled.");
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use common::ribbit;
+
+// Format the source given on standard input.
+fn format(source: &str) -> String {
+    let output: Output = ribbit(&["fmt", "-"], source);
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    return String::from_utf8_lossy(&output.stdout).to_string();
+}
+
+fn find_programs(directory: &Path, programs: &mut Vec<PathBuf>) -> () {
+    for entry in fs::read_dir(directory).unwrap() {
+        let path: PathBuf = entry.unwrap().path();
+
+        if path.is_dir() {
+            find_programs(&path, programs);
+        } else if path.extension().is_some_and(|extension| extension == "rbt") {
+            programs.push(path);
+        }
+    }
+}
+
+#[test]
+fn formatting_is_idempotent() -> () {
+    let mut programs: Vec<PathBuf> = Vec::new();
+
+    find_programs(&common::directory(), &mut programs);
+
+    for program in programs {
+        let formatted: String = format(&fs::read_to_string(&program).unwrap());
+
+        assert_eq!(format(&formatted), formatted, "{}", program.display());
+    }
+}
+
+#[test]
+fn comments_and_blank_lines_are_kept() -> () {
+    const SOURCE: &str = "\
+// The header.
+
+
+import   shapes.circle ;
+class Counter extends Base { // Counts.
+    int count = 0;   // Starts at zero.
+
+
+
+    Counter(int count) { this.count = count; }
+    // Nothing after this.
+}
+int   twice(int n){
+    // Doubled.
+    return n*2;
+
+
+}
+string name = match twice(1) { 2 => \"two\", // The answer.
+    _ => { return; }
+};
+println((1 + 2) * 3 - (4 - 5), -2 ^ 2, (-2) ^ 2, x++, 'a', \"\\\"\\n\");
+";
+    const EXPECTED: &str = "\
+// The header.
+
+import shapes.circle;
+class Counter extends Base { // Counts.
+    int count = 0; // Starts at zero.
+
+    Counter(int count) {
+        this.count = count;
+    }
+    // Nothing after this.
+}
+int twice(int n) {
+    // Doubled.
+    return n * 2;
+}
+string name = match twice(1) {
+    2 => \"two\", // The answer.
+    _ => {
+        return;
+    }
+};
+println((1 + 2) * 3 - (4 - 5), -2 ^ 2, (-2) ^ 2, x++, 'a', \"\\\"\\n\");
+";
+
+    assert_eq!(format(SOURCE), EXPECTED);
+}
+
+#[test]
+fn long_lines_are_broken() -> () {
+    let source: String = "list<string> words = [".to_owned() +
+        &vec!["\"word\""; 20].join(", ") + "];\nbool b = first_condition && second_condition || \
+        third_condition && fourth_condition_which_is_long || fifth_condition_which_is_long;\n";
+    let expected: String = "list<string> words = [\n".to_owned() +
+        &vec!["    \"word\""; 20].join(",\n") + "\n];\nbool b = first_condition && \
+        second_condition || third_condition && fourth_condition_which_is_long ||\n    \
+        fifth_condition_which_is_long;\n";
+
+    assert_eq!(format(&source), expected);
+}
+
+#[test]
+fn files_are_checked_and_written() -> () {
+    let directory: PathBuf = env::temp_dir().join("ribbit-rs-fmt-".to_owned() +
+        &std::process::id().to_string());
+    let path: String = directory.join("program.rbt").display().to_string();
+    let directory_path: String = directory.display().to_string();
+
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(&path, "int x=1;").unwrap();
+
+    let check: Output = ribbit(&["fmt", "--check", &directory_path], "");
+    let write: Output = ribbit(&["fmt", "--write", &directory_path], "");
+    let written: String = fs::read_to_string(&path).unwrap();
+    let checked: Output = ribbit(&["fmt", "--check", &path], "");
+
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(check.status.code(), Some(1));
+    assert_eq!(String::from_utf8_lossy(&check.stdout), path.to_owned() + "\n");
+    assert!(write.status.success());
+    assert_eq!(written, "int x = 1;\n");
+    assert!(checked.status.success());
+    assert!(checked.stdout.is_empty());
+}
@@ -0,0 +1,147 @@
+mod common;
+
+use std::process::Output;
+use common::ribbit;
+
+const URI: &str = "file:///tmp/ribbit-lsp-test.rbt";
+
+// Run the language server on the given messages, returning the contents of the messages it sent
+// and its exit code.
+fn run_server(messages: &[String]) -> (Vec<String>, Option<i32>) {
+    let input: String = messages.iter()
+        .map(|message| "Content-Length: ".to_owned() + &message.len().to_string() + "\r\n\r\n" +
+            message)
+        .collect();
+
+    let output: Output = ribbit(&["lsp"], &input);
+    let stdout: String = String::from_utf8_lossy(&output.stdout).to_string();
+    let mut sent: Vec<String> = Vec::new();
+    let mut rest: &str = &stdout;
+
+    while let Some((header, after)) = rest.split_once("\r\n\r\n") {
+        let length: usize = header.trim_start_matches("Content-Length: ").parse().unwrap();
+
+        sent.push(after[..length].to_owned());
+        rest = &after[length..];
+    }
+
+    return (sent, output.status.code());
+}
+
+fn request(id: i32, method: &str, params: &str) -> String {
+    return "{\"jsonrpc\":\"2.0\",\"id\":".to_owned() + &id.to_string() + ",\"method\":\"" + method +
+        "\",\"params\":" + params + "}";
+}
+
+fn notification(method: &str, params: &str) -> String {
+    return "{\"jsonrpc\":\"2.0\",\"method\":\"".to_owned() + method + "\",\"params\":" + params +
+        "}";
+}
+
+fn open(text: &str) -> String {
+    return notification(
+        "textDocument/didOpen",
+        &("{\"textDocument\":{\"uri\":\"".to_owned() + URI +
+            "\",\"languageId\":\"ribbit\",\"version\":1,\"text\":\"" + text + "\"}}")
+    );
+}
+
+fn at(line: i32, character: i32) -> String {
+    return "{\"textDocument\":{\"uri\":\"".to_owned() + URI + "\"},\"position\":{\"line\":" +
+        &line.to_string() + ",\"character\":" + &character.to_string() + "}}";
+}
+
+// A session which is shut down properly, around the given messages.
+fn session(messages: Vec<String>) -> Vec<String> {
+    let mut all: Vec<String> = vec![request(0, "initialize", "{}")];
+
+    all.extend(messages);
+    all.push(request(99, "shutdown", "null"));
+    all.push(notification("exit", "null"));
+
+    let (sent, code): (Vec<String>, Option<i32>) = run_server(&all);
+
+    assert_eq!(code, Some(0));
+    assert!(sent[0].contains("\"hoverProvider\":true"), "{}", sent[0]);
+    assert_eq!(sent.last().unwrap(), "{\"jsonrpc\":\"2.0\",\"id\":99,\"result\":null}");
+
+    return sent[1..sent.len() - 1].to_vec();
+}
+
+#[test]
+fn diagnostics_are_published() -> () {
+    let sent: Vec<String> = session(vec![
+        open("int x = 1;\\nprintln(x);"),
+        notification(
+            "textDocument/didChange",
+            &("{\"textDocument\":{\"uri\":\"".to_owned() + URI + "\",\"version\":2},\
+                \"contentChanges\":[{\"text\":\"int s = \\\"é\\\" + 1;\\nint unused = 2;\"}]}")
+        ),
+        notification("textDocument/didChange", &("{\"textDocument\":{\"uri\":\"".to_owned() +
+            URI + "\"},\"contentChanges\":[{\"text\":\"int f(int n) { return 1; }\"}]}")),
+        notification("textDocument/didChange", &("{\"textDocument\":{\"uri\":\"".to_owned() +
+            URI + "\"},\"contentChanges\":[{\"text\":\"println(1 / 0);\"}]}")),
+        notification("textDocument/didClose", &("{\"textDocument\":{\"uri\":\"".to_owned() + URI +
+            "\"}}"))
+    ]);
+
+    assert_eq!(sent.len(), 5);
+    assert!(sent[0].contains("\"diagnostics\":[]"), "{}", sent[0]);
+    assert!(sent[1].contains("\"range\":{\"start\":{\"line\":0,\"character\":12},\
+        \"end\":{\"line\":0,\"character\":13}},\"severity\":1"), "{}", sent[1]);
+    assert!(sent[2].contains("\"severity\":2,\"source\":\"ribbit\",\
+        \"message\":\"Parameter `n` is never used.\""), "{}", sent[2]);
+    assert!(sent[3].contains("\"severity\":1,\"source\":\"ribbit\",\
+        \"message\":\"Division by zero in a constant expression.\""), "{}", sent[3]);
+    assert!(sent[4].contains("\"diagnostics\":[]"), "{}", sent[4]);
+}
+
+#[test]
+fn hover_definition_and_symbols() -> () {
+    let sent: Vec<String> = session(vec![
+        open("int twice(int n) {\\n    return n * 2;\\n}\\nfloat x = twice(2) / 2.0;\\n\
+            class A { int a = 1; }\\nenum E { One }"),
+        request(1, "textDocument/hover", &at(3, 6)),
+        request(2, "textDocument/hover", &at(3, 0)),
+        request(3, "textDocument/definition", &at(1, 11)),
+        request(4, "textDocument/documentSymbol", &("{\"textDocument\":{\"uri\":\"".to_owned() +
+            URI + "\"}}")),
+        request(5, "textDocument/completion", &at(0, 0))
+    ]);
+
+    assert_eq!(sent[1], "{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"contents\":\
+        {\"kind\":\"plaintext\",\"value\":\"float\"},\"range\":{\"start\":{\"line\":3,\
+        \"character\":6},\"end\":{\"line\":3,\"character\":7}}}}");
+    assert_eq!(sent[2], "{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":null}");
+    assert_eq!(sent[3], "{\"jsonrpc\":\"2.0\",\"id\":3,\"result\":{\"uri\":\"".to_owned() + URI +
+        "\",\"range\":{\"start\":{\"line\":0,\"character\":14},\"end\":{\"line\":0,\
+        \"character\":15}}}}");
+    assert!(sent[4].contains("{\"name\":\"A\",\"kind\":5,"), "{}", sent[4]);
+    assert!(sent[4].contains("\"children\":[{\"name\":\"a\",\"kind\":7,"), "{}", sent[4]);
+    assert!(sent[4].contains("{\"name\":\"One\",\"kind\":22,"), "{}", sent[4]);
+    assert!(sent[5].contains("{\"label\":\"while\",\"kind\":14}"), "{}", sent[5]);
+}
+
+#[test]
+fn bad_messages_are_answered_with_errors() -> () {
+    let sent: Vec<String> = session(vec![
+        "{not json".to_owned(),
+        request(1, "textDocument/unknown", "{}"),
+        request(2, "textDocument/hover", "{}")
+    ]);
+
+    assert!(sent[0].contains("\"id\":null,\"error\":{\"code\":-32700"), "{}", sent[0]);
+    assert!(sent[1].contains("\"id\":1,\"error\":{\"code\":-32601"), "{}", sent[1]);
+    assert!(sent[2].contains("\"id\":2,\"error\":{\"code\":-32602"), "{}", sent[2]);
+}
+
+#[test]
+fn exiting_without_shutting_down_fails() -> () {
+    let (sent, code): (Vec<String>, Option<i32>) = run_server(&[
+        request(0, "initialize", "{}"),
+        notification("exit", "null")
+    ]);
+
+    assert_eq!(sent.len(), 1);
+    assert_eq!(code, Some(1));
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use std::process::Output;
+use common::ribbit;
+
+// Run the REPL with the given input, as if it were typed in.
+fn repl(input: &str) -> Output {
+    return ribbit(&[], input);
+}
+
+#[test]
+fn entries_keep_their_state() -> () {
+    let output: Output = repl("int x = 2;\nint double(int n) {\n    return n * 2;\n}\ndouble(x)\n");
+    let stdout: String = String::from_utf8_lossy(&output.stdout).to_string();
+
+    assert!(output.status.success());
+    assert!(stdout.contains("4: int"), "{}", stdout);
+}
+
+#[test]
+fn ints_given_as_floats_are_converted() -> () {
+    let output: Output = repl("float half(float x) {\n    return x / 2;\n}\nhalf(3)\n");
+    let stdout: String = String::from_utf8_lossy(&output.stdout).to_string();
+
+    assert!(output.status.success());
+    assert!(stdout.contains("1.5: float"), "{}", stdout);
+}
+
+#[test]
+fn unfinished_entries_can_be_left() -> () {
+    let output: Output = repl("int f() {\n:type 1.5\n:cancel\nint x = 1;\nx\n[x,\n");
+    let stdout: String = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr: String = String::from_utf8_lossy(&output.stderr).to_string();
+
+    assert!(output.status.success());
+    assert!(stdout.contains("float"), "{}", stdout);
+    assert!(stdout.contains("1: int"), "{}", stdout);
+    assert!(stderr.contains("The last entry was not finished, so it has not been run."), "{}",
+            stderr);
+}
+
+#[test]
+fn errors_do_not_end_the_session() -> () {
+    let output: Output = repl("int x = missing;\nint x = 1;\nx + 1\n:type [x]\n:quit\nx\n");
+    let stdout: String = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr: String = String::from_utf8_lossy(&output.stderr).to_string();
+
+    assert!(output.status.success());
+    assert!(stderr.contains("Undefined variable `missing`."), "{}", stderr);
+    assert!(stdout.contains("2: int"), "{}", stdout);
+    assert!(stdout.contains("list<int>"), "{}", stdout);
+    // Nothing runs after `:quit`.
+    assert!(!stdout.contains("1: int"), "{}", stdout);
+}
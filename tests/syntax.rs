@@ -0,0 +1,48 @@
+mod common;
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use common::{directory, programs};
+
+// Every program in `tests/programs` has the tokens its source is split into stored in
+// `name.tokens`, and the tree it is parsed into stored in `name.ast`, followed by the errors found
+// along the way, if any. Setting `RIBBIT_BLESS` writes what is produced to those files instead.
+const DUMPS: [&str; 2] = ["tokens", "ast"];
+
+fn dump(command: &str, program: &Path) -> String {
+    let output: Output = Command::new(env!("CARGO_BIN_EXE_ribbit-rs"))
+        .args([command, program.file_name().unwrap().to_str().unwrap()])
+        .current_dir(directory())
+        .output()
+        .unwrap();
+
+    return String::from_utf8_lossy(&output.stdout).to_string() +
+        &String::from_utf8_lossy(&output.stderr);
+}
+
+#[test]
+fn syntax_matches_expected_dumps() -> () {
+    let bless: bool = env::var_os("RIBBIT_BLESS").is_some();
+    let mut failures: Vec<String> = Vec::new();
+
+    for program in programs() {
+        for command in DUMPS {
+            let produced: String = dump(command, &program);
+            let expected_path: PathBuf = program.with_extension(command);
+
+            if bless {
+                fs::write(&expected_path, &produced).unwrap();
+                continue;
+            }
+
+            if fs::read_to_string(&expected_path).unwrap_or_default() != produced {
+                failures.push(program.file_name().unwrap().to_string_lossy().to_string() +
+                    ": unexpected " + command);
+            }
+        }
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
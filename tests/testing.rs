@@ -0,0 +1,121 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const TESTS: &str = "\
+int twice(int n) { return n * 2; }
+test \"passes\" { assert_eq(twice(2), 4); }
+test \"fails\" {
+    assert(twice(2) == 5);
+}
+test \"expects an error\" { expect_error(twice(1)); }
+test \"uses a later global\" { assert_eq(later, 3); }
+int later = 3;
+";
+
+// Write the given files to a new directory, and run the test command on it.
+fn run_tests(name: &str, files: &[(&str, &str)], args: &[&str]) -> (String, Option<i32>) {
+    let directory: PathBuf = env::temp_dir().join("ribbit-rs-testing-".to_owned() + name + "-" +
+        &std::process::id().to_string());
+
+    fs::create_dir_all(&directory).unwrap();
+
+    for (file, text) in files {
+        fs::write(directory.join(file), text).unwrap();
+    }
+
+    let output: Output = Command::new(env!("CARGO_BIN_EXE_ribbit-rs"))
+        .args(args)
+        .arg("test")
+        .arg(&directory)
+        .output()
+        .unwrap();
+
+    fs::remove_dir_all(&directory).unwrap();
+
+    let stdout: String = String::from_utf8_lossy(&output.stdout)
+        .replace(&(directory.display().to_string() + "/"), "");
+
+    return (stdout, output.status.code());
+}
+
+#[test]
+fn tests_are_run_and_reported() -> () {
+    for backend in ["--backend=tree", "--backend=vm"] {
+        let (stdout, code): (String, Option<i32>) = run_tests(&backend[10..], &[
+            ("tests.rbt", TESTS),
+            ("plain.rbt", "println(\"not shown\");"),
+            ("broken.rbt", "int x = ;")
+        ], &[backend]);
+
+        assert_eq!(stdout, "\
+FAIL broken.rbt
+    Error on line 1: Expected an expression. Found Semicolon instead.
+     1 | int x = ;
+                 ^ <- Here
+SKIP plain.rbt (no tests)
+PASS tests.rbt: passes
+FAIL tests.rbt: fails
+    Error on line 4: Assertion failed.
+     4 |     assert(twice(2) == 5);
+             ^^^^^^ <- Here
+FAIL tests.rbt: expects an error
+    Error on line 6: Expected an error, but there was none.
+     6 | test \"expects an error\" { expect_error(twice(1)); }
+                                   ^^^^^^^^^^^^ <- Here
+PASS tests.rbt: uses a later global
+
+2 passed, 3 failed.
+");
+        assert_eq!(code, Some(1));
+    }
+}
+
+#[test]
+fn tests_can_be_filtered() -> () {
+    let (stdout, code): (String, Option<i32>) = run_tests("filter", &[
+        ("tests.rbt", TESTS),
+        ("plain.rbt", "println(1);")
+    ], &["--filter=pass"]);
+
+    assert_eq!(stdout, "PASS tests.rbt: passes\n\n1 passed, 0 failed.\n");
+    assert_eq!(code, Some(0));
+
+    // Programs which cannot be parsed might have matching tests, so they are reported anyway.
+    let (stdout, code): (String, Option<i32>) = run_tests("filter-broken", &[
+        ("tests.rbt", TESTS),
+        ("broken.rbt", "test \"passes too\" { int x = ; }")
+    ], &["--filter=pass"]);
+
+    assert!(stdout.starts_with("FAIL broken.rbt\n    Error on line 1: Expected an expression."));
+    assert!(stdout.ends_with("PASS tests.rbt: passes\n\n1 passed, 1 failed.\n"));
+    assert_eq!(code, Some(1));
+}
+
+#[test]
+fn single_tests_can_be_run() -> () {
+    let directory: PathBuf = env::temp_dir().join("ribbit-rs-testing-single-".to_owned() +
+        &std::process::id().to_string());
+    let path: PathBuf = directory.join("tests.rbt");
+
+    fs::create_dir_all(&directory).unwrap();
+    fs::write(&path, "println(\"top\");\ntest \"a\" { println(\"a\"); }").unwrap();
+
+    let found: Output = Command::new(env!("CARGO_BIN_EXE_ribbit-rs"))
+        .args(["--test=a", "run"])
+        .arg(&path)
+        .output()
+        .unwrap();
+    let missing: Output = Command::new(env!("CARGO_BIN_EXE_ribbit-rs"))
+        .args(["--test=b", "run"])
+        .arg(&path)
+        .output()
+        .unwrap();
+
+    fs::remove_dir_all(&directory).unwrap();
+
+    assert_eq!(String::from_utf8_lossy(&found.stdout), "top\na\n");
+    assert_eq!(missing.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&missing.stderr).contains("There is no test named `b`."));
+}